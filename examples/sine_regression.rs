@@ -0,0 +1,7 @@
+use novigrad::{datasets::sine_regression::load_sine_regression, train_model, Device};
+
+fn main() {
+    let device = Device::default();
+    let details = load_sine_regression(&device).unwrap();
+    train_model::<f32>(details).unwrap();
+}
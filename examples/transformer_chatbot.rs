@@ -1,13 +1,13 @@
 use novigrad::{
-    batch::make_batches,
+    batch::DataLoader,
     datasets::into_one_hot_encoded_rows,
-    error, get_row_argmax,
+    error,
     neural_program::NeuralProgram,
     schedulers::DefaultStreamScheduler,
     tensor::{Error, ErrorEnum, Tensor},
-    transformer_model::TransformerModel,
-    Adam, Device, NeuralMachine, SoftmaxCrossEntropyLoss, TensorWithGrad, Tokenizer,
-    TokenizerTrait,
+    transformer_model::{TransformerModel, TransformerModelConfig},
+    Adam, Device, FeedForwardActivation, NeuralMachine, Reduction, SoftmaxCrossEntropyLoss,
+    TensorWithGrad, Tokenizer, TokenizerTrait,
 };
 use std::{fs::read_to_string, io};
 
@@ -15,6 +15,7 @@ fn main() -> Result<(), Error> {
     let device = Device::default();
     let mut tokenizer = Tokenizer::ascii_tokenizer();
     let sequence_length = 32; //256;
+    let max_position = 64;
     let padding_token = 0;
     let layers = 1;
     let num_heads = 12;
@@ -22,18 +23,25 @@ fn main() -> Result<(), Error> {
     let n_embd = 768;
     let vocab_size = tokenizer.vocab_size();
     let causal_mask = true;
-    let model = TransformerModel::new(
+    let model = TransformerModel::new_with_max_position(
         &device,
-        layers,
-        num_heads,
-        dropout_probability,
-        n_embd,
-        sequence_length,
-        vocab_size,
-        causal_mask,
+        &TransformerModelConfig {
+            layers,
+            num_heads,
+            dropout_probability,
+            n_embd,
+            sequence_length,
+            max_position,
+            vocab_size,
+            causal_mask,
+            activation: FeedForwardActivation::Gelu,
+            seed: None,
+        },
     )?;
 
-    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    // The padding is not a target.
+    let loss_operator =
+        SoftmaxCrossEntropyLoss::new_with_ignore_index(&device, padding_token, Reduction::Sum);
     let batch_size = 32;
     let clip_grad_norm = true;
     let optimizer = Adam::try_new(0.2, 0.9, 0.999, 1e-8, 0.0)?;
@@ -49,6 +57,8 @@ fn main() -> Result<(), Error> {
     let maximum_device_streams = 16;
     let epochs = 100;
     let shuffle_examples = true;
+    // Fixed so that runs can be repeated exactly.
+    let shuffle_seed = Some(42);
     let mut neural_machine = NeuralMachine::<f32, DefaultStreamScheduler>::try_new(
         &device,
         program,
@@ -73,17 +83,31 @@ fn main() -> Result<(), Error> {
 
     let train_examples = train_examples
         .iter()
-        .map(|example| generate_examples(example, &mut tokenizer, sequence_length, &device))
+        .map(|example| {
+            generate_examples(
+                example,
+                &mut tokenizer,
+                sequence_length,
+                max_position,
+                padding_token,
+                &device,
+            )
+        })
         .collect::<Result<Vec<_>, _>>()
         .unwrap()
         .concat();
 
-    let indices = (0..train_examples.len()).collect::<Vec<_>>();
+    let mut data_loader = DataLoader::new(
+        train_examples.len(),
+        batch_size,
+        shuffle_examples,
+        shuffle_seed,
+    );
 
     for epoch in 0..epochs {
         println!("Epoch: {} / {}", epoch, epochs);
 
-        let batches = make_batches(&indices, shuffle_examples, batch_size);
+        let batches = data_loader.next_epoch();
         let mut total_loss = 0.0;
 
         for batch in batches.iter() {
@@ -104,16 +128,10 @@ fn main() -> Result<(), Error> {
         let prompt = &train_corpus[0..25];
         println!("Prompt:  {}", prompt);
         let prompt_tokens = tokenizer.encode(prompt);
-        let max_len = 60;
-        let auto_regressive_tokens = auto_regressive_inference(
-            &mut neural_machine,
-            &device,
-            &prompt_tokens,
-            sequence_length,
-            vocab_size,
-            max_len,
-            padding_token,
-        )?;
+        let max_new_tokens = 35;
+        let eos_token = None;
+        let auto_regressive_tokens =
+            neural_machine.generate(&prompt_tokens, max_new_tokens, eos_token, padding_token)?;
         let actual_output = tokenizer.decode(&auto_regressive_tokens)?;
 
         println!("Chatbot: {}", actual_output);
@@ -137,44 +155,6 @@ fn _read_prompt() -> Result<String, Error> {
     }
 }
 
-fn auto_regressive_inference(
-    neural_machine: &mut NeuralMachine<f32, DefaultStreamScheduler>,
-    device: &Device,
-    prompt_tokens: &[usize],
-    sequence_length: usize,
-    vocab_size: usize,
-    max_len: usize,
-    padding_token: usize,
-) -> Result<Vec<usize>, Error> {
-    let mut auto_regressive_tokens = prompt_tokens.to_owned();
-
-    // TODO implement another stopping criterion.
-    while auto_regressive_tokens.len() < max_len {
-        let input_tokens = if auto_regressive_tokens.len() <= sequence_length {
-            vec![
-                auto_regressive_tokens.clone(),
-                vec![padding_token; sequence_length - auto_regressive_tokens.len()],
-            ]
-            .concat()
-        } else {
-            auto_regressive_tokens[(auto_regressive_tokens.len() - sequence_length)..].to_owned()
-        };
-
-        let input_one_hot = into_one_hot_encoded_rows(device, &input_tokens, vocab_size)?;
-
-        let actual_output_one_hot = neural_machine.infer(&input_one_hot)?;
-        let last_row = if auto_regressive_tokens.len() <= sequence_length {
-            auto_regressive_tokens.len() - 1
-        } else {
-            sequence_length - 1
-        };
-        let predicted_next_token = get_row_argmax(&actual_output_one_hot.tensor(), last_row)?;
-        //println!("predicted next token: {}", predicted_next_token);
-        auto_regressive_tokens.push(predicted_next_token);
-    }
-    Ok(auto_regressive_tokens)
-}
-
 fn read_text_examples(corpus: &str) -> Vec<String> {
     let begin_marker = "[example]";
     let end_marker = "[/example]";
@@ -207,16 +187,20 @@ fn generate_examples(
     example: &str,
     tokenizer: &mut Tokenizer,
     sequence_length: usize,
+    max_position: usize,
+    padding_token: usize,
     device: &Device,
 ) -> Result<Vec<(TensorWithGrad, TensorWithGrad)>, Error> {
     let vocab_size = tokenizer.vocab_size();
     let tokens = tokenizer.encode(example);
     let mut examples = vec![];
     for i in 0..(tokens.len() - sequence_length) {
-        let input_tokens = &tokens[i..i + sequence_length];
+        let mut input_tokens = tokens[i..i + sequence_length].to_owned();
+        add_padding(&mut input_tokens, max_position, padding_token);
         let input_one_hot = into_one_hot_encoded_rows(&device, &input_tokens, vocab_size)?;
 
-        let output_tokens = &tokens[i + 1..i + sequence_length + 1];
+        let mut output_tokens = tokens[i + 1..i + sequence_length + 1].to_owned();
+        add_padding(&mut output_tokens, max_position, padding_token);
         let output_one_hot = into_one_hot_encoded_rows(&device, &output_tokens, vocab_size)?;
 
         //println!("in {:?}", input_tokens);
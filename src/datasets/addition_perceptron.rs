@@ -45,6 +45,7 @@ pub fn load_addition_perceptron(
         optimizer,
         epochs: 100,
         shuffle_examples: false,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 0.1 },
         final_metrics_max: Metrics { total_loss: 15.0 },
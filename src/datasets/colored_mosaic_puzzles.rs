@@ -151,6 +151,7 @@ pub fn load_colored_mosaic_puzzles(
         optimizer,
         epochs: 100,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 5.0 },
         final_metrics_max: Metrics { total_loss: 0.0 },
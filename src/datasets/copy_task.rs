@@ -0,0 +1,57 @@
+use crate::{
+    display::RawPrinter, multi_head_attention_model::MultiHeadAttentionModel, tensor::Error, Adam,
+    Device, Metrics, SoftmaxCrossEntropyLoss,
+};
+
+use super::{synthetic::synthetic_dataset, DatasetDetails};
+
+/// The copy task: the expected output sequence is the input sequence.
+/// A model can only solve it if attention routes each token to its own position,
+/// so it is a regression guard for attention bugs.
+pub fn load_copy_task(
+    device: &Device,
+) -> Result<DatasetDetails<MultiHeadAttentionModel, SoftmaxCrossEntropyLoss, Adam, RawPrinter>, Error>
+{
+    let number_of_examples = 8;
+    let sequence_length = 6;
+    let vocab_size = 8;
+    let n_embd = 32;
+    let num_heads = 4;
+    let seed = 2;
+    let examples = synthetic_dataset(
+        device,
+        number_of_examples,
+        sequence_length,
+        vocab_size,
+        seed,
+    )?;
+
+    let model = MultiHeadAttentionModel::new_with_seed(
+        device,
+        sequence_length,
+        vocab_size,
+        n_embd,
+        num_heads,
+        Some(seed),
+    )?;
+    let loss_operator = SoftmaxCrossEntropyLoss::new(device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0)?;
+    let details = DatasetDetails {
+        device: device.clone(),
+        train_examples: examples,
+        test_examples: vec![],
+        model,
+        loss_operator,
+        optimizer,
+        epochs: 50,
+        shuffle_examples: true,
+        shuffle_seed: Some(seed),
+        clip_gradient_norm: true,
+        initial_metrics_min: Metrics { total_loss: 50.0 },
+        final_metrics_max: Metrics { total_loss: 0.5 },
+        maximum_incorrect_predicted_next_tokens: 0,
+        printer: RawPrinter::default(),
+        batch_size: 1,
+    };
+    Ok(details)
+}
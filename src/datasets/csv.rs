@@ -0,0 +1,51 @@
+use std::fs;
+
+use crate::{
+    error, new_tensor_with_grad,
+    tensor::{Error, ErrorEnum},
+    Device, TensorWithGrad,
+};
+
+/// Load a CSV file where each line is an example.
+/// The input of an example is a 1 x feature_cols.len() row with the values of the feature columns.
+/// The output of an example is a 1 x 1 row with the value of the label column.
+/// A first line that is not numeric is treated as a header row and skipped.
+pub fn load_csv(
+    device: &Device,
+    file_path: &str,
+    feature_cols: &[usize],
+    label_col: usize,
+) -> Result<Vec<(TensorWithGrad, TensorWithGrad)>, Error> {
+    let text = fs::read_to_string(file_path).map_err(|_| error!(ErrorEnum::InputOutputError))?;
+    let mut examples = Vec::new();
+    let lines = text.lines().filter(|line| !line.trim().is_empty());
+    for (i, line) in lines.enumerate() {
+        let cells = line
+            .split(',')
+            .map(|cell| cell.trim().parse::<f32>())
+            .collect::<Vec<_>>();
+        if i == 0 && cells.iter().any(|cell| cell.is_err()) {
+            continue;
+        }
+        let cells = cells
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| error!(ErrorEnum::InputOutputError))?;
+        let cell = |col: &usize| {
+            cells
+                .get(*col)
+                .copied()
+                .ok_or(error!(ErrorEnum::IncorrectOperatorConfiguration))
+        };
+        let input = feature_cols
+            .iter()
+            .map(cell)
+            .collect::<Result<Vec<_>, _>>()?;
+        let output = vec![cell(&label_col)?];
+        examples.push((
+            new_tensor_with_grad!(device, 1, input.len(), input, &[], false, false)?,
+            new_tensor_with_grad!(device, 1, 1, output, &[], false, false)?,
+        ));
+    }
+    Ok(examples)
+}
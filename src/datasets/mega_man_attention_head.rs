@@ -53,6 +53,7 @@ pub fn load_mega_man_attention_head(
         optimizer,
         epochs: 200,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 100.0 },
         final_metrics_max: Metrics { total_loss: 450.0 },
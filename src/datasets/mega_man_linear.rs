@@ -46,6 +46,7 @@ pub fn load_mega_man_linear(
         optimizer,
         epochs: 50,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 5500.0 },
         final_metrics_max: Metrics { total_loss: 0.01 },
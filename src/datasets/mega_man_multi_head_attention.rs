@@ -48,6 +48,7 @@ pub fn load_mega_man_multi_head_attention(
         optimizer,
         epochs: 100,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 3000.0 },
         final_metrics_max: Metrics {
@@ -57,6 +57,7 @@ pub fn load_mega_man_transformers(
         optimizer,
         epochs: 100,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 7000.0 },
         final_metrics_max: Metrics { total_loss: 150.0 },
@@ -1,7 +1,5 @@
 use std::fs;
 
-use more_asserts::debug_assert_lt;
-
 use crate::{
     display::TensorPrinter,
     error, new_tensor, new_tensor_with_grad,
@@ -12,11 +10,19 @@ use crate::{
 
 pub mod addition_perceptron;
 pub mod colored_mosaic_puzzles;
+pub mod copy_task;
+mod csv;
+pub use csv::load_csv;
 pub mod mega_man_attention_head;
 pub mod mega_man_linear;
 pub mod mega_man_multi_head_attention;
 pub mod mega_man_transformers;
 pub mod simple;
+pub mod sine_regression;
+pub mod synthetic;
+
+#[cfg(test)]
+mod tests;
 
 pub struct DatasetDetails<Model, LossOperator, Optimizer, Printer>
 where
@@ -33,6 +39,8 @@ where
     pub optimizer: Optimizer,
     pub batch_size: usize,
     pub shuffle_examples: bool,
+    /// Seed of the shuffling, for reproducible runs. None uses a random seed.
+    pub shuffle_seed: Option<u64>,
     pub clip_gradient_norm: bool,
     pub epochs: usize,
     pub initial_metrics_min: Metrics,
@@ -46,7 +54,10 @@ pub fn into_one_hot_encoded_rows(
     input_tokens: &[usize],
     num_classes: usize,
 ) -> Result<TensorWithGrad, Error> {
-    debug_assert_lt!(*input_tokens.iter().max().unwrap(), num_classes);
+    match input_tokens.iter().max() {
+        Some(max_token) if *max_token < num_classes => {}
+        _ => return Err(error!(ErrorEnum::IncorrectOperatorConfiguration)),
+    }
     let len = input_tokens.len() * num_classes;
     let result = new_tensor!(
         device,
@@ -69,6 +69,7 @@ pub fn load_simple(
         optimizer,
         epochs: 500,
         shuffle_examples: true,
+        shuffle_seed: None,
         clip_gradient_norm: true,
         initial_metrics_min: Metrics { total_loss: 5.0 },
         final_metrics_max: Metrics { total_loss: 1e-4 },
@@ -0,0 +1,60 @@
+use crate::{
+    display::RawPrinter, multi_layer_perceptron::MultiLayerPerceptronModel, new_tensor_with_grad,
+    stochastic_gradient_descent::StochasticGradientDescent,
+    sum_of_squared_errors::SumOfSquaredErrors, tensor::Error, Device, Metrics, Reduction,
+    TensorWithGrad,
+};
+
+use super::DatasetDetails;
+
+/// Points of sin(x) for x in [-π, π].
+fn load_examples(
+    device: &Device,
+    count: usize,
+) -> Result<Vec<(TensorWithGrad, TensorWithGrad)>, Error> {
+    (0..count)
+        .map(|i| {
+            let x =
+                -std::f32::consts::PI + 2.0 * std::f32::consts::PI * i as f32 / (count - 1) as f32;
+            let input = new_tensor_with_grad!(device, 1, 1, vec![x], &[], false, false)?;
+            let output = new_tensor_with_grad!(device, 1, 1, vec![x.sin()], &[], false, false)?;
+            Ok((input, output))
+        })
+        .collect()
+}
+
+/// A regression of the sine function with a multi-layer perceptron and the mean squared error.
+pub fn load_sine_regression(
+    device: &Device,
+) -> Result<
+    DatasetDetails<
+        MultiLayerPerceptronModel,
+        SumOfSquaredErrors,
+        StochasticGradientDescent,
+        RawPrinter,
+    >,
+    Error,
+> {
+    let model = MultiLayerPerceptronModel::new(device, 1, 16, 1, Some(42))?;
+    let examples = load_examples(device, 16)?;
+    let loss_operator = SumOfSquaredErrors::new_with_reduction(device, Reduction::Mean);
+    let optimizer = StochasticGradientDescent::new(0.1);
+    let details = DatasetDetails {
+        device: device.clone(),
+        train_examples: examples,
+        test_examples: vec![],
+        model,
+        loss_operator,
+        optimizer,
+        epochs: 1000,
+        shuffle_examples: true,
+        shuffle_seed: Some(42),
+        clip_gradient_norm: false,
+        initial_metrics_min: Metrics { total_loss: 1.0 },
+        final_metrics_max: Metrics { total_loss: 0.1 },
+        maximum_incorrect_predicted_next_tokens: 0,
+        printer: RawPrinter::default(),
+        batch_size: 1,
+    };
+    Ok(details)
+}
@@ -0,0 +1,28 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{tensor::Error, Device, TensorWithGrad};
+
+use super::into_one_hot_encoded_rows;
+
+/// Generate `num_examples` random token sequences for the copy task.
+/// The expected output of an example is its input.
+/// The same seed always generates the same examples, which makes benchmarks reproducible.
+pub fn synthetic_dataset(
+    device: &Device,
+    num_examples: usize,
+    seq_len: usize,
+    vocab_size: usize,
+    seed: u64,
+) -> Result<Vec<(TensorWithGrad, TensorWithGrad)>, Error> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..num_examples)
+        .map(|_| {
+            let tokens = (0..seq_len)
+                .map(|_| rng.gen_range(0..vocab_size))
+                .collect::<Vec<_>>();
+            let input = into_one_hot_encoded_rows(device, &tokens, vocab_size)?;
+            let output = into_one_hot_encoded_rows(device, &tokens, vocab_size)?;
+            Ok((input, output))
+        })
+        .collect()
+}
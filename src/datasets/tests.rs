@@ -0,0 +1,185 @@
+use std::fs;
+
+use crate::{
+    datasets::{into_one_hot_encoded_rows, load_csv, synthetic::synthetic_dataset},
+    neural_program::NeuralProgram,
+    schedulers::DefaultStreamScheduler,
+    tensor::{Error, ErrorEnum, Tensor},
+    Adam, Device, Embedding, Linear, Model, NeuralMachine, Softmax, SoftmaxCrossEntropyLoss,
+    TensorWithGrad, UnaryModel, UnaryOperator, WeightsInitialization,
+};
+
+#[test]
+fn into_one_hot_encoded_rows_with_last_class() {
+    let device = Device::cpu();
+    let num_classes = 4;
+    let one_hot = into_one_hot_encoded_rows(&device, &[0, num_classes - 1], num_classes).unwrap();
+    assert_eq!(
+        vec![
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, //
+        ],
+        one_hot.tensor().get_values().unwrap(),
+    );
+}
+
+#[test]
+fn into_one_hot_encoded_rows_with_token_equal_to_num_classes() {
+    let device = Device::cpu();
+    let num_classes = 4;
+    let result = into_one_hot_encoded_rows(&device, &[0, num_classes], num_classes);
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        result.map(|_| ()).map_err(|e: Error| e.error().clone()),
+    );
+}
+
+#[test]
+fn into_one_hot_encoded_rows_with_empty_input() {
+    let device = Device::cpu();
+    let result = into_one_hot_encoded_rows(&device, &[], 4);
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        result.map(|_| ()).map_err(|e: Error| e.error().clone()),
+    );
+}
+
+#[test]
+fn load_csv_with_header() {
+    let device = Device::cpu();
+    let file_path = std::env::temp_dir().join("novigrad_load_csv_with_header.csv");
+    fs::write(
+        &file_path,
+        "x1,x2,label\n\
+         1.0,2.0,3.0\n\
+         4.5,-5.0,0.0\n",
+    )
+    .unwrap();
+    let examples = load_csv(&device, file_path.to_str().unwrap(), &[0, 1], 2).unwrap();
+    fs::remove_file(&file_path).unwrap();
+
+    assert_eq!(2, examples.len());
+    let (input, output) = &examples[1];
+    assert_eq!(vec![1, 2], *input.tensor().size());
+    assert_eq!(vec![1, 1], *output.tensor().size());
+    assert_eq!(vec![4.5, -5.0], input.tensor().get_values().unwrap());
+    assert_eq!(vec![0.0], output.tensor().get_values().unwrap());
+}
+
+#[test]
+fn load_csv_with_non_numeric_cell() {
+    let device = Device::cpu();
+    let file_path = std::env::temp_dir().join("novigrad_load_csv_with_non_numeric_cell.csv");
+    fs::write(&file_path, "1.0,2.0\nfoo,3.0\n").unwrap();
+    let result = load_csv(&device, file_path.to_str().unwrap(), &[0], 1);
+    fs::remove_file(&file_path).unwrap();
+
+    assert_eq!(
+        Err(ErrorEnum::InputOutputError),
+        result.map(|_| ()).map_err(|e: Error| e.error().clone())
+    );
+}
+
+#[test]
+fn synthetic_dataset_is_reproducible() {
+    let device = Device::cpu();
+    let values = |examples: &[(TensorWithGrad, TensorWithGrad)]| {
+        examples
+            .iter()
+            .map(|(x, y)| {
+                (
+                    x.tensor().get_values().unwrap(),
+                    y.tensor().get_values().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let examples = synthetic_dataset(&device, 4, 5, 7, 42).unwrap();
+    let same_seed_examples = synthetic_dataset(&device, 4, 5, 7, 42).unwrap();
+    let other_seed_examples = synthetic_dataset(&device, 4, 5, 7, 43).unwrap();
+
+    assert_eq!(4, examples.len());
+    assert_eq!(vec![5, 7], *examples[0].0.tensor().size());
+    assert_eq!(values(&examples), values(&same_seed_examples));
+    assert_ne!(values(&examples), values(&other_seed_examples));
+    for (input, output) in values(&examples) {
+        assert_eq!(input, output);
+    }
+}
+
+struct CopyModel {
+    seq_len: usize,
+    vocab_size: usize,
+    embedding: Embedding,
+    linear: Linear,
+    softmax: Softmax,
+}
+
+impl UnaryModel for CopyModel {}
+
+impl CopyModel {
+    fn new(device: &Device, seq_len: usize, vocab_size: usize) -> Result<Self, Error> {
+        let n_embd = 16;
+        let model = Self {
+            seq_len,
+            vocab_size,
+            embedding: Embedding::new(device, vocab_size, n_embd)?,
+            linear: Linear::new(
+                device,
+                vocab_size,
+                n_embd,
+                WeightsInitialization::Kaiming,
+                seq_len,
+            )?,
+            softmax: Softmax::new_with_next_is_cross_entropy_loss(device),
+        };
+        Ok(model)
+    }
+}
+
+impl UnaryOperator for CopyModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let embedding = self.embedding.forward(input)?;
+        let linear = self.linear.forward(&embedding)?;
+        self.softmax.forward(&linear)
+    }
+}
+
+impl Model for CopyModel {
+    fn input_size(&self) -> Vec<usize> {
+        vec![self.seq_len, self.vocab_size]
+    }
+    fn output_size(&self) -> Vec<usize> {
+        vec![self.seq_len, self.vocab_size]
+    }
+}
+
+#[test]
+fn simple_model_overfits_synthetic_copy_task() {
+    let device = Device::cpu();
+    let seq_len = 4;
+    let vocab_size = 6;
+    let examples = synthetic_dataset(&device, 4, seq_len, vocab_size, 7).unwrap();
+    let model = CopyModel::new(&device, seq_len, vocab_size).unwrap();
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.05, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let mut total_loss = 0.0;
+    for _ in 0..100 {
+        total_loss = 0.0;
+        for (input, output) in examples.iter() {
+            neural_machine.infer(input).unwrap();
+            let loss = neural_machine.loss(output).unwrap();
+            let loss: &Tensor = &loss.tensor();
+            let loss: f32 = loss.try_into().unwrap();
+            total_loss += loss;
+            neural_machine.compute_gradient().unwrap();
+            neural_machine.optimize().unwrap();
+        }
+    }
+    assert!(total_loss < 0.1, "total_loss: {}", total_loss);
+}
@@ -0,0 +1,50 @@
+use cblas::{Layout, Transpose};
+extern crate blas_src;
+extern crate cblas_sys as ffi;
+
+use super::sgemm::Sgemm;
+
+/// Column-major sgemm through the system BLAS.
+pub unsafe fn sgemm(sgemm: &Sgemm, c: &mut [f32]) {
+    let layout = Layout::ColumnMajor;
+    let transa = match sgemm.transa {
+        false => Transpose::None,
+        true => Transpose::Ordinary,
+    };
+    let transb = match sgemm.transb {
+        false => Transpose::None,
+        true => Transpose::Ordinary,
+    };
+    ffi::cblas_sgemm(
+        layout.into(),
+        transa.into(),
+        transb.into(),
+        sgemm.m as i32,
+        sgemm.n as i32,
+        sgemm.k as i32,
+        sgemm.alpha,
+        sgemm.a.as_ptr(),
+        sgemm.lda as i32,
+        sgemm.b.as_ptr(),
+        sgemm.ldb as i32,
+        sgemm.beta,
+        c.as_mut_ptr(),
+        sgemm.ldc as i32,
+    )
+}
+
+pub unsafe fn sdot(n: i32, x: *const f32, incx: i32, y: *const f32, incy: i32) -> f32 {
+    ffi::cblas_sdot(n, x, incx, y, incy)
+}
+
+pub unsafe fn scopy(n: i32, x: *const f32, incx: i32, y: *mut f32, incy: i32) {
+    ffi::cblas_scopy(n, x, incx, y, incy)
+}
+
+pub unsafe fn saxpy(n: i32, alpha: f32, x: *const f32, incx: i32, y: *mut f32, incy: i32) {
+    ffi::cblas_saxpy(n, alpha, x, incx, y, incy)
+}
+
+pub unsafe fn sscal(n: i32, alpha: f32, x: *mut f32, incx: i32) {
+    ffi::cblas_sscal(n, alpha, x, incx)
+}
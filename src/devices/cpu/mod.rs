@@ -1,19 +1,36 @@
-use std::f32::consts::E;
+use std::{f32::consts::E, ops::Range};
+// With both features, the system BLAS is only used to check the no-blas routines.
+#[cfg(all(feature = "blas", any(not(feature = "no-blas"), test)))]
+#[cfg_attr(feature = "no-blas", allow(dead_code))]
+mod blas;
+#[cfg(feature = "no-blas")]
+mod no_blas;
+mod sgemm;
+mod simd;
 pub mod slice;
-use cblas::{Layout, Transpose};
-extern crate cblas_sys as ffi;
+// The system BLAS computes the products when the no-blas feature is off.
+#[cfg(any(feature = "no-blas", test))]
+mod tiled;
 use crate::{
     error,
+    opcode::OpCode,
     slice::DeviceSlice,
     stream::{DeviceStream, DeviceStreamEnum},
     tensor::{Error, ErrorEnum, Tensor},
     EPSILON,
 };
+#[cfg(not(feature = "no-blas"))]
+use blas as ffi;
+#[cfg(feature = "no-blas")]
+use no_blas as ffi;
 
+use self::sgemm::Sgemm;
 use self::slice::CpuDevSlice;
 
 use super::DeviceTrait;
-extern crate blas_src;
+
+#[cfg(not(any(feature = "blas", feature = "no-blas")))]
+compile_error!("The CPU device needs either the blas feature or the no-blas feature.");
 
 #[cfg(test)]
 mod tests;
@@ -39,41 +56,23 @@ impl DeviceTrait for CpuDevice {
         ldc: i32,
         _device_stream: &DeviceStream,
     ) -> Result<(), Error> {
-        let layout = Layout::ColumnMajor;
-        let transa = match transa {
-            false => Transpose::None,
-            true => Transpose::Ordinary,
-        };
-        let transb = match transb {
-            false => Transpose::None,
-            true => Transpose::Ordinary,
+        let sgemm = Sgemm {
+            transa,
+            transb,
+            m: m as usize,
+            n: n as usize,
+            k: k as usize,
+            alpha: unsafe { *alpha.as_ptr() },
+            a: unsafe { std::slice::from_raw_parts(a.as_ptr(), a.len()) },
+            lda: lda as usize,
+            b: unsafe { std::slice::from_raw_parts(b.as_ptr(), b.len()) },
+            ldb: ldb as usize,
+            beta: unsafe { *beta.as_ptr() },
+            ldc: ldc as usize,
         };
+        let c = unsafe { std::slice::from_raw_parts_mut(c.as_mut_ptr(), c.len()) };
 
-        let a = a.as_ptr();
-        let b = b.as_ptr();
-        let c = c.as_mut_ptr();
-
-        let alpha = unsafe { *alpha.as_ptr() };
-        let beta = unsafe { *beta.as_ptr() };
-
-        unsafe {
-            ffi::cblas_sgemm(
-                layout.into(),
-                transa.into(),
-                transb.into(),
-                m,
-                n,
-                k,
-                alpha,
-                a,
-                lda,
-                b,
-                ldb,
-                beta,
-                c,
-                ldc,
-            )
-        }
+        unsafe { ffi::sgemm(&sgemm, c) }
         Ok(())
     }
 
@@ -89,7 +88,7 @@ impl DeviceTrait for CpuDevice {
         let incy = 1;
         let x = x.as_ptr();
         let y = y.as_ptr();
-        let result = unsafe { ffi::cblas_sdot(n, x, incx, y, incy) };
+        let result = unsafe { ffi::sdot(n, x, incx, y, incy) };
         let output = output.as_mut_ptr();
         unsafe {
             *output = result;
@@ -112,7 +111,7 @@ impl DeviceTrait for CpuDevice {
         let x = x.wrapping_add(x_offset as usize);
         let y = y.as_mut_ptr();
         let y = y.wrapping_add(y_offset as usize);
-        unsafe { ffi::cblas_scopy(n, x, x_inc, y, y_inc) }
+        unsafe { ffi::scopy(n, x, x_inc, y, y_inc) }
         Ok(())
     }
 
@@ -129,7 +128,7 @@ impl DeviceTrait for CpuDevice {
         let alpha = unsafe { *alpha.as_ptr() };
         let x = x.as_ptr();
         let y = y.as_mut_ptr();
-        unsafe { ffi::cblas_saxpy(n, alpha, x, incx, y, incy) }
+        unsafe { ffi::saxpy(n, alpha, x, incx, y, incy) }
         Ok(())
     }
 
@@ -137,9 +136,8 @@ impl DeviceTrait for CpuDevice {
         let n = x.len() as i32;
         let x = x.as_mut_ptr();
         let incx = 1;
-        let alpha = alpha.get_values()?;
-        let alpha = alpha[0];
-        unsafe { ffi::cblas_sscal(n, alpha, x, incx) }
+        let alpha = unsafe { *alpha.as_ptr() };
+        unsafe { ffi::sscal(n, alpha, x, incx) }
         Ok(())
     }
 
@@ -236,11 +234,52 @@ impl DeviceTrait for CpuDevice {
 
     fn reduce_sum(
         &self,
-        _input: &Tensor,
-        _output: &Tensor,
+        input: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let n = input.len();
+        let input = input.as_ptr();
+        let output = output.as_mut_ptr();
+        let mut sum = 0.0;
+        let mut i = 0;
+        while i < n {
+            sum += unsafe { *input.add(i) };
+            i += 1;
+        }
+        unsafe {
+            *output = sum;
+        };
+        Ok(())
+    }
+
+    fn reduce_sum_axis(
+        &self,
+        axis: usize,
+        input: &Tensor,
+        output: &Tensor,
         _device_stream: &DeviceStream,
     ) -> Result<(), Error> {
-        todo!()
+        let rows = input.rows();
+        let cols = input.cols();
+        let input = input.as_ptr();
+        let output = output.as_mut_ptr();
+        let (outer, inner) = match axis {
+            0 => (cols, rows),
+            _ => (rows, cols),
+        };
+        for i in 0..outer {
+            let mut sum = 0.0;
+            for j in 0..inner {
+                let (row, col) = match axis {
+                    0 => (j, i),
+                    _ => (i, j),
+                };
+                sum += unsafe { *input.add(row * cols + col) };
+            }
+            unsafe { *output.add(i) = sum };
+        }
+        Ok(())
     }
 
     fn mul(
@@ -261,19 +300,7 @@ impl DeviceTrait for CpuDevice {
         let left_ptr = left.as_ptr();
         let right_ptr = right.as_ptr();
 
-        unsafe {
-            let mut index = 0;
-            while index < len {
-                let left_cell = left_ptr.add(index);
-                let right_cell = right_ptr.add(index);
-                let result_cell = result_ptr.add(index);
-                let left = *left_cell;
-                let right = *right_cell;
-                let value = left * right;
-                *result_cell = value;
-                index += 1;
-            }
-        }
+        unsafe { simd::mul(left_ptr, right_ptr, result_ptr, len) };
         Ok(())
     }
 
@@ -317,22 +344,13 @@ impl DeviceTrait for CpuDevice {
         output: &Tensor,
         _device_stream: &DeviceStream,
     ) -> Result<(), Error> {
-        let rows = input.rows();
-        let cols = input.cols();
+        if *input.size() != *output.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let len = input.len();
         let values = input.as_ptr();
         let result_values = output.as_mut_ptr();
-        let mut row = 0;
-        while row < rows {
-            let mut col = 0;
-            while col < cols {
-                let x = unsafe { values.add(input.index(row, col)) };
-                let y = sigmoid(unsafe { *x });
-                unsafe { *result_values.add(output.index(row, col)) = y };
-                col += 1;
-            }
-            row += 1;
-        }
-
+        unsafe { simd::sigmoid(values, result_values, len) };
         Ok(())
     }
 
@@ -400,19 +418,7 @@ impl DeviceTrait for CpuDevice {
         let left_ptr = left.as_ptr();
         let right_ptr = right.as_ptr();
 
-        unsafe {
-            let mut index = 0;
-            while index < len {
-                let left_cell = left_ptr.add(index);
-                let right_cell = right_ptr.add(index);
-                let result_cell = result_ptr.add(index);
-                let left = *left_cell;
-                let right = *right_cell;
-                let value = left / right;
-                *result_cell = value;
-                index += 1;
-            }
-        }
+        unsafe { simd::div(left_ptr, right_ptr, result_ptr, len) };
 
         Ok(())
     }
@@ -435,20 +441,17 @@ impl DeviceTrait for CpuDevice {
         }
         let rows = p.rows();
         let cols = p.cols();
-        let mut row = 0;
         let p_values = p.as_ptr();
         let q_values = q.as_ptr();
-        let mut sum = 0.0;
-        while row < rows {
-            let mut col = 0;
-            while col < cols {
-                let p_i = unsafe { *p_values.add(p.index(row, col)) };
-                let q_i = unsafe { *q_values.add(q.index(row, col)) };
-                sum += p_i * f32::ln(q_i + EPSILON);
-                col += 1;
-            }
-            row += 1;
-        }
+        // The terms are reduced in row-major order with pairwise summation,
+        // so the loss is reproducible bit-for-bit.
+        let term = |i: usize| {
+            let (row, col) = (i / cols, i % cols);
+            let p_i = unsafe { *p_values.add(p.index(row, col)) };
+            let q_i = unsafe { *q_values.add(q.index(row, col)) };
+            p_i * f32::ln(q_i + EPSILON)
+        };
+        let sum = pairwise_sum_by(0..(rows * cols), &term);
 
         debug_assert!(sum.is_finite());
         let loss_value = -sum;
@@ -488,19 +491,58 @@ impl DeviceTrait for CpuDevice {
         output: &Tensor,
         _device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        let rows = input.rows();
+        let cols = input.cols();
+        if output.rows() != cols || output.cols() != rows {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
         let input_ptr = input.as_ptr();
         let output_ptr = output.as_mut_ptr();
+        // The indices are computed from rows and cols to avoid locking the sizes for each value.
+        if std::ptr::eq(input_ptr, output_ptr) {
+            // In place, which is only possible for square matrices.
+            for row in 0..rows {
+                for col in (row + 1)..cols {
+                    unsafe {
+                        std::ptr::swap(
+                            output_ptr.add(row * cols + col),
+                            output_ptr.add(col * rows + row),
+                        )
+                    };
+                }
+            }
+            return Ok(());
+        }
+        for row in 0..rows {
+            for col in 0..cols {
+                let value = unsafe { *input_ptr.add(row * cols + col) };
+                unsafe { *output_ptr.add(col * rows + row) = value };
+            }
+        }
+        Ok(())
+    }
+
+    fn bias_add(
+        &self,
+        input: &Tensor,
+        bias: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
         let rows = input.rows();
         let cols = input.cols();
-        let mut row = 0;
-        while row < rows {
-            let mut col = 0;
-            while col < cols {
-                let value = unsafe { *input_ptr.add(input.index(row, col)) };
-                unsafe { *output_ptr.add(output.index(col, row)) = value };
-                col += 1;
+        let bias_rows = bias.rows();
+        let input_ptr = input.as_ptr();
+        let bias_ptr = bias.as_ptr();
+        let output_ptr = output.as_mut_ptr();
+        for row in 0..rows {
+            let bias_row = row % bias_rows;
+            for col in 0..cols {
+                unsafe {
+                    *output_ptr.add(row * cols + col) =
+                        *input_ptr.add(row * cols + col) + *bias_ptr.add(bias_row * cols + col);
+                }
             }
-            row += 1;
         }
         Ok(())
     }
@@ -509,6 +551,11 @@ impl DeviceTrait for CpuDevice {
         Ok(DeviceStreamEnum::CpuDeviceStream)
     }
 
+    /// Every operator runs on the CPU.
+    fn supports(&self, _opcode: &OpCode) -> bool {
+        true
+    }
+
     fn min(
         &self,
         input1: &Tensor,
@@ -593,6 +640,221 @@ impl DeviceTrait for CpuDevice {
 
         Ok(())
     }
+
+    fn silu(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let len = input.len();
+        let input_ptr = input.as_ptr();
+        let output_ptr = output.as_mut_ptr();
+        unsafe {
+            let mut index = 0;
+            while index < len {
+                *output_ptr.add(index) = silu(*input_ptr.add(index));
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn silu_backward(
+        &self,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if input.len() != output_gradient.len() || input.len() != input_gradient.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let len = input.len();
+        let input_ptr = input.as_ptr();
+        let output_gradient_ptr = output_gradient.as_ptr();
+        let input_gradient_ptr = input_gradient.as_mut_ptr();
+        unsafe {
+            let mut index = 0;
+            while index < len {
+                let x = *input_ptr.add(index);
+                let g = *output_gradient_ptr.add(index);
+                *input_gradient_ptr.add(index) = g * silu_derivative(x);
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn leaky_relu(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if input.len() != output.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let len = input.len();
+        let input_ptr = input.as_ptr();
+        let output_ptr = output.as_mut_ptr();
+        unsafe {
+            let mut index = 0;
+            while index < len {
+                let x = *input_ptr.add(index);
+                *output_ptr.add(index) = if x > 0.0 { x } else { negative_slope * x };
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn leaky_relu_backward(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if input.len() != output_gradient.len() || input.len() != input_gradient.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let len = input.len();
+        let input_ptr = input.as_ptr();
+        let output_gradient_ptr = output_gradient.as_ptr();
+        let input_gradient_ptr = input_gradient.as_mut_ptr();
+        unsafe {
+            let mut index = 0;
+            while index < len {
+                let x = *input_ptr.add(index);
+                let g = *output_gradient_ptr.add(index);
+                let slope = if x > 0.0 { 1.0 } else { negative_slope };
+                *input_gradient_ptr.add(index) = g * slope;
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn log_softmax(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if *input.size() != *output.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let rows = input.rows();
+        let cols = input.cols();
+        let input = input.as_ptr();
+        let output = output.as_mut_ptr();
+        let mut row = 0;
+        while row < rows {
+            // Find max
+            let mut max = unsafe { *input.add(row * cols) };
+            let mut col = 0;
+            while col < cols {
+                let x = unsafe { *input.add(row * cols + col) };
+                max = max.max(x);
+                col += 1;
+            }
+
+            let mut sum = 0.0;
+            let mut col = 0;
+            while col < cols {
+                let x = unsafe { *input.add(row * cols + col) };
+                sum += E.powf(x - max);
+                col += 1;
+            }
+
+            // x - max - log(sum(exp(x - max)))
+            let log_sum = sum.ln();
+            let mut col = 0;
+            while col < cols {
+                let x = unsafe { *input.add(row * cols + col) };
+                unsafe { *output.add(row * cols + col) = x - max - log_sum };
+                col += 1;
+            }
+            row += 1;
+        }
+        Ok(())
+    }
+
+    fn log_softmax_backward(
+        &self,
+        output: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if *output.size() != *output_gradient.size() || *output.size() != *input_gradient.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let rows = output.rows();
+        let cols = output.cols();
+        let output = output.as_ptr();
+        let output_gradient = output_gradient.as_ptr();
+        let input_gradient = input_gradient.as_mut_ptr();
+        let mut row = 0;
+        while row < rows {
+            let mut sum = 0.0;
+            let mut col = 0;
+            while col < cols {
+                sum += unsafe { *output_gradient.add(row * cols + col) };
+                col += 1;
+            }
+
+            let mut col = 0;
+            while col < cols {
+                let index = row * cols + col;
+                let softmax = E.powf(unsafe { *output.add(index) });
+                let g = unsafe { *output_gradient.add(index) };
+                unsafe { *input_gradient.add(index) = g - softmax * sum };
+                col += 1;
+            }
+            row += 1;
+        }
+        Ok(())
+    }
+
+    fn cosine_similarity(
+        &self,
+        left: &Tensor,
+        right: &Tensor,
+        output: &Tensor,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if *left.size() != *right.size() || output.rows() != left.rows() || output.cols() != 1 {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let rows = left.rows();
+        let cols = left.cols();
+        let left = left.as_ptr();
+        let right = right.as_ptr();
+        let output = output.as_mut_ptr();
+        let mut row = 0;
+        while row < rows {
+            let mut dot = 0.0;
+            let mut left_norm = 0.0;
+            let mut right_norm = 0.0;
+            let mut col = 0;
+            while col < cols {
+                let x = unsafe { *left.add(row * cols + col) };
+                let y = unsafe { *right.add(row * cols + col) };
+                dot += x * y;
+                left_norm += x * x;
+                right_norm += y * y;
+                col += 1;
+            }
+            let norms = f32::sqrt(left_norm) * f32::sqrt(right_norm);
+            unsafe { *output.add(row) = dot / norms.max(EPSILON) };
+            row += 1;
+        }
+        Ok(())
+    }
 }
 
 impl CpuDevice {
@@ -650,6 +912,27 @@ impl CpuDevice {
     }
 }
 
+const PAIRWISE_SUM_BLOCK: usize = 8;
+
+/// Sum the values by splitting them in two halves recursively, down to blocks of
+/// PAIRWISE_SUM_BLOCK values that are summed in order.
+/// The order of the additions only depends on the number of values, so the result is
+/// deterministic, and the rounding error grows as O(log n) instead of O(n).
+/// https://en.wikipedia.org/wiki/Pairwise_summation
+pub fn pairwise_sum(values: &[f32]) -> f32 {
+    pairwise_sum_by(0..values.len(), &|i| values[i])
+}
+
+/// The pairwise sum of term(i) for each i of the range, see pairwise_sum.
+/// The terms are computed as they are summed, so they are never stored.
+pub fn pairwise_sum_by(range: Range<usize>, term: &impl Fn(usize) -> f32) -> f32 {
+    if range.len() <= PAIRWISE_SUM_BLOCK {
+        return range.map(term).sum();
+    }
+    let middle = range.start + range.len() / 2;
+    pairwise_sum_by(range.start..middle, term) + pairwise_sum_by(middle..range.end, term)
+}
+
 pub fn sigmoid(x: f32) -> f32 {
     1.0 / (1.0 + E.powf(-x))
 }
@@ -663,3 +946,14 @@ pub fn gelu_derivative(x: f32) -> f32 {
     // GELU'(x) ≈ 0.5 * (1 + (4 * x) / (5 * (1 + tanh^2(sqrt(2/5) * x))))
     0.5 * (1.0 + (4.0 * x) / (5.0 * (1.0 + ((2.0 / 5.0 as f32).sqrt() * x).tanh().powi(2))))
 }
+
+pub fn silu(x: f32) -> f32 {
+    // SiLU(x) = x * sigmoid(x)
+    x * sigmoid(x)
+}
+
+pub fn silu_derivative(x: f32) -> f32 {
+    // SiLU'(x) = sigmoid(x) * (1 + x * (1 - sigmoid(x)))
+    let s = sigmoid(x);
+    s * (1.0 + x * (1.0 - s))
+}
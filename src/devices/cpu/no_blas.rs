@@ -0,0 +1,55 @@
+use rayon::prelude::*;
+
+use super::{sgemm::Sgemm, tiled::BlockSize};
+
+/// Pure-Rust column-major sgemm, used when the crate is built with the no-blas feature.
+/// C := alpha * op(A) * op(B) + beta * C
+///
+/// The product is tiled, see Sgemm::tiled, and the blocks of columns of C
+/// are computed in parallel.
+pub unsafe fn sgemm(sgemm: &Sgemm, c: &mut [f32]) {
+    if sgemm.m == 0 || sgemm.n == 0 {
+        return;
+    }
+    let block_size = BlockSize::default();
+
+    c.par_chunks_mut(sgemm.ldc * block_size.cols)
+        .enumerate()
+        .for_each(|(block, c_block)| {
+            sgemm.tiled_column_block(&block_size, block * block_size.cols, c_block)
+        });
+}
+
+pub unsafe fn sdot(n: i32, x: *const f32, incx: i32, y: *const f32, incy: i32) -> f32 {
+    let mut sum = 0.0;
+    let mut index = 0;
+    while index < n {
+        sum += *x.offset((index * incx) as isize) * *y.offset((index * incy) as isize);
+        index += 1;
+    }
+    sum
+}
+
+pub unsafe fn scopy(n: i32, x: *const f32, incx: i32, y: *mut f32, incy: i32) {
+    let mut index = 0;
+    while index < n {
+        *y.offset((index * incy) as isize) = *x.offset((index * incx) as isize);
+        index += 1;
+    }
+}
+
+pub unsafe fn saxpy(n: i32, alpha: f32, x: *const f32, incx: i32, y: *mut f32, incy: i32) {
+    let mut index = 0;
+    while index < n {
+        *y.offset((index * incy) as isize) += alpha * *x.offset((index * incx) as isize);
+        index += 1;
+    }
+}
+
+pub unsafe fn sscal(n: i32, alpha: f32, x: *mut f32, incx: i32) {
+    let mut index = 0;
+    while index < n {
+        *x.offset((index * incx) as isize) *= alpha;
+        index += 1;
+    }
+}
@@ -0,0 +1,16 @@
+/// The operands of a column-major sgemm.
+/// C := alpha * op(A) * op(B) + beta * C
+pub struct Sgemm<'a> {
+    pub transa: bool,
+    pub transb: bool,
+    pub m: usize,
+    pub n: usize,
+    pub k: usize,
+    pub alpha: f32,
+    pub a: &'a [f32],
+    pub lda: usize,
+    pub b: &'a [f32],
+    pub ldb: usize,
+    pub beta: f32,
+    pub ldc: usize,
+}
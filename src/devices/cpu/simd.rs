@@ -0,0 +1,76 @@
+use wide::f32x8;
+
+const LANES: usize = 8;
+
+/// Load LANES values starting at ptr.
+///
+/// # Safety
+/// ptr must be valid for reading LANES f32.
+unsafe fn load(ptr: *const f32) -> f32x8 {
+    f32x8::from(std::ptr::read_unaligned(ptr as *const [f32; LANES]))
+}
+
+/// Store LANES values starting at ptr.
+///
+/// # Safety
+/// ptr must be valid for writing LANES f32.
+unsafe fn store(ptr: *mut f32, value: f32x8) {
+    let value: [f32; LANES] = value.into();
+    std::ptr::write_unaligned(ptr as *mut [f32; LANES], value)
+}
+
+/// result[i] = left[i] * right[i]
+///
+/// # Safety
+/// All pointers must be valid for len f32.
+/// result can alias left or right because each chunk is loaded before it is stored.
+pub unsafe fn mul(left: *const f32, right: *const f32, result: *mut f32, len: usize) {
+    let mut index = 0;
+    while index + LANES <= len {
+        let value = load(left.add(index)) * load(right.add(index));
+        store(result.add(index), value);
+        index += LANES;
+    }
+    while index < len {
+        *result.add(index) = *left.add(index) * *right.add(index);
+        index += 1;
+    }
+}
+
+/// result[i] = left[i] / right[i]
+///
+/// # Safety
+/// All pointers must be valid for len f32.
+/// result can alias left or right because each chunk is loaded before it is stored.
+pub unsafe fn div(left: *const f32, right: *const f32, result: *mut f32, len: usize) {
+    let mut index = 0;
+    while index + LANES <= len {
+        let value = load(left.add(index)) / load(right.add(index));
+        store(result.add(index), value);
+        index += LANES;
+    }
+    while index < len {
+        *result.add(index) = *left.add(index) / *right.add(index);
+        index += 1;
+    }
+}
+
+/// result[i] = 1 / (1 + exp(-input[i]))
+///
+/// # Safety
+/// Both pointers must be valid for len f32.
+/// result can alias input because each chunk is loaded before it is stored.
+pub unsafe fn sigmoid(input: *const f32, result: *mut f32, len: usize) {
+    let one = f32x8::splat(1.0);
+    let mut index = 0;
+    while index + LANES <= len {
+        let x = load(input.add(index));
+        let value = one / (one + (-x).exp());
+        store(result.add(index), value);
+        index += LANES;
+    }
+    while index < len {
+        *result.add(index) = super::sigmoid(*input.add(index));
+        index += 1;
+    }
+}
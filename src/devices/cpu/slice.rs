@@ -1,4 +1,8 @@
-use crate::{slice::DevSliceTrait, tensor::Error};
+use crate::{
+    error,
+    slice::DevSliceTrait,
+    tensor::{Error, ErrorEnum},
+};
 
 #[derive(Debug)]
 pub struct CpuDevSlice {
@@ -9,6 +13,12 @@ impl CpuDevSlice {
     pub fn new(slice: Vec<f32>) -> Self {
         Self { slice }
     }
+    pub fn slice(&self) -> &[f32] {
+        &self.slice
+    }
+    pub fn slice_mut(&mut self) -> &mut [f32] {
+        &mut self.slice
+    }
 }
 
 impl DevSliceTrait for CpuDevSlice {
@@ -30,6 +40,23 @@ impl DevSliceTrait for CpuDevSlice {
         Ok(())
     }
 
+    fn get_values_at(&self, offset: usize, len: usize) -> Result<Vec<f32>, Error> {
+        match self.slice.get(offset..offset + len) {
+            Some(values) => Ok(values.to_vec()),
+            None => Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+        }
+    }
+
+    fn set_values_at(&mut self, offset: usize, new_values: &[f32]) -> Result<(), Error> {
+        match self.slice.get_mut(offset..offset + new_values.len()) {
+            Some(values) => {
+                values.copy_from_slice(new_values);
+                Ok(())
+            }
+            None => Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+        }
+    }
+
     fn len(&self) -> usize {
         self.slice.len()
     }
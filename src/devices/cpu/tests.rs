@@ -1,3 +1,4 @@
+use super::{sgemm::Sgemm, tiled::BlockSize};
 use crate::new_tensor;
 
 /// This is the example from https://docs.rs/cblas/latest/cblas/.
@@ -172,3 +173,421 @@ fn cpu_tensor() {
         vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0,]
     );
 }
+
+fn random_values(len: usize, low: f32, high: f32) -> Vec<f32> {
+    use rand::{distributions::Uniform, thread_rng, Rng};
+    let mut rng = thread_rng();
+    let uniform = Uniform::new(low, high);
+    (0..len).map(|_| rng.sample(uniform)).collect()
+}
+
+#[test]
+fn simd_element_wise_ops_match_scalar() {
+    use crate::devices::cpu::sigmoid;
+    use crate::devices::DeviceTrait;
+    use crate::Device;
+    use more_asserts::assert_le;
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    // Not a multiple of 8 to exercise the scalar remainder.
+    let (rows, cols) = (13, 7);
+    let len = rows * cols;
+    let left_values = random_values(len, -4.0, 4.0);
+    let right_values = random_values(len, 0.5, 4.0);
+    let left = new_tensor!(device, rows, cols, left_values.clone()).unwrap();
+    let right = new_tensor!(device, rows, cols, right_values.clone()).unwrap();
+    let output = new_tensor!(device, rows, cols, vec![0.0; len]).unwrap();
+
+    device.mul(&left, &right, &output, &device_stream).unwrap();
+    let expected = left_values
+        .iter()
+        .zip(right_values.iter())
+        .map(|(x, y)| x * y);
+    for (actual, expected) in output.get_values().unwrap().iter().zip(expected) {
+        assert_eq!(*actual, expected);
+    }
+
+    device.div(&left, &right, &output, &device_stream).unwrap();
+    let expected = left_values
+        .iter()
+        .zip(right_values.iter())
+        .map(|(x, y)| x / y);
+    for (actual, expected) in output.get_values().unwrap().iter().zip(expected) {
+        assert_eq!(*actual, expected);
+    }
+
+    device.sigmoid(&left, &output, &device_stream).unwrap();
+    let expected = left_values.iter().map(|x| sigmoid(*x));
+    for (actual, expected) in output.get_values().unwrap().iter().zip(expected) {
+        assert_le!((actual - expected).abs(), 1e-6);
+    }
+}
+
+/// Run with cargo test --release -- --ignored --nocapture simd_element_wise_speedup
+#[test]
+#[ignore]
+fn simd_element_wise_speedup() {
+    use crate::devices::DeviceTrait;
+    use crate::Device;
+    use std::time::Instant;
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    let (rows, cols) = (1024, 1024);
+    let len = rows * cols;
+    let iterations = 100;
+    let left_values = random_values(len, -4.0, 4.0);
+    let right_values = random_values(len, 0.5, 4.0);
+    let left = new_tensor!(device, rows, cols, left_values.clone()).unwrap();
+    let right = new_tensor!(device, rows, cols, right_values.clone()).unwrap();
+    let output = new_tensor!(device, rows, cols, vec![0.0; len]).unwrap();
+
+    let mut scalar_output = vec![0.0; len];
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for index in 0..len {
+            scalar_output[index] = std::hint::black_box(left_values[index] * right_values[index]);
+        }
+    }
+    let scalar = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        device.mul(&left, &right, &output, &device_stream).unwrap();
+    }
+    let simd = start.elapsed();
+
+    println!(
+        "mul on {}x{}: scalar {:?}, simd {:?}, speedup {:.2}",
+        rows,
+        cols,
+        scalar,
+        simd,
+        scalar.as_secs_f64() / simd.as_secs_f64()
+    );
+}
+
+/// The definition of column-major sgemm, one dot product per element of C.
+fn reference_sgemm(
+    transa: bool,
+    transb: bool,
+    (m, n, k): (usize, usize, usize),
+    (alpha, beta): (f32, f32),
+    (a, lda): (&[f32], usize),
+    (b, ldb): (&[f32], usize),
+    (c, ldc): (&mut [f32], usize),
+) {
+    for col in 0..n {
+        for row in 0..m {
+            let sum = (0..k)
+                .map(|l| {
+                    let a_value = if transa {
+                        a[row * lda + l]
+                    } else {
+                        a[l * lda + row]
+                    };
+                    let b_value = if transb {
+                        b[l * ldb + col]
+                    } else {
+                        b[col * ldb + l]
+                    };
+                    a_value * b_value
+                })
+                .sum::<f32>();
+            c[col * ldc + row] = alpha * sum + beta * c[col * ldc + row];
+        }
+    }
+}
+
+/// Compare the no-blas sgemm with a reference on every transposition,
+/// with leading dimensions larger than the matrices.
+/// The error is relative to the magnitude of the expected value.
+#[cfg(feature = "no-blas")]
+fn check_no_blas_sgemm(tolerance: f32, reference: impl Fn(&Sgemm, &mut [f32])) {
+    use more_asserts::assert_le;
+    // The second shape spans several blocks in every dimension and has partial blocks.
+    for (m, n, k) in [(3, 4, 5), (70, 130, 300)] {
+        for (transa, transb) in [(false, false), (false, true), (true, false), (true, true)] {
+            let lda = if transa { k } else { m } + 2;
+            let ldb = if transb { n } else { k } + 3;
+            let ldc = m + 1;
+            let a = random_values(lda * if transa { m } else { k }, -1.0, 1.0);
+            let b = random_values(ldb * if transb { k } else { n }, -1.0, 1.0);
+            let c = random_values(ldc * n, -1.0, 1.0);
+            let sgemm = Sgemm {
+                transa,
+                transb,
+                m,
+                n,
+                k,
+                alpha: 0.5,
+                a: &a,
+                lda,
+                b: &b,
+                ldb,
+                beta: 2.0,
+                ldc,
+            };
+            let mut expected = c.clone();
+            let mut actual = c.clone();
+            reference(&sgemm, &mut expected);
+            unsafe { super::no_blas::sgemm(&sgemm, &mut actual) };
+            for (actual, expected) in actual.iter().zip(expected.iter()) {
+                assert_le!(
+                    (actual - expected).abs(),
+                    tolerance * expected.abs().max(1.0)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "no-blas")]
+#[test]
+fn no_blas_sgemm_matches_the_reference() {
+    check_no_blas_sgemm(1e-3, |sgemm, c| {
+        reference_sgemm(
+            sgemm.transa,
+            sgemm.transb,
+            (sgemm.m, sgemm.n, sgemm.k),
+            (sgemm.alpha, sgemm.beta),
+            (sgemm.a, sgemm.lda),
+            (sgemm.b, sgemm.ldb),
+            (c, sgemm.ldc),
+        )
+    });
+}
+
+#[cfg(all(feature = "blas", feature = "no-blas"))]
+#[test]
+fn no_blas_sgemm_matches_cblas() {
+    check_no_blas_sgemm(1e-5, |sgemm, c| unsafe { super::blas::sgemm(sgemm, c) });
+}
+
+/// CpuDevice::gemm computes the product with the tiled kernel,
+/// so the results are the same bits as the tiled sgemm.
+#[cfg(feature = "no-blas")]
+#[test]
+fn no_blas_gemm_uses_the_tiled_kernel() {
+    use crate::devices::DeviceTrait;
+    use crate::Device;
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    let (m, n, k) = (70, 130, 300);
+    let a_values = random_values(m * k, -1.0, 1.0);
+    let b_values = random_values(k * n, -1.0, 1.0);
+    let a = new_tensor!(device, k, m, a_values.clone()).unwrap();
+    let b = new_tensor!(device, n, k, b_values.clone()).unwrap();
+    let c = new_tensor!(device, n, m, vec![0.0; m * n]).unwrap();
+    let alpha = new_tensor!(device, 1, 1, vec![1.0]).unwrap();
+    let beta = new_tensor!(device, 1, 1, vec![0.0]).unwrap();
+    device
+        .gemm(
+            false,
+            false,
+            m as i32,
+            n as i32,
+            k as i32,
+            &alpha,
+            &a,
+            m as i32,
+            &b,
+            k as i32,
+            &beta,
+            &c,
+            m as i32,
+            &device_stream,
+        )
+        .unwrap();
+
+    let sgemm = Sgemm {
+        transa: false,
+        transb: false,
+        m,
+        n,
+        k,
+        alpha: 1.0,
+        a: &a_values,
+        lda: m,
+        b: &b_values,
+        ldb: k,
+        beta: 0.0,
+        ldc: m,
+    };
+    let mut expected = vec![0.0; m * n];
+    sgemm.tiled(&BlockSize::default(), &mut expected);
+    assert_eq!(c.get_values().unwrap(), expected);
+}
+
+/// The tiled sgemm against the naive triple loop, on random 64x64 matrices,
+/// with blocks that divide the matrices, with blocks that do not, and with the default blocks.
+#[test]
+fn tiled_sgemm_matches_naive_on_64x64() {
+    use more_asserts::assert_le;
+    let n = 64;
+    let a = random_values(n * n, -1.0, 1.0);
+    let b = random_values(n * n, -1.0, 1.0);
+    let mut expected = vec![0.0; n * n];
+    reference_sgemm(
+        false,
+        false,
+        (n, n, n),
+        (1.0, 0.0),
+        (&a, n),
+        (&b, n),
+        (&mut expected, n),
+    );
+    let sgemm = Sgemm {
+        transa: false,
+        transb: false,
+        m: n,
+        n,
+        k: n,
+        alpha: 1.0,
+        a: &a,
+        lda: n,
+        b: &b,
+        ldb: n,
+        beta: 0.0,
+        ldc: n,
+    };
+    for block_size in [
+        BlockSize {
+            rows: 16,
+            depth: 32,
+            cols: 16,
+        },
+        BlockSize {
+            rows: 7,
+            depth: 20,
+            cols: 9,
+        },
+        BlockSize::default(),
+    ] {
+        let mut actual = vec![0.0; n * n];
+        sgemm.tiled(&block_size, &mut actual);
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert_le!((actual - expected).abs(), 1e-4);
+        }
+    }
+}
+
+/// Run with cargo test --release -- --ignored --nocapture tiled_sgemm_speedup
+#[test]
+#[ignore]
+fn tiled_sgemm_speedup() {
+    use std::time::Instant;
+    let n = 768;
+    let a = random_values(n * n, -1.0, 1.0);
+    let b = random_values(n * n, -1.0, 1.0);
+    let mut c = vec![0.0; n * n];
+
+    let start = Instant::now();
+    reference_sgemm(
+        false,
+        false,
+        (n, n, n),
+        (1.0, 0.0),
+        (&a, n),
+        (&b, n),
+        (&mut c, n),
+    );
+    let naive = start.elapsed();
+
+    let sgemm = Sgemm {
+        transa: false,
+        transb: false,
+        m: n,
+        n,
+        k: n,
+        alpha: 1.0,
+        a: &a,
+        lda: n,
+        b: &b,
+        ldb: n,
+        beta: 0.0,
+        ldc: n,
+    };
+    let start = Instant::now();
+    sgemm.tiled(&BlockSize::default(), &mut c);
+    let tiled = start.elapsed();
+    std::hint::black_box(&c);
+
+    println!(
+        "sgemm on {}x{}: naive {:?}, tiled {:?}, speedup {:.2}",
+        n,
+        n,
+        naive,
+        tiled,
+        naive.as_secs_f64() / tiled.as_secs_f64()
+    );
+}
+
+#[test]
+fn cross_entropy_loss_is_reproducible() {
+    use crate::devices::DeviceTrait;
+    use crate::Device;
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    let (rows, cols) = (17, 33);
+    let len = rows * cols;
+    let expected = new_tensor!(device, rows, cols, random_values(len, 0.0, 1.0)).unwrap();
+    let actual = new_tensor!(device, rows, cols, random_values(len, 0.01, 1.0)).unwrap();
+    let loss = new_tensor!(device, 1, 1, vec![0.0]).unwrap();
+
+    device
+        .cross_entropy_loss(&expected, &actual, &loss, &device_stream)
+        .unwrap();
+    let first = loss.get_values().unwrap()[0];
+    for _ in 0..10 {
+        device
+            .cross_entropy_loss(&expected, &actual, &loss, &device_stream)
+            .unwrap();
+        assert_eq!(first.to_bits(), loss.get_values().unwrap()[0].to_bits());
+    }
+}
+
+/// The terms are summed as they are computed, in the order of pairwise_sum on row-major terms.
+#[test]
+fn cross_entropy_loss_is_the_pairwise_sum_of_the_terms() {
+    use crate::devices::cpu::pairwise_sum;
+    use crate::devices::DeviceTrait;
+    use crate::{Device, EPSILON};
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    let (rows, cols) = (17, 33);
+    let len = rows * cols;
+    let p = random_values(len, 0.0, 1.0);
+    let q = random_values(len, 0.01, 1.0);
+    let expected = new_tensor!(device, rows, cols, p.clone()).unwrap();
+    let actual = new_tensor!(device, rows, cols, q.clone()).unwrap();
+    let loss = new_tensor!(device, 1, 1, vec![0.0]).unwrap();
+
+    device
+        .cross_entropy_loss(&expected, &actual, &loss, &device_stream)
+        .unwrap();
+    let terms = p
+        .iter()
+        .zip(q.iter())
+        .map(|(p_i, q_i)| p_i * f32::ln(q_i + EPSILON))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        (-pairwise_sum(&terms)).to_bits(),
+        loss.get_values().unwrap()[0].to_bits()
+    );
+}
+
+#[test]
+fn pairwise_sum_is_more_accurate_than_a_running_sum() {
+    use crate::devices::cpu::pairwise_sum;
+    use more_asserts::assert_lt;
+    // The ulp of 1e8 is 8, so a running sum drops every 1.0 that follows it.
+    let mut values = vec![1e8];
+    values.extend(vec![1.0; 10_000]);
+    let exact = values.iter().map(|x| *x as f64).sum::<f64>();
+    let running_sum = values.iter().fold(0.0_f32, |sum, x| sum + x);
+    let pairwise = pairwise_sum(&values);
+    let running_sum_error = (running_sum as f64 - exact).abs();
+    let pairwise_error = (pairwise as f64 - exact).abs();
+    assert_eq!(10_000.0, running_sum_error);
+    assert_lt!(pairwise_error, 10.0);
+}
@@ -0,0 +1,116 @@
+use super::sgemm::Sgemm;
+
+/// The sizes of the blocks of the tiled sgemm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlockSize {
+    /// The rows of a block of A and C.
+    pub rows: usize,
+    /// The depth, the k dimension, of a block of A and B.
+    pub depth: usize,
+    /// The columns of a block of B and C.
+    pub cols: usize,
+}
+
+impl Default for BlockSize {
+    fn default() -> Self {
+        Self {
+            rows: 64,
+            depth: 256,
+            cols: 64,
+        }
+    }
+}
+
+impl Sgemm<'_> {
+    /// The tiled product, one block of columns of C after the other.
+    ///
+    /// Each block of op(A) and op(B) is packed in a contiguous buffer,
+    /// so that the inner loop is a contiguous multiply-add that fits in the cache
+    /// and that the compiler vectorizes, whatever the transposition of A and B.
+    /// The blocks of columns of C are independent, so no_blas::sgemm computes them in parallel.
+    #[cfg(test)]
+    pub fn tiled(&self, block_size: &BlockSize, c: &mut [f32]) {
+        if self.m == 0 || self.n == 0 {
+            return;
+        }
+        for (block, c_block) in c.chunks_mut(self.ldc * block_size.cols).enumerate() {
+            self.tiled_column_block(block_size, block * block_size.cols, c_block);
+        }
+    }
+
+    /// The columns of C from first_col, which are the first values of c_block.
+    pub fn tiled_column_block(
+        &self,
+        block_size: &BlockSize,
+        first_col: usize,
+        c_block: &mut [f32],
+    ) {
+        let (m, n, k) = (self.m, self.n, self.k);
+        let ldc = self.ldc;
+        let cols = block_size.cols.min(n - first_col);
+        // Column-major, with one column of `rows` values per column of the block of C.
+        let mut packed_a = vec![0.0; block_size.rows * block_size.depth];
+        let mut packed_b = vec![0.0; block_size.depth * block_size.cols];
+        let mut sums = vec![0.0; block_size.rows * block_size.cols];
+
+        let mut first_row = 0;
+        while first_row < m {
+            let rows = block_size.rows.min(m - first_row);
+            let sums = &mut sums[..rows * cols];
+            sums.fill(0.0);
+
+            let mut first_l = 0;
+            while first_l < k {
+                let depth = block_size.depth.min(k - first_l);
+                for l in 0..depth {
+                    for row in 0..rows {
+                        packed_a[l * rows + row] = self.a_at(first_row + row, first_l + l);
+                    }
+                }
+                for col in 0..cols {
+                    for l in 0..depth {
+                        packed_b[col * depth + l] = self.b_at(first_l + l, first_col + col);
+                    }
+                }
+                for col in 0..cols {
+                    let sums_col = &mut sums[col * rows..(col + 1) * rows];
+                    for l in 0..depth {
+                        let b_value = packed_b[col * depth + l];
+                        let a_col = &packed_a[l * rows..(l + 1) * rows];
+                        for (sum, a_value) in sums_col.iter_mut().zip(a_col.iter()) {
+                            *sum += a_value * b_value;
+                        }
+                    }
+                }
+                first_l += depth;
+            }
+
+            for col in 0..cols {
+                let c_col = &mut c_block[col * ldc + first_row..col * ldc + first_row + rows];
+                let sums_col = &sums[col * rows..(col + 1) * rows];
+                for (c_value, sum) in c_col.iter_mut().zip(sums_col.iter()) {
+                    // Like BLAS, C is not read when beta is 0.
+                    *c_value = match self.beta == 0.0 {
+                        true => self.alpha * sum,
+                        false => self.alpha * sum + self.beta * *c_value,
+                    };
+                }
+            }
+            first_row += rows;
+        }
+    }
+
+    fn a_at(&self, row: usize, l: usize) -> f32 {
+        match self.transa {
+            false => self.a[l * self.lda + row],
+            true => self.a[row * self.lda + l],
+        }
+    }
+
+    fn b_at(&self, l: usize, col: usize) -> f32 {
+        match self.transb {
+            false => self.b[col * self.ldb + l],
+            true => self.b[l * self.ldb + col],
+        }
+    }
+}
@@ -0,0 +1,14 @@
+extern "C" __global__ void bias_add_kernel(float *input, float *bias, float *output, int rows, int cols, int bias_rows)
+{
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx >= rows * cols)
+    {
+        return;
+    }
+
+    int row = idx / cols;
+    int col = idx % cols;
+
+    output[idx] = input[idx] + bias[(row % bias_rows) * cols + col];
+}
@@ -0,0 +1,26 @@
+extern "C" __global__ void cosine_similarity_kernel(float *left, float *right, float *output, int rows, int cols)
+{
+    const float EPSILON = 1e-8;
+
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (row >= rows)
+    {
+        return;
+    }
+
+    float dot = 0.0f;
+    float left_norm = 0.0f;
+    float right_norm = 0.0f;
+    for (int col = 0; col < cols; col++)
+    {
+        float x = left[row * cols + col];
+        float y = right[row * cols + col];
+        dot += x * y;
+        left_norm += x * x;
+        right_norm += y * y;
+    }
+
+    float norms = sqrtf(left_norm) * sqrtf(right_norm);
+    output[row] = dot / fmaxf(norms, EPSILON);
+}
@@ -0,0 +1,9 @@
+extern "C" __global__ void leaky_relu_backward_kernel(float negative_slope, float *input, float *output_gradient, float *input_gradient, int n)
+{
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n)
+    {
+        float slope = input[i] > 0.0f ? 1.0f : negative_slope;
+        input_gradient[i] = output_gradient[i] * slope;
+    }
+}
@@ -0,0 +1,9 @@
+extern "C" __global__ void leaky_relu_kernel(float negative_slope, float *input, float *output, int n)
+{
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n)
+    {
+        float x = input[i];
+        output[i] = x > 0.0f ? x : negative_slope * x;
+    }
+}
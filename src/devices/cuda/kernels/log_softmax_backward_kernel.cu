@@ -0,0 +1,22 @@
+// TODO use a smarter reduce instead of doing the same reduction in every thread !
+
+extern "C" __global__ void log_softmax_backward_kernel(float *output, float *output_gradient, float *input_gradient, int rows, int cols)
+{
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx >= rows * cols)
+    {
+        return;
+    }
+
+    int row = idx / cols;
+
+    float sum = 0.0f;
+    for (int j = 0; j < cols; j++)
+    {
+        sum += output_gradient[row * cols + j];
+    }
+
+    // grad - softmax(x) * sum(grad)
+    input_gradient[idx] = output_gradient[idx] - expf(output[idx]) * sum;
+}
@@ -0,0 +1,30 @@
+// TODO use a smarter reduce instead of doing the same reduction in every thread !
+
+extern "C" __global__ void log_softmax_kernel(float *input, float *output, int rows, int cols)
+{
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx >= rows * cols)
+    {
+        return;
+    }
+
+    int row = idx / cols;
+    int col = idx % cols;
+
+    // Find the maximum value in the current row
+    float max_val = input[row * cols + 0];
+    for (int j = 1; j < cols; j++)
+    {
+        max_val = fmaxf(max_val, input[row * cols + j]);
+    }
+
+    float exp_sum = 0.0f;
+    for (int j = 0; j < cols; j++)
+    {
+        exp_sum += expf(input[row * cols + j] - max_val);
+    }
+
+    // x - max - log(sum(exp(x - max)))
+    output[row * cols + col] = input[row * cols + col] - max_val - logf(exp_sum);
+}
@@ -0,0 +1,34 @@
+// One thread per output element.
+extern "C" __global__ void reduce_sum_axis_kernel(float *input, float *output, int rows, int cols, int axis)
+{
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (axis == 0)
+    {
+        // Column sums.
+        if (idx >= cols)
+        {
+            return;
+        }
+        float sum = 0.0f;
+        for (int row = 0; row < rows; row++)
+        {
+            sum += input[row * cols + idx];
+        }
+        output[idx] = sum;
+    }
+    else
+    {
+        // Row sums.
+        if (idx >= rows)
+        {
+            return;
+        }
+        float sum = 0.0f;
+        for (int col = 0; col < cols; col++)
+        {
+            sum += input[idx * cols + col];
+        }
+        output[idx] = sum;
+    }
+}
@@ -0,0 +1,11 @@
+extern "C" __global__ void silu_backward_kernel(float *input, float *output_gradient, float *input_gradient, int n)
+{
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n)
+    {
+        // SiLU'(x) = sigmoid(x) * (1 + x * (1 - sigmoid(x)))
+        float x = input[i];
+        float s = 1.0f / (1.0f + expf(-x));
+        input_gradient[i] = output_gradient[i] * s * (1.0f + x * (1.0f - s));
+    }
+}
@@ -0,0 +1,10 @@
+extern "C" __global__ void silu_kernel(float *input, float *output, int n)
+{
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n)
+    {
+        // SiLU(x) = x * sigmoid(x)
+        float x = input[i];
+        output[i] = x / (1.0f + expf(-x));
+    }
+}
@@ -0,0 +1,26 @@
+extern "C" __global__ void transpose_kernel(float *input, float *output, int rows, int cols)
+{
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx >= rows * cols)
+    {
+        return;
+    }
+
+    int row = idx / cols;
+    int col = idx % cols;
+
+    if (input == output)
+    {
+        // In place, which is only possible for square matrices.
+        if (row < col)
+        {
+            float tmp = output[row * cols + col];
+            output[row * cols + col] = output[col * rows + row];
+            output[col * rows + row] = tmp;
+        }
+        return;
+    }
+
+    output[col * rows + row] = input[row * cols + col];
+}
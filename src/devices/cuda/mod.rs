@@ -1,3 +1,5 @@
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{fs::File, io::Read, mem, sync::Arc};
 pub mod slice;
 pub mod stream;
@@ -16,10 +18,11 @@ use stream::CudaDeviceStream;
 
 use crate::{
     error,
+    opcode::OpCode,
     slice::DeviceSlice,
     stream::{DeviceStream, DeviceStreamEnum, StreamTrait},
     tensor::{Error, ErrorEnum, Tensor},
-    DeviceTrait, EPSILON,
+    DeviceTrait, WarmupTensors, EPSILON,
 };
 
 use self::slice::CudaDevSlice;
@@ -27,8 +30,233 @@ use self::slice::CudaDevSlice;
 #[derive(Debug)]
 pub struct CudaDev {
     pub dev: Arc<CudaDevice>,
+    #[cfg(test)]
+    module_loads: AtomicUsize,
+}
+
+/// A CUDA module that CudaDev loads when it is created.
+pub struct KernelModule {
+    pub name: &'static str,
+    pub func_names: &'static [&'static str],
+    pub src_file_path: &'static str,
+    /// Launch the functions of the module once, see Device::warmup.
+    warmup: fn(&CudaDev, &WarmupTensors, &DeviceStream) -> Result<(), Error>,
 }
 
+/// The modules loaded by CudaDev::try_new, which are also the kernels launched by the warmup.
+pub const KERNEL_MODULES: &[KernelModule] = &[
+    KernelModule {
+        name: "sin_kernel_module",
+        func_names: &["sin_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/sin_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            // sin_kernel takes its output first.
+            device.launch_unary_kernel(
+                "sin_kernel_module",
+                "sin_kernel",
+                &tensors.c,
+                &tensors.d,
+                device_stream,
+            )
+        },
+    },
+    KernelModule {
+        name: "sum_kernel_module",
+        func_names: &["sum_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/sum_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.reduce_sum(&tensors.a, &tensors.scalar, device_stream)
+        },
+    },
+    KernelModule {
+        name: "dot_kernel_module",
+        func_names: &["dot_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/dot_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.dot(&tensors.a, &tensors.b, &tensors.scalar, device_stream)
+        },
+    },
+    KernelModule {
+        name: "cross_entropy_loss_kernel_module",
+        func_names: &["cross_entropy_loss_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/cross_entropy_loss_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.cross_entropy_loss(&tensors.a, &tensors.b, &tensors.scalar, device_stream)
+        },
+    },
+    KernelModule {
+        name: "pow_kernel_module",
+        func_names: &["pow_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/pow_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.pow(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "scalar_add_kernel_module",
+        func_names: &["scalar_add_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/scalar_add_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.scalar_add(&device_stream.zero, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "mul_kernel_module",
+        func_names: &["mul_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/mul_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.mul(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "div_kernel_module",
+        func_names: &["div_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/div_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.div(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "min_kernel_module",
+        func_names: &["min_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/min_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.min(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "sigmoid_kernel_module",
+        func_names: &["sigmoid_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/sigmoid_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.sigmoid(&tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "gelu_kernel_module",
+        func_names: &["gelu_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/gelu_kernel.cu",
+        warmup: |device, tensors, device_stream| device.gelu(&tensors.a, &tensors.c, device_stream),
+    },
+    KernelModule {
+        name: "gelu_derivative_kernel_module",
+        func_names: &["gelu_derivative_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/gelu_derivative_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.gelu_derivative(&tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "silu_kernel_module",
+        func_names: &["silu_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/silu_kernel.cu",
+        warmup: |device, tensors, device_stream| device.silu(&tensors.a, &tensors.c, device_stream),
+    },
+    KernelModule {
+        name: "silu_backward_kernel_module",
+        func_names: &["silu_backward_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/silu_backward_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.silu_backward(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "leaky_relu_kernel_module",
+        func_names: &["leaky_relu_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/leaky_relu_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.leaky_relu(0.01, &tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "leaky_relu_backward_kernel_module",
+        func_names: &["leaky_relu_backward_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/leaky_relu_backward_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.leaky_relu_backward(0.01, &tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "log_softmax_kernel_module",
+        func_names: &["log_softmax_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/log_softmax_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.log_softmax(&tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "log_softmax_backward_kernel_module",
+        func_names: &["log_softmax_backward_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/log_softmax_backward_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.log_softmax_backward(&tensors.c, &tensors.b, &tensors.d, device_stream)
+        },
+    },
+    KernelModule {
+        name: "cosine_similarity_kernel_module",
+        func_names: &["cosine_similarity_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/cosine_similarity_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.cosine_similarity(&tensors.a, &tensors.b, &tensors.scalar, device_stream)
+        },
+    },
+    KernelModule {
+        name: "sqrt_kernel_module",
+        func_names: &["sqrt_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/sqrt_kernel.cu",
+        warmup: |device, tensors, device_stream| device.sqrt(&tensors.a, &tensors.c, device_stream),
+    },
+    KernelModule {
+        name: "clip_kernel_module",
+        func_names: &["clip_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/clip_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            let (min, max) = (&device_stream.zero, &device_stream.one);
+            device.clip(min, max, &tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "softmax_kernel_module",
+        func_names: &["softmax_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/softmax_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.softmax(&tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "standardization_kernel_module",
+        func_names: &["standardization_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/standardization_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.standardization(&tensors.a, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "transpose_kernel_module",
+        func_names: &["transpose_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/transpose_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.transpose(&tensors.scalar, &tensors.scalar, device_stream)
+        },
+    },
+    KernelModule {
+        name: "bias_add_kernel_module",
+        func_names: &["bias_add_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/bias_add_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.bias_add(&tensors.a, &tensors.b, &tensors.c, device_stream)
+        },
+    },
+    KernelModule {
+        name: "reduce_sum_axis_kernel_module",
+        func_names: &["reduce_sum_axis_kernel"],
+        src_file_path: "./src/devices/cuda/kernels/reduce_sum_axis_kernel.cu",
+        warmup: |device, tensors, device_stream| {
+            device.reduce_sum_axis(0, &tensors.a, &tensors.c, device_stream)
+        },
+    },
+];
+
 impl CudaDev {
     pub fn try_default() -> Result<CudaDev, Error> {
         let dev = CudaDevice::new(0);
@@ -142,107 +370,24 @@ impl CudaDev {
     }
 
     pub fn try_new(dev: Arc<driver::CudaDevice>) -> Result<Self, Error> {
-        let device = CudaDev { dev };
-
-        device.load_module(
-            "sin_kernel_module",
-            &["sin_kernel"],
-            "./src/devices/cuda/kernels/sin_kernel.cu",
-        )?;
-
-        device.load_module(
-            "sum_kernel_module",
-            &["sum_kernel"],
-            "./src/devices/cuda/kernels/sum_kernel.cu",
-        )?;
-
-        device.load_module(
-            "dot_kernel_module",
-            &["dot_kernel"],
-            "./src/devices/cuda/kernels/dot_kernel.cu",
-        )?;
-
-        device.load_module(
-            "cross_entropy_loss_kernel_module",
-            &["cross_entropy_loss_kernel"],
-            "./src/devices/cuda/kernels/cross_entropy_loss_kernel.cu",
-        )?;
-
-        device.load_module(
-            "pow_kernel_module",
-            &["pow_kernel"],
-            "./src/devices/cuda/kernels/pow_kernel.cu",
-        )?;
-
-        device.load_module(
-            "scalar_add_kernel_module",
-            &["scalar_add_kernel"],
-            "./src/devices/cuda/kernels/scalar_add_kernel.cu",
-        )?;
-
-        device.load_module(
-            "mul_kernel_module",
-            &["mul_kernel"],
-            "./src/devices/cuda/kernels/mul_kernel.cu",
-        )?;
-
-        device.load_module(
-            "div_kernel_module",
-            &["div_kernel"],
-            "./src/devices/cuda/kernels/div_kernel.cu",
-        )?;
-
-        device.load_module(
-            "min_kernel_module",
-            &["min_kernel"],
-            "./src/devices/cuda/kernels/min_kernel.cu",
-        )?;
-
-        device.load_module(
-            "sigmoid_kernel_module",
-            &["sigmoid_kernel"],
-            "./src/devices/cuda/kernels/sigmoid_kernel.cu",
-        )?;
-
-        device.load_module(
-            "gelu_kernel_module",
-            &["gelu_kernel"],
-            "./src/devices/cuda/kernels/gelu_kernel.cu",
-        )?;
-
-        device.load_module(
-            "gelu_derivative_kernel_module",
-            &["gelu_derivative_kernel"],
-            "./src/devices/cuda/kernels/gelu_derivative_kernel.cu",
-        )?;
-
-        device.load_module(
-            "sqrt_kernel_module",
-            &["sqrt_kernel"],
-            "./src/devices/cuda/kernels/sqrt_kernel.cu",
-        )?;
-
-        device.load_module(
-            "clip_kernel_module",
-            &["clip_kernel"],
-            "./src/devices/cuda/kernels/clip_kernel.cu",
-        )?;
-
-        device.load_module(
-            "softmax_kernel_module",
-            &["softmax_kernel"],
-            "./src/devices/cuda/kernels/softmax_kernel.cu",
-        )?;
-
-        device.load_module(
-            "standardization_kernel_module",
-            &["standardization_kernel"],
-            "./src/devices/cuda/kernels/standardization_kernel.cu",
-        )?;
-
+        let device = CudaDev {
+            dev,
+            #[cfg(test)]
+            module_loads: Default::default(),
+        };
+        for module in KERNEL_MODULES.iter() {
+            device.load_module(module)?;
+        }
         Ok(device)
     }
 
+    /// The number of modules loaded since the device was created.
+    /// The kernels are only loaded by try_new, never by their launches.
+    #[cfg(test)]
+    pub fn module_loads(&self) -> usize {
+        self.module_loads.load(Ordering::Relaxed)
+    }
+
     fn get_func(&self, module_name: &str, func_name: &str) -> Result<CudaFunction, Error> {
         let kernel = self
             .dev
@@ -251,14 +396,9 @@ impl CudaDev {
         Ok(kernel)
     }
 
-    fn load_module(
-        &self,
-        module_name: &str,
-        func_names: &[&'static str],
-        src_file_path: &str,
-    ) -> Result<(), Error> {
+    fn load_module(&self, module: &KernelModule) -> Result<(), Error> {
         let mut cuda_code = String::default();
-        File::open(src_file_path)
+        File::open(module.src_file_path)
             .map_err(|_| error!(ErrorEnum::InputOutputError))?
             .read_to_string(&mut cuda_code)
             .map_err(|_| error!(ErrorEnum::InputOutputError))?;
@@ -266,8 +406,10 @@ impl CudaDev {
             .map_err(|err| error!(ErrorEnum::NvRtcCompilePtxError(err)))?;
 
         self.dev
-            .load_ptx(ptx, module_name, func_names)
+            .load_ptx(ptx, module.name, module.func_names)
             .map_err(|_| error!(ErrorEnum::NvRtcLoadPtxError))?;
+        #[cfg(test)]
+        self.module_loads.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 }
@@ -512,6 +654,38 @@ impl DeviceTrait for CudaDev {
         }
     }
 
+    fn reduce_sum_axis(
+        &self,
+        axis: usize,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func("reduce_sum_axis_kernel_module", "reduce_sum_axis_kernel")?;
+        let rows = input.rows();
+        let cols = input.cols();
+        let cfg = LaunchConfig::for_num_elems(output.len() as u32);
+        let input = &input.device_slice().buffer;
+        let output = &output.device_slice().buffer;
+        match (input, output) {
+            (DeviceSlice::CudaDevSlice(input), DeviceSlice::CudaDevSlice(output)) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (input.slice(), output.slice(), rows, cols, axis as i32),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
     fn mul(
         &self,
         left: &Tensor,
@@ -591,6 +765,220 @@ impl DeviceTrait for CudaDev {
         )
     }
 
+    fn silu(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        self.launch_unary_kernel(
+            "silu_kernel_module",
+            "silu_kernel",
+            input,
+            output,
+            device_stream,
+        )
+    }
+
+    fn silu_backward(
+        &self,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        self.launch_binary_kernel(
+            "silu_backward_kernel_module",
+            "silu_backward_kernel",
+            input,
+            output_gradient,
+            input_gradient,
+            device_stream,
+        )
+    }
+
+    fn leaky_relu(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func("leaky_relu_kernel_module", "leaky_relu_kernel")?;
+        let n = input.len();
+        let cfg = LaunchConfig::for_num_elems(n as u32);
+        let input = &input.device_slice().buffer;
+        let output = &output.device_slice().buffer;
+        match (input, output) {
+            (DeviceSlice::CudaDevSlice(input), DeviceSlice::CudaDevSlice(output)) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (negative_slope, input.slice(), output.slice(), n),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
+    fn leaky_relu_backward(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func(
+            "leaky_relu_backward_kernel_module",
+            "leaky_relu_backward_kernel",
+        )?;
+        let n = input.len();
+        let cfg = LaunchConfig::for_num_elems(n as u32);
+        let input = &input.device_slice().buffer;
+        let output_gradient = &output_gradient.device_slice().buffer;
+        let input_gradient = &input_gradient.device_slice().buffer;
+        match (input, output_gradient, input_gradient) {
+            (
+                DeviceSlice::CudaDevSlice(input),
+                DeviceSlice::CudaDevSlice(output_gradient),
+                DeviceSlice::CudaDevSlice(input_gradient),
+            ) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (
+                            negative_slope,
+                            input.slice(),
+                            output_gradient.slice(),
+                            input_gradient.slice(),
+                            n,
+                        ),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
+    fn log_softmax(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        self.launch_axis_kernel(
+            "log_softmax_kernel_module",
+            "log_softmax_kernel",
+            input,
+            output,
+            device_stream,
+        )
+    }
+
+    fn log_softmax_backward(
+        &self,
+        output: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func(
+            "log_softmax_backward_kernel_module",
+            "log_softmax_backward_kernel",
+        )?;
+        let rows = output.rows();
+        let cols = output.cols();
+        let n = output.len();
+        let cfg = LaunchConfig::for_num_elems(n as u32);
+        let output = &output.device_slice().buffer;
+        let output_gradient = &output_gradient.device_slice().buffer;
+        let input_gradient = &input_gradient.device_slice().buffer;
+        match (output, output_gradient, input_gradient) {
+            (
+                DeviceSlice::CudaDevSlice(output),
+                DeviceSlice::CudaDevSlice(output_gradient),
+                DeviceSlice::CudaDevSlice(input_gradient),
+            ) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (
+                            output.slice(),
+                            output_gradient.slice(),
+                            input_gradient.slice(),
+                            rows,
+                            cols,
+                        ),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
+    fn cosine_similarity(
+        &self,
+        left: &Tensor,
+        right: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if *left.size() != *right.size() || output.rows() != left.rows() || output.cols() != 1 {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func(
+            "cosine_similarity_kernel_module",
+            "cosine_similarity_kernel",
+        )?;
+        let rows = left.rows();
+        let cols = left.cols();
+        let cfg = LaunchConfig::for_num_elems(rows as u32);
+        let left = &left.device_slice().buffer;
+        let right = &right.device_slice().buffer;
+        let output = &output.device_slice().buffer;
+        match (left, right, output) {
+            (
+                DeviceSlice::CudaDevSlice(left),
+                DeviceSlice::CudaDevSlice(right),
+                DeviceSlice::CudaDevSlice(output),
+            ) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (left.slice(), right.slice(), output.slice(), rows, cols),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
     fn div(
         &self,
         left: &Tensor,
@@ -752,23 +1140,102 @@ impl DeviceTrait for CudaDev {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
-        let _cuda_stream = get_cuda_stream(device_stream)?;
-        // TODO implement transpose in CUDA.
-        let self_values = input.get_values()?;
-        let mut other_values = output.get_values()?;
         let rows = input.rows();
         let cols = input.cols();
-        let mut row = 0;
-        while row < rows {
-            let mut col = 0;
-            while col < cols {
-                let value = self_values[input.index(row, col)];
-                other_values[output.index(col, row)] = value;
-                col += 1;
+        if output.rows() != cols || output.cols() != rows {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func("transpose_kernel_module", "transpose_kernel")?;
+        let cfg = LaunchConfig::for_num_elems((rows * cols) as u32);
+        let input = &input.device_slice().buffer;
+        let output = &output.device_slice().buffer;
+        match (input, output) {
+            (DeviceSlice::CudaDevSlice(input), DeviceSlice::CudaDevSlice(output)) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (input.slice(), output.slice(), rows, cols),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
             }
-            row += 1;
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
         }
-        output.set_values(other_values)
+    }
+
+    fn bias_add(
+        &self,
+        input: &Tensor,
+        bias: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let rows = input.rows();
+        let cols = input.cols();
+        let bias_rows = bias.rows();
+        let cuda_stream = get_cuda_stream(device_stream)?;
+        let kernel = self.get_func("bias_add_kernel_module", "bias_add_kernel")?;
+        let cfg = LaunchConfig::for_num_elems((rows * cols) as u32);
+        let input = &input.device_slice().buffer;
+        let bias = &bias.device_slice().buffer;
+        let output = &output.device_slice().buffer;
+        match (input, bias, output) {
+            (
+                DeviceSlice::CudaDevSlice(input),
+                DeviceSlice::CudaDevSlice(bias),
+                DeviceSlice::CudaDevSlice(output),
+            ) => {
+                let result = unsafe {
+                    kernel.launch_on_stream(
+                        cuda_stream,
+                        cfg,
+                        (
+                            input.slice(),
+                            bias.slice(),
+                            output.slice(),
+                            rows,
+                            cols,
+                            bias_rows,
+                        ),
+                    )
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(error!(ErrorEnum::NvLaunchError)),
+                }
+            }
+            _ => Err(error!(ErrorEnum::NvLaunchError)),
+        }
+    }
+
+    fn warmup(&self, tensors: &WarmupTensors, device_stream: &DeviceStream) -> Result<(), Error> {
+        let one = &device_stream.one;
+        let zero = &device_stream.zero;
+        self.gemm(
+            false,
+            false,
+            1,
+            1,
+            1,
+            one,
+            one,
+            1,
+            one,
+            1,
+            zero,
+            &tensors.scalar,
+            1,
+            device_stream,
+        )?;
+        for module in KERNEL_MODULES.iter() {
+            (module.warmup)(self, tensors, device_stream)?;
+        }
+        Ok(())
     }
 
     fn stream(&self) -> Result<DeviceStreamEnum, Error> {
@@ -822,6 +1289,12 @@ impl DeviceTrait for CudaDev {
         };
         Ok(DeviceStreamEnum::CudaDeviceStream(cuda_stream))
     }
+
+    /// The operators with a kernel or a cuBLAS call, but not the ones that copy
+    /// their values to the host.
+    fn supports(&self, opcode: &OpCode) -> bool {
+        !opcode.is_computed_on_host()
+    }
 }
 
 fn get_cuda_stream(device_stream: &DeviceStream) -> Result<&CudaStream, Error> {
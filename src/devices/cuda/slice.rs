@@ -18,6 +18,9 @@ impl CudaDevSlice {
     pub fn slice(&self) -> &CudaSlice<f32> {
         &self.slice
     }
+    pub fn slice_mut(&mut self) -> &mut CudaSlice<f32> {
+        &mut self.slice
+    }
 }
 
 impl DevSliceTrait for CudaDevSlice {
@@ -45,6 +48,29 @@ impl DevSliceTrait for CudaDevSlice {
             .map_err(|_| error!(ErrorEnum::UnsupportedOperation))
     }
 
+    fn get_values_at(&self, offset: usize, len: usize) -> Result<Vec<f32>, Error> {
+        if offset + len > self.slice.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let mut values = vec![0.0; len];
+        let dev = self.slice.device();
+        let view = self.slice.slice(offset..offset + len);
+        dev.dtoh_sync_copy_into(&view, &mut values)
+            .map_err(|_| error!(ErrorEnum::UnsupportedOperation))?;
+        Ok(values)
+    }
+
+    fn set_values_at(&mut self, offset: usize, new_values: &[f32]) -> Result<(), Error> {
+        let end = offset + new_values.len();
+        if end > self.slice.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let dev = self.slice.device();
+        let mut view = self.slice.slice_mut(offset..end);
+        dev.htod_sync_copy_into(new_values, &mut view)
+            .map_err(|_| error!(ErrorEnum::UnsupportedOperation))
+    }
+
     fn len(&self) -> usize {
         self.slice.len()
     }
@@ -389,3 +389,63 @@ fn cuda_cross_entropy_loss() {
 
     assert_eq!(expected, actual,);
 }
+
+#[test]
+fn warmup_loads_every_kernel_once() {
+    use super::{CudaDev, KERNEL_MODULES};
+    use crate::devices::DeviceTrait;
+    use crate::Device;
+    use std::sync::Arc;
+
+    let cuda = Arc::new(CudaDev::try_default().unwrap());
+    let device = Device::new(cuda.clone());
+    device.warmup().unwrap();
+    for module in KERNEL_MODULES.iter() {
+        for func_name in module.func_names.iter() {
+            assert!(cuda.dev.has_func(module.name, func_name));
+        }
+    }
+    assert_eq!(KERNEL_MODULES.len(), cuda.module_loads());
+
+    // The module loaded at startup is reused by the next launch.
+    let device_stream = device.new_stream().unwrap();
+    let input = new_tensor!(device, 1, 1, vec![0.0]).unwrap();
+    let output = new_tensor!(device, 1, 1, vec![1.0]).unwrap();
+    device.sigmoid(&input, &output, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![0.5], output.get_values().unwrap());
+    assert_eq!(KERNEL_MODULES.len(), cuda.module_loads());
+}
+
+#[test]
+fn transpose_cpu_and_cuda_parity() {
+    use crate::Device;
+
+    let cpu = Device::cpu();
+    let cuda = Device::cuda().unwrap();
+    let rows = 3;
+    let cols = 5;
+    let values = (0..rows * cols).map(|x| x as f32).collect::<Vec<_>>();
+    let transpose = |device: &Device| {
+        let device_stream = device.new_stream().unwrap();
+        let input = new_tensor!(device, rows, cols, values.clone()).unwrap();
+        let output = new_tensor!(device, cols, rows, vec![0.0; rows * cols]).unwrap();
+        input
+            .transpose_into(device, &output, &device_stream)
+            .unwrap();
+        device_stream.wait_for().unwrap();
+        output.get_values().unwrap()
+    };
+    assert_eq!(transpose(&cpu), transpose(&cuda));
+}
+
+#[test]
+fn cuda_supports_softmax() {
+    use crate::devices::DeviceTrait;
+    use crate::{opcode::OpCode, Device};
+
+    let device = Device::cuda().unwrap();
+    assert!(device.supports(&OpCode::Softmax));
+    // MaxPool2D is computed on the host.
+    assert!(!device.supports(&OpCode::MaxPool2D));
+}
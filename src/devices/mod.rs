@@ -1,10 +1,11 @@
 mod cpu;
 use crate::{error, tensor::Error, tensor::ErrorEnum};
+use std::collections::HashSet;
 use std::mem;
 use std::{
     fmt,
     ops::Deref,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, OnceLock, RwLock},
 };
 #[cfg(test)]
 mod tests;
@@ -14,9 +15,9 @@ pub use cpu::*;
 mod cuda;
 #[cfg(feature = "cuda")]
 pub use cuda::*;
-use stream::{DeviceStream, DeviceStreamEnum};
+use stream::{DeviceStream, DeviceStreamEnum, HostStreamMemory, StreamTrait};
 
-use crate::{tensor::Tensor, TensorWithGrad};
+use crate::{opcode::OpCode, tensor::Tensor, TensorWithGrad};
 pub mod slice;
 pub mod stream;
 use core::fmt::Debug;
@@ -165,6 +166,11 @@ pub trait DeviceTrait {
         device_stream: &DeviceStream,
     ) -> Result<(), Error>;
 
+    /// SSCAL scales a vector by a constant.
+    /// x = alpha * x
+    /// The canonical semantics are the ones of cuBLAS with the device pointer mode:
+    /// alpha is a tensor with exactly one value, which is read on the device
+    /// when the instruction executes. Any other length is an error on every device.
     fn scal(&self, alpha: &Tensor, x: &Tensor, device_stream: &DeviceStream) -> Result<(), Error>;
 
     fn scalar_add(
@@ -225,6 +231,67 @@ pub trait DeviceTrait {
         device_stream: &DeviceStream,
     ) -> Result<(), Error>;
 
+    fn silu(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    /// Compute output_gradient * SiLU'(input).
+    fn silu_backward(
+        &self,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    fn leaky_relu(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    /// Compute output_gradient * LeakyRelu'(input).
+    fn leaky_relu_backward(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    /// Row-wise log(softmax(x)).
+    fn log_softmax(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    /// Compute input_gradient = output_gradient - softmax(x) * sum(output_gradient), row-wise,
+    /// where softmax(x) = exp(output).
+    fn log_softmax_backward(
+        &self,
+        output: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
+    /// Per-row cosine similarity of left and right into output, which has one column.
+    fn cosine_similarity(
+        &self,
+        left: &Tensor,
+        right: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
     fn sqrt(
         &self,
         input: &Tensor,
@@ -239,6 +306,17 @@ pub trait DeviceTrait {
         device_stream: &DeviceStream,
     ) -> Result<(), Error>;
 
+    /// Sum along an axis.
+    /// With axis 0, the rows are summed into one row, so output is [1, cols].
+    /// With axis 1, the columns are summed into one column, so output is [rows, 1].
+    fn reduce_sum_axis(
+        &self,
+        axis: usize,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
     /// H(P, Q) = - Σ (P(i) * log(Q(i)))
     /// https://en.wikipedia.org/wiki/Entropy_(information_theory)
     fn cross_entropy_loss(
@@ -265,10 +343,43 @@ pub trait DeviceTrait {
         device_stream: &DeviceStream,
     ) -> Result<(), Error>;
 
+    /// output = input + bias, where the bias is broadcast to every row of the input.
+    /// The bias has 1 row, or as many rows as the input. output can be input.
+    fn bias_add(
+        &self,
+        input: &Tensor,
+        bias: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error>;
+
     /// Allocate a slice on the device.
     fn slice(&self, n: i32) -> Result<DeviceSlice, Error>;
 
     fn stream(&self) -> Result<DeviceStreamEnum, Error>;
+
+    /// Whether the device implements the operator natively, so that callers can fall back to
+    /// another device before building a model.
+    /// Operators that are computed on the host are not native on an accelerator.
+    fn supports(&self, opcode: &OpCode) -> bool;
+
+    /// Launch every kernel of the device once on the dummy tensors, see Device::warmup.
+    /// A device without kernels to load has nothing to warm up.
+    fn warmup(&self, _tensors: &WarmupTensors, _device_stream: &DeviceStream) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The dummy tensors of Device::warmup.
+pub struct WarmupTensors {
+    /// 1x2 inputs.
+    pub a: Tensor,
+    pub b: Tensor,
+    /// 1x2 outputs.
+    pub c: Tensor,
+    pub d: Tensor,
+    /// 1x1 output.
+    pub scalar: Tensor,
 }
 
 impl Debug for dyn DeviceTrait + Send + Sync {
@@ -284,6 +395,10 @@ pub struct Device {
     tensors: Arc<RwLock<Vec<Tensor>>>,
     internal_tensors: Arc<RwLock<Vec<TensorWithGrad>>>,
     parameter_tensors: Arc<RwLock<Vec<TensorWithGrad>>>,
+    host_internal_tensors: Arc<RwLock<Vec<TensorWithGrad>>>,
+    host_parameter_tensors: Arc<RwLock<Vec<TensorWithGrad>>>,
+    host: Arc<OnceLock<Device>>,
+    host_streams: Arc<Mutex<Vec<HostStreamMemory>>>,
     device: Arc<dyn DeviceTrait + Send + Sync>,
 }
 
@@ -310,6 +425,10 @@ impl Device {
             tensors: Default::default(),
             internal_tensors: Default::default(),
             parameter_tensors: Default::default(),
+            host_internal_tensors: Default::default(),
+            host_parameter_tensors: Default::default(),
+            host: Default::default(),
+            host_streams: Default::default(),
             device,
         }
     }
@@ -319,6 +438,23 @@ impl Device {
         DeviceStream::try_new(self, variant)
     }
 
+    /// Launch every kernel once on dummy tensors.
+    /// The first launch of a CUDA kernel pays for its JIT compilation,
+    /// so benchmarks should call this first to measure steady-state performance.
+    /// The kernels are the modules of KERNEL_MODULES on a CUDA device.
+    pub fn warmup(&self) -> Result<(), Error> {
+        let device_stream = self.new_stream()?;
+        let tensors = WarmupTensors {
+            a: new_tensor!(self, 1, 2, vec![0.5, 0.25])?,
+            b: new_tensor!(self, 1, 2, vec![0.25, 0.5])?,
+            c: new_tensor!(self, 1, 2, vec![0.0; 2])?,
+            d: new_tensor!(self, 1, 2, vec![0.0; 2])?,
+            scalar: new_tensor!(self, 1, 1, vec![0.0])?,
+        };
+        self.device.warmup(&tensors, &device_stream)?;
+        device_stream.wait_for()
+    }
+
     pub fn copy_to(
         &self,
         x: &Tensor,
@@ -341,6 +477,51 @@ impl Device {
         Self::new(Arc::new(CpuDevice::default()))
     }
 
+    /// A CPU device that shares tensor names with this device.
+    /// Operators created with it stay on the host (e.g. a huge embedding table)
+    /// while the rest of the model runs on this device.
+    /// Instructions whose outputs are on the host are executed by the host,
+    /// and ToDevice moves tensors across the boundary.
+    /// The internal tensors and the parameters of the host operators are registered
+    /// with this device too, see host_internal_tensors and host_parameter_tensors,
+    /// so that they are trained on the host.
+    /// The host device is created once and shared by the clones of this device.
+    pub fn host(&self) -> Self {
+        self.host
+            .get_or_init(|| {
+                let mut host = Self::cpu();
+                host.next_name = self.next_name.clone();
+                host.internal_tensors = self.host_internal_tensors.clone();
+                host.parameter_tensors = self.host_parameter_tensors.clone();
+                host.host_internal_tensors = self.host_internal_tensors.clone();
+                host.host_parameter_tensors = self.host_parameter_tensors.clone();
+                host
+            })
+            .clone()
+    }
+
+    /// Execute on the host with a host stream.
+    /// The working memory of the host streams is pooled in the host device,
+    /// so that concurrent host instructions do not share it and nothing is allocated
+    /// once the pool is warm.
+    pub fn execute_on_host(
+        &self,
+        f: impl FnOnce(&Device, &DeviceStream) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let host = self.host();
+        let memory = host.host_streams.lock().unwrap().pop();
+        let host_stream = match memory {
+            Some(memory) => DeviceStream::from_host_memory(memory),
+            None => host.new_stream()?,
+        };
+        let result = f(&host, &host_stream);
+        host.host_streams
+            .lock()
+            .unwrap()
+            .push(host_stream.into_host_memory());
+        result
+    }
+
     pub fn get_memory_info(&self) -> Result<MemoryInfo, Error> {
         Ok(MemoryInfo {
             used: *self.used.read().unwrap(),
@@ -386,6 +567,23 @@ impl Device {
         Ok(tensor)
     }
 
+    pub fn zeros(&self, rows: usize, cols: usize) -> Result<Tensor, Error> {
+        self.full(rows, cols, 0.0)
+    }
+
+    pub fn ones(&self, rows: usize, cols: usize) -> Result<Tensor, Error> {
+        self.full(rows, cols, 1.0)
+    }
+
+    pub fn full(&self, rows: usize, cols: usize, value: f32) -> Result<Tensor, Error> {
+        new_tensor!(self, rows, cols, vec![value; rows * cols])
+    }
+
+    /// The values start, start + 1, ..., in row-major order, for example for positions.
+    pub fn iota(&self, rows: usize, cols: usize, start: f32) -> Result<Tensor, Error> {
+        Tensor::from_iter(self, rows, cols, (0..rows * cols).map(|i| start + i as f32))
+    }
+
     pub fn tensor_with_grad(
         &self,
         rows: usize,
@@ -474,12 +672,100 @@ impl Device {
         self.parameter_tensors.read().unwrap()
     }
 
+    /// Free the gradient buffers of the internal tensors with the given names,
+    /// and stop tracking them so that no program resets their gradients.
+    pub fn release_gradients(&self, names: &HashSet<usize>) {
+        self.internal_tensors.write().unwrap().retain(|tensor| {
+            let is_released = names.contains(&tensor.tensor().name());
+            if is_released {
+                tensor.gradient().deallocate(self);
+            }
+            !is_released
+        });
+    }
+
+    /// The internal tensors of the operators created with the host device.
+    pub fn host_internal_tensors(&self) -> impl Deref<Target = Vec<TensorWithGrad>> + '_ {
+        self.host_internal_tensors.read().unwrap()
+    }
+
+    /// The parameters of the operators created with the host device.
+    pub fn host_parameter_tensors(&self) -> impl Deref<Target = Vec<TensorWithGrad>> + '_ {
+        self.host_parameter_tensors.read().unwrap()
+    }
+
+    /// Stop optimizing a parameter.
+    /// It still has a gradient, which is reset like the gradients of the internal tensors,
+    /// so that the gradients of its inputs are computed.
+    pub fn freeze_parameter(&self, parameter: &TensorWithGrad) {
+        let name = parameter.tensor().name();
+        let mut parameter_tensors = self.parameter_tensors.write().unwrap();
+        if let Some(index) = parameter_tensors
+            .iter()
+            .position(|x| x.tensor().name() == name)
+        {
+            let parameter = parameter_tensors.remove(index);
+            self.internal_tensors.write().unwrap().push(parameter);
+        }
+    }
+
+    /// Bucket the values of all the parameters into `bins` equal-width bins
+    /// between the global min and max.
+    /// Each bin is returned as (lower bound, count).
+    pub fn parameter_histogram(&self, bins: usize) -> Result<Vec<(f32, usize)>, Error> {
+        let mut values = vec![];
+        for t in self.parameter_tensors.read().unwrap().iter() {
+            values.append(&mut t.tensor().get_values()?);
+        }
+        Ok(histogram(&values, bins))
+    }
+
+    /// Same as parameter_histogram, but for the gradients of the parameters.
+    pub fn gradient_histogram(&self, bins: usize) -> Result<Vec<(f32, usize)>, Error> {
+        let mut values = vec![];
+        for t in self.parameter_tensors.read().unwrap().iter() {
+            values.append(&mut t.gradient().get_values()?);
+        }
+        Ok(histogram(&values, bins))
+    }
+
     pub fn buffer(&self, len: usize) -> DevSlice {
         let used: &mut usize = &mut self.used.write().unwrap();
         let bytes = len * mem::size_of::<f32>();
         *used += bytes;
         DevSlice::new(self, len)
     }
+
+    /// Account for a buffer of len values that was dropped.
+    pub fn release(&self, len: usize) {
+        let used: &mut usize = &mut self.used.write().unwrap();
+        let bytes = len * mem::size_of::<f32>();
+        *used = used.saturating_sub(bytes);
+    }
+}
+
+fn histogram(values: &[f32], bins: usize) -> Vec<(f32, usize)> {
+    if bins == 0 || values.is_empty() {
+        return vec![];
+    }
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let width = (max - min) / bins as f32;
+    let mut counts = vec![0; bins];
+    for value in values {
+        let bin = if width > 0.0 {
+            ((value - min) / width) as usize
+        } else {
+            0
+        };
+        // The max value belongs to the last bin.
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| (min + i as f32 * width, count))
+        .collect()
 }
 
 impl DeviceTrait for Device {
@@ -500,6 +786,7 @@ impl DeviceTrait for Device {
         ldc: i32,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([alpha, a, b, beta, c])?;
         self.device.gemm(
             transa,
             transb,
@@ -525,6 +812,7 @@ impl DeviceTrait for Device {
         result: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([left, right, result])?;
         if *left.size() != *right.size() {
             return Err(error!(ErrorEnum::IncompatibleTensorShapes));
         }
@@ -545,6 +833,7 @@ impl DeviceTrait for Device {
         y_inc: i32,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([x, y])?;
         self.device
             .copy(n, x, x_offset, x_inc, y, y_offset, y_inc, device_stream)
     }
@@ -559,10 +848,15 @@ impl DeviceTrait for Device {
         incy: i32,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([alpha, x, y])?;
         self.device.axpy(n, alpha, x, incx, y, incy, device_stream)
     }
 
     fn scal(&self, alpha: &Tensor, x: &Tensor, device_stream: &DeviceStream) -> Result<(), Error> {
+        Tensor::check_dtypes([alpha, x])?;
+        if alpha.len() != 1 {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
         self.device.scal(alpha, x, device_stream)
     }
 
@@ -572,6 +866,7 @@ impl DeviceTrait for Device {
         x: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([alpha, x])?;
         self.device.scalar_add(alpha, x, device_stream)
     }
 
@@ -581,6 +876,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.softmax(input, output, device_stream)
     }
 
@@ -590,12 +886,33 @@ impl DeviceTrait for Device {
         y: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([x, y])?;
         if &y.size() as &[usize] != &[1, 1] {
             return Err(error!(ErrorEnum::IncompatibleTensorShapes));
         }
         self.device.reduce_sum(x, y, device_stream)
     }
 
+    fn reduce_sum_axis(
+        &self,
+        axis: usize,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
+        let expected_size = match axis {
+            0 => [1, input.cols()],
+            1 => [input.rows(), 1],
+            _ => return Err(error!(ErrorEnum::IncorrectOperatorConfiguration)),
+        };
+        if *output.size() != expected_size {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        self.device
+            .reduce_sum_axis(axis, input, output, device_stream)
+    }
+
     fn mul(
         &self,
         left: &Tensor,
@@ -603,6 +920,7 @@ impl DeviceTrait for Device {
         result: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([left, right, result])?;
         self.device.mul(left, right, result, device_stream)
     }
 
@@ -613,6 +931,7 @@ impl DeviceTrait for Device {
         result: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([left, right, result])?;
         self.device.pow(left, right, result, device_stream)
     }
 
@@ -622,6 +941,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.sigmoid(input, output, device_stream)
     }
 
@@ -631,6 +951,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.gelu(input, output, device_stream)
     }
 
@@ -640,15 +961,103 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.gelu_derivative(input, output, device_stream)
     }
 
+    fn silu(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
+        self.device.silu(input, output, device_stream)
+    }
+
+    fn silu_backward(
+        &self,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output_gradient, input_gradient])?;
+        self.device
+            .silu_backward(input, output_gradient, input_gradient, device_stream)
+    }
+
+    fn leaky_relu(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
+        self.device
+            .leaky_relu(negative_slope, input, output, device_stream)
+    }
+
+    fn leaky_relu_backward(
+        &self,
+        negative_slope: f32,
+        input: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output_gradient, input_gradient])?;
+        self.device.leaky_relu_backward(
+            negative_slope,
+            input,
+            output_gradient,
+            input_gradient,
+            device_stream,
+        )
+    }
+
+    fn log_softmax(
+        &self,
+        input: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
+        self.device.log_softmax(input, output, device_stream)
+    }
+
+    fn log_softmax_backward(
+        &self,
+        output: &Tensor,
+        output_gradient: &Tensor,
+        input_gradient: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([output, output_gradient, input_gradient])?;
+        self.device
+            .log_softmax_backward(output, output_gradient, input_gradient, device_stream)
+    }
+
+    fn cosine_similarity(
+        &self,
+        left: &Tensor,
+        right: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([left, right, output])?;
+        self.device
+            .cosine_similarity(left, right, output, device_stream)
+    }
+
     fn sqrt(
         &self,
         input: &Tensor,
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.sqrt(input, output, device_stream)
     }
 
@@ -659,6 +1068,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input1, input2, output])?;
         self.device.div(input1, input2, output, device_stream)
     }
 
@@ -669,6 +1079,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input1, input2, output])?;
         self.device.min(input1, input2, output, device_stream)
     }
 
@@ -680,6 +1091,7 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([min, max, input, output])?;
         self.device.clip(min, max, input, output, device_stream)
     }
 
@@ -690,6 +1102,7 @@ impl DeviceTrait for Device {
         loss: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([expected, actual, loss])?;
         self.device
             .cross_entropy_loss(expected, actual, loss, device_stream)
     }
@@ -701,6 +1114,7 @@ impl DeviceTrait for Device {
         loss: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([expected, actual, loss])?;
         self.device
             .reduce_sum_square(expected, actual, loss, device_stream)
     }
@@ -711,9 +1125,27 @@ impl DeviceTrait for Device {
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.transpose(input, output, device_stream)
     }
 
+    fn bias_add(
+        &self,
+        input: &Tensor,
+        bias: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, bias, output])?;
+        if bias.cols() != input.cols()
+            || (bias.rows() != 1 && bias.rows() != input.rows())
+            || *output.size() != *input.size()
+        {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        self.device.bias_add(input, bias, output, device_stream)
+    }
+
     fn slice(&self, n: i32) -> Result<DeviceSlice, Error> {
         self.device.slice(n)
     }
@@ -722,12 +1154,21 @@ impl DeviceTrait for Device {
         self.device.stream()
     }
 
+    fn supports(&self, opcode: &OpCode) -> bool {
+        self.device.supports(opcode)
+    }
+
+    fn warmup(&self, tensors: &WarmupTensors, device_stream: &DeviceStream) -> Result<(), Error> {
+        self.device.warmup(tensors, device_stream)
+    }
+
     fn standardization(
         &self,
         input: &Tensor,
         output: &Tensor,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes([input, output])?;
         self.device.standardization(input, output, device_stream)
     }
 }
@@ -1,9 +1,9 @@
 use super::cpu::slice::CpuDevSlice;
 #[cfg(feature = "cuda")]
 use super::cuda::slice::CudaDevSlice;
-use crate::tensor::Error;
-use crate::Device;
+use crate::tensor::{Error, ErrorEnum};
 use crate::DeviceTrait;
+use crate::{error, Device};
 use std::borrow::BorrowMut;
 
 #[derive(Debug)]
@@ -23,6 +23,10 @@ pub trait DevSliceTrait {
     fn as_mut_ptr(&mut self) -> *mut f32;
     fn get_values(&self) -> Result<Vec<f32>, Error>;
     fn set_values(&mut self, new_values: Vec<f32>) -> Result<(), Error>;
+    /// The values offset..offset + len, without copying the rest of the buffer.
+    fn get_values_at(&self, offset: usize, len: usize) -> Result<Vec<f32>, Error>;
+    /// Overwrite the values offset..offset + new_values.len(), and only them.
+    fn set_values_at(&mut self, offset: usize, new_values: &[f32]) -> Result<(), Error>;
     fn len(&self) -> usize;
 }
 
@@ -32,6 +36,38 @@ impl DevSlice {
         let slice = device.slice(len as i32).unwrap();
         DevSlice { buffer: slice }
     }
+
+    /// Copy the values of a buffer that may be on another device,
+    /// without staging them in a temporary host vector.
+    pub fn copy_from(&mut self, source: &DevSlice) -> Result<(), Error> {
+        if self.len() != source.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        match (&mut self.buffer, &source.buffer) {
+            (DeviceSlice::CpuDevSlice(destination), DeviceSlice::CpuDevSlice(source)) => {
+                destination.slice_mut().copy_from_slice(source.slice());
+                Ok(())
+            }
+            #[cfg(feature = "cuda")]
+            (DeviceSlice::CudaDevSlice(destination), DeviceSlice::CpuDevSlice(source)) => {
+                let dev = destination.slice().device();
+                dev.htod_sync_copy_into(source.slice(), destination.slice_mut())
+                    .map_err(|_| error!(ErrorEnum::UnsupportedOperation))
+            }
+            #[cfg(feature = "cuda")]
+            (DeviceSlice::CpuDevSlice(destination), DeviceSlice::CudaDevSlice(source)) => {
+                let dev = source.slice().device();
+                dev.dtoh_sync_copy_into(source.slice(), destination.slice_mut())
+                    .map_err(|_| error!(ErrorEnum::UnsupportedOperation))
+            }
+            #[cfg(feature = "cuda")]
+            (DeviceSlice::CudaDevSlice(destination), DeviceSlice::CudaDevSlice(source)) => {
+                let dev = source.slice().device();
+                dev.dtod_copy(source.slice(), destination.slice_mut())
+                    .map_err(|_| error!(ErrorEnum::UnsupportedOperation))
+            }
+        }
+    }
 }
 
 impl DevSliceTrait for DevSlice {
@@ -67,6 +103,22 @@ impl DevSliceTrait for DevSlice {
         }
     }
 
+    fn get_values_at(&self, offset: usize, len: usize) -> Result<Vec<f32>, Error> {
+        match self.buffer {
+            DeviceSlice::CpuDevSlice(ref slice) => slice.get_values_at(offset, len),
+            #[cfg(feature = "cuda")]
+            DeviceSlice::CudaDevSlice(ref slice) => slice.get_values_at(offset, len),
+        }
+    }
+
+    fn set_values_at(&mut self, offset: usize, new_values: &[f32]) -> Result<(), Error> {
+        match self.buffer.borrow_mut() {
+            DeviceSlice::CpuDevSlice(ref mut slice) => slice.set_values_at(offset, new_values),
+            #[cfg(feature = "cuda")]
+            DeviceSlice::CudaDevSlice(ref mut slice) => slice.set_values_at(offset, new_values),
+        }
+    }
+
     fn len(&self) -> usize {
         match &self.buffer {
             DeviceSlice::CpuDevSlice(slice) => slice.len(),
@@ -17,6 +17,8 @@ pub struct DeviceStream {
     pub minus_one: Tensor,
     pub alpha: Tensor,
     pub zero: Tensor,
+    pub clip_min: Tensor,
+    pub clip_max: Tensor,
 }
 
 impl DeviceStream {
@@ -29,9 +31,53 @@ impl DeviceStream {
             minus_one: new_tensor!(device, 1, 1, vec![-1.0],)?,
             alpha: new_tensor!(device, 1, 1, vec![0.0],)?,
             zero: new_tensor!(device, 1, 1, vec![0.0],)?,
+            clip_min: new_tensor!(device, 1, 1, vec![0.0],)?,
+            clip_max: new_tensor!(device, 1, 1, vec![0.0],)?,
         };
         Ok(that)
     }
+
+    /// A host stream around the working memory of a previous host stream.
+    pub fn from_host_memory(memory: HostStreamMemory) -> Self {
+        Self {
+            variant: DeviceStreamEnum::CpuDeviceStream,
+            max_alpha: memory.max_alpha,
+            l2_norm: memory.l2_norm,
+            one: memory.one,
+            minus_one: memory.minus_one,
+            alpha: memory.alpha,
+            zero: memory.zero,
+            clip_min: memory.clip_min,
+            clip_max: memory.clip_max,
+        }
+    }
+
+    pub fn into_host_memory(self) -> HostStreamMemory {
+        HostStreamMemory {
+            max_alpha: self.max_alpha,
+            l2_norm: self.l2_norm,
+            one: self.one,
+            minus_one: self.minus_one,
+            alpha: self.alpha,
+            zero: self.zero,
+            clip_min: self.clip_min,
+            clip_max: self.clip_max,
+        }
+    }
+}
+
+/// The working memory of a host stream, without the stream.
+/// A DeviceStream can not be sent to other threads because of its CUDA variant,
+/// so host streams are kept in this form between host instructions.
+pub struct HostStreamMemory {
+    max_alpha: Tensor,
+    l2_norm: Tensor,
+    one: Tensor,
+    minus_one: Tensor,
+    alpha: Tensor,
+    zero: Tensor,
+    clip_min: Tensor,
+    clip_max: Tensor,
 }
 
 pub enum DeviceStreamEnum {
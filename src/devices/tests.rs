@@ -2,7 +2,13 @@ use std::ops::Div;
 
 use more_asserts::assert_le;
 
-use crate::{new_tensor, stream::StreamTrait, Device, DeviceTrait};
+use crate::{
+    new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::StreamTrait,
+    tensor::{DType, ErrorEnum},
+    Device, DeviceTrait,
+};
 
 #[test]
 fn clip_min() {
@@ -292,3 +298,220 @@ fn standardization_is_deterministic() {
 
     assert_eq!(elements1, elements2);
 }
+
+#[test]
+fn parameter_histogram() {
+    let device = Device::default();
+    let _parameter_1 =
+        new_tensor_with_grad!(device, 2, 2, vec![0.0, 1.0, 2.0, 9.0], &[], true, true).unwrap();
+    let _parameter_2 =
+        new_tensor_with_grad!(device, 1, 3, vec![4.5, 5.0, 10.0], &[], true, true).unwrap();
+    // Not a parameter.
+    let _activation = new_tensor_with_grad!(device, 1, 1, vec![100.0], &[], true, false).unwrap();
+
+    let histogram = device.parameter_histogram(4).unwrap();
+    assert_eq!(vec![(0.0, 3), (2.5, 1), (5.0, 1), (7.5, 2)], histogram);
+    let total = histogram.iter().map(|(_, count)| count).sum::<usize>();
+    assert_eq!(device.parameter_count(), total);
+
+    // All the gradients are 0.
+    let gradient_histogram = device.gradient_histogram(2).unwrap();
+    assert_eq!(vec![(0.0, 7), (0.0, 0)], gradient_histogram);
+}
+
+#[test]
+fn zeros_ones_and_full() {
+    let device = Device::cpu();
+    let zeros = device.zeros(2, 3).unwrap();
+    assert_eq!(vec![2, 3], *zeros.size());
+    assert_eq!(vec![0.0; 6], zeros.get_values().unwrap());
+
+    let ones = device.ones(3, 2).unwrap();
+    assert_eq!(vec![3, 2], *ones.size());
+    assert_eq!(vec![1.0; 6], ones.get_values().unwrap());
+
+    let full = device.full(1, 4, 0.5).unwrap();
+    assert_eq!(vec![0.5; 4], full.get_values().unwrap());
+}
+
+#[test]
+fn warmup_succeeds_on_a_device_without_kernels() {
+    let device = Device::cpu();
+    device.warmup().unwrap();
+}
+
+#[test]
+fn scal_rejects_alpha_with_more_than_one_value() {
+    let device = Device::cpu();
+    let device_stream = device.new_stream().unwrap();
+    let alpha = new_tensor!(device, 1, 2, vec![2.0, 3.0]).unwrap();
+    let x = new_tensor!(device, 1, 3, vec![1.0, 2.0, 3.0]).unwrap();
+    let result = device.scal(&alpha, &x, &device_stream);
+    assert_eq!(
+        Err(&crate::tensor::ErrorEnum::IncompatibleTensorShapes),
+        result.as_ref().map_err(|e| e.error())
+    );
+    assert_eq!(vec![1.0, 2.0, 3.0], x.get_values().unwrap());
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn scal_cpu_and_cuda_parity() {
+    let values = vec![1.0, -2.0, 3.5, 0.0, -0.25, 8.0];
+    let scale = |device: Device| {
+        let device_stream = device.new_stream().unwrap();
+        let alpha = new_tensor!(device, 1, 1, vec![-1.5]).unwrap();
+        let x = new_tensor!(device, 2, 3, values.clone()).unwrap();
+        device.scal(&alpha, &x, &device_stream).unwrap();
+        device_stream.wait_for().unwrap();
+        x.get_values().unwrap()
+    };
+    let cpu = scale(Device::cpu());
+    let cuda = scale(Device::cuda().unwrap());
+    assert_eq!(cpu, cuda);
+}
+
+#[test]
+fn bias_add() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let input = new_tensor!(device, 3, 4, (0..12).map(|x| x as f32).collect()).unwrap();
+    let bias = new_tensor!(device, 1, 4, vec![0.5, -1.0, 2.0, 10.0]).unwrap();
+    let output = new_tensor!(device, 3, 4, vec![0.0; 12]).unwrap();
+
+    device
+        .bias_add(&input, &bias, &output, &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+
+    let expected = new_tensor!(
+        device,
+        3,
+        4,
+        vec![
+            //
+            0.5, 0.0, 4.0, 13.0, //
+            4.5, 4.0, 8.0, 17.0, //
+            8.5, 8.0, 12.0, 21.0, //
+        ],
+    )
+    .unwrap();
+    assert_eq!(expected, output);
+
+    let bias = new_tensor!(device, 2, 4, vec![0.0; 8]).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        device
+            .bias_add(&input, &bias, &output, &device_stream)
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn iota() {
+    let device = Device::default();
+    let tensor = device.iota(2, 3, 0.0).unwrap();
+    assert_eq!(vec![2, 3], *tensor.size());
+    assert_eq!(
+        vec![vec![0.0, 1.0, 2.0], vec![3.0, 4.0, 5.0]],
+        tensor.to_vec2().unwrap()
+    );
+    assert_eq!(
+        vec![10.0, 11.0, 12.0],
+        device.iota(1, 3, 10.0).unwrap().get_values().unwrap()
+    );
+}
+
+#[test]
+fn reduce_sum_axis() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let rows = 3;
+    let cols = 4;
+    let values = (0..rows * cols)
+        .map(|x| (x as f32).sin())
+        .collect::<Vec<_>>();
+    let input = new_tensor!(device, rows, cols, values.clone()).unwrap();
+    let column_sums = new_tensor!(device, 1, cols, vec![0.0; cols]).unwrap();
+    let row_sums = new_tensor!(device, rows, 1, vec![0.0; rows]).unwrap();
+
+    device
+        .reduce_sum_axis(0, &input, &column_sums, &device_stream)
+        .unwrap();
+    device
+        .reduce_sum_axis(1, &input, &row_sums, &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+
+    let expected_column_sums = (0..cols)
+        .map(|col| (0..rows).map(|row| values[row * cols + col]).sum::<f32>())
+        .collect::<Vec<_>>();
+    let expected_row_sums = (0..rows)
+        .map(|row| (0..cols).map(|col| values[row * cols + col]).sum::<f32>())
+        .collect::<Vec<_>>();
+    assert_eq!(expected_column_sums, column_sums.get_values().unwrap());
+    assert_eq!(expected_row_sums, row_sums.get_values().unwrap());
+
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        device
+            .reduce_sum_axis(0, &input, &row_sums, &device_stream)
+            .map_err(|e| e.error().clone())
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        device
+            .reduce_sum_axis(2, &input, &row_sums, &device_stream)
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn cpu_supports_softmax() {
+    let device = Device::cpu();
+    assert!(device.supports(&OpCode::Softmax));
+    assert!(device.supports(&OpCode::SoftmaxCrossEntropyLoss));
+}
+
+#[test]
+fn cpu_supports_the_operators_computed_on_the_host() {
+    let device = Device::cpu();
+    assert!(OpCode::MaxPool2D.is_computed_on_host());
+    assert!(!OpCode::Gemm.is_computed_on_host());
+    assert!(device.supports(&OpCode::MaxPool2D));
+}
+
+#[test]
+fn execute_on_host_reuses_the_host_and_its_streams() {
+    let device = Device::cpu();
+    device.execute_on_host(|_, _| Ok(())).unwrap();
+    let tensor_count = device.tensor_count();
+    for _ in 0..3 {
+        device.execute_on_host(|_, _| Ok(())).unwrap();
+    }
+    // The host shares tensor names with the device, so new stream tensors would be counted.
+    assert_eq!(tensor_count, device.tensor_count());
+}
+
+#[test]
+fn device_rejects_f16_tensors() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let input = new_tensor!(device, 1, 2, vec![0.0, 0.0])
+        .unwrap()
+        .with_dtype(DType::F16);
+    let output = new_tensor!(device, 1, 2, vec![1.0, 1.0])
+        .unwrap()
+        .with_dtype(DType::F16);
+    assert_eq!(
+        Err(ErrorEnum::DTypeMismatch {
+            expected: DType::F32,
+            found: DType::F16,
+        }),
+        device
+            .sigmoid(&input, &output, &device_stream)
+            .map_err(|e| e.error().clone())
+    );
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![1.0, 1.0], output.get_values().unwrap());
+}
@@ -1,11 +1,14 @@
 use crate::{
-    tensor::Error, AttentionHead, Device, Embedding, Linear, Model, Softmax, TensorWithGrad,
-    TernaryOperator, UnaryModel, UnaryOperator, WeightsInitialization,
+    tensor::Error, AttentionHead, Device, Embedding, Linear, Model, ModelConfig, Softmax,
+    TensorWithGrad, TernaryOperator, UnaryModel, UnaryOperator, WeightsInitialization,
 };
 
 pub struct AttentionHeadModel {
     input_shape: Vec<usize>,
     output_shape: Vec<usize>,
+    n_embd: usize,
+    causal_mask: bool,
+    dropout_probability: f32,
     embedding: Embedding,
     attention_head: AttentionHead,
     linear: Linear,
@@ -31,6 +34,7 @@ impl AttentionHeadModel {
             n_embd,
             causal_mask,
             dropout_probability,
+            false,
         )?;
         let linear = Linear::new(
             device,
@@ -43,6 +47,9 @@ impl AttentionHeadModel {
         let model = Self {
             input_shape: vec![sequence_length, vocab_size],
             output_shape: vec![sequence_length, vocab_size],
+            n_embd,
+            causal_mask,
+            dropout_probability,
             embedding,
             attention_head,
             linear,
@@ -72,4 +79,14 @@ impl Model for AttentionHeadModel {
     fn output_size(&self) -> Vec<usize> {
         self.output_shape.clone()
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::AttentionHead {
+            sequence_length: self.input_shape[0],
+            vocab_size: self.input_shape[1],
+            n_embd: self.n_embd,
+            causal_mask: self.causal_mask,
+            dropout_probability: self.dropout_probability,
+        })
+    }
 }
@@ -1,7 +1,7 @@
 use crate::tensor::Error;
 use crate::{
-    new_tensor_with_grad, BinaryOperator, Device, Embedding, Linear, MatMul, Model, Reshape,
-    Softmax, TensorWithGrad, UnaryModel, UnaryOperator, WeightsInitialization,
+    new_tensor_with_grad, BinaryOperator, Device, Embedding, Linear, MatMul, Model, ModelConfig,
+    Reshape, Softmax, TensorWithGrad, UnaryModel, UnaryOperator, WeightsInitialization,
 };
 
 pub struct MegaManModel {
@@ -72,4 +72,11 @@ impl Model for MegaManModel {
     fn output_size(&self) -> Vec<usize> {
         self.output_shape.clone()
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::MegaMan {
+            sequence_length: self.input_shape[0],
+            vocab_size: self.input_shape[1],
+        })
+    }
 }
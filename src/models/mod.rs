@@ -2,7 +2,12 @@ mod model;
 pub use model::*;
 pub mod attention_head_model;
 pub mod mega_man;
+pub mod model_builder;
 pub mod multi_head_attention_model;
+pub mod multi_layer_perceptron;
 pub mod perceptron;
 pub mod simple;
 pub mod transformer_model;
+
+#[cfg(test)]
+mod tests;
@@ -1,8 +1,179 @@
-use crate::UnaryOperator;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attention_head_model::AttentionHeadModel,
+    error,
+    mega_man::MegaManModel,
+    multi_head_attention_model::MultiHeadAttentionModel,
+    perceptron::PerceptronModel,
+    simple::SimpleModel,
+    tensor::{Error, ErrorEnum},
+    transformer_model::{TransformerModel, TransformerModelConfig},
+    Device, FeedForwardActivation, TensorWithGrad, UnaryOperator,
+};
 
 pub trait Model {
     fn input_size(&self) -> Vec<usize>;
     fn output_size(&self) -> Vec<usize>;
+
+    /// The architecture of the model, which can be serialized and rebuilt with ModelConfig::build.
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Err(error!(ErrorEnum::UnsupportedOperation))
+    }
 }
 
 pub trait UnaryModel: UnaryOperator + Model {}
+
+/// Layer types and dimensions of a model.
+/// The weights are not included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ModelConfig {
+    Perceptron,
+    Simple {
+        sequence_length: usize,
+        vocab_size: usize,
+    },
+    MegaMan {
+        sequence_length: usize,
+        vocab_size: usize,
+    },
+    AttentionHead {
+        sequence_length: usize,
+        vocab_size: usize,
+        n_embd: usize,
+        causal_mask: bool,
+        dropout_probability: f32,
+    },
+    MultiHeadAttention {
+        sequence_length: usize,
+        vocab_size: usize,
+        n_embd: usize,
+        num_heads: usize,
+    },
+    Transformer {
+        layers: usize,
+        num_heads: usize,
+        dropout_probability: f32,
+        n_embd: usize,
+        sequence_length: usize,
+        /// None for a model without position embeddings.
+        max_position: Option<usize>,
+        vocab_size: usize,
+        causal_mask: bool,
+        /// The activation of the feed-forward networks, Gelu for a model
+        /// without position embeddings.
+        #[serde(default)]
+        activation: FeedForwardActivation,
+        /// The seed of the weights of a model with position embeddings,
+        /// see TransformerModelConfig. None uses a random seed.
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+}
+
+impl ModelConfig {
+    pub fn build(&self, device: &Device) -> Result<Box<dyn UnaryModel>, Error> {
+        let model: Box<dyn UnaryModel> = match *self {
+            ModelConfig::Perceptron => Box::new(PerceptronModel::new(device)?),
+            ModelConfig::Simple {
+                sequence_length,
+                vocab_size,
+            } => Box::new(SimpleModel::new(device, sequence_length, vocab_size)?),
+            ModelConfig::MegaMan {
+                sequence_length,
+                vocab_size,
+            } => Box::new(MegaManModel::new(device, sequence_length, vocab_size)?),
+            ModelConfig::AttentionHead {
+                sequence_length,
+                vocab_size,
+                n_embd,
+                causal_mask,
+                dropout_probability,
+            } => Box::new(AttentionHeadModel::new(
+                device,
+                sequence_length,
+                vocab_size,
+                n_embd,
+                causal_mask,
+                dropout_probability,
+            )?),
+            ModelConfig::MultiHeadAttention {
+                sequence_length,
+                vocab_size,
+                n_embd,
+                num_heads,
+            } => Box::new(MultiHeadAttentionModel::new_with_dimensions(
+                device,
+                sequence_length,
+                vocab_size,
+                n_embd,
+                num_heads,
+            )?),
+            ModelConfig::Transformer {
+                layers,
+                num_heads,
+                dropout_probability,
+                n_embd,
+                sequence_length,
+                max_position,
+                vocab_size,
+                causal_mask,
+                activation,
+                seed,
+            } => match max_position {
+                Some(max_position) => Box::new(TransformerModel::new_with_max_position(
+                    device,
+                    &TransformerModelConfig {
+                        layers,
+                        num_heads,
+                        dropout_probability,
+                        n_embd,
+                        sequence_length,
+                        max_position,
+                        vocab_size,
+                        causal_mask,
+                        activation,
+                        seed,
+                    },
+                )?),
+                // The model without position embeddings has no seed and uses Gelu.
+                None if seed.is_some() || activation != FeedForwardActivation::Gelu => {
+                    return Err(error!(ErrorEnum::IncorrectOperatorConfiguration))
+                }
+                None => Box::new(TransformerModel::new(
+                    device,
+                    layers,
+                    num_heads,
+                    dropout_probability,
+                    n_embd,
+                    sequence_length,
+                    vocab_size,
+                    causal_mask,
+                )?),
+            },
+        };
+        Ok(model)
+    }
+}
+
+impl UnaryModel for Box<dyn UnaryModel> {}
+
+impl UnaryOperator for Box<dyn UnaryModel> {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        self.as_ref().forward(input)
+    }
+}
+
+impl Model for Box<dyn UnaryModel> {
+    fn input_size(&self) -> Vec<usize> {
+        self.as_ref().input_size()
+    }
+
+    fn output_size(&self) -> Vec<usize> {
+        self.as_ref().output_size()
+    }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        self.as_ref().config()
+    }
+}
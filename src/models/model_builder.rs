@@ -0,0 +1,183 @@
+use crate::{
+    error, tensor::Error, tensor::ErrorEnum, Device, Embedding, Linear, Model, MultiHeadAttention,
+    MultiHeadAttentionConfig, Reshape, Sigmoid, Softmax, TensorWithGrad, TernaryOperator,
+    UnaryModel, UnaryOperator, WeightsInitialization,
+};
+
+/// Assemble a sequential model layer by layer.
+/// The builder tracks the shape of the output of the last layer, so each layer only takes
+/// the dimensions that it changes:
+///
+///   ModelBuilder::new(&device, sequence_length, vocab_size)
+///       .embedding(n_embd)
+///       .attention(num_heads, causal_mask)
+///       .linear(vocab_size)
+///       .softmax()
+///       .build()?
+///
+/// The first error of a layer is returned by build.
+pub struct ModelBuilder {
+    device: Device,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+    layers: Vec<Box<dyn UnaryOperator>>,
+    error: Option<Error>,
+}
+
+impl ModelBuilder {
+    /// The input is a sequence of one-hot encoded tokens.
+    pub fn new(device: &Device, sequence_length: usize, vocab_size: usize) -> Self {
+        Self {
+            device: device.clone(),
+            input_shape: vec![sequence_length, vocab_size],
+            output_shape: vec![sequence_length, vocab_size],
+            layers: vec![],
+            error: None,
+        }
+    }
+
+    pub fn output_size(&self) -> &[usize] {
+        &self.output_shape
+    }
+
+    fn push<F>(mut self, make_layer: F) -> Self
+    where
+        F: FnOnce(&Device, usize, usize) -> Result<(Box<dyn UnaryOperator>, Vec<usize>), Error>,
+    {
+        if self.error.is_some() {
+            return self;
+        }
+        let (rows, cols) = (self.output_shape[0], self.output_shape[1]);
+        match make_layer(&self.device, rows, cols) {
+            Ok((layer, output_shape)) => {
+                self.layers.push(layer);
+                self.output_shape = output_shape;
+            }
+            Err(error) => self.error = Some(error),
+        }
+        self
+    }
+
+    /// Map each token to a learned vector of n_embd values.
+    pub fn embedding(self, n_embd: usize) -> Self {
+        self.push(|device, rows, cols| {
+            let layer = Embedding::new(device, cols, n_embd)?;
+            Ok((Box::new(layer), vec![rows, n_embd]))
+        })
+    }
+
+    /// Multi-head self-attention, without dropout.
+    pub fn attention(self, num_heads: usize, causal_mask: bool) -> Self {
+        self.push(|device, rows, cols| {
+            let dropout_probability = 0.0;
+            let learnable_scale = false;
+            let layer = MultiHeadAttention::try_new(
+                device,
+                &MultiHeadAttentionConfig {
+                    rows,
+                    cols,
+                    causal_mask,
+                    num_heads,
+                    num_kv_heads: num_heads,
+                    dropout_probability,
+                    learnable_scale,
+                    seed: None,
+                    position_independent: false,
+                },
+            )?;
+            Ok((Box::new(SelfAttention { layer }), vec![rows, cols]))
+        })
+    }
+
+    pub fn linear(self, output_cols: usize) -> Self {
+        self.push(|device, rows, cols| {
+            let layer = Linear::new(
+                device,
+                output_cols,
+                cols,
+                WeightsInitialization::Kaiming,
+                rows,
+            )?;
+            Ok((Box::new(layer), vec![rows, output_cols]))
+        })
+    }
+
+    pub fn sigmoid(self) -> Self {
+        self.push(|device, rows, cols| Ok((Box::new(Sigmoid::new(device)), vec![rows, cols])))
+    }
+
+    /// The number of values must not change.
+    pub fn reshape(self, output_size: &[usize]) -> Self {
+        let output_size = output_size.to_owned();
+        self.push(|device, rows, cols| {
+            if output_size.len() != 2 || output_size.iter().product::<usize>() != rows * cols {
+                return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+            }
+            let layer = Reshape::new(device, vec![rows, cols], output_size.clone());
+            Ok((Box::new(layer), output_size))
+        })
+    }
+
+    /// The output layer of a model trained with SoftmaxCrossEntropyLoss,
+    /// like the other models of the crate.
+    pub fn softmax(self) -> Self {
+        self.push(|device, rows, cols| {
+            let layer = Softmax::new_with_next_is_cross_entropy_loss(device);
+            Ok((Box::new(layer), vec![rows, cols]))
+        })
+    }
+
+    pub fn build(self) -> Result<Box<dyn UnaryModel>, Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.layers.is_empty() {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let model = SequentialModel {
+            input_shape: self.input_shape,
+            output_shape: self.output_shape,
+            layers: self.layers,
+        };
+        Ok(Box::new(model))
+    }
+}
+
+struct SelfAttention {
+    layer: MultiHeadAttention,
+}
+
+impl UnaryOperator for SelfAttention {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        self.layer.forward(input, input, input)
+    }
+}
+
+/// The model built by ModelBuilder.
+struct SequentialModel {
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+    layers: Vec<Box<dyn UnaryOperator>>,
+}
+
+impl UnaryModel for SequentialModel {}
+
+impl UnaryOperator for SequentialModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let mut state = input.clone();
+        for layer in self.layers.iter() {
+            state = layer.forward(&state)?;
+        }
+        Ok(state)
+    }
+}
+
+impl Model for SequentialModel {
+    fn input_size(&self) -> Vec<usize> {
+        self.input_shape.clone()
+    }
+
+    fn output_size(&self) -> Vec<usize> {
+        self.output_shape.clone()
+    }
+}
@@ -1,11 +1,14 @@
 use crate::{
-    tensor::Error, Device, Embedding, Linear, Model, MultiHeadAttention, Softmax, TensorWithGrad,
-    TernaryOperator, UnaryModel, UnaryOperator, WeightsInitialization,
+    derive_seeds, tensor::Error, Device, Embedding, Linear, Model, ModelConfig, MultiHeadAttention,
+    MultiHeadAttentionConfig, Softmax, TensorWithGrad, TernaryOperator, UnaryModel, UnaryOperator,
+    WeightsInitialization,
 };
 
 pub struct MultiHeadAttentionModel {
     input_shape: Vec<usize>,
     output_shape: Vec<usize>,
+    n_embd: usize,
+    num_heads: usize,
     embedding: Embedding,
     multi_head_attention: MultiHeadAttention,
     linear: Linear,
@@ -18,30 +21,62 @@ impl MultiHeadAttentionModel {
     pub fn new(device: &Device, sequence_length: usize, vocab_size: usize) -> Result<Self, Error> {
         let n_embd = 768;
         let num_heads = 12;
+        Self::new_with_dimensions(device, sequence_length, vocab_size, n_embd, num_heads)
+    }
+
+    pub fn new_with_dimensions(
+        device: &Device,
+        sequence_length: usize,
+        vocab_size: usize,
+        n_embd: usize,
+        num_heads: usize,
+    ) -> Result<Self, Error> {
+        Self::new_with_seed(device, sequence_length, vocab_size, n_embd, num_heads, None)
+    }
+
+    /// The seeds of the embedding, of the attention and of the linear layer are derived from seed.
+    pub fn new_with_seed(
+        device: &Device,
+        sequence_length: usize,
+        vocab_size: usize,
+        n_embd: usize,
+        num_heads: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
         let dropout_probability = 0.0;
+        let seeds = derive_seeds(seed, 3);
 
-        let embedding = Embedding::new(device, vocab_size, n_embd)?;
+        let embedding = Embedding::new_with_seed(device, vocab_size, n_embd, seeds[0])?;
         let causal_mask = true;
         let multi_head_attention = MultiHeadAttention::try_new(
             device,
-            sequence_length,
-            n_embd,
-            causal_mask,
-            num_heads,
-            dropout_probability,
+            &MultiHeadAttentionConfig {
+                rows: sequence_length,
+                cols: n_embd,
+                causal_mask,
+                num_heads,
+                num_kv_heads: num_heads,
+                dropout_probability,
+                learnable_scale: false,
+                seed: seeds[1],
+                position_independent: false,
+            },
         )
         .unwrap();
-        let linear = Linear::new(
+        let linear = Linear::new_with_seed(
             device,
             vocab_size,
             n_embd,
             WeightsInitialization::Kaiming,
             sequence_length,
+            seeds[2],
         )?;
         let softmax = Softmax::new_with_next_is_cross_entropy_loss(device);
         let model = Self {
             input_shape: vec![sequence_length, vocab_size],
             output_shape: vec![sequence_length, vocab_size],
+            n_embd,
+            num_heads,
             embedding,
             multi_head_attention,
             linear,
@@ -71,4 +106,13 @@ impl Model for MultiHeadAttentionModel {
     fn output_size(&self) -> Vec<usize> {
         self.output_shape.clone()
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::MultiHeadAttention {
+            sequence_length: self.input_shape[0],
+            vocab_size: self.input_shape[1],
+            n_embd: self.n_embd,
+            num_heads: self.num_heads,
+        })
+    }
 }
@@ -0,0 +1,71 @@
+use crate::{
+    derive_seeds, tensor::Error, Device, Linear, Model, Sigmoid, TensorWithGrad, UnaryModel,
+    UnaryOperator, WeightsInitialization,
+};
+
+/// A linear layer, a sigmoid and another linear layer, for regression.
+/// The input and the output are rows.
+pub struct MultiLayerPerceptronModel {
+    input_cols: usize,
+    output_cols: usize,
+    linear_1: Linear,
+    sigmoid: Sigmoid,
+    linear_2: Linear,
+}
+
+impl UnaryModel for MultiLayerPerceptronModel {}
+
+impl MultiLayerPerceptronModel {
+    /// The seeds of the linear layers are derived from seed.
+    pub fn new(
+        device: &Device,
+        input_cols: usize,
+        hidden_cols: usize,
+        output_cols: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let seeds = derive_seeds(seed, 2);
+        let linear_1 = Linear::new_with_seed(
+            device,
+            hidden_cols,
+            input_cols,
+            WeightsInitialization::Kaiming,
+            1,
+            seeds[0],
+        )?;
+        let sigmoid = Sigmoid::new(device);
+        let linear_2 = Linear::new_with_seed(
+            device,
+            output_cols,
+            hidden_cols,
+            WeightsInitialization::Kaiming,
+            1,
+            seeds[1],
+        )?;
+        let model = Self {
+            input_cols,
+            output_cols,
+            linear_1,
+            sigmoid,
+            linear_2,
+        };
+        Ok(model)
+    }
+}
+
+impl UnaryOperator for MultiLayerPerceptronModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let hidden = self.linear_1.forward(input)?;
+        let activated = self.sigmoid.forward(&hidden)?;
+        self.linear_2.forward(&activated)
+    }
+}
+
+impl Model for MultiLayerPerceptronModel {
+    fn input_size(&self) -> Vec<usize> {
+        vec![1, self.input_cols]
+    }
+    fn output_size(&self) -> Vec<usize> {
+        vec![1, self.output_cols]
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    tensor::Error, Device, Linear, Model, TensorWithGrad, UnaryModel, UnaryOperator,
+    tensor::Error, Device, Linear, Model, ModelConfig, TensorWithGrad, UnaryModel, UnaryOperator,
     WeightsInitialization,
 };
 
@@ -30,4 +30,8 @@ impl Model for PerceptronModel {
     fn output_size(&self) -> Vec<usize> {
         vec![1, 1]
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::Perceptron)
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
-    tensor::Error, Device, Embedding, Linear, Model, Reshape, Sigmoid, Softmax, TensorWithGrad,
-    UnaryModel, UnaryOperator, WeightsInitialization,
+    tensor::Error, Device, Embedding, Linear, Model, ModelConfig, Reshape, Sigmoid, Softmax,
+    TensorWithGrad, UnaryModel, UnaryOperator, WeightsInitialization,
 };
 
 pub struct SimpleModel {
@@ -91,4 +91,11 @@ impl Model for SimpleModel {
     fn output_size(&self) -> Vec<usize> {
         self.output_shape.clone()
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::Simple {
+            sequence_length: self.input_shape[0],
+            vocab_size: self.input_shape[1],
+        })
+    }
 }
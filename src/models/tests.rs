@@ -0,0 +1,409 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    datasets::into_one_hot_encoded_rows,
+    model_builder::ModelBuilder,
+    neural_program::NeuralProgram,
+    schedulers::DefaultStreamScheduler,
+    stream::StreamTrait,
+    tensor::ErrorEnum,
+    transformer_model::{TransformerModel, TransformerModelConfig},
+    Adam, Category, Device, FeedForwardActivation, Model, ModelConfig, NeuralMachine,
+    SoftmaxCrossEntropyLoss, TensorWithGrad,
+};
+
+fn transformer_model_config(sequence_length: usize, max_position: usize) -> TransformerModelConfig {
+    TransformerModelConfig {
+        layers: 1,
+        num_heads: 2,
+        dropout_probability: 0.0,
+        n_embd: 8,
+        sequence_length,
+        max_position,
+        vocab_size: 16,
+        causal_mask: true,
+        activation: FeedForwardActivation::Gelu,
+        seed: None,
+    }
+}
+
+#[test]
+fn transformer_model_with_max_position_larger_than_sequence_length() {
+    let device = Device::default();
+    let layers = 1;
+    let num_heads = 2;
+    let dropout_probability = 0.0;
+    let n_embd = 8;
+    let train_len = 32;
+    let max_position = 64;
+    let vocab_size = 16;
+    let causal_mask = true;
+    let model = TransformerModel::new_with_max_position(
+        &device,
+        &TransformerModelConfig {
+            layers,
+            num_heads,
+            dropout_probability,
+            n_embd,
+            sequence_length: train_len,
+            max_position,
+            vocab_size,
+            causal_mask,
+            activation: FeedForwardActivation::Gelu,
+            seed: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(vec![train_len, vocab_size], model.input_size());
+
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, true, 1).unwrap();
+    NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 4).unwrap();
+
+    let inference_len = 48;
+    let inference_device = Device::default();
+    let inference_model = model
+        .with_sequence_length(&inference_device, inference_len)
+        .unwrap();
+    assert_eq!(
+        vec![inference_len, vocab_size],
+        inference_model.input_size()
+    );
+    let mut neural_machine = NeuralMachine::<f32, DefaultStreamScheduler>::try_new_no_grad(
+        &inference_device,
+        &inference_model,
+        4,
+    )
+    .unwrap();
+    let input_tokens = (0..inference_len)
+        .map(|i| i % vocab_size)
+        .collect::<Vec<_>>();
+    let input = into_one_hot_encoded_rows(&inference_device, &input_tokens, vocab_size).unwrap();
+    let output: TensorWithGrad = neural_machine.infer(&input).unwrap();
+    assert_eq!(vec![inference_len, vocab_size], *output.tensor().size());
+
+    assert!(model
+        .with_sequence_length(&Device::default(), max_position + 1)
+        .is_err());
+}
+
+#[test]
+fn transformer_model_with_max_position_smaller_than_sequence_length() {
+    let device = Device::default();
+    let model = TransformerModel::new_with_max_position(&device, &transformer_model_config(32, 16));
+    assert!(model.is_err());
+}
+
+#[test]
+fn transformer_model_rebuilt_from_serialized_config() {
+    let device = Device::default();
+    let config = TransformerModelConfig {
+        dropout_probability: 0.1,
+        ..transformer_model_config(16, 32)
+    };
+    let model = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    let config = model.config().unwrap();
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, true, 1).unwrap();
+    let neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 4).unwrap();
+
+    let serialized = serde_json::to_string(&config).unwrap();
+    let deserialized: ModelConfig = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(config, deserialized);
+
+    let rebuilt_device = Device::default();
+    let rebuilt_model = deserialized.build(&rebuilt_device).unwrap();
+    assert_eq!(model.input_size(), rebuilt_model.input_size());
+    assert_eq!(model.output_size(), rebuilt_model.output_size());
+    assert_eq!(config, rebuilt_model.config().unwrap());
+
+    // Parameters are registered on the device, so the machine needs its own device.
+    let machine_device = Device::default();
+    let rebuilt_loss_operator = SoftmaxCrossEntropyLoss::new(&machine_device);
+    let rebuilt_neural_machine = NeuralMachine::<f32, DefaultStreamScheduler>::from_config(
+        &machine_device,
+        &deserialized,
+        &rebuilt_loss_operator,
+        &optimizer,
+        true,
+        1,
+        4,
+    )
+    .unwrap();
+    for category in [
+        Category::EnableDropout,
+        Category::DisableDropout,
+        Category::Inference,
+        Category::Loss,
+        Category::Gradient,
+        Category::Optimization,
+    ] {
+        assert_eq!(
+            neural_machine.instructions(&category).len(),
+            rebuilt_neural_machine.instructions(&category).len()
+        );
+    }
+}
+
+#[test]
+fn model_built_with_the_builder_forward_passes() {
+    let device = Device::default();
+    let sequence_length = 4;
+    let vocab_size = 6;
+    let model = ModelBuilder::new(&device, sequence_length, vocab_size)
+        .embedding(8)
+        .attention(2, true)
+        .linear(vocab_size)
+        .softmax()
+        .build()
+        .unwrap();
+    assert_eq!(vec![sequence_length, vocab_size], model.input_size());
+    assert_eq!(vec![sequence_length, vocab_size], model.output_size());
+
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, true, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let input = into_one_hot_encoded_rows(&device, &[1, 2, 3, 4], vocab_size).unwrap();
+    let output: TensorWithGrad = neural_machine.infer(&input).unwrap();
+    assert_eq!(vec![sequence_length, vocab_size], *output.tensor().size());
+    for row in output.tensor().rows_iter().unwrap().iter() {
+        assert_lt!((1.0 - row.iter().sum::<f32>()).abs(), 1e-5);
+    }
+}
+
+#[test]
+fn model_builder_reports_the_first_error() {
+    let device = Device::default();
+    let result = ModelBuilder::new(&device, 4, 6)
+        .embedding(8)
+        .reshape(&[3, 3])
+        .linear(6)
+        .build();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        result.map(|_| ()).map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn transformer_model_logits_are_the_input_of_the_softmax() {
+    let device = Device::default();
+    let sequence_length = 4;
+    let vocab_size = 6;
+    let model =
+        TransformerModel::new(&device, 1, 2, 0.0, 8, sequence_length, vocab_size, true).unwrap();
+    let input = into_one_hot_encoded_rows(&device, &[1, 2, 3, 4], vocab_size).unwrap();
+    let (logits, probabilities) = model.forward_with_logits(&input).unwrap();
+    assert_eq!(vec![sequence_length, vocab_size], *logits.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    for tensor in probabilities.get_tape().iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+
+    let logits = logits.tensor().get_values().unwrap();
+    let probabilities = probabilities.tensor().get_values().unwrap();
+    for (logits, probabilities) in logits
+        .chunks(vocab_size)
+        .zip(probabilities.chunks(vocab_size))
+    {
+        let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let sum = logits.iter().map(|x| (x - max).exp()).sum::<f32>();
+        for (logit, probability) in logits.iter().zip(probabilities.iter()) {
+            assert_lt!(((logit - max).exp() / sum - probability).abs(), 1e-5);
+        }
+    }
+}
+
+#[test]
+fn transformer_model_distinguishes_the_positions_of_a_token() {
+    let device = Device::default();
+    let sequence_length = 4;
+    let vocab_size = 6;
+    let config = TransformerModelConfig {
+        vocab_size,
+        causal_mask: false,
+        ..transformer_model_config(sequence_length, sequence_length)
+    };
+    let model = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    // Without a mask, only the position embeddings make the rows of a repeated token differ.
+    let input = into_one_hot_encoded_rows(&device, &[3, 3, 3, 3], vocab_size).unwrap();
+    let (logits, _probabilities) = model.forward_with_logits(&input).unwrap();
+
+    let device_stream = device.new_stream().unwrap();
+    for tensor in logits.get_tape().iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+
+    let logits = logits.tensor().get_values().unwrap();
+    let rows = logits.chunks(vocab_size).collect::<Vec<_>>();
+    for row in rows.iter().skip(1) {
+        assert_ne!(rows[0], *row);
+    }
+}
+
+#[test]
+fn transformer_model_new_has_no_position_embedding() {
+    let device = Device::default();
+    let model = TransformerModel::new(&device, 1, 2, 0.0, 8, 4, 6, true).unwrap();
+    assert!(model.position_embedding().is_none());
+    assert_eq!(4, model.max_position());
+    assert_eq!(vec![4, 6], model.input_size());
+}
+
+#[test]
+fn transformer_models_with_the_same_seed_have_the_same_position_embedding() {
+    let device = Device::default();
+    let config = TransformerModelConfig {
+        seed: Some(42),
+        ..transformer_model_config(4, 8)
+    };
+    let model_1 = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    let model_2 = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    assert_eq!(
+        model_1
+            .position_embedding()
+            .unwrap()
+            .tensor()
+            .get_values()
+            .unwrap(),
+        model_2
+            .position_embedding()
+            .unwrap()
+            .tensor()
+            .get_values()
+            .unwrap()
+    );
+}
+
+#[test]
+fn transformer_model_trained_below_max_position_infers_at_max_position() {
+    let device = Device::default();
+    let sequence_length = 4;
+    let max_position = 8;
+    let vocab_size = 6;
+    let n_embd = 8;
+    let config = TransformerModelConfig {
+        vocab_size,
+        n_embd,
+        seed: Some(42),
+        ..transformer_model_config(sequence_length, max_position)
+    };
+    let model = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    let position_embedding = model.position_embedding().unwrap().clone();
+    let initial_position_embedding = position_embedding.tensor().get_values().unwrap();
+
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, true, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 4).unwrap();
+
+    let input_tokens = vec![1, 2, 3, 4];
+    let output_tokens = vec![2, 3, 4, 5];
+    let input = into_one_hot_encoded_rows(&device, &input_tokens, vocab_size).unwrap();
+    let output = into_one_hot_encoded_rows(&device, &output_tokens, vocab_size).unwrap();
+    for _ in 0..3 {
+        neural_machine.infer(&input).unwrap();
+        neural_machine.loss(&output).unwrap();
+        neural_machine.compute_gradient().unwrap();
+        neural_machine.optimize().unwrap();
+    }
+
+    // Only the positions of the training sequences are gathered, so only they are trained.
+    let trained_position_embedding = position_embedding.tensor().get_values().unwrap();
+    let trained_len = sequence_length * n_embd;
+    assert_ne!(
+        initial_position_embedding[..trained_len],
+        trained_position_embedding[..trained_len]
+    );
+    assert_eq!(
+        initial_position_embedding[trained_len..],
+        trained_position_embedding[trained_len..]
+    );
+    let expected = neural_machine
+        .infer(&input)
+        .unwrap()
+        .tensor()
+        .get_values()
+        .unwrap();
+
+    // With a causal mask, the rows of a longer prompt that starts with the training sequence
+    // are the rows of the training sequence.
+    let inference_device = Device::default();
+    let inference_model = model
+        .with_sequence_length(&inference_device, max_position)
+        .unwrap();
+    let mut inference_machine = NeuralMachine::<f32, DefaultStreamScheduler>::try_new_no_grad(
+        &inference_device,
+        &inference_model,
+        4,
+    )
+    .unwrap();
+    let mut prompt = input_tokens.clone();
+    prompt.extend([5, 4, 3, 2]);
+    let input = into_one_hot_encoded_rows(&inference_device, &prompt, vocab_size).unwrap();
+    let actual = inference_machine.infer(&input).unwrap();
+    assert_eq!(vec![max_position, vocab_size], *actual.tensor().size());
+    let actual = actual.tensor().get_values().unwrap();
+    // The zip stops after the rows of the training sequence.
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        assert_lt!((actual - expected).abs(), 1e-5);
+    }
+}
+
+#[test]
+fn transformer_model_rebuilt_from_config_has_the_same_seeded_weights() {
+    let device = Device::default();
+    let config = TransformerModelConfig {
+        seed: Some(7),
+        ..transformer_model_config(4, 8)
+    };
+    let model = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    let config = model.config().unwrap();
+    let serialized = serde_json::to_string(&config).unwrap();
+    let deserialized: ModelConfig = serde_json::from_str(&serialized).unwrap();
+
+    let rebuilt_device = Device::default();
+    deserialized.build(&rebuilt_device).unwrap();
+    let weights = |device: &Device| {
+        device
+            .parameter_tensors()
+            .iter()
+            .map(|x| x.tensor().get_values().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(weights(&device)[0], weights(&rebuilt_device)[0]);
+}
+
+#[test]
+fn transformer_model_rebuilt_from_config_keeps_the_activation() {
+    let device = Device::default();
+    let config = TransformerModelConfig {
+        activation: FeedForwardActivation::Silu,
+        ..transformer_model_config(4, 8)
+    };
+    let model = TransformerModel::new_with_max_position(&device, &config).unwrap();
+    let config = model.config().unwrap();
+    let serialized = serde_json::to_string(&config).unwrap();
+    let deserialized: ModelConfig = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(config, deserialized);
+    match deserialized {
+        ModelConfig::Transformer { activation, .. } => {
+            assert_eq!(FeedForwardActivation::Silu, activation)
+        }
+        _ => panic!("expected a transformer config"),
+    }
+}
@@ -1,8 +1,16 @@
 use crate::statistics::layer_norm::LayerNormalization;
-use crate::tensor::Error;
-use crate::transformer::Transformer;
-use crate::{Device, Dropout, UnaryModel, UnaryOperator, WeightsInitialization};
-use crate::{Embedding, Linear, Model, Softmax, TensorWithGrad};
+use crate::tensor::{Error, ErrorEnum};
+use crate::transformer::{Transformer, TransformerConfig};
+use crate::{
+    derive_seeds, error, new_tensor_with_grad, weights_initialization_rng, Add, BinaryOperator,
+    MatMul,
+};
+use crate::{
+    Device, Dropout, FeedForwardActivation, UnaryModel, UnaryOperator, WeightsInitialization,
+};
+use crate::{Embedding, Linear, Model, ModelConfig, Softmax, TensorWithGrad};
+use rand::Rng;
+use rand_distr::Normal;
 
 /// See
 /// Full GPT Architecture
@@ -16,18 +24,60 @@ use crate::{Embedding, Linear, Model, Softmax, TensorWithGrad};
 /// OpenAI GPT 1
 /// https://huggingface.co/openai-community/openai-gpt
 pub struct TransformerModel {
+    layers: usize,
+    num_heads: usize,
+    dropout_probability: f32,
+    n_embd: usize,
+    sequence_length: usize,
+    max_position: Option<usize>,
+    causal_mask: bool,
+    activation: FeedForwardActivation,
     input_shape: Vec<usize>,
     output_shape: Vec<usize>,
     embedding: Embedding,
+    position_embedding: Option<PositionEmbedding>,
+    add: Add,
     dropout: Dropout,
     transformers: Vec<Transformer>,
     layer_norm: LayerNormalization,
     linear: Linear,
     softmax: Softmax,
+    seed: Option<u64>,
+    /// The parameters, in the order in which they were created.
+    parameters: Vec<TensorWithGrad>,
 }
 
 impl UnaryModel for TransformerModel {}
 
+/// The learned position embeddings of the positions of the input.
+struct PositionEmbedding {
+    /// One row per position, up to max_position.
+    table: TensorWithGrad,
+    /// The one-hot rows of the positions 0..sequence_length, which gather their rows of the table.
+    positions: TensorWithGrad,
+    matmul: MatMul,
+}
+
+/// The dimensions of a TransformerModel with learned position embeddings.
+/// The model runs on sequences of length `sequence_length`
+/// and the position embedding table has `max_position` rows.
+#[derive(Clone, Copy, Debug)]
+pub struct TransformerModelConfig {
+    pub layers: usize,
+    pub num_heads: usize,
+    pub dropout_probability: f32,
+    pub n_embd: usize,
+    pub sequence_length: usize,
+    pub max_position: usize,
+    pub vocab_size: usize,
+    pub causal_mask: bool,
+    /// The activation of the feed-forward networks of the layers.
+    pub activation: FeedForwardActivation,
+    /// The seeds of the embedding, of the position embedding and of the linear layer
+    /// are derived from seed.
+    pub seed: Option<u64>,
+}
+
 impl TransformerModel {
     pub fn new(
         device: &Device,
@@ -65,22 +115,198 @@ impl TransformerModel {
         let softmax = Softmax::new_with_next_is_cross_entropy_loss(device);
 
         let model = Self {
+            layers,
+            num_heads,
+            dropout_probability,
+            n_embd,
+            sequence_length,
+            max_position: None,
+            causal_mask,
+            activation: FeedForwardActivation::Gelu,
+            input_shape: vec![sequence_length, vocab_size],
+            output_shape: vec![sequence_length, vocab_size],
+            embedding,
+            position_embedding: None,
+            add: Add::new(device),
+            dropout,
+            transformers,
+            layer_norm,
+            linear,
+            softmax,
+            seed: None,
+            parameters: vec![],
+        };
+        Ok(model)
+    }
+
+    /// A model with learned position embeddings, one row per position up to `max_position`,
+    /// which are added to the token embeddings.
+    ///
+    /// The input has `sequence_length` rows and only the position embeddings
+    /// of these positions are gathered, so that the rows of the table after `sequence_length`
+    /// are not used nor trained.
+    /// The other parameters are shared by every position,
+    /// so that the trained model can run on a longer sequence, see with_sequence_length.
+    pub fn new_with_max_position(
+        device: &Device,
+        config: &TransformerModelConfig,
+    ) -> Result<Self, Error> {
+        let TransformerModelConfig {
+            layers,
+            num_heads,
+            dropout_probability,
+            n_embd,
+            sequence_length,
+            max_position,
+            vocab_size,
+            causal_mask,
+            activation,
+            seed,
+        } = *config;
+        if sequence_length > max_position {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let first_parameter = device.parameter_tensors().len();
+        let seeds = derive_seeds(seed, 3);
+        let embedding = Embedding::new_with_seed(device, vocab_size, n_embd, seeds[0])?;
+        let position_embedding =
+            get_position_embedding(device, sequence_length, max_position, n_embd, seeds[1])?;
+        let dropout = Dropout::try_new(device, sequence_length, n_embd, dropout_probability)?;
+        let transformer_config = TransformerConfig {
+            rows: sequence_length,
+            cols: n_embd,
+            causal_mask,
+            num_heads,
+            attention_dropout_probability: dropout_probability,
+            residual_dropout_probability: dropout_probability,
+            position_independent: true,
+            activation,
+        };
+        let transformers = (0..layers)
+            .map(|_| Transformer::try_new_with_config(device, &transformer_config))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let layer_norm =
+            LayerNormalization::try_new_position_independent(device, sequence_length, n_embd)?;
+        let linear = Linear::new_with_seed(
+            device,
+            vocab_size,
+            n_embd,
+            WeightsInitialization::Kaiming,
+            1,
+            seeds[2],
+        )?;
+        let softmax = Softmax::new_with_next_is_cross_entropy_loss(device);
+        let parameters = device.parameter_tensors()[first_parameter..].to_vec();
+
+        let model = Self {
+            layers,
+            num_heads,
+            dropout_probability,
+            n_embd,
+            sequence_length,
+            max_position: Some(max_position),
+            causal_mask,
+            activation,
             input_shape: vec![sequence_length, vocab_size],
             output_shape: vec![sequence_length, vocab_size],
             embedding,
+            position_embedding: Some(position_embedding),
+            add: Add::new(device),
             dropout,
             transformers,
             layer_norm,
             linear,
             softmax,
+            seed,
+            parameters,
         };
         Ok(model)
     }
+
+    /// The same model, with the current values of its parameters,
+    /// for inputs of `sequence_length` rows, up to `max_position`.
+    /// This is how a model trained on short sequences runs on a longer prompt.
+    ///
+    /// The parameters of the new model are registered on `device`,
+    /// so a device that does not train the first model should be used.
+    pub fn with_sequence_length(
+        &self,
+        device: &Device,
+        sequence_length: usize,
+    ) -> Result<Self, Error> {
+        let max_position = self
+            .max_position
+            .ok_or(error!(ErrorEnum::UnsupportedOperation))?;
+        let model = Self::new_with_max_position(
+            device,
+            &TransformerModelConfig {
+                layers: self.layers,
+                num_heads: self.num_heads,
+                dropout_probability: self.dropout_probability,
+                n_embd: self.n_embd,
+                sequence_length,
+                max_position,
+                vocab_size: self.input_shape[1],
+                causal_mask: self.causal_mask,
+                activation: self.activation,
+                seed: self.seed,
+            },
+        )?;
+        for (parameter, new_parameter) in self.parameters.iter().zip(model.parameters.iter()) {
+            new_parameter
+                .tensor()
+                .set_values(parameter.tensor().get_values()?)?;
+        }
+        Ok(model)
+    }
+
+    pub fn sequence_length(&self) -> usize {
+        self.sequence_length
+    }
+
+    /// The context window at inference, which is the sequence length
+    /// for a model without position embeddings.
+    pub fn max_position(&self) -> usize {
+        self.max_position.unwrap_or(self.sequence_length)
+    }
+
+    /// The learned position embeddings, one row per position up to max_position,
+    /// if the model was created with new_with_max_position.
+    pub fn position_embedding(&self) -> Option<&TensorWithGrad> {
+        self.position_embedding
+            .as_ref()
+            .map(|position_embedding| &position_embedding.table)
+    }
 }
 
 impl UnaryOperator for TransformerModel {
     fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let (_logits, probabilities) = self.forward_with_logits(input)?;
+        Ok(probabilities)
+    }
+}
+
+impl TransformerModel {
+    /// The logits, which are the output of the last linear layer, and the probabilities,
+    /// which are their softmax.
+    /// The logits are used for temperature calibration and confidence analysis.
+    pub fn forward_with_logits(
+        &self,
+        input: &TensorWithGrad,
+    ) -> Result<(TensorWithGrad, TensorWithGrad), Error> {
         let embedding = self.embedding.forward(input)?;
+        let embedding = match &self.position_embedding {
+            Some(PositionEmbedding {
+                table,
+                positions,
+                matmul,
+            }) => {
+                let position_embedding = matmul.forward(positions, table)?;
+                self.add.forward(&embedding, &position_embedding)?
+            }
+            None => embedding,
+        };
         let dropout = self.dropout.forward(&embedding)?;
         let mut transformed_outputs = vec![];
         for (layer, transformer) in self.transformers.iter().enumerate() {
@@ -93,9 +319,9 @@ impl UnaryOperator for TransformerModel {
         }
         let transformed = &transformed_outputs[transformed_outputs.len() - 1];
         let normalized_output = self.layer_norm.forward(&transformed)?;
-        let linear = self.linear.forward(&normalized_output)?;
-        let softmax = self.softmax.forward(&linear)?;
-        Ok(softmax)
+        let logits = self.linear.forward(&normalized_output)?;
+        let probabilities = self.softmax.forward(&logits)?;
+        Ok((logits, probabilities))
     }
 }
 
@@ -107,4 +333,55 @@ impl Model for TransformerModel {
     fn output_size(&self) -> Vec<usize> {
         self.output_shape.clone()
     }
+
+    fn config(&self) -> Result<ModelConfig, Error> {
+        Ok(ModelConfig::Transformer {
+            layers: self.layers,
+            num_heads: self.num_heads,
+            dropout_probability: self.dropout_probability,
+            n_embd: self.n_embd,
+            sequence_length: self.sequence_length,
+            max_position: self.max_position,
+            vocab_size: self.input_shape[1],
+            causal_mask: self.causal_mask,
+            activation: self.activation,
+            seed: self.seed,
+        })
+    }
+}
+
+/// The learned position embeddings, one row per position, which are added to the token embeddings.
+/// Like GPT-1, they are initialized with N(0, 0.02).
+fn get_position_embedding(
+    device: &Device,
+    sequence_length: usize,
+    max_position: usize,
+    n_embd: usize,
+    seed: Option<u64>,
+) -> Result<PositionEmbedding, Error> {
+    let mut rng = weights_initialization_rng(seed);
+    let distribution =
+        Normal::new(0.0, 0.02).map_err(|_| error!(ErrorEnum::UnsupportedOperation))?;
+    let values = (0..max_position * n_embd)
+        .map(|_| rng.sample(distribution))
+        .collect::<Vec<_>>();
+    let table = new_tensor_with_grad!(device, max_position, n_embd, values, &[], true, true)?;
+    let mut positions = vec![0.0; sequence_length * max_position];
+    for position in 0..sequence_length {
+        positions[position * max_position + position] = 1.0;
+    }
+    let positions = new_tensor_with_grad!(
+        device,
+        sequence_length,
+        max_position,
+        positions,
+        &[],
+        false,
+        false
+    )?;
+    Ok(PositionEmbedding {
+        table,
+        positions,
+        matmul: MatMul::new(device, false),
+    })
 }
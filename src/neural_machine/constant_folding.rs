@@ -0,0 +1,87 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{opcode::OpCode, stream::StreamTrait, tensor::Error, Category, Device, Instruction};
+
+/// Evaluate once the inference instructions whose inputs are all constants.
+///
+/// A tensor is constant when it is a literal: it is not a machine input,
+/// no instruction writes to it, and it is not a parameter.
+/// The parameters, frozen or not, can be changed after the machine is built
+/// (e.g. loaded weights or an exponential moving average), so they are never folded.
+/// The output of an evaluated instruction becomes constant too if that instruction is
+/// its only writer, so whole subgraphs (positional encodings, one-hot constants) are folded.
+///
+/// Returns the remaining instructions and the evaluated ones.
+pub fn fold_constant_instructions(
+    device: &Device,
+    machine_inputs: &[usize],
+    instructions: Vec<Instruction>,
+) -> Result<(Vec<Instruction>, Vec<Instruction>), Error> {
+    let mut writers = HashMap::<usize, usize>::new();
+    for instruction in instructions.iter() {
+        for output in instruction.outputs().iter() {
+            *writers.entry(output.name()).or_default() += 1;
+        }
+    }
+
+    // Frozen parameters are internal tensors.
+    let parameters = device
+        .parameter_tensors()
+        .iter()
+        .chain(device.internal_tensors().iter())
+        .chain(device.host_parameter_tensors().iter())
+        .chain(device.host_internal_tensors().iter())
+        .map(|x| x.tensor().name())
+        .collect::<HashSet<_>>();
+
+    let is_constant = |name: &usize, constants: &HashSet<usize>| {
+        constants.contains(name)
+            || (!machine_inputs.contains(name)
+                && !writers.contains_key(name)
+                && !parameters.contains(name))
+    };
+
+    let mut constants = HashSet::<usize>::new();
+    let mut folded = vec![false; instructions.len()];
+    for (i, instruction) in instructions.iter().enumerate() {
+        if instruction.category() != Category::Inference || !is_foldable(instruction.opcode()) {
+            continue;
+        }
+        let inputs = instruction.inputs();
+        let outputs = instruction.outputs();
+        let inputs_are_constant = !inputs.is_empty()
+            && inputs
+                .iter()
+                .all(|input| is_constant(&input.name(), &constants));
+        let outputs_have_one_writer = outputs.iter().all(|output| {
+            writers.get(&output.name()) == Some(&1)
+                && !inputs.iter().any(|input| input.name() == output.name())
+        });
+        if inputs_are_constant && outputs_have_one_writer {
+            for output in outputs.iter() {
+                constants.insert(output.name());
+            }
+            folded[i] = true;
+        }
+    }
+
+    let device_stream = device.new_stream()?;
+    let mut remaining_instructions = vec![];
+    let mut constant_instructions = vec![];
+    for (instruction, folded) in instructions.into_iter().zip(folded) {
+        if folded {
+            instruction.execute(device, &device_stream)?;
+            constant_instructions.push(instruction);
+        } else {
+            remaining_instructions.push(instruction);
+        }
+    }
+    device_stream.wait_for()?;
+
+    Ok((remaining_instructions, constant_instructions))
+}
+
+/// Random instructions must be executed on every forward pass.
+fn is_foldable(opcode: &OpCode) -> bool {
+    !matches!(opcode, OpCode::Bernoulli)
+}
@@ -1,6 +1,6 @@
 use crate::{
     opcode::OpCode,
-    stream::DeviceStream,
+    stream::{DeviceStream, DeviceStreamEnum},
     tensor::{Error, Tensor},
     Device, OperatorAttributes,
 };
@@ -136,16 +136,78 @@ impl Instruction {
         self.category.clone()
     }
 
+    /// Copy this instruction into another category.
+    pub fn with_category(&self, category: Category) -> Self {
+        let mut instruction = self.clone();
+        instruction.category = category;
+        instruction
+    }
+
     pub fn opcode(&self) -> &OpCode {
         &self.opcode
     }
+    pub fn attributes(&self) -> &OperatorAttributes {
+        &self.attributes
+    }
     pub fn inputs(&self) -> impl Deref<Target = Vec<Tensor>> + '_ {
         self.inputs.deref()
     }
     pub fn outputs(&self) -> impl Deref<Target = Vec<Tensor>> + '_ {
         self.outputs.deref()
     }
+
+    /// Hardware-independent estimate of the floating-point operations of the instruction:
+    /// 2 * m * n * k for Gemm, plus the biases and the activation for LinearActivation,
+    /// no operation for the memory management op codes,
+    /// and the number of output values for the other op codes.
+    pub fn estimated_flops(&self) -> u64 {
+        match (&self.opcode, &self.attributes) {
+            (OpCode::Gemm, OperatorAttributes::ThreeBools(transa, _, _)) => {
+                // op(A) is m by k and C is m by n.
+                let a = &self.inputs[0];
+                let (m, k) = match transa {
+                    false => (a.rows(), a.cols()),
+                    true => (a.cols(), a.rows()),
+                };
+                let n = self.outputs[0].len() / m.max(1);
+                2 * (m * n * k) as u64
+            }
+            (OpCode::LinearActivation, _) => {
+                let a = &self.inputs[0];
+                let (m, k) = (a.rows(), a.cols());
+                let len = self.outputs[0].len();
+                let n = len / m.max(1);
+                let has_biases = self.inputs.len() == 3;
+                let element_wise_passes = if has_biases { 2 } else { 1 };
+                2 * (m * n * k) as u64 + (element_wise_passes * len) as u64
+            }
+            (OpCode::Allocate | OpCode::Deallocate, _) => 0,
+            _ => self.outputs.iter().map(|x| x.len() as u64).sum(),
+        }
+    }
+
     pub fn execute(&self, device: &Device, device_stream: &DeviceStream) -> Result<(), Error> {
+        if self.is_pinned_to_host(device_stream) {
+            return device
+                .execute_on_host(|host, host_stream| self.execute_on_device(host, host_stream));
+        }
+        self.execute_on_device(device, device_stream)
+    }
+
+    /// Instructions of operators that were created on the host device
+    /// only write host tensors. They can not run on a GPU stream.
+    fn is_pinned_to_host(&self, device_stream: &DeviceStream) -> bool {
+        !matches!(device_stream.variant, DeviceStreamEnum::CpuDeviceStream)
+            && !matches!(self.opcode, OpCode::ToDevice)
+            && !self.outputs.is_empty()
+            && self.outputs.iter().all(|x| x.is_on_host())
+    }
+
+    fn execute_on_device(
+        &self,
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
         let attributes = &self.attributes;
         let inputs: Vec<&Tensor> = self.inputs.iter().collect();
         #[cfg(debug_assertions)]
@@ -1,7 +1,12 @@
+#[cfg(test)]
+mod tests;
+
+mod constant_folding;
 mod instruction;
 pub use instruction::*;
 mod neural_machine;
 pub use neural_machine::*;
 pub mod neural_program;
+mod operator_fusion;
 pub mod schedulers;
 pub mod streams;
@@ -3,14 +3,23 @@ use std::{marker::PhantomData, ops::Deref, sync::Arc};
 use crate::schedulers::SchedulerTrait;
 use crate::stream::StreamTrait;
 use crate::{
-    neural_machine::streams::stream::print_streams, neural_program::NeuralProgram,
-    schedulers::StreamExecutor, stream::DeviceStream, tensor::Error, Category, Device, Instruction,
-    TensorWithGrad,
+    datasets::into_one_hot_encoded_rows,
+    error, get_row_argmax,
+    lr_scheduler::LearningRateTensors,
+    neural_machine::streams::stream::print_streams,
+    neural_program::NeuralProgram,
+    schedulers::StreamExecutor,
+    stream::DeviceStream,
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, Device, DeviceTrait, Instruction, ModelConfig, OptimizerTrait,
+    TensorWithGrad, UnaryModel,
 };
 
+use super::constant_folding::fold_constant_instructions;
+use super::operator_fusion::fuse_linear_activation_instructions;
 use super::streams::{
     instruction::make_simple_instructions,
-    stream::{make_streams, Stream},
+    stream::{make_streams, merge_stream_chains, Stream},
     verify_machine_inputs,
 };
 
@@ -20,10 +29,16 @@ where
 {
     device: Device,
     io_stream: DeviceStream,
+    handler: StreamExecutor,
     example_input: TensorWithGrad,
     example_output: TensorWithGrad,
     machine_output: TensorWithGrad,
     loss: TensorWithGrad,
+    gradient_buffers: Vec<Tensor>,
+    host_gradient_buffers: Vec<Tensor>,
+    learning_rate_tensors: LearningRateTensors,
+
+    constant_instructions: Arc<Vec<Instruction>>,
 
     enable_dropout_instructions: Arc<Vec<Instruction>>,
     enable_dropout_streams: Arc<Vec<Stream>>,
@@ -61,7 +76,55 @@ where
         program: NeuralProgram,
         maximum_device_streams: usize,
     ) -> Result<Self, Error> {
-        let all_instructions = program.instructions;
+        Self::try_new_with_limit(
+            device,
+            program,
+            maximum_device_streams,
+            usize::MAX,
+            usize::MAX,
+        )
+    }
+
+    /// Like try_new, but fails if the compiled program has more than max_instructions
+    /// instructions, to protect a server from an accidentally huge model.
+    /// The constant instructions, which are executed once when the machine is built,
+    /// are not counted.
+    /// The schedulers dispatch at most queue_capacity streams ahead to each device stream.
+    pub fn try_new_with_limit(
+        device: &Device,
+        program: NeuralProgram,
+        maximum_device_streams: usize,
+        max_instructions: usize,
+        queue_capacity: usize,
+    ) -> Result<Self, Error> {
+        let example_input = program.example_input;
+        let example_output = program.example_output;
+        let machine_output = program.machine_output;
+        let loss = program.loss;
+        let learning_rate_tensors = program.learning_rate_tensors;
+
+        let machine_inputs = vec![
+            example_input.tensor().name(),
+            example_output.tensor().name(),
+        ];
+        let (all_instructions, constant_instructions) =
+            fold_constant_instructions(device, &machine_inputs, program.instructions)?;
+        let constant_instructions = Arc::new(constant_instructions);
+
+        let machine_tensors = vec![
+            example_input.tensor().name(),
+            example_output.tensor().name(),
+            machine_output.tensor().name(),
+            loss.tensor().name(),
+        ];
+        let all_instructions =
+            fuse_linear_activation_instructions(&machine_tensors, all_instructions);
+        if all_instructions.len() > max_instructions {
+            return Err(error!(ErrorEnum::InstructionLimitExceeded {
+                limit: max_instructions,
+                found: all_instructions.len(),
+            }));
+        }
 
         let enable_dropout_instructions = all_instructions
             .clone()
@@ -105,11 +168,6 @@ where
             .collect();
         let optimization_instructions = Arc::new(optimization_instructions);
 
-        let example_input = program.example_input;
-        let example_output = program.example_output;
-        let machine_output = program.machine_output;
-        let loss = program.loss;
-
         let enable_dropout_streams =
             Self::assign_streams(&example_input, &enable_dropout_instructions);
         let enable_dropout_streams = Arc::new(enable_dropout_streams);
@@ -130,49 +188,58 @@ where
         let optimization_streams = Self::assign_streams(&example_input, &optimization_instructions);
         let optimization_streams = Arc::new(optimization_streams);
 
+        #[cfg(not(feature = "verify_streams"))]
         let handler = StreamExecutor::new();
+        #[cfg(feature = "verify_streams")]
+        let handler = StreamExecutor::with_verifier();
 
-        let mut enable_dropout_scheduler = Scheduler::new(
+        let mut enable_dropout_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &enable_dropout_streams,
             &handler,
             &enable_dropout_instructions,
+            queue_capacity,
         );
-        let mut disable_dropout_scheduler = Scheduler::new(
+        let mut disable_dropout_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &disable_dropout_streams,
             &handler,
             &disable_dropout_instructions,
+            queue_capacity,
         );
-        let mut inference_scheduler = Scheduler::new(
+        let mut inference_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &inference_streams,
             &handler,
             &inference_instructions,
+            queue_capacity,
         );
-        let mut loss_scheduler = Scheduler::new(
+        let mut loss_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &loss_streams,
             &handler,
             &loss_instructions,
+            queue_capacity,
         );
-        let mut gradient_scheduler = Scheduler::new(
+        let mut gradient_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &gradient_streams,
             &handler,
             &gradient_instructions,
+            queue_capacity,
         );
-        let mut optimization_scheduler = Scheduler::new(
+        let mut optimization_scheduler = Scheduler::with_queue_capacity(
             device,
             maximum_device_streams,
             &optimization_streams,
             &handler,
             &optimization_instructions,
+            queue_capacity,
         );
 
         enable_dropout_scheduler.start();
@@ -185,10 +252,15 @@ where
         let machine = NeuralMachine::<T, Scheduler> {
             device: device.clone(),
             io_stream: device.new_stream()?,
+            handler,
             example_input,
             example_output,
             machine_output,
             loss,
+            gradient_buffers: vec![],
+            host_gradient_buffers: vec![],
+            learning_rate_tensors,
+            constant_instructions,
             enable_dropout_instructions,
             enable_dropout_streams,
             enable_dropout_scheduler,
@@ -215,6 +287,43 @@ where
         Ok(machine)
     }
 
+    /// Rebuild the model described by `config` and compile it into a neural machine.
+    /// The weights are freshly initialized.
+    pub fn from_config(
+        device: &Device,
+        config: &ModelConfig,
+        loss_operator: &impl BinaryOperator,
+        optimizer: &impl OptimizerTrait,
+        must_clip_grad_norm: bool,
+        batch_size: usize,
+        maximum_device_streams: usize,
+    ) -> Result<Self, Error> {
+        let model = config.build(device)?;
+        let program = NeuralProgram::try_new(
+            device,
+            &model,
+            loss_operator,
+            optimizer,
+            must_clip_grad_norm,
+            batch_size,
+        )?;
+        Self::try_new(device, program, maximum_device_streams)
+    }
+
+    /// Compile the forward pass of the model for inference, see NeuralProgram::try_new_no_grad.
+    /// Dropout is disabled, and infer only executes the compiled inference instructions,
+    /// so serving does not create tensors.
+    pub fn try_new_no_grad(
+        device: &Device,
+        model: &impl UnaryModel,
+        maximum_device_streams: usize,
+    ) -> Result<Self, Error> {
+        let program = NeuralProgram::try_new_no_grad(device, model)?;
+        let mut machine = Self::try_new(device, program, maximum_device_streams)?;
+        machine.disable_dropout()?;
+        Ok(machine)
+    }
+
     pub fn instructions(&self, category: &Category) -> impl Deref<Target = Vec<Instruction>> {
         match category {
             Category::EnableDropout => self.enable_dropout_instructions.clone(),
@@ -226,6 +335,12 @@ where
         }
     }
 
+    /// Instructions that were evaluated once when the machine was built
+    /// because all their inputs are constants.
+    pub fn constant_instructions(&self) -> impl Deref<Target = Vec<Instruction>> {
+        self.constant_instructions.clone()
+    }
+
     pub fn enable_dropout(&mut self) -> Result<(), Error> {
         self.forward(&Category::EnableDropout)?;
         Ok(())
@@ -256,6 +371,113 @@ where
         Ok(())
     }
 
+    /// Run the backward pass and add its gradients to the parameter gradients.
+    /// The parameter gradients are only reset by optimize and zero_gradients,
+    /// so successive calls accumulate them, e.g. for very large effective batches.
+    /// The previous gradients are saved in device buffers, which are allocated by the
+    /// first call, so the gradients never leave the device.
+    pub fn accumulate_gradient(&mut self) -> Result<(), Error> {
+        self.allocate_gradient_buffers()?;
+        let parameters = self.device.parameter_tensors().clone();
+        let host_parameters = self.device.host_parameter_tensors().clone();
+
+        for (parameter, buffer) in parameters.iter().zip(self.gradient_buffers.iter()) {
+            self.device
+                .copy_to(&parameter.gradient(), buffer, &self.io_stream)?;
+        }
+        self.io_stream.wait_for()?;
+        if !host_parameters.is_empty() {
+            let buffers = &self.host_gradient_buffers;
+            self.device.execute_on_host(|host, host_stream| {
+                for (parameter, buffer) in host_parameters.iter().zip(buffers.iter()) {
+                    host.copy_to(&parameter.gradient(), buffer, host_stream)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        self.zero_gradients()?;
+        self.compute_gradient()?;
+
+        for (parameter, buffer) in parameters.iter().zip(self.gradient_buffers.iter()) {
+            let gradient: &Tensor = &parameter.gradient();
+            let n = gradient.len() as i32;
+            self.device.axpy(
+                n,
+                &self.io_stream.one,
+                buffer,
+                1,
+                gradient,
+                1,
+                &self.io_stream,
+            )?;
+        }
+        self.io_stream.wait_for()?;
+        if !host_parameters.is_empty() {
+            let buffers = &self.host_gradient_buffers;
+            self.device.execute_on_host(|host, host_stream| {
+                for (parameter, buffer) in host_parameters.iter().zip(buffers.iter()) {
+                    let gradient: &Tensor = &parameter.gradient();
+                    let n = gradient.len() as i32;
+                    host.axpy(n, &host_stream.one, buffer, 1, gradient, 1, host_stream)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn allocate_gradient_buffers(&mut self) -> Result<(), Error> {
+        let parameters = self.device.parameter_tensors().clone();
+        if self.gradient_buffers.len() != parameters.len() {
+            self.gradient_buffers = parameters
+                .iter()
+                .map(|parameter| {
+                    let gradient: &Tensor = &parameter.gradient();
+                    self.device.zeros(gradient.rows(), gradient.cols())
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        let host_parameters = self.device.host_parameter_tensors().clone();
+        if self.host_gradient_buffers.len() != host_parameters.len() {
+            let host = self.device.host();
+            self.host_gradient_buffers = host_parameters
+                .iter()
+                .map(|parameter| {
+                    let gradient: &Tensor = &parameter.gradient();
+                    host.zeros(gradient.rows(), gradient.cols())
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(())
+    }
+
+    /// Reset the parameter gradients.
+    pub fn zero_gradients(&mut self) -> Result<(), Error> {
+        let zero = &self.io_stream.zero;
+        for parameter in self.device.parameter_tensors().iter() {
+            let gradient: &Tensor = &parameter.gradient();
+            self.device.scal(zero, gradient, &self.io_stream)?;
+        }
+        self.io_stream.wait_for()?;
+        if !self.device.host_parameter_tensors().is_empty() {
+            self.device.execute_on_host(|host, host_stream| {
+                for parameter in host.parameter_tensors().iter() {
+                    let gradient: &Tensor = &parameter.gradient();
+                    host.scal(&host_stream.zero, gradient, host_stream)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Change the learning rate of the optimizer for the next optimize,
+    /// e.g. with a learning rate scheduler.
+    pub fn set_learning_rate(&mut self, learning_rate: f32) -> Result<(), Error> {
+        self.learning_rate_tensors.set_learning_rate(learning_rate)
+    }
+
     pub fn optimize(&mut self) -> Result<(), Error> {
         self.forward(&Category::Optimization)?;
         Ok(())
@@ -270,7 +492,8 @@ where
             Category::Gradient => &mut self.gradient_scheduler,
             Category::Optimization => &mut self.optimization_scheduler,
         };
-        scheduler.execute();
+        scheduler.execute()?;
+        self.handler.verify()?;
         self.io_stream.wait_for_default()?;
         Ok(())
     }
@@ -294,6 +517,77 @@ where
         Ok(self.machine_output.clone())
     }
 
+    /// Greedy auto-regressive generation.
+    /// The prompt followed by at most max_new_tokens generated tokens is returned.
+    /// Generation stops early after eos_token is generated.
+    /// The machine input is the one-hot encoding of the last tokens, padded with padding_token
+    /// while there are fewer tokens than input rows.
+    pub fn generate(
+        &mut self,
+        prompt_tokens: &[usize],
+        max_new_tokens: usize,
+        eos_token: Option<usize>,
+        padding_token: usize,
+    ) -> Result<Vec<usize>, Error> {
+        if prompt_tokens.is_empty() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let (sequence_length, vocab_size) = {
+            let example_input: &Tensor = &self.example_input.tensor();
+            (example_input.rows(), example_input.cols())
+        };
+        let mut tokens = prompt_tokens.to_owned();
+        for _ in 0..max_new_tokens {
+            let mut input_tokens =
+                tokens[tokens.len().saturating_sub(sequence_length)..].to_owned();
+            let last_row = input_tokens.len() - 1;
+            input_tokens.resize(sequence_length, padding_token);
+            let input = into_one_hot_encoded_rows(&self.device, &input_tokens, vocab_size)?;
+            let output = self.infer(&input)?;
+            let next_token = get_row_argmax(&output.tensor(), last_row)?;
+            tokens.push(next_token);
+            if Some(next_token) == eos_token {
+                break;
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Estimated floating-point operations of one forward pass, see Instruction::estimated_flops.
+    pub fn estimated_flops(&self) -> u64 {
+        self.inference_instructions
+            .iter()
+            .map(|x| x.estimated_flops())
+            .sum()
+    }
+
+    /// Check that the input and the output of every example have the sizes declared by the model.
+    /// The first mismatch is returned.
+    pub fn validate_dataset(
+        &self,
+        examples: &[(TensorWithGrad, TensorWithGrad)],
+    ) -> Result<(), Error> {
+        let expected_input_size: Vec<usize> = self.example_input.tensor().size().clone();
+        let expected_output_size: Vec<usize> = self.example_output.tensor().size().clone();
+        for (example, (input, output)) in examples.iter().enumerate() {
+            for (tensor, operand, expected) in [
+                ("input", input, &expected_input_size),
+                ("output", output, &expected_output_size),
+            ] {
+                let found: Vec<usize> = operand.tensor().size().clone();
+                if &found != expected {
+                    return Err(error!(ErrorEnum::ExampleSizeMismatch {
+                        example,
+                        tensor: tensor.to_owned(),
+                        expected: expected.clone(),
+                        found,
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn print(&self) {
         println!("------------------------------");
         println!("Booting Neural Machine...");
@@ -327,6 +621,10 @@ where
             + self.gradient_instructions.len()
             + self.optimization_instructions.len();
         println!("Instructions: {}", total_instructions);
+        println!(
+            "Constant Instructions: {}",
+            self.constant_instructions.len()
+        );
         println!(
             "Inference Instructions: {}",
             self.inference_instructions.len()
@@ -427,12 +725,13 @@ where
         let minimum_dependents_for_stream = 12;
         let minimum_stream_instructions = 32;
 
-        make_streams(
+        let streams = make_streams(
             &simple_instructions,
             minimum_write_before_read_for_new_stream,
             minimum_dependents_for_stream,
             minimum_stream_instructions,
-        )
+        );
+        merge_stream_chains(streams)
     }
 }
 
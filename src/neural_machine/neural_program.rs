@@ -1,7 +1,12 @@
 use crate::clip_grad_norm::clip_grad_norm;
 use crate::{
-    instruction, new_tensor, new_tensor_with_grad, opcode::OpCode, tensor::Error, BinaryOperator,
-    Category, Device, Instruction, OperatorAttributes, OptimizerTrait, TensorWithGrad, UnaryModel,
+    instruction,
+    lr_scheduler::LearningRateTensors,
+    new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    tensor::{Error, Tensor},
+    BinaryOperator, Category, Device, Instruction, OperatorAttributes, OptimizerTrait,
+    TensorWithGrad, UnaryModel,
 };
 use std::collections::HashSet;
 
@@ -11,6 +16,7 @@ pub struct NeuralProgram {
     pub machine_output: TensorWithGrad,
     pub loss: TensorWithGrad,
     pub instructions: Vec<Instruction>,
+    pub learning_rate_tensors: LearningRateTensors,
 }
 
 impl NeuralProgram {
@@ -65,17 +71,29 @@ impl NeuralProgram {
             processed_forward_tensors.insert(tensor_name);
         }
 
+        // The operators that stay on the host have their own tensors,
+        // which are reset and optimized with host instructions.
+        let host = device.host();
+        let has_host_tensors =
+            !host.internal_tensors().is_empty() || !host.parameter_tensors().is_empty();
+        let host_zero = if has_host_tensors {
+            Some(new_tensor!(host, 1, 1, vec![0.0])?)
+        } else {
+            None
+        };
+
         // Gradient instructions
-        let internal_tensors = device.internal_tensors();
-        for tensor in internal_tensors.iter() {
-            let inst = instruction!(
-                OpCode::ScalarMul,
-                OperatorAttributes::None,
-                &[&zero, &tensor.gradient()],
-                &[&tensor.gradient()],
+        instructions.append(&mut reset_gradients(
+            &zero,
+            &device.internal_tensors(),
+            Category::Gradient,
+        ));
+        if let Some(host_zero) = &host_zero {
+            instructions.append(&mut reset_gradients(
+                host_zero,
+                &host.internal_tensors(),
                 Category::Gradient,
-            );
-            instructions.push(inst);
+            ));
         }
 
         let mut processed_backward_tensors = HashSet::<usize>::new();
@@ -92,49 +110,151 @@ impl NeuralProgram {
         }
 
         // Optimization instructions
-        let parameters = device.parameter_tensors();
-        let gradient = parameters.iter().map(|t| t.gradient()).collect::<Vec<_>>();
-
-        if must_clip_grad_norm {
-            let mut clip_instructions = clip_grad_norm(device, &gradient)?;
-            instructions.append(&mut clip_instructions);
+        instructions.append(&mut optimization_instructions(
+            device,
+            &zero,
+            optimizer,
+            must_clip_grad_norm,
+            batch_size,
+        )?);
+        if let (Some(host_zero), false) = (&host_zero, host.parameter_tensors().is_empty()) {
+            instructions.append(&mut optimization_instructions(
+                &host,
+                host_zero,
+                optimizer,
+                must_clip_grad_norm,
+                batch_size,
+            )?);
         }
 
-        // Average the loss gradient over the batch size
-        if batch_size != 1 {
-            let batch_size_reciprocal = new_tensor!(device, 1, 1, vec![1.0 / batch_size as f32])?;
-            for g in gradient.iter() {
-                instructions.push(instruction!(
-                    OpCode::ScalarMul,
-                    OperatorAttributes::None,
-                    &[&batch_size_reciprocal, &g],
-                    &[&g],
-                    Category::Optimization,
-                ));
+        let program = NeuralProgram {
+            example_input,
+            example_output,
+            machine_output,
+            loss,
+            instructions,
+            learning_rate_tensors: optimizer.learning_rate_tensors(),
+        };
+        Ok(program)
+    }
+
+    /// A program with only the forward pass of the model, for inference and serving.
+    /// The gradient buffers of its internal tensors are freed once it is built.
+    /// The machine output is also the loss, and the example output is not used.
+    pub fn try_new_no_grad(
+        device: &Device,
+        model: &impl UnaryModel,
+    ) -> Result<NeuralProgram, Error> {
+        let input_shape = model.input_size();
+        let input_len = input_shape[0] * input_shape[1];
+        let example_input = new_tensor_with_grad!(
+            device,
+            input_shape[0],
+            input_shape[1],
+            vec![0.7; input_len],
+            &[],
+            false,
+            false,
+        )?;
+        let example_output = new_tensor_with_grad!(device, 0, 0, vec![], &[], false, false)?;
+
+        let host = device.host();
+        let internal_tensors = device.internal_tensors().len();
+        let host_internal_tensors = host.internal_tensors().len();
+        let machine_output = model.forward(&example_input)?;
+        let tape = machine_output.get_tape();
+        let mut instructions = vec![];
+
+        let mut processed_forward_tensors = HashSet::<usize>::new();
+        for tensor in tape.iter() {
+            let tensor_name = tensor.tensor().name();
+            if processed_forward_tensors.contains(&tensor_name) {
+                continue;
+            }
+            for instruction in tensor.forward_instructions().into_iter() {
+                instructions.push(instruction);
             }
+            processed_forward_tensors.insert(tensor_name);
         }
 
-        let mut optimizer_instructions = optimizer.optimize(device, &parameters)?;
-        instructions.append(&mut optimizer_instructions);
-
-        for tensor in parameters.iter() {
-            let inst = instruction!(
-                OpCode::ScalarMul,
-                OperatorAttributes::None,
-                &[&zero, &tensor.gradient()],
-                &[&tensor.gradient()],
-                Category::Optimization,
-            );
-            instructions.push(inst);
+        // The internal tensors created by the forward pass are the last ones.
+        for (device, first) in [(device, internal_tensors), (&host, host_internal_tensors)] {
+            let names = device.internal_tensors()[first..]
+                .iter()
+                .map(|x| x.tensor().name())
+                .collect::<HashSet<_>>();
+            device.release_gradients(&names);
         }
 
         let program = NeuralProgram {
             example_input,
             example_output,
-            machine_output,
-            loss,
+            machine_output: machine_output.clone(),
+            loss: machine_output,
             instructions,
+            learning_rate_tensors: Default::default(),
         };
         Ok(program)
     }
 }
+
+fn reset_gradients(
+    zero: &Tensor,
+    tensors: &[TensorWithGrad],
+    category: Category,
+) -> Vec<Instruction> {
+    tensors
+        .iter()
+        .map(|tensor| {
+            instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[zero, &tensor.gradient()],
+                &[&tensor.gradient()],
+                category.clone(),
+            )
+        })
+        .collect()
+}
+
+/// The gradient norm is clipped separately for the parameters of each device.
+fn optimization_instructions(
+    device: &Device,
+    zero: &Tensor,
+    optimizer: &impl OptimizerTrait,
+    must_clip_grad_norm: bool,
+    batch_size: usize,
+) -> Result<Vec<Instruction>, Error> {
+    let mut instructions = vec![];
+    let parameters = device.parameter_tensors();
+    let gradient = parameters.iter().map(|t| t.gradient()).collect::<Vec<_>>();
+
+    if must_clip_grad_norm {
+        let mut clip_instructions = clip_grad_norm(device, &gradient)?;
+        instructions.append(&mut clip_instructions);
+    }
+
+    // Average the loss gradient over the batch size
+    if batch_size != 1 {
+        let batch_size_reciprocal = new_tensor!(device, 1, 1, vec![1.0 / batch_size as f32])?;
+        for g in gradient.iter() {
+            instructions.push(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&batch_size_reciprocal, &g],
+                &[&g],
+                Category::Optimization,
+            ));
+        }
+    }
+
+    let mut optimizer_instructions = optimizer.optimize(device, &parameters)?;
+    instructions.append(&mut optimizer_instructions);
+
+    instructions.append(&mut reset_gradients(
+        zero,
+        &parameters,
+        Category::Optimization,
+    ));
+    Ok(instructions)
+}
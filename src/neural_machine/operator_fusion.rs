@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::{
+    opcode::OpCode, tensor::Tensor, Category, Instruction, LinearActivation, OperatorAttributes,
+};
+
+/// Fuse each Linear that is followed by an element-wise activation into one LinearActivation
+/// inference instruction.
+///
+/// The instructions emitted by Linear are ScalarMul (zeroing of the product), Gemm,
+/// and BiasAdd of the biases if the layer has biases.
+/// They are fused with the activation only if the product and the pre-activation tensors are
+/// not machine tensors and no other instruction, of any category, reads or writes them.
+/// The fused instruction takes the place of the activation.
+pub fn fuse_linear_activation_instructions(
+    machine_tensors: &[usize],
+    instructions: Vec<Instruction>,
+) -> Vec<Instruction> {
+    let mut readers = HashMap::<usize, usize>::new();
+    let mut writers = HashMap::<usize, usize>::new();
+    for instruction in instructions.iter() {
+        for input in instruction.inputs().iter() {
+            *readers.entry(input.name()).or_default() += 1;
+        }
+        for output in instruction.outputs().iter() {
+            *writers.entry(output.name()).or_default() += 1;
+        }
+    }
+    let count = |counts: &HashMap<usize, usize>, tensor: &Tensor| {
+        counts.get(&tensor.name()).copied().unwrap_or_default()
+    };
+    let is_intermediate = |tensor: &Tensor, expected_readers: usize, expected_writers: usize| {
+        !machine_tensors.contains(&tensor.name())
+            && count(&readers, tensor) == expected_readers
+            && count(&writers, tensor) == expected_writers
+    };
+
+    let mut removed = vec![false; instructions.len()];
+    let mut fused = HashMap::<usize, Instruction>::new();
+    for i in 1..instructions.len() {
+        let zeroing = &instructions[i - 1];
+        let gemm = &instructions[i];
+        let transb = match (gemm.opcode(), gemm.attributes(), gemm.category()) {
+            (
+                OpCode::Gemm,
+                OperatorAttributes::ThreeBools(false, transb, false),
+                Category::Inference,
+            ) => *transb,
+            _ => continue,
+        };
+        let (input, weights, product) = match (&gemm.inputs()[..], &gemm.outputs()[..]) {
+            ([input, weights, c], [product]) if c.name() == product.name() => {
+                (input.clone(), weights.clone(), product.clone())
+            }
+            _ => continue,
+        };
+        if !is_zeroing_of(zeroing, &product) || removed[i - 1] {
+            continue;
+        }
+        // The product is read by the zeroing, the Gemm and its consumer.
+        if !is_intermediate(&product, 3, 2) {
+            continue;
+        }
+        let Some(consumer) = next_reader(&instructions, i, &product) else {
+            continue;
+        };
+
+        let (bias_add, biases, pre_activation) = match (
+            instructions[consumer].opcode(),
+            &instructions[consumer].inputs()[..],
+            &instructions[consumer].outputs()[..],
+        ) {
+            (OpCode::BiasAdd, [x, biases], [y])
+                if x.name() == product.name() && y.name() != product.name() =>
+            {
+                if !is_intermediate(y, 1, 1) {
+                    continue;
+                }
+                (Some(consumer), Some(biases.clone()), y.clone())
+            }
+            _ => (None, None, product.clone()),
+        };
+        let activation_index = match bias_add {
+            Some(bias_add) => match next_reader(&instructions, bias_add, &pre_activation) {
+                Some(index) => index,
+                None => continue,
+            },
+            None => consumer,
+        };
+        let activation = &instructions[activation_index];
+        let output = match (&activation.inputs()[..], &activation.outputs()[..]) {
+            ([x], [output])
+                if x.name() == pre_activation.name()
+                    && output.len() == product.len()
+                    && count(&writers, output) == 1 =>
+            {
+                output.clone()
+            }
+            _ => continue,
+        };
+        if activation.category() != Category::Inference
+            || !LinearActivation::is_fusable_activation(activation.opcode())
+        {
+            continue;
+        }
+
+        // The fused instruction runs later than the Gemm, so its inputs must not change between.
+        let mut inputs = vec![&input, &weights];
+        if let Some(biases) = &biases {
+            inputs.push(biases);
+        }
+        let inputs_are_stable = instructions[(i + 1)..activation_index]
+            .iter()
+            .all(|instruction| {
+                instruction
+                    .outputs()
+                    .iter()
+                    .all(|x| inputs.iter().all(|input| input.name() != x.name()))
+            });
+        if !inputs_are_stable {
+            continue;
+        }
+
+        let attributes = OperatorAttributes::LinearActivation(
+            transb,
+            activation.opcode().clone(),
+            Box::new(activation.attributes().clone()),
+        );
+        let fused_instruction = Instruction::new(
+            OpCode::LinearActivation,
+            attributes,
+            &inputs,
+            &[&output],
+            Category::Inference,
+            #[cfg(debug_assertions)]
+            activation.file(),
+            #[cfg(debug_assertions)]
+            activation.line(),
+            #[cfg(debug_assertions)]
+            activation.column(),
+        );
+        removed[i - 1] = true;
+        removed[i] = true;
+        if let Some(bias_add) = bias_add {
+            removed[bias_add] = true;
+        }
+        fused.insert(activation_index, fused_instruction);
+    }
+
+    instructions
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !removed[*i])
+        .map(|(i, instruction)| fused.remove(&i).unwrap_or(instruction))
+        .collect()
+}
+
+/// The ScalarMul by zero that MatMul emits before its Gemm.
+fn is_zeroing_of(instruction: &Instruction, tensor: &Tensor) -> bool {
+    match (
+        instruction.opcode(),
+        instruction.category(),
+        &instruction.inputs()[..],
+        &instruction.outputs()[..],
+    ) {
+        (OpCode::ScalarMul, Category::Inference, [alpha, x], [y]) => {
+            x.name() == tensor.name()
+                && y.name() == tensor.name()
+                && matches!(alpha.get_values().as_deref(), Ok([value]) if *value == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn next_reader(instructions: &[Instruction], after: usize, tensor: &Tensor) -> Option<usize> {
+    instructions
+        .iter()
+        .enumerate()
+        .skip(after + 1)
+        .find(|(_, instruction)| {
+            instruction
+                .inputs()
+                .iter()
+                .any(|x| x.name() == tensor.name())
+        })
+        .map(|(index, _)| index)
+}
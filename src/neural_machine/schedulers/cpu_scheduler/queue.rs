@@ -6,20 +6,34 @@ use std::{
 pub struct Queue<T> {
     deque: Mutex<VecDeque<T>>,
     cvar: Condvar,
+    capacity: usize,
+    not_full_cvar: Condvar,
 }
 
 impl<T> Default for Queue<T> {
     fn default() -> Self {
+        Self::with_capacity(usize::MAX)
+    }
+}
+
+impl<T> Queue<T> {
+    /// A bounded queue. push_back blocks while the queue holds capacity items.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0);
         Self {
             deque: Default::default(),
             cvar: Default::default(),
+            capacity,
+            not_full_cvar: Default::default(),
         }
     }
-}
 
-impl<T> Queue<T> {
     pub fn push_back(&self, item: T) {
-        self.deque.lock().unwrap().push_back(item);
+        let mut deque = self.deque.lock().unwrap();
+        while deque.len() >= self.capacity {
+            deque = self.not_full_cvar.wait(deque).unwrap();
+        }
+        deque.push_back(item);
         self.cvar.notify_one();
     }
 
@@ -31,6 +45,8 @@ impl<T> Queue<T> {
         if deque.is_empty() {
             panic!();
         }
-        deque.pop_front()
+        let item = deque.pop_front();
+        self.not_full_cvar.notify_one();
+        item
     }
 }
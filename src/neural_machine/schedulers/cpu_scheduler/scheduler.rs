@@ -20,6 +20,7 @@ where
     execution_units: Option<Vec<ExecutionUnit<Handler>>>,
     controller_handle: Option<JoinHandle<Controller>>,
     execution_unit_handles: Option<Vec<JoinHandle<Result<ExecutionUnit<Handler>, Error>>>>,
+    stream_count: usize,
 }
 
 impl<Handler> SchedulerTrait<Handler> for CpuStreamScheduler<Handler>
@@ -32,6 +33,28 @@ where
         streams: &Arc<Vec<Stream>>,
         handler: &Handler,
         instructions: &Arc<Vec<Instruction>>,
+    ) -> Self {
+        Self::with_queue_capacity(
+            device,
+            maximum_device_streams,
+            streams,
+            handler,
+            instructions,
+            usize::MAX,
+        )
+    }
+
+    /// Create a scheduler whose dispatch queues hold at most queue_capacity streams.
+    /// The controller blocks when the dispatch queue of an execution unit is full.
+    /// The completion queue of the controller stays unbounded so that execution units never
+    /// block on the controller, which would deadlock with a controller blocked on them.
+    fn with_queue_capacity(
+        device: &Device,
+        maximum_device_streams: usize,
+        streams: &Arc<Vec<Stream>>,
+        handler: &Handler,
+        instructions: &Arc<Vec<Instruction>>,
+        queue_capacity: usize,
     ) -> Self {
         // Create structures
 
@@ -39,7 +62,7 @@ where
         let scheduler_command_queue = Arc::new(Queue::default());
         let controller_command_queue = Arc::new(Queue::default());
         let execution_unit_command_queues = (0..maximum_device_streams)
-            .map(|_| Arc::new(Queue::<Command>::default()))
+            .map(|_| Arc::new(Queue::<Command>::with_capacity(queue_capacity)))
             .collect::<Vec<_>>();
 
         // For the execution units.
@@ -72,6 +95,7 @@ where
             execution_units: Some(execution_units),
             controller_handle: None,
             execution_unit_handles: None,
+            stream_count: streams.len(),
         }
     }
 
@@ -128,11 +152,16 @@ where
     }
 
     /// Execute all streams.
-    fn execute(&mut self) {
+    /// A program without streams, e.g. a category without instructions,
+    /// has no completion to wait for.
+    fn execute(&mut self) -> Result<(), Error> {
+        if self.stream_count == 0 {
+            return Ok(());
+        }
         self.controller_command_queue.push_back(Command::Execute);
         let command = self.scheduler_command_queue.pop_front();
         match command {
-            Some(Command::ExecutionCompletion) => {}
+            Some(Command::ExecutionCompletion) => Ok(()),
             _ => panic!(),
         }
     }
@@ -1,10 +1,25 @@
-use crate::schedulers::{
-    transaction::Access,
-    verification::{
-        verify_that_accesses_are_not_reordered,
-        verify_that_all_instructions_are_executed_in_each_scheduler_execution,
-        verify_that_all_instructions_are_executed_with_out_of_order_execution,
+use std::sync::Arc;
+
+use crate::{
+    datasets::mega_man_multi_head_attention::get_multi_head_attention_model_instructions,
+    instruction, new_tensor,
+    opcode::OpCode,
+    schedulers::{
+        run_scheduler,
+        transaction::Access,
+        verification::{
+            verify_that_accesses_are_not_reordered,
+            verify_that_all_instructions_are_executed_in_each_scheduler_execution,
+            verify_that_all_instructions_are_executed_with_out_of_order_execution,
+        },
+        InstructionEmitter, SchedulerTrait, StreamExecutor,
+    },
+    streams::{
+        instruction::make_simple_instructions,
+        stream::{make_streams, merge_stream_chains, Stream},
     },
+    tensor::ErrorEnum,
+    Category, Device, OperatorAttributes,
 };
 
 use super::scheduler::CpuStreamScheduler;
@@ -41,3 +56,134 @@ fn test_all_instructions_are_executed_in_each_scheduler_execution() {
     verify_that_all_instructions_are_executed_in_each_scheduler_execution::<CpuStreamScheduler<_>>(
     );
 }
+
+#[test]
+fn test_verifier_catches_corrupted_stream_dependencies() {
+    let device = Device::cpu();
+    let x = new_tensor!(device, 1, 2, vec![1.0, 2.0]).unwrap();
+    let y = new_tensor!(device, 1, 2, vec![0.0, 0.0]).unwrap();
+    let z = new_tensor!(device, 1, 2, vec![0.0, 0.0]).unwrap();
+    let instructions = Arc::new(vec![
+        instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&x, &x],
+            &[&y],
+            Category::Inference,
+        ),
+        instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&y, &y],
+            &[&z],
+            Category::Inference,
+        ),
+    ]);
+    let make_streams = |dependencies: [Vec<usize>; 2]| {
+        let streams = dependencies
+            .into_iter()
+            .enumerate()
+            .map(|(id, dependencies)| Stream {
+                id,
+                dependencies,
+                instructions: Arc::new(vec![id]),
+            })
+            .collect::<Vec<_>>();
+        Arc::new(streams)
+    };
+    let execute = |streams: &Arc<Vec<Stream>>| {
+        let handler = StreamExecutor::with_verifier();
+        let mut scheduler = CpuStreamScheduler::new(&device, 1, streams, &handler, &instructions);
+        run_scheduler(&mut scheduler).unwrap();
+        handler.verify()
+    };
+
+    // Stream 1 reads what stream 0 writes.
+    let streams = make_streams([vec![], vec![0]]);
+    assert!(execute(&streams).is_ok());
+
+    // The dependency is reversed, so stream 1 reads y before it is written.
+    let streams = make_streams([vec![1], vec![]]);
+    assert!(matches!(
+        execute(&streams).map_err(|e| e.error().clone()),
+        Err(ErrorEnum::MemoryModelViolation { .. })
+    ));
+}
+
+#[test]
+fn test_bounded_queues_complete_all_streams() {
+    let device = Device::default();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let instructions = Arc::new(instructions);
+    let simple_instructions = make_simple_instructions(&instructions);
+    let streams = merge_stream_chains(make_streams(&simple_instructions, 4, 12, 32));
+    let streams = Arc::new(streams);
+
+    let handler = InstructionEmitter::new();
+    let mut scheduler =
+        CpuStreamScheduler::with_queue_capacity(&device, 4, &streams, &handler, &instructions, 1);
+    scheduler.start();
+    for _ in 0..3 {
+        scheduler.execute().unwrap();
+        let mut executed_instructions = handler.executed_instructions.lock().unwrap();
+        executed_instructions.sort();
+        assert_eq!(
+            (0..instructions.len()).collect::<Vec<_>>(),
+            *executed_instructions
+        );
+        executed_instructions.clear();
+    }
+    scheduler.stop();
+}
+
+#[test]
+fn test_bounded_queues_do_not_deadlock_on_serialized_streams() {
+    let device = Device::cpu();
+    let n = 16;
+    let mut tensors = vec![new_tensor!(device, 1, 2, vec![1.0, 2.0]).unwrap()];
+    for _ in 0..n {
+        tensors.push(new_tensor!(device, 1, 2, vec![0.0, 0.0]).unwrap());
+    }
+    let instructions = (0..n)
+        .map(|i| {
+            instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&tensors[i], &tensors[i]],
+                &[&tensors[i + 1]],
+                Category::Inference,
+            )
+        })
+        .collect::<Vec<_>>();
+    let instructions = Arc::new(instructions);
+    // Each stream depends on the previous one.
+    let streams = (0..n)
+        .map(|id| Stream {
+            id,
+            dependencies: if id == 0 { vec![] } else { vec![id - 1] },
+            instructions: Arc::new(vec![id]),
+        })
+        .collect::<Vec<_>>();
+    let streams = Arc::new(streams);
+
+    let handler = StreamExecutor::with_verifier();
+    let mut scheduler =
+        CpuStreamScheduler::with_queue_capacity(&device, 1, &streams, &handler, &instructions, 1);
+    run_scheduler(&mut scheduler).unwrap();
+    assert!(handler.verify().is_ok());
+    assert_eq!(
+        vec![2.0_f32.powi(n as i32), 2.0_f32.powi(n as i32 + 1)],
+        tensors[n].get_values().unwrap()
+    );
+}
+
+#[test]
+fn test_program_without_streams_completes() {
+    let device = Device::cpu();
+    let instructions = Arc::new(vec![]);
+    let streams = Arc::new(vec![]);
+    let handler = InstructionEmitter::new();
+    let mut scheduler = CpuStreamScheduler::new(&device, 4, &streams, &handler, &instructions);
+    run_scheduler(&mut scheduler).unwrap();
+    assert!(handler.executed_instructions.lock().unwrap().is_empty());
+}
@@ -73,7 +73,7 @@ where
 
     fn stop(&mut self) {}
 
-    fn execute(&mut self) {
+    fn execute(&mut self) -> Result<(), Error> {
         self.completed_logical_streams = 0;
         self.current_pending_dependencies = self.initial_pending_dependencies.clone();
         // Queue immediately all logical streams with no dependencies.
@@ -86,13 +86,14 @@ where
         }
 
         // Launch initial logical streams on physical streams.
-        while self.maybe_launch_device_stream().unwrap() {}
+        while self.maybe_launch_device_stream()? {}
 
         // While any logical stream has not completed its execution.
         while self.completed_logical_streams != self.dependents.len() {
-            let _ = self.wait_for_device_stream().unwrap();
-            let _ = self.maybe_launch_device_stream().unwrap();
+            self.wait_for_device_stream()?;
+            self.maybe_launch_device_stream()?;
         }
+        Ok(())
     }
 }
 
@@ -1,16 +1,28 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
 pub mod cpu_scheduler;
 pub mod gpu_scheduler;
 pub mod transaction;
 pub mod verification;
+pub mod work_stealing_scheduler;
 
 #[allow(unused)]
 use cpu_scheduler::scheduler::CpuStreamScheduler;
 #[allow(unused)]
 use gpu_scheduler::GpuStreamScheduler;
-use transaction::{Transaction, TransactionEmitter};
+use transaction::{Transaction, TransactionEmitter, TransactionVerifier};
 
-use crate::{stream::DeviceStream, streams::stream::Stream, tensor::Error, Device, Instruction};
+use crate::{
+    stream::{DeviceStream, StreamTrait},
+    streams::stream::Stream,
+    tensor::Error,
+    Device, Instruction,
+};
+
+#[cfg(test)]
+mod tests;
 
 pub trait SchedulerTrait<Handler>
 where
@@ -24,11 +36,34 @@ where
         instructions: &Arc<Vec<Instruction>>,
     ) -> Self;
 
+    /// Like new, but the queues that dispatch streams to the device streams hold at most
+    /// queue_capacity streams. Schedulers without dispatch queues ignore queue_capacity.
+    fn with_queue_capacity(
+        device: &Device,
+        maximum_device_streams: usize,
+        streams: &Arc<Vec<Stream>>,
+        handler: &Handler,
+        instructions: &Arc<Vec<Instruction>>,
+        _queue_capacity: usize,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(
+            device,
+            maximum_device_streams,
+            streams,
+            handler,
+            instructions,
+        )
+    }
+
     fn start(&mut self);
 
     fn stop(&mut self);
 
-    fn execute(&mut self);
+    /// Execute all the streams and return the first error of the execution.
+    fn execute(&mut self) -> Result<(), Error>;
 }
 
 pub trait StreamEventHandler {
@@ -82,7 +117,9 @@ impl StreamEventHandler for InstructionEmitter {
 }
 
 #[derive(Clone)]
-pub struct StreamExecutor {}
+pub struct StreamExecutor {
+    verifier: Option<TransactionVerifier>,
+}
 
 impl Default for StreamExecutor {
     fn default() -> Self {
@@ -92,7 +129,23 @@ impl Default for StreamExecutor {
 
 impl StreamExecutor {
     pub fn new() -> Self {
-        Self {}
+        Self { verifier: None }
+    }
+
+    /// Verify the memory model while executing, see TransactionVerifier.
+    /// This is slow and meant for debugging schedulers.
+    pub fn with_verifier() -> Self {
+        Self {
+            verifier: Some(TransactionVerifier::default()),
+        }
+    }
+
+    /// Report the memory model violations seen since the last call.
+    pub fn verify(&self) -> Result<(), Error> {
+        match &self.verifier {
+            Some(verifier) => verifier.verify(),
+            None => Ok(()),
+        }
     }
 }
 
@@ -105,12 +158,17 @@ impl StreamEventHandler for StreamExecutor {
         device: &Device,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        if let Some(verifier) = &self.verifier {
+            verifier.before_execute(streams, instructions, stream);
+        }
         let stream_instructions = streams[stream].instructions.clone();
-        let instructions = instructions.clone();
         for i in stream_instructions.iter() {
             let instruction = &instructions[*i];
             instruction.execute(device, device_stream)?;
         }
+        if let Some(verifier) = &self.verifier {
+            verifier.after_execute(streams, instructions, stream);
+        }
         Ok(())
     }
 }
@@ -132,7 +190,51 @@ pub fn execute_streams<Scheduler>(
         &handler,
         instructions,
     );
-    run_scheduler(&mut scheduler);
+    run_scheduler(&mut scheduler).unwrap();
+}
+
+/// Execute streams one at a time on the calling thread.
+/// Among the streams whose dependencies are executed, the one with the lowest id is executed first,
+/// so the order is a fixed topological order.
+/// This is a deterministic baseline to diff against the parallel schedulers.
+pub fn execute_streams_sequential<Handler>(
+    device: &Device,
+    streams: &Arc<Vec<Stream>>,
+    instructions: &Arc<Vec<Instruction>>,
+    handler: &mut Handler,
+) -> Result<(), Error>
+where
+    Handler: StreamEventHandler,
+{
+    let mut pending_dependencies = streams
+        .iter()
+        .map(|x| x.dependencies.len())
+        .collect::<Vec<_>>();
+    let mut dependents = vec![vec![]; streams.len()];
+    for (dependent, dependencies) in streams.iter().map(|x| &x.dependencies).enumerate() {
+        for dependency in dependencies.iter() {
+            dependents[*dependency].push(dependent);
+        }
+    }
+    let mut ready_streams = pending_dependencies
+        .iter()
+        .enumerate()
+        .filter(|(_, pending)| **pending == 0)
+        .map(|(stream, _)| stream)
+        .collect::<BTreeSet<_>>();
+
+    let device_stream = device.new_stream()?;
+    while let Some(stream) = ready_streams.pop_first() {
+        handler.on_execute(streams, instructions, stream, device, &device_stream)?;
+        device_stream.wait_for()?;
+        for dependent in dependents[stream].iter() {
+            pending_dependencies[*dependent] -= 1;
+            if pending_dependencies[*dependent] == 0 {
+                ready_streams.insert(*dependent);
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Simulate an execution of streams and emit operand transactions.
@@ -155,7 +257,7 @@ where
         &handler,
         instructions,
     );
-    run_scheduler(&mut scheduler);
+    run_scheduler(&mut scheduler).unwrap();
     handler.clone().actual_transactions.lock().unwrap().clone()
 }
 
@@ -178,7 +280,7 @@ where
         &handler,
         instructions,
     );
-    run_scheduler(&mut scheduler);
+    run_scheduler(&mut scheduler).unwrap();
     handler
         .clone()
         .executed_instructions
@@ -187,13 +289,14 @@ where
         .clone()
 }
 
-pub fn run_scheduler<Handler>(scheduler: &mut impl SchedulerTrait<Handler>)
+pub fn run_scheduler<Handler>(scheduler: &mut impl SchedulerTrait<Handler>) -> Result<(), Error>
 where
     Handler: StreamEventHandler + Clone + Send + Sync + 'static,
 {
     scheduler.start();
-    scheduler.execute();
+    let result = scheduler.execute();
     scheduler.stop();
+    result
 }
 
 #[cfg(feature = "cuda")]
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    datasets::mega_man_multi_head_attention::get_multi_head_attention_model_instructions,
+    streams::{
+        instruction::make_simple_instructions,
+        stream::{make_streams, merge_stream_chains},
+    },
+    Device,
+};
+
+use super::{
+    execute_streams_sequential,
+    transaction::{
+        get_all_instruction_transactions, get_operand_transaction_pairs, group_by_operand, Access,
+        Transaction, TransactionEmitter,
+    },
+};
+
+#[test]
+fn sequential_execution_preserves_the_order_of_accesses() {
+    let device = Device::cpu();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let instructions = Arc::new(instructions);
+    let simple_instructions = make_simple_instructions(&instructions);
+    let simple_instructions = Arc::new(simple_instructions);
+    let streams = merge_stream_chains(make_streams(&simple_instructions, 4, 12, 32));
+    let streams = Arc::new(streams);
+
+    let execute = || {
+        let mut handler = TransactionEmitter::new(&simple_instructions);
+        execute_streams_sequential(&device, &streams, &instructions, &mut handler).unwrap();
+        let transactions = handler.actual_transactions.lock().unwrap().clone();
+        transactions
+    };
+    let actual_transactions = execute();
+    // The order is fixed.
+    assert_eq!(actual_transactions, execute());
+
+    let expected_transactions = get_all_instruction_transactions(&simple_instructions);
+    assert_eq!(expected_transactions.len(), actual_transactions.len());
+    for (access, prior_access) in [
+        (Access::Read, Access::Write),
+        (Access::Write, Access::Write),
+        (Access::Write, Access::Read),
+    ] {
+        let expected_pairs =
+            get_operand_transaction_pairs(&access, &prior_access, &expected_transactions);
+        let actual_pairs =
+            get_operand_transaction_pairs(&access, &prior_access, &actual_transactions);
+        assert_eq!(expected_pairs, actual_pairs);
+    }
+}
+
+/// Interleave the operands randomly, keeping the order of the transactions of each operand.
+fn shuffle_operands(transactions: &[Transaction], rng: &mut StdRng) -> Vec<Transaction> {
+    let mut operand_transactions = group_by_operand(transactions)
+        .into_values()
+        .map(|x| x.into_iter().rev().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut shuffled = vec![];
+    while !operand_transactions.is_empty() {
+        let operand = rng.gen_range(0..operand_transactions.len());
+        shuffled.push(operand_transactions[operand].pop().unwrap());
+        if operand_transactions[operand].is_empty() {
+            operand_transactions.swap_remove(operand);
+        }
+    }
+    shuffled
+}
+
+#[test]
+fn operand_transaction_pairs_do_not_depend_on_the_interleaving_of_operands() {
+    let device = Device::cpu();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let simple_instructions = make_simple_instructions(&instructions);
+    let transactions = get_all_instruction_transactions(&simple_instructions);
+    let mut rng = StdRng::seed_from_u64(42);
+    for _ in 0..4 {
+        let shuffled = shuffle_operands(&transactions, &mut rng);
+        assert_ne!(transactions, shuffled);
+        for (access, prior_access) in [
+            (Access::Read, Access::Write),
+            (Access::Write, Access::Write),
+            (Access::Write, Access::Read),
+        ] {
+            assert_eq!(
+                get_operand_transaction_pairs(&access, &prior_access, &transactions),
+                get_operand_transaction_pairs(&access, &prior_access, &shuffled)
+            );
+        }
+    }
+}
+
+#[test]
+fn transactions_have_a_total_order() {
+    let transaction = |instruction, operand, access| Transaction {
+        instruction,
+        operand,
+        access,
+    };
+    let mut transactions = vec![
+        transaction(1, 0, Access::Read),
+        transaction(0, 2, Access::Write),
+        transaction(0, 2, Access::Read),
+        transaction(0, 1, Access::Write),
+    ];
+    transactions.sort();
+    assert_eq!(
+        vec![
+            transaction(0, 1, Access::Write),
+            transaction(0, 2, Access::Read),
+            transaction(0, 2, Access::Write),
+            transaction(1, 0, Access::Read),
+        ],
+        transactions
+    );
+}
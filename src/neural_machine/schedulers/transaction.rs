@@ -1,25 +1,67 @@
 use std::{
+    cmp::Ordering,
     collections::BTreeMap,
     sync::{Arc, Mutex},
 };
 
-use crate::{stream::DeviceStream, streams::stream::Stream, tensor::Error, Device, Instruction};
+use crate::{
+    error,
+    stream::DeviceStream,
+    streams::{instruction::make_simple_instructions, stream::Stream},
+    tensor::{Error, ErrorEnum},
+    Device, Instruction,
+};
 
 use super::StreamEventHandler;
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Access {
     Read,
     Write,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+/// A read is ordered before a write.
+impl Ord for Access {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let rank = |access: &Access| match access {
+            Access::Read => 0,
+            Access::Write => 1,
+        };
+        rank(self).cmp(&rank(other))
+    }
+}
+
+impl PartialOrd for Access {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Transaction {
     pub instruction: usize,
     pub operand: usize,
     pub access: Access,
 }
 
+/// Total order of transactions: by instruction, then by operand, then by access.
+/// It does not depend on the declaration order of the fields, and two transactions are equal
+/// only if all their fields are equal, so sorting gives the same result for any input order.
+impl Ord for Transaction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.instruction
+            .cmp(&other.instruction)
+            .then_with(|| self.operand.cmp(&other.operand))
+            .then_with(|| self.access.cmp(&other.access))
+    }
+}
+
+impl PartialOrd for Transaction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 pub fn get_instruction_transactions(
     instruction: usize,
     inputs: &[usize],
@@ -85,12 +127,15 @@ pub fn get_operand_transaction_pairs(
         }
     }
     for (_, pairs) in operand_pairs.iter_mut() {
-        // The tests use == so sorting makes the tests pass.
+        // The pairs are sorted with the total order of Transaction, so the result only depends on
+        // the order of the transactions of each operand, not on how the operands are interleaved.
         pairs.sort();
     }
     operand_pairs
 }
 
+/// The transactions of each operand keep their relative order.
+/// The operands are iterated in increasing order.
 #[allow(unused)]
 pub fn group_by_operand(transactions: &[Transaction]) -> BTreeMap<usize, Vec<Transaction>> {
     let mut operand_transactions = BTreeMap::<usize, Vec<Transaction>>::new();
@@ -154,3 +199,123 @@ impl StreamEventHandler for TransactionEmitter {
         Ok(())
     }
 }
+
+/// Runtime verifier of the memory model.
+/// Before the instructions of a stream are executed, it checks that the prior
+/// transactions of each of their operands were executed, following the 4 pillars above:
+/// the prior write of a read, the prior write of a write, and the prior reads of a write.
+/// A violation means that the dependencies of the streams are wrong.
+/// The verifier is keyed by instruction list, so one verifier can be shared by many schedulers.
+#[derive(Clone, Default)]
+pub struct TransactionVerifier {
+    states: Arc<Mutex<BTreeMap<usize, VerifierState>>>,
+    violations: Arc<Mutex<Vec<(Transaction, Transaction)>>>,
+}
+
+struct VerifierState {
+    prior_transactions: Vec<Vec<(Transaction, Transaction)>>,
+    executed: Vec<bool>,
+    executed_count: usize,
+}
+
+impl VerifierState {
+    fn new(instructions: &[Instruction]) -> Self {
+        let simple_instructions = make_simple_instructions(instructions);
+        let mut last_write = BTreeMap::<usize, Transaction>::new();
+        let mut reads_since_write = BTreeMap::<usize, Vec<Transaction>>::new();
+        let mut prior_transactions = vec![];
+        for (instruction, (inputs, outputs)) in simple_instructions.iter().enumerate() {
+            let mut pairs = vec![];
+            for transaction in get_instruction_transactions(instruction, inputs, outputs) {
+                let operand = transaction.operand;
+                let mut priors = vec![];
+                if let Some(write) = last_write.get(&operand) {
+                    priors.push(write.clone());
+                }
+                if transaction.access == Access::Write {
+                    priors.extend(reads_since_write.remove(&operand).unwrap_or_default());
+                    last_write.insert(operand, transaction.clone());
+                } else {
+                    reads_since_write
+                        .entry(operand)
+                        .or_default()
+                        .push(transaction.clone());
+                }
+                for prior in priors {
+                    if prior.instruction != instruction {
+                        pairs.push((transaction.clone(), prior));
+                    }
+                }
+            }
+            prior_transactions.push(pairs);
+        }
+        Self {
+            prior_transactions,
+            executed: vec![false; instructions.len()],
+            executed_count: 0,
+        }
+    }
+}
+
+impl TransactionVerifier {
+    /// Call before executing the instructions of a stream.
+    pub fn before_execute(
+        &self,
+        streams: &Arc<Vec<Stream>>,
+        instructions: &Arc<Vec<Instruction>>,
+        stream: usize,
+    ) {
+        let key = Arc::as_ptr(instructions) as usize;
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(key)
+            .or_insert_with(|| VerifierState::new(instructions));
+        let stream_instructions = &streams[stream].instructions;
+        for (i, instruction) in stream_instructions.iter().enumerate() {
+            for (transaction, prior) in state.prior_transactions[*instruction].iter() {
+                let executed_before = state.executed[prior.instruction]
+                    || stream_instructions[0..i].contains(&prior.instruction);
+                if !executed_before {
+                    self.violations
+                        .lock()
+                        .unwrap()
+                        .push((transaction.clone(), prior.clone()));
+                }
+            }
+        }
+    }
+
+    /// Call after executing the instructions of a stream.
+    pub fn after_execute(
+        &self,
+        streams: &Arc<Vec<Stream>>,
+        instructions: &Arc<Vec<Instruction>>,
+        stream: usize,
+    ) {
+        let key = Arc::as_ptr(instructions) as usize;
+        let mut states = self.states.lock().unwrap();
+        if let Some(state) = states.get_mut(&key) {
+            for instruction in streams[stream].instructions.iter() {
+                state.executed[*instruction] = true;
+                state.executed_count += 1;
+            }
+            // Every instruction was executed, the next execution starts over.
+            if state.executed_count == state.executed.len() {
+                state.executed.iter_mut().for_each(|x| *x = false);
+                state.executed_count = 0;
+            }
+        }
+    }
+
+    /// Report and clear the violations seen since the last call.
+    pub fn verify(&self) -> Result<(), Error> {
+        let violations = std::mem::take(&mut *self.violations.lock().unwrap());
+        match violations.first() {
+            None => Ok(()),
+            Some((transaction, prior)) => Err(error!(ErrorEnum::MemoryModelViolation {
+                transaction: format!("{:?}", transaction),
+                prior: format!("{:?}", prior),
+            })),
+        }
+    }
+}
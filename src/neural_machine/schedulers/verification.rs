@@ -3,7 +3,10 @@ use std::sync::Arc;
 use crate::{
     datasets::mega_man_multi_head_attention::get_multi_head_attention_model_instructions,
     schedulers::simulate_execution_and_collect_instructions,
-    streams::{instruction::make_simple_instructions, stream::make_streams},
+    streams::{
+        instruction::make_simple_instructions,
+        stream::{make_streams, merge_stream_chains},
+    },
     Device,
 };
 
@@ -31,12 +34,12 @@ where
     let minimum_write_before_read_for_new_stream = 4;
     let minimum_dependents_for_stream = 12;
     let minimum_stream_instructions = 32;
-    let actual_streams = make_streams(
+    let actual_streams = merge_stream_chains(make_streams(
         &simple_instructions,
         minimum_write_before_read_for_new_stream,
         minimum_dependents_for_stream,
         minimum_stream_instructions,
-    );
+    ));
     let actual_streams = Arc::new(actual_streams);
     let maximum_device_streams = 32;
     let actual_transactions = simulate_execution_and_collect_transactions::<Scheduler>(
@@ -68,12 +71,12 @@ where
     let minimum_write_before_read_for_new_stream = 4;
     let minimum_dependents_for_stream = 12;
     let minimum_stream_instructions = 32;
-    let actual_streams = make_streams(
+    let actual_streams = merge_stream_chains(make_streams(
         &simple_instructions,
         minimum_write_before_read_for_new_stream,
         minimum_dependents_for_stream,
         minimum_stream_instructions,
-    );
+    ));
     let actual_streams = Arc::new(actual_streams);
     let maximum_device_streams = 32;
 
@@ -110,12 +113,12 @@ where
     let minimum_write_before_read_for_new_stream = 4;
     let minimum_dependents_for_stream = 12;
     let minimum_stream_instructions = 32;
-    let streams = make_streams(
+    let streams = merge_stream_chains(make_streams(
         &simple_instructions,
         minimum_write_before_read_for_new_stream,
         minimum_dependents_for_stream,
         minimum_stream_instructions,
-    );
+    ));
     let streams = Arc::new(streams);
     let maximum_device_streams = 32;
 
@@ -133,7 +136,7 @@ where
 
     let n = 10;
     for _ in 0..n {
-        scheduler.execute();
+        scheduler.execute().unwrap();
         let executed_instructions = &mut handler.executed_instructions.lock().unwrap();
 
         // Same length
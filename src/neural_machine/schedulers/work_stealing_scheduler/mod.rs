@@ -0,0 +1,225 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+};
+#[cfg(test)]
+mod tests;
+
+use crate::{stream::StreamTrait, streams::stream::Stream, tensor::Error, Device, Instruction};
+
+use super::{SchedulerTrait, StreamEventHandler};
+
+/// A scheduler with one deque of ready streams per worker.
+/// A worker takes the most recent stream of its own deque, and when its deque is empty,
+/// it steals the oldest stream of another worker.
+/// The streams that become ready when a stream completes are pushed on the deque of the worker
+/// that completed it, so chains stay on one worker while idle workers balance skewed DAGs.
+/// See https://en.wikipedia.org/wiki/Work_stealing
+pub struct WorkStealingStreamScheduler<Handler>
+where
+    Handler: StreamEventHandler + Send + Sync,
+{
+    device: Device,
+    handler: Handler,
+    streams: Arc<Vec<Stream>>,
+    instructions: Arc<Vec<Instruction>>,
+    maximum_device_streams: usize,
+    state: Arc<SharedState>,
+    worker_handles: Vec<JoinHandle<Result<(), Error>>>,
+}
+
+struct SharedState {
+    dependents: Vec<Vec<usize>>,
+    initial_pending_dependencies: Vec<usize>,
+    state: Mutex<State>,
+    /// Notified when a stream is queued or when the workers must stop.
+    work_cvar: Condvar,
+    /// Notified when a stream completes or fails.
+    completed_cvar: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    current_pending_dependencies: Vec<usize>,
+    deques: Vec<VecDeque<usize>>,
+    queued_streams: usize,
+    running_streams: usize,
+    completed_streams: usize,
+    /// The first error of the execution. No stream is queued after it.
+    error: Option<Error>,
+    /// Whether the workers must stop.
+    stop: bool,
+}
+
+impl State {
+    fn push(&mut self, worker: usize, stream: usize) {
+        self.deques[worker].push_back(stream);
+        self.queued_streams += 1;
+    }
+}
+
+impl SharedState {
+    /// Wait for a queued stream. Returns None when the workers must stop.
+    fn pop(&self, worker: usize) -> Option<usize> {
+        let mut state = self.state.lock().unwrap();
+        while state.queued_streams == 0 && !state.stop {
+            state = self.work_cvar.wait(state).unwrap();
+        }
+        if state.stop {
+            return None;
+        }
+        let n = state.deques.len();
+        let stream = match state.deques[worker].pop_back() {
+            Some(stream) => stream,
+            None => (1..n)
+                .map(|i| (worker + i) % n)
+                .find_map(|victim| state.deques[victim].pop_front())?,
+        };
+        state.queued_streams -= 1;
+        state.running_streams += 1;
+        Some(stream)
+    }
+
+    /// Queue the dependents that are ready, or cancel the queued streams if the stream failed.
+    fn complete(&self, worker: usize, stream: usize, result: Result<(), Error>) {
+        let mut state = self.state.lock().unwrap();
+        state.running_streams -= 1;
+        match result {
+            Ok(()) => {
+                state.completed_streams += 1;
+                if state.error.is_none() {
+                    for dependent in self.dependents[stream].iter() {
+                        state.current_pending_dependencies[*dependent] -= 1;
+                        if state.current_pending_dependencies[*dependent] == 0 {
+                            state.push(worker, *dependent);
+                            self.work_cvar.notify_one();
+                        }
+                    }
+                }
+            }
+            Err(error) => {
+                if state.error.is_none() {
+                    state.error = Some(error);
+                }
+                state.deques.iter_mut().for_each(|deque| deque.clear());
+                state.queued_streams = 0;
+            }
+        }
+        self.completed_cvar.notify_all();
+    }
+}
+
+impl<Handler> WorkStealingStreamScheduler<Handler>
+where
+    Handler: StreamEventHandler + Clone + Send + Sync + 'static,
+{
+    fn spawn_worker(&self, worker: usize) -> JoinHandle<Result<(), Error>> {
+        let device = self.device.clone();
+        let mut handler = self.handler.clone();
+        let streams = self.streams.clone();
+        let instructions = self.instructions.clone();
+        let state = self.state.clone();
+        thread::spawn(move || {
+            let device_stream = device.new_stream()?;
+            while let Some(stream) = state.pop(worker) {
+                let result = device_stream.wait_for_default().and_then(|_| {
+                    handler.on_execute(&streams, &instructions, stream, &device, &device_stream)?;
+                    device_stream.wait_for()
+                });
+                state.complete(worker, stream, result);
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<Handler> SchedulerTrait<Handler> for WorkStealingStreamScheduler<Handler>
+where
+    Handler: StreamEventHandler + Clone + Send + Sync + 'static,
+{
+    fn new(
+        device: &Device,
+        maximum_device_streams: usize,
+        streams: &Arc<Vec<Stream>>,
+        handler: &Handler,
+        instructions: &Arc<Vec<Instruction>>,
+    ) -> Self {
+        // Streams are spread over the workers with a modulo, so there must be at least one worker.
+        assert!(
+            maximum_device_streams > 0,
+            "WorkStealingStreamScheduler needs at least one device stream"
+        );
+        let pending_dependencies = streams.iter().map(|x| x.dependencies.len()).collect();
+        let mut dependents = vec![vec![]; streams.len()];
+        for (dependent, dependencies) in streams.iter().map(|x| &x.dependencies).enumerate() {
+            for dependency in dependencies.iter() {
+                dependents[*dependency].push(dependent);
+            }
+        }
+        let state = SharedState {
+            dependents,
+            initial_pending_dependencies: pending_dependencies,
+            state: Mutex::new(State {
+                deques: vec![Default::default(); maximum_device_streams],
+                ..Default::default()
+            }),
+            work_cvar: Default::default(),
+            completed_cvar: Default::default(),
+        };
+        Self {
+            device: device.clone(),
+            handler: handler.clone(),
+            streams: streams.clone(),
+            instructions: instructions.clone(),
+            maximum_device_streams,
+            state: Arc::new(state),
+            worker_handles: Default::default(),
+        }
+    }
+
+    fn start(&mut self) {
+        self.state.state.lock().unwrap().stop = false;
+        self.worker_handles = (0..self.maximum_device_streams)
+            .map(|worker| self.spawn_worker(worker))
+            .collect();
+    }
+
+    fn stop(&mut self) {
+        self.state.state.lock().unwrap().stop = true;
+        self.state.work_cvar.notify_all();
+        for handle in self.worker_handles.drain(..) {
+            handle.join().unwrap().unwrap();
+        }
+    }
+
+    /// Execute all streams.
+    /// When a stream fails, the queued streams are cancelled and, once the running streams
+    /// are done, the first error is returned.
+    fn execute(&mut self) -> Result<(), Error> {
+        let shared_state = &self.state;
+        let mut state = shared_state.state.lock().unwrap();
+        state.completed_streams = 0;
+        state.error = None;
+        state.current_pending_dependencies = shared_state.initial_pending_dependencies.clone();
+        // Queue immediately all streams with no dependencies, spread over the workers.
+        for (stream, pending_dependencies) in
+            shared_state.initial_pending_dependencies.iter().enumerate()
+        {
+            if *pending_dependencies == 0 {
+                state.push(stream % self.maximum_device_streams, stream);
+            }
+        }
+        shared_state.work_cvar.notify_all();
+        while match &state.error {
+            None => state.completed_streams < self.streams.len(),
+            Some(_) => state.running_streams > 0,
+        } {
+            state = shared_state.completed_cvar.wait(state).unwrap();
+        }
+        match state.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
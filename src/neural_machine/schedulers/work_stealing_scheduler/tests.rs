@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use crate::{
+    datasets::mega_man_multi_head_attention::get_multi_head_attention_model_instructions,
+    instruction, new_tensor,
+    opcode::OpCode,
+    schedulers::{
+        run_scheduler,
+        transaction::Access,
+        verification::{
+            verify_that_accesses_are_not_reordered,
+            verify_that_all_instructions_are_executed_in_each_scheduler_execution,
+            verify_that_all_instructions_are_executed_with_out_of_order_execution,
+        },
+        SchedulerTrait, StreamExecutor,
+    },
+    streams::{
+        instruction::make_simple_instructions,
+        stream::{make_streams, merge_stream_chains, Stream},
+    },
+    tensor::ErrorEnum,
+    Category, Device, OperatorAttributes,
+};
+
+use super::WorkStealingStreamScheduler;
+
+#[test]
+fn test_reads_and_writes_of_same_operand_are_not_reordered() {
+    let access = Access::Read;
+    let prior_access = Access::Write;
+    verify_that_accesses_are_not_reordered::<WorkStealingStreamScheduler<_>>(access, prior_access);
+}
+
+#[test]
+fn test_writes_and_writes_of_same_operand_are_not_reordered() {
+    let access = Access::Write;
+    let prior_access = Access::Write;
+    verify_that_accesses_are_not_reordered::<WorkStealingStreamScheduler<_>>(access, prior_access);
+}
+
+#[test]
+fn test_writes_and_reads_of_same_operand_are_not_reordered() {
+    let access = Access::Write;
+    let prior_access = Access::Read;
+    verify_that_accesses_are_not_reordered::<WorkStealingStreamScheduler<_>>(access, prior_access);
+}
+
+#[test]
+fn test_all_instructions_are_executed_with_out_of_order_execution() {
+    verify_that_all_instructions_are_executed_with_out_of_order_execution::<
+        WorkStealingStreamScheduler<_>,
+    >();
+}
+
+#[test]
+fn test_all_instructions_are_executed_in_each_scheduler_execution() {
+    verify_that_all_instructions_are_executed_in_each_scheduler_execution::<
+        WorkStealingStreamScheduler<_>,
+    >();
+}
+
+#[test]
+fn test_dependencies_are_respected_at_runtime() {
+    let device = Device::cpu();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let instructions = Arc::new(instructions);
+    let simple_instructions = make_simple_instructions(&instructions);
+    let streams = merge_stream_chains(make_streams(&simple_instructions, 4, 12, 32));
+    let streams = Arc::new(streams);
+
+    let handler = StreamExecutor::with_verifier();
+    let mut scheduler =
+        WorkStealingStreamScheduler::new(&device, 8, &streams, &handler, &instructions);
+    run_scheduler(&mut scheduler).unwrap();
+    handler.verify().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "at least one device stream")]
+fn test_zero_device_streams_are_rejected() {
+    let device = Device::cpu();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let instructions = Arc::new(instructions);
+    let simple_instructions = make_simple_instructions(&instructions);
+    let streams = Arc::new(make_streams(&simple_instructions, 4, 12, 32));
+
+    let handler = StreamExecutor::new();
+    WorkStealingStreamScheduler::new(&device, 0, &streams, &handler, &instructions);
+}
+
+#[test]
+fn test_failed_instruction_is_returned_by_execute() {
+    let device = Device::cpu();
+    let x = new_tensor!(device, 1, 2, vec![1.0, 2.0]).unwrap();
+    let y = new_tensor!(device, 1, 2, vec![0.0, 0.0]).unwrap();
+    let z = new_tensor!(device, 1, 3, vec![0.0, 0.0, 0.0]).unwrap();
+    let instructions = Arc::new(vec![
+        instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&x, &x],
+            &[&y],
+            Category::Inference,
+        ),
+        // The output does not have the size of the inputs.
+        instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&y, &y],
+            &[&z],
+            Category::Inference,
+        ),
+        instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&z, &z],
+            &[&z],
+            Category::Inference,
+        ),
+    ]);
+    let streams = (0..instructions.len())
+        .map(|id| Stream {
+            id,
+            dependencies: if id == 0 { vec![] } else { vec![id - 1] },
+            instructions: Arc::new(vec![id]),
+        })
+        .collect::<Vec<_>>();
+    let streams = Arc::new(streams);
+
+    let handler = StreamExecutor::new();
+    let mut scheduler =
+        WorkStealingStreamScheduler::new(&device, 2, &streams, &handler, &instructions);
+    scheduler.start();
+    // The workers keep running after a failure, so the scheduler can execute again.
+    for _ in 0..2 {
+        assert_eq!(
+            Err(ErrorEnum::IncompatibleTensorShapes),
+            scheduler.execute().map_err(|e| e.error().clone())
+        );
+    }
+    scheduler.stop();
+    assert_eq!(vec![0.0; 3], z.get_values().unwrap());
+}
@@ -1,4 +1,9 @@
-use std::{collections::BTreeSet, fmt::Display, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    io::Write,
+    sync::Arc,
+};
 
 use super::instruction::{get_instruction_dependencies, Dependencies};
 
@@ -36,25 +41,139 @@ pub fn print_streams(name: &str, streams: &[Stream]) {
     }
 }
 
+/// Graphviz DOT of the stream DAG, with one node per stream and
+/// one edge from each dependency to its dependent stream.
+/// Render it with: dot -Tsvg streams.dot > streams.svg
+pub fn streams_to_dot(streams: &[Stream]) -> String {
+    let mut dot = String::from("digraph streams {\n");
+    for (i, stream) in streams.iter().enumerate() {
+        dot += &format!(
+            "    s{} [label=\"stream {}\\n{} instructions\"];\n",
+            i,
+            i,
+            stream.instructions.len()
+        );
+    }
+    for (i, stream) in streams.iter().enumerate() {
+        for dependency in stream.dependencies.iter() {
+            dot += &format!("    s{} -> s{};\n", dependency, i);
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+/// The distribution of the stream sizes, to quantify how much the instructions are serialized.
+pub struct StreamSizeReport {
+    /// For each stream size (in instructions), the number of streams with that size.
+    pub histogram: BTreeMap<usize, usize>,
+    pub total_instructions: usize,
+    pub largest_stream_instructions: usize,
+}
+
+impl StreamSizeReport {
+    /// The fraction of the instructions that are in the largest stream.
+    /// When it is close to 1, most of the instructions run one after the other.
+    pub fn largest_stream_fraction(&self) -> f32 {
+        if self.total_instructions == 0 {
+            return 0.0;
+        }
+        self.largest_stream_instructions as f32 / self.total_instructions as f32
+    }
+}
+
+impl Display for StreamSizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "instructions  streams")?;
+        for (instructions, streams) in self.histogram.iter() {
+            writeln!(f, "{:>12}  {}", instructions, streams)?;
+        }
+        writeln!(
+            f,
+            "Largest stream: {} / {} instructions ({:.3})",
+            self.largest_stream_instructions,
+            self.total_instructions,
+            self.largest_stream_fraction()
+        )
+    }
+}
+
+pub fn stream_size_report(streams: &[Stream]) -> StreamSizeReport {
+    let mut histogram = BTreeMap::new();
+    for stream in streams.iter() {
+        *histogram.entry(stream.instructions.len()).or_insert(0) += 1;
+    }
+    StreamSizeReport {
+        histogram,
+        total_instructions: streams.iter().map(|x| x.instructions.len()).sum(),
+        largest_stream_instructions: streams
+            .iter()
+            .map(|x| x.instructions.len())
+            .max()
+            .unwrap_or(0),
+    }
+}
+
 /// Group <N> instructions in <M> streams using a dependency analysis.
+/// The dependencies and the assignments are printed with the verbose_streams feature.
 pub fn make_streams(
     instructions: &[(Vec<usize>, Vec<usize>)],
     minimum_write_before_read_for_new_stream: usize,
     minimum_dependents_for_stream: usize,
     minimum_stream_instructions: usize,
+) -> Vec<Stream> {
+    make_streams_with_output(
+        instructions,
+        minimum_write_before_read_for_new_stream,
+        minimum_dependents_for_stream,
+        minimum_stream_instructions,
+        cfg!(feature = "verbose_streams"),
+        &mut std::io::stdout(),
+    )
+}
+
+/// Same streams as make_streams, but nothing is printed, even with the verbose_streams feature.
+pub fn make_streams_quiet(
+    instructions: &[(Vec<usize>, Vec<usize>)],
+    minimum_write_before_read_for_new_stream: usize,
+    minimum_dependents_for_stream: usize,
+    minimum_stream_instructions: usize,
+) -> Vec<Stream> {
+    make_streams_with_output(
+        instructions,
+        minimum_write_before_read_for_new_stream,
+        minimum_dependents_for_stream,
+        minimum_stream_instructions,
+        false,
+        &mut std::io::stdout(),
+    )
+}
+
+/// Same streams as make_streams. When verbose is true, the dependencies and the assignments
+/// are written to output, which is the only output of the stream assignment.
+pub fn make_streams_with_output(
+    instructions: &[(Vec<usize>, Vec<usize>)],
+    minimum_write_before_read_for_new_stream: usize,
+    minimum_dependents_for_stream: usize,
+    minimum_stream_instructions: usize,
+    verbose: bool,
+    output: &mut dyn Write,
 ) -> Vec<Stream> {
     // A list of dependencies for each instruction.
     let instruction_dependencies = get_instruction_dependencies(instructions);
 
-    #[cfg(feature = "verbose_streams")]
-    for (i, i_dependencies) in instruction_dependencies.iter().enumerate() {
-        println!(
-            "[assign_streams] INSTRUCTION_DEPENDENCIES  instruction: {},  write_before_read: {:?},  read_before_write: {:?},  write_before_write: {:?}",
-            i,
-            i_dependencies.write_before_read_dependencies,
-            i_dependencies.read_before_write_dependencies,
-            i_dependencies.write_before_write_dependencies,
-        );
+    if verbose {
+        for (i, i_dependencies) in instruction_dependencies.iter().enumerate() {
+            writeln!(
+                output,
+                "[assign_streams] INSTRUCTION_DEPENDENCIES  instruction: {},  write_before_read: {:?},  read_before_write: {:?},  write_before_write: {:?}",
+                i,
+                i_dependencies.write_before_read_dependencies,
+                i_dependencies.read_before_write_dependencies,
+                i_dependencies.write_before_write_dependencies,
+            )
+            .unwrap();
+        }
     }
 
     let instruction_streams = assign_instructions_to_streams(
@@ -62,23 +181,31 @@ pub fn make_streams(
         minimum_dependents_for_stream,
         minimum_stream_instructions,
         &instruction_dependencies,
+        verbose,
+        output,
     );
 
-    #[cfg(feature = "verbose_streams")]
-    {
+    if verbose {
         for (i, stream) in instruction_streams.iter().enumerate() {
-            println!("STREAM-ASSIGNMENT Instruction {}  stream {}", i, stream);
+            writeln!(
+                output,
+                "STREAM-ASSIGNMENT Instruction {}  stream {}",
+                i, stream
+            )
+            .unwrap();
         }
     }
 
     let max_stream = instruction_streams.iter().max();
     let stream_count = match max_stream {
         Some(&usize::MAX) => {
-            println!("Instruction streams:");
-            for (i, stream) in instruction_streams.iter().enumerate() {
-                println!("Instruction {}  stream {}", i, stream);
+            if verbose {
+                writeln!(output, "Instruction streams:").unwrap();
+                for (i, stream) in instruction_streams.iter().enumerate() {
+                    writeln!(output, "Instruction {}  stream {}", i, stream).unwrap();
+                }
             }
-            panic!();
+            panic!("an instruction was not assigned to a stream");
         }
         Some(max_stream) => max_stream + 1,
         None => 0,
@@ -124,6 +251,78 @@ pub fn make_streams(
     streams
 }
 
+/// Merge chains of streams to reduce the scheduling overhead of tiny streams.
+/// A stream is merged into its dependency when that dependency is its only dependency
+/// and it is the only dependent of that dependency.
+/// Such a stream can only start after its dependency, and nothing else waits for that dependency,
+/// so running both in one stream keeps the same ordering.
+/// The instructions of a merged stream are in program order.
+pub fn merge_stream_chains(streams: Vec<Stream>) -> Vec<Stream> {
+    let mut dependents = vec![vec![]; streams.len()];
+    for (dependent, stream) in streams.iter().enumerate() {
+        for dependency in stream.dependencies.iter() {
+            dependents[*dependency].push(dependent);
+        }
+    }
+
+    let mut merged_into = vec![STREAM_NONE; streams.len()];
+    for (i, stream) in streams.iter().enumerate() {
+        if let [dependency] = stream.dependencies[..] {
+            if dependents[dependency].len() == 1 {
+                merged_into[i] = dependency;
+            }
+        }
+    }
+    // The first stream of the chain of each stream.
+    let chain_heads = (0..streams.len())
+        .map(|i| {
+            let mut head = i;
+            while merged_into[head] != STREAM_NONE {
+                head = merged_into[head];
+            }
+            head
+        })
+        .collect::<Vec<_>>();
+
+    let mut new_ids = vec![STREAM_NONE; streams.len()];
+    let mut next_id = 0;
+    for (i, head) in chain_heads.iter().enumerate() {
+        if *head == i {
+            new_ids[i] = next_id;
+            next_id += 1;
+        }
+    }
+
+    let mut merged_instructions = vec![vec![]; next_id];
+    let mut merged_dependencies = vec![vec![]; next_id];
+    for (i, stream) in streams.iter().enumerate() {
+        let id = new_ids[chain_heads[i]];
+        merged_instructions[id].extend_from_slice(&stream.instructions);
+        for dependency in stream.dependencies.iter() {
+            let dependency = new_ids[chain_heads[*dependency]];
+            if dependency != id {
+                merged_dependencies[id].push(dependency);
+            }
+        }
+    }
+
+    merged_instructions
+        .into_iter()
+        .zip(merged_dependencies)
+        .enumerate()
+        .map(|(id, (mut instructions, mut dependencies))| {
+            instructions.sort();
+            dependencies.sort();
+            dependencies.dedup();
+            Stream {
+                id,
+                dependencies,
+                instructions: instructions.into(),
+            }
+        })
+        .collect()
+}
+
 /// Assign a stream to each instruction.
 /// - `minimum_write_before_read_for_new_stream` dictates how instructions with many inputs are dealt with.
 /// - `minimum_stream_instructions` dictates how many instructions should streams have at the end.
@@ -138,6 +337,8 @@ fn assign_instructions_to_streams(
     minimum_dependents_for_stream: usize,
     minimum_stream_instructions: usize,
     instruction_dependencies: &[Dependencies],
+    verbose: bool,
+    output: &mut dyn Write,
 ) -> Vec<usize> {
     let n = instruction_dependencies.len();
     let mut instructions_with_no_stream = (0..n).collect::<BTreeSet<_>>();
@@ -234,12 +435,15 @@ fn assign_instructions_to_streams(
         }
     }
 
-    #[cfg(feature = "verbose_streams")]
-    for (i_inst, i_stream) in instruction_streams.iter().enumerate() {
-        println!(
-            "[assign_streams] INSTRUCTION_STREAM  instruction: {},  stream: {}",
-            i_inst, i_stream,
-        );
+    if verbose {
+        for (i_inst, i_stream) in instruction_streams.iter().enumerate() {
+            writeln!(
+                output,
+                "[assign_streams] INSTRUCTION_STREAM  instruction: {},  stream: {}",
+                i_inst, i_stream,
+            )
+            .unwrap();
+        }
     }
 
     instruction_streams
@@ -5,11 +5,14 @@ use crate::{
     filter_instructions,
     neural_machine::streams::{
         instruction::{make_simple_instructions, print_instructions},
-        stream::{make_streams, print_streams},
+        stream::{
+            make_streams, make_streams_quiet, make_streams_with_output, merge_stream_chains,
+            print_streams, stream_size_report, streams_to_dot,
+        },
     },
 };
 use crate::{Category, Device};
-use more_asserts::assert_ge;
+use more_asserts::{assert_ge, assert_lt};
 use test_case::test_case;
 
 #[test_case(None ; "no category filter")]
@@ -373,3 +376,166 @@ fn many_independent_instructions_in_one_stream() {
 
     assert_eq!(vec![0, 1], *streams[0].instructions);
 }
+
+#[test]
+fn streams_to_dot_has_one_node_per_stream_and_one_edge_per_dependency() {
+    // 0 -> 1 -> 2 and 0 -> 3 -> 4, joined by 2, 4 -> 5
+    let simple_instructions = vec![
+        (vec![0], vec![1]),
+        (vec![1], vec![2]),
+        (vec![0], vec![3]),
+        (vec![3], vec![4]),
+        (vec![2, 4], vec![5]),
+    ];
+    let streams = make_streams(&simple_instructions, 1, 1, 1);
+    let dot = streams_to_dot(&streams);
+
+    assert!(dot.starts_with("digraph streams {"));
+    let nodes = dot.lines().filter(|x| x.contains("[label=")).count();
+    let edges = dot.lines().filter(|x| x.contains(" -> ")).count();
+    assert_eq!(streams.len(), nodes);
+    let dependencies = streams.iter().map(|x| x.dependencies.len()).sum::<usize>();
+    assert_ge!(dependencies, 1);
+    assert_eq!(dependencies, edges);
+    for (i, stream) in streams.iter().enumerate() {
+        let label = format!(
+            "s{} [label=\"stream {}\\n{} instructions\"];",
+            i,
+            i,
+            stream.instructions.len()
+        );
+        assert!(dot.contains(&label));
+        for dependency in stream.dependencies.iter() {
+            assert!(dot.contains(&format!("s{} -> s{};", dependency, i)));
+        }
+    }
+}
+
+#[test]
+fn merge_stream_chains_merges_only_chains() {
+    let instructions = vec![
+        (vec![0], vec![1]),
+        (vec![1], vec![2]),
+        (vec![1], vec![3]),
+        (vec![1], vec![4]),
+        (vec![1], vec![5]),
+        (vec![2, 3, 4, 5], vec![6]),
+        (vec![6], vec![7]),
+    ];
+    let minimum_write_before_read_for_new_stream = 4;
+    let minimum_dependents_for_stream = 12;
+    let minimum_stream_instructions = 1;
+    let streams = make_streams(
+        &instructions,
+        minimum_write_before_read_for_new_stream,
+        minimum_dependents_for_stream,
+        minimum_stream_instructions,
+    );
+    assert_eq!(7, streams.len());
+
+    let streams = merge_stream_chains(streams);
+
+    // Only the chain of instructions 5 and 6 is merged.
+    assert_eq!(6, streams.len());
+    assert_eq!(vec![4], *streams[0].dependencies);
+    assert_eq!(vec![4], *streams[1].dependencies);
+    assert_eq!(vec![4], *streams[2].dependencies);
+    assert_eq!(vec![4], *streams[3].dependencies);
+    assert_eq!(vec![] as Vec<usize>, *streams[4].dependencies);
+    assert_eq!(vec![0, 1, 2, 3], *streams[5].dependencies);
+    assert_eq!(vec![0], *streams[4].instructions);
+    assert_eq!(vec![5, 6], *streams[5].instructions);
+}
+
+#[test]
+fn merge_stream_chains_reduces_the_stream_count() {
+    let device = Device::default();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let simple_instructions = make_simple_instructions(&instructions);
+    let minimum_write_before_read_for_new_stream = 4;
+    let minimum_dependents_for_stream = 12;
+    let minimum_stream_instructions = 32;
+    let streams = make_streams(
+        &simple_instructions,
+        minimum_write_before_read_for_new_stream,
+        minimum_dependents_for_stream,
+        minimum_stream_instructions,
+    );
+    let stream_count = streams.len();
+    let mut expected_instructions = streams
+        .iter()
+        .map(|x| x.instructions.deref().clone())
+        .collect::<Vec<_>>()
+        .concat();
+    expected_instructions.sort();
+
+    let streams = merge_stream_chains(streams);
+    assert_lt!(streams.len(), stream_count);
+
+    // Every instruction is still in exactly one stream, and no stream depends on itself.
+    let mut actual_instructions = streams
+        .iter()
+        .map(|x| x.instructions.deref().clone())
+        .collect::<Vec<_>>()
+        .concat();
+    actual_instructions.sort();
+    assert_eq!(expected_instructions, actual_instructions);
+    for (i, stream) in streams.iter().enumerate() {
+        assert!(!stream.dependencies.contains(&i));
+    }
+}
+
+#[test]
+fn stream_size_histogram_sums_to_the_instruction_count() {
+    let device = Device::default();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let simple_instructions = make_simple_instructions(&instructions);
+    let minimum_write_before_read_for_new_stream = 4;
+    let minimum_dependents_for_stream = 12;
+    let minimum_stream_instructions = 32;
+    let streams = make_streams(
+        &simple_instructions,
+        minimum_write_before_read_for_new_stream,
+        minimum_dependents_for_stream,
+        minimum_stream_instructions,
+    );
+    let report = stream_size_report(&streams);
+    assert_eq!(
+        instructions.len(),
+        report
+            .histogram
+            .iter()
+            .map(|(instructions, streams)| instructions * streams)
+            .sum::<usize>()
+    );
+    assert_eq!(streams.len(), report.histogram.values().sum::<usize>());
+    assert_eq!(instructions.len(), report.total_instructions);
+    let fraction = report.largest_stream_fraction();
+    assert_lt!(0.0, fraction);
+    assert_ge!(1.0, fraction);
+}
+
+#[test]
+fn quiet_streams_are_the_same_streams() {
+    let device = Device::default();
+    let instructions = get_multi_head_attention_model_instructions(&device).unwrap();
+    let simple_instructions = make_simple_instructions(&instructions);
+    let mut verbose_output = vec![];
+    let streams =
+        make_streams_with_output(&simple_instructions, 4, 12, 32, true, &mut verbose_output);
+    assert!(!verbose_output.is_empty());
+    let mut quiet_output = vec![];
+    let quiet_streams =
+        make_streams_with_output(&simple_instructions, 4, 12, 32, false, &mut quiet_output);
+    assert!(quiet_output.is_empty());
+    assert_eq!(streams.len(), quiet_streams.len());
+    for (stream, quiet_stream) in streams.iter().zip(quiet_streams.iter()) {
+        assert_eq!(stream.id, quiet_stream.id);
+        assert_eq!(stream.dependencies, quiet_stream.dependencies);
+        assert_eq!(stream.instructions, quiet_stream.instructions);
+    }
+    assert_eq!(
+        streams.len(),
+        make_streams_quiet(&simple_instructions, 4, 12, 32).len()
+    );
+}
@@ -0,0 +1,588 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    instruction,
+    model_builder::ModelBuilder,
+    neural_program::NeuralProgram,
+    new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    perceptron::PerceptronModel,
+    schedulers::DefaultStreamScheduler,
+    simple::SimpleModel,
+    stochastic_gradient_descent::StochasticGradientDescent,
+    stream::StreamTrait,
+    sum_of_squared_errors::SumOfSquaredErrors,
+    tensor::{Error, ErrorEnum, Tensor},
+    Adam, Add, BinaryOperator, Category, Device, Instruction, Linear, Model, NeuralMachine,
+    OperatorAttributes, Sigmoid, SoftmaxCrossEntropyLoss, TensorWithGrad, UnaryModel,
+    UnaryOperator, WeightsInitialization,
+};
+
+use super::operator_fusion::fuse_linear_activation_instructions;
+
+struct PositionalModel {
+    linear: Linear,
+    add: Add,
+    positional_encoding: TensorWithGrad,
+}
+
+impl UnaryModel for PositionalModel {}
+
+impl PositionalModel {
+    fn new(device: &Device, table: &Tensor, scale: &Tensor) -> Result<Self, Error> {
+        let linear = Linear::new(device, 1, 2, WeightsInitialization::Kaiming, 1)?;
+        let add = Add::new(device);
+        let positional_encoding =
+            new_tensor_with_grad!(device, 1, 1, vec![0.0], &[], false, false)?;
+        positional_encoding.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[scale, table],
+            &[&positional_encoding.tensor()],
+            Category::Inference,
+        ));
+        let model = Self {
+            linear,
+            add,
+            positional_encoding,
+        };
+        Ok(model)
+    }
+}
+
+impl UnaryOperator for PositionalModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let linear = self.linear.forward(input)?;
+        self.add.forward(&linear, &self.positional_encoding)
+    }
+}
+
+impl Model for PositionalModel {
+    fn input_size(&self) -> Vec<usize> {
+        vec![1, 2]
+    }
+    fn output_size(&self) -> Vec<usize> {
+        vec![1, 1]
+    }
+}
+
+#[test]
+fn positional_encoding_is_executed_only_once() {
+    let device = Device::default();
+    let table = new_tensor!(device, 1, 1, vec![2.0]).unwrap();
+    let scale = new_tensor!(device, 1, 1, vec![0.5]).unwrap();
+    let model = PositionalModel::new(&device, &table, &scale).unwrap();
+    let positional_encoding = model.positional_encoding.tensor();
+
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let maximum_device_streams = 4;
+    let mut neural_machine = NeuralMachine::<f32, DefaultStreamScheduler>::try_new(
+        &device,
+        program,
+        maximum_device_streams,
+    )
+    .unwrap();
+
+    let constant_instructions = neural_machine.constant_instructions();
+    assert_eq!(1, constant_instructions.len());
+    assert_eq!(
+        positional_encoding.name(),
+        constant_instructions[0].outputs()[0].name()
+    );
+    let writes_positional_encoding = neural_machine
+        .instructions(&Category::Inference)
+        .iter()
+        .any(|i| {
+            i.outputs()
+                .iter()
+                .any(|x| x.name() == positional_encoding.name())
+        });
+    assert!(!writes_positional_encoding);
+    assert_eq!(vec![1.0], positional_encoding.get_values().unwrap());
+
+    let input = new_tensor_with_grad!(device, 1, 2, vec![0.3, 0.4], &[], false, false).unwrap();
+    let first_output = neural_machine.infer(&input).unwrap().tensor().get_values();
+
+    // If the positional encoding was executed again, it would read the new table.
+    table.set_values(vec![100.0]).unwrap();
+    let second_output = neural_machine.infer(&input).unwrap().tensor().get_values();
+    assert_eq!(first_output, second_output);
+    assert_eq!(vec![1.0], positional_encoding.get_values().unwrap());
+}
+
+#[test]
+fn parameter_subgraphs_are_not_folded() {
+    for frozen in [false, true] {
+        let device = Device::default();
+        let table = new_tensor_with_grad!(device, 1, 1, vec![2.0], &[], true, true).unwrap();
+        if frozen {
+            device.freeze_parameter(&table);
+        }
+        let scale = new_tensor!(device, 1, 1, vec![0.5]).unwrap();
+        let model = PositionalModel::new(&device, &table.tensor(), &scale).unwrap();
+
+        let loss_operator = SumOfSquaredErrors::new(&device);
+        let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+        let program =
+            NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+        let mut neural_machine =
+            NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+        assert!(neural_machine.constant_instructions().is_empty());
+
+        let input = new_tensor_with_grad!(device, 1, 2, vec![0.3, 0.4], &[], false, false).unwrap();
+        let first_output = neural_machine.infer(&input).unwrap().tensor().get_values();
+
+        // The weights are loaded after the machine is built.
+        table.tensor().set_values(vec![100.0]).unwrap();
+        let second_output = neural_machine.infer(&input).unwrap().tensor().get_values();
+        assert_ne!(first_output, second_output);
+    }
+}
+
+#[test]
+fn accumulate_gradient_twice_doubles_the_gradients() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let input = new_tensor_with_grad!(device, 1, 2, vec![2.0, 3.0], &[], false, false).unwrap();
+    let expected_output =
+        new_tensor_with_grad!(device, 1, 1, vec![5.0], &[], false, false).unwrap();
+    let gradients = || {
+        device
+            .parameter_tensors()
+            .iter()
+            .map(|x| x.gradient().get_values().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    neural_machine.infer(&input).unwrap();
+    neural_machine.loss(&expected_output).unwrap();
+    neural_machine.accumulate_gradient().unwrap();
+    let single_pass_gradients = gradients();
+    assert!(single_pass_gradients
+        .iter()
+        .all(|x| x.iter().all(|x| *x != 0.0)));
+
+    neural_machine.zero_gradients().unwrap();
+    assert!(gradients().iter().all(|x| x.iter().all(|x| *x == 0.0)));
+
+    neural_machine.infer(&input).unwrap();
+    neural_machine.loss(&expected_output).unwrap();
+    neural_machine.accumulate_gradient().unwrap();
+    // The buffers of the previous gradients are allocated once.
+    let tensor_count = device.tensor_count();
+    neural_machine.accumulate_gradient().unwrap();
+    assert_eq!(tensor_count, device.tensor_count());
+    let two_pass_gradients = gradients();
+
+    for (single, double) in single_pass_gradients.iter().zip(two_pass_gradients.iter()) {
+        for (single, double) in single.iter().zip(double.iter()) {
+            assert_lt!((2.0 * single - double).abs(), 1e-4);
+        }
+    }
+}
+
+#[test]
+fn no_grad_machine_matches_infer_without_gradient_buffers() {
+    let device = Device::default();
+    let sequence_length = 3;
+    let vocab_size = 5;
+    let model = SimpleModel::new(&device, sequence_length, vocab_size).unwrap();
+    let mut values = vec![0.0; sequence_length * vocab_size];
+    for (row, token) in [1, 4, 2].into_iter().enumerate() {
+        values[row * vocab_size + token] = 1.0;
+    }
+    let input = new_tensor_with_grad!(
+        device,
+        sequence_length,
+        vocab_size,
+        values,
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+
+    let internal_tensors = device.internal_tensors().len();
+    let mut no_grad_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new_no_grad(&device, &model, 1).unwrap();
+    // The internal tensors of the forward pass do not keep their gradients.
+    assert_eq!(internal_tensors, device.internal_tensors().len());
+    assert!(no_grad_machine.instructions(&Category::Gradient).is_empty());
+
+    let output = no_grad_machine.infer(&input).unwrap();
+    // Each inference only executes the compiled instructions.
+    assert_eq!(
+        output.tensor().name(),
+        no_grad_machine.infer(&input).unwrap().tensor().name()
+    );
+    let output = output.tensor().get_values();
+
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+    let expected = neural_machine.infer(&input).unwrap().tensor().get_values();
+
+    for (expected, actual) in expected.unwrap().iter().zip(output.unwrap().iter()) {
+        assert_lt!((expected - actual).abs(), 1e-6);
+    }
+}
+
+#[test]
+fn no_grad_machine_uses_less_device_memory_than_a_training_machine() {
+    let sequence_length = 3;
+    let vocab_size = 5;
+    // Each machine has its own device, so that the device counts only its tensors.
+    let training_device = Device::default();
+    let model = SimpleModel::new(&training_device, sequence_length, vocab_size).unwrap();
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&training_device);
+    let optimizer = StochasticGradientDescent::new(0.01);
+    let program = NeuralProgram::try_new(
+        &training_device,
+        &model,
+        &loss_operator,
+        &optimizer,
+        false,
+        1,
+    )
+    .unwrap();
+    NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&training_device, program, 1).unwrap();
+
+    let no_grad_device = Device::default();
+    let model = SimpleModel::new(&no_grad_device, sequence_length, vocab_size).unwrap();
+    NeuralMachine::<f32, DefaultStreamScheduler>::try_new_no_grad(&no_grad_device, &model, 1)
+        .unwrap();
+
+    assert_lt!(
+        no_grad_device.tensor_count(),
+        training_device.tensor_count()
+    );
+    let used = |device: &Device| device.get_memory_info().unwrap().used;
+    assert_lt!(used(&no_grad_device), used(&training_device));
+}
+
+#[test]
+fn validate_dataset_reports_the_first_mismatched_example() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let make_example = |input_size: &[usize], output_size: &[usize]| {
+        let input_len = input_size[0] * input_size[1];
+        let output_len = output_size[0] * output_size[1];
+        let input = new_tensor_with_grad!(
+            device,
+            input_size[0],
+            input_size[1],
+            vec![0.0; input_len],
+            &[],
+            false,
+            false
+        )
+        .unwrap();
+        let output = new_tensor_with_grad!(
+            device,
+            output_size[0],
+            output_size[1],
+            vec![0.0; output_len],
+            &[],
+            false,
+            false
+        )
+        .unwrap();
+        (input, output)
+    };
+    let input_size = model.input_size();
+    let output_size = model.output_size();
+    let mut examples = vec![
+        make_example(&input_size, &output_size),
+        make_example(&input_size, &output_size),
+    ];
+    assert!(neural_machine.validate_dataset(&examples).is_ok());
+
+    let bad_output_size = vec![output_size[0], output_size[1] + 1];
+    examples.push(make_example(&input_size, &bad_output_size));
+    examples.push(make_example(
+        &[input_size[0] + 1, input_size[1]],
+        &output_size,
+    ));
+    let result = neural_machine.validate_dataset(&examples);
+    assert_eq!(
+        Err(ErrorEnum::ExampleSizeMismatch {
+            example: 2,
+            tensor: "output".into(),
+            expected: output_size,
+            found: bad_output_size,
+        }),
+        result.map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn estimated_flops_of_simple_model() {
+    let device = Device::default();
+    let sequence_length = 3;
+    let vocab_size = 5;
+    let n_embd = 384;
+    let model = SimpleModel::new(&device, sequence_length, vocab_size).unwrap();
+    let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let gemm = |m: usize, n: usize, k: usize| (2 * m * n * k) as u64;
+    // Each MatMul zeroes its output, and each Linear adds its biases.
+    let matmul = |m: usize, n: usize, k: usize| gemm(m, n, k) + (m * n) as u64;
+    let linear = |m: usize, n: usize, k: usize| matmul(m, n, k) + (m * n) as u64;
+    // A Linear followed by a sigmoid is fused, so its product is not zeroed.
+    let linear_sigmoid = |m: usize, n: usize, k: usize| gemm(m, n, k) + (2 * m * n) as u64;
+    let embedding = matmul(sequence_length, n_embd, vocab_size);
+    let linear_sigmoid_0 = linear_sigmoid(sequence_length, n_embd, n_embd);
+    let reshape = (sequence_length * n_embd) as u64;
+    let linear_sigmoid_1 = linear_sigmoid(1, n_embd, sequence_length * n_embd);
+    let linear_2 = linear(1, vocab_size, n_embd);
+    let softmax = vocab_size as u64;
+    let expected = embedding + linear_sigmoid_0 + reshape + linear_sigmoid_1 + linear_2 + softmax;
+    assert_eq!(expected, neural_machine.estimated_flops());
+}
+
+#[test]
+fn linear_sigmoid_is_fused_into_one_instruction() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![0.1, -0.2, 0.3, 0.4, 0.5, -0.6],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let linear = Linear::new(&device, 4, 3, WeightsInitialization::Kaiming, 2).unwrap();
+    let sigmoid = Sigmoid::new(&device);
+    let output = sigmoid.forward(&linear.forward(&input).unwrap()).unwrap();
+    let instructions = output
+        .get_tape()
+        .iter()
+        .flat_map(|x| x.forward_instructions())
+        .collect::<Vec<_>>();
+    assert_eq!(4, instructions.len());
+
+    let execute = |instructions: &[Instruction]| {
+        let device_stream = device.new_stream().unwrap();
+        output.tensor().set_values(vec![0.0; 8]).unwrap();
+        for instruction in instructions.iter() {
+            instruction.execute(&device, &device_stream).unwrap();
+        }
+        device_stream.wait_for().unwrap();
+        output.tensor().get_values().unwrap()
+    };
+    let unfused_output = execute(&instructions);
+
+    let machine_tensors = [input.tensor().name(), output.tensor().name()];
+    let fused_instructions = fuse_linear_activation_instructions(&machine_tensors, instructions);
+    assert_eq!(1, fused_instructions.len());
+    assert!(matches!(
+        fused_instructions[0].opcode(),
+        OpCode::LinearActivation
+    ));
+    assert_eq!(unfused_output, execute(&fused_instructions));
+}
+
+#[test]
+fn machine_output_is_not_fused_away() {
+    let device = Device::default();
+    let input =
+        new_tensor_with_grad!(device, 1, 3, vec![0.1, 0.2, 0.3], &[], false, false).unwrap();
+    let linear = Linear::new(&device, 2, 3, WeightsInitialization::Kaiming, 1).unwrap();
+    let pre_activation = linear.forward(&input).unwrap();
+    let output = Sigmoid::new(&device).forward(&pre_activation).unwrap();
+    let instructions = output
+        .get_tape()
+        .iter()
+        .flat_map(|x| x.forward_instructions())
+        .collect::<Vec<_>>();
+    let machine_tensors = [
+        input.tensor().name(),
+        pre_activation.tensor().name(),
+        output.tensor().name(),
+    ];
+    let fused_instructions = fuse_linear_activation_instructions(&machine_tensors, instructions);
+    assert_eq!(4, fused_instructions.len());
+}
+
+fn new_generation_machine(device: &Device) -> NeuralMachine<f32, DefaultStreamScheduler> {
+    let model = ModelBuilder::new(device, 4, 6)
+        .embedding(8)
+        .linear(6)
+        .softmax()
+        .build()
+        .unwrap();
+    let loss_operator = SoftmaxCrossEntropyLoss::new(device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    NeuralMachine::<f32, DefaultStreamScheduler>::try_new(device, program, 1).unwrap()
+}
+
+#[test]
+fn generate_produces_max_new_tokens_after_the_prompt() {
+    let device = Device::default();
+    let mut neural_machine = new_generation_machine(&device);
+    let prompt = [1, 2, 3];
+    let max_new_tokens = 5;
+    let tokens = neural_machine
+        .generate(&prompt, max_new_tokens, None, 0)
+        .unwrap();
+    // The generated tokens do not fit in the 4 input rows.
+    assert_eq!(prompt.len() + max_new_tokens, tokens.len());
+    assert_eq!(prompt, tokens[..prompt.len()]);
+}
+
+#[test]
+fn generate_stops_after_the_eos_token() {
+    let device = Device::default();
+    let mut neural_machine = new_generation_machine(&device);
+    let prompt = [1, 2];
+    let first_token = neural_machine.generate(&prompt, 1, None, 0).unwrap()[2];
+    let tokens = neural_machine
+        .generate(&prompt, 5, Some(first_token), 0)
+        .unwrap();
+    assert_eq!(vec![1, 2, first_token], tokens);
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        neural_machine
+            .generate(&[], 5, None, 0)
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn neural_machine_with_an_instruction_limit() {
+    let build = |max_instructions: usize| {
+        let device = Device::default();
+        let model = PerceptronModel::new(&device).unwrap();
+        let loss_operator = SumOfSquaredErrors::new(&device);
+        let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+        let program =
+            NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new_with_limit(
+            &device,
+            program,
+            1,
+            max_instructions,
+            usize::MAX,
+        )
+    };
+    let instructions = {
+        let neural_machine = build(usize::MAX).unwrap();
+        [
+            Category::EnableDropout,
+            Category::DisableDropout,
+            Category::Inference,
+            Category::Loss,
+            Category::Gradient,
+            Category::Optimization,
+        ]
+        .iter()
+        .map(|category| neural_machine.instructions(category).len())
+        .sum::<usize>()
+    };
+
+    assert!(build(instructions).is_ok());
+    assert_eq!(
+        Err(ErrorEnum::InstructionLimitExceeded {
+            limit: instructions - 1,
+            found: instructions,
+        }),
+        build(instructions - 1)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn neural_machine_with_bounded_scheduler_queues() {
+    let device = Device::default();
+    let sequence_length = 3;
+    let vocab_size = 5;
+    let model = SimpleModel::new(&device, sequence_length, vocab_size).unwrap();
+    let mut values = vec![0.0; sequence_length * vocab_size];
+    for (row, token) in [1, 4, 2].into_iter().enumerate() {
+        values[row * vocab_size + token] = 1.0;
+    }
+    let input = new_tensor_with_grad!(
+        device,
+        sequence_length,
+        vocab_size,
+        values,
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let expected_output = new_tensor_with_grad!(
+        device,
+        1,
+        vocab_size,
+        vec![0.0, 0.0, 0.0, 1.0, 0.0],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+
+    let run = |queue_capacity: usize| {
+        let loss_operator = SoftmaxCrossEntropyLoss::new(&device);
+        let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+        let program =
+            NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+        let mut neural_machine = NeuralMachine::<f32, DefaultStreamScheduler>::try_new_with_limit(
+            &device,
+            program,
+            4,
+            usize::MAX,
+            queue_capacity,
+        )
+        .unwrap();
+        let output = neural_machine.infer(&input).unwrap().tensor().get_values();
+        let loss = neural_machine
+            .loss(&expected_output)
+            .unwrap()
+            .tensor()
+            .get_values();
+        neural_machine.zero_gradients().unwrap();
+        neural_machine.compute_gradient().unwrap();
+        let gradients = device
+            .parameter_tensors()
+            .iter()
+            .map(|x| x.gradient().get_values().unwrap())
+            .collect::<Vec<_>>();
+        (output.unwrap(), loss.unwrap(), gradients)
+    };
+
+    // The controller blocks on the full dispatch queues and every stream still executes.
+    let unbounded = run(usize::MAX);
+    let bounded = run(1);
+    assert_eq!(unbounded, bounded);
+}
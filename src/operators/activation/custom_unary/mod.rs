@@ -0,0 +1,223 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::stream::{DeviceStream, StreamTrait};
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad, Category, ExecutableOperator,
+    OperatorAttributes,
+};
+use crate::{tensor::Tensor, UnaryOperator};
+use crate::{
+    tensor::{Error, ErrorEnum},
+    TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An element-wise function of a CustomUnary operator.
+#[derive(Clone)]
+pub struct ElementwiseFn(Arc<dyn Fn(f32) -> f32 + Send + Sync>);
+
+impl ElementwiseFn {
+    pub fn new(f: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn call(&self, x: f32) -> f32 {
+        (self.0)(x)
+    }
+}
+
+impl Debug for ElementwiseFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ElementwiseFn")
+    }
+}
+
+/// Step of the central differences of numerical_derivative.
+const FINITE_DIFFERENCE_STEP: f32 = 1e-3;
+
+/// f'(x) = (f(x + h) - f(x - h)) / 2h
+/// See https://en.wikipedia.org/wiki/Numerical_differentiation
+pub fn numerical_derivative(f: &ElementwiseFn) -> ElementwiseFn {
+    let f = f.clone();
+    let h = FINITE_DIFFERENCE_STEP;
+    ElementwiseFn::new(move |x| (f.call(x + h) - f.call(x - h)) / (2.0 * h))
+}
+
+static NUMERICAL_DERIVATIVE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Warn, once per process, that a CustomUnary without backward uses a numerical derivative.
+/// Returns true if the warning was printed by this call.
+fn warn_numerical_derivative_once() -> bool {
+    let warned = NUMERICAL_DERIVATIVE_WARNED.swap(true, Ordering::Relaxed);
+    if !warned {
+        eprintln!(
+            "Warning: CustomUnary without backward, the numerical derivative is slow and approximate"
+        );
+    }
+    !warned
+}
+
+/// Apply a user-supplied element-wise closure.
+/// The closures run on the host, so they are meant for quick experimentation.
+/// The derivative is the analytic backward when one is provided.
+/// Otherwise, it falls back to numerical differentiation, which calls the forward closure twice per value.
+pub struct CustomUnary {
+    device: Device,
+    forward: ElementwiseFn,
+    derivative: ElementwiseFn,
+}
+
+impl CustomUnary {
+    /// Use the analytic backward when there is one,
+    /// and new_with_numerical_derivative otherwise, with a warning the first time.
+    pub fn new(
+        device: &Device,
+        forward: impl Fn(f32) -> f32 + Send + Sync + 'static,
+        backward: Option<Box<dyn Fn(f32) -> f32 + Send + Sync>>,
+    ) -> Self {
+        match backward {
+            Some(backward) => Self::new_with_derivative(device, forward, backward),
+            None => {
+                warn_numerical_derivative_once();
+                Self::new_with_numerical_derivative(device, forward)
+            }
+        }
+    }
+
+    /// Use the derivative closure in the backward pass.
+    pub fn new_with_derivative(
+        device: &Device,
+        forward: impl Fn(f32) -> f32 + Send + Sync + 'static,
+        derivative: impl Fn(f32) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            device: device.clone(),
+            forward: ElementwiseFn::new(forward),
+            derivative: ElementwiseFn::new(derivative),
+        }
+    }
+
+    /// Differentiate the forward closure numerically in the backward pass.
+    /// This is slow, since every derivative calls the forward closure twice,
+    /// and it is less accurate than an analytic derivative.
+    /// Prefer new_with_derivative when the derivative is known.
+    pub fn new_with_numerical_derivative(
+        device: &Device,
+        forward: impl Fn(f32) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        let forward = ElementwiseFn::new(forward);
+        let derivative = numerical_derivative(&forward);
+        Self {
+            device: device.clone(),
+            forward,
+            derivative,
+        }
+    }
+}
+
+fn get_elementwise_fn(attributes: &OperatorAttributes) -> Result<&ElementwiseFn, Error> {
+    match attributes {
+        OperatorAttributes::ElementwiseFn(f) => Ok(f),
+        _ => Err(error!(ErrorEnum::UnsupportedOperation)),
+    }
+}
+
+impl ExecutableOperator for CustomUnary {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let f = get_elementwise_fn(attributes)?;
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        let values = input.get_values()?.into_iter().map(|x| f.call(x)).collect();
+        output.set_values(values)
+    }
+}
+
+impl UnaryOperator for CustomUnary {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::CustomUnary,
+            OperatorAttributes::ElementwiseFn(self.forward.clone()),
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::CustomUnaryBackward,
+                OperatorAttributes::ElementwiseFn(self.derivative.clone()),
+                &[&input.tensor(), &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// input_gradient = output_gradient * f'(x)
+pub struct CustomUnaryBackward {}
+
+impl ExecutableOperator for CustomUnaryBackward {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let derivative = get_elementwise_fn(attributes)?;
+        let input = inputs[0];
+        let output_gradient = inputs[1];
+        let input_gradient = outputs[0];
+        device_stream.wait_for()?;
+        let values = input
+            .get_values()?
+            .into_iter()
+            .zip(output_gradient.get_values()?)
+            .map(|(x, dy)| dy * derivative.call(x))
+            .collect();
+        input_gradient.set_values(values)
+    }
+}
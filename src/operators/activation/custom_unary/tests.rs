@@ -0,0 +1,72 @@
+use more_asserts::assert_lt;
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+use crate::{
+    custom_unary::{warn_numerical_derivative_once, CustomUnary},
+    new_tensor_with_grad,
+    stream::StreamTrait,
+    Device, TensorWithGrad, UnaryOperator,
+};
+
+fn gradient_check(operator: &CustomUnary, f: impl Fn(f64) -> f64, tolerance: f64) {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 8;
+    let mut rng = thread_rng();
+    let uniform = Uniform::new(-2.0, 2.0);
+    let values = (0..rows * cols)
+        .map(|_| rng.sample(uniform))
+        .collect::<Vec<f32>>();
+
+    let input =
+        new_tensor_with_grad!(device, rows, cols, values.clone(), &[], true, false).unwrap();
+    let output: TensorWithGrad = operator.forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    let output_gradient = output.gradient();
+    output_gradient
+        .set_values(vec![1.0; output_gradient.len()])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let actual_output = output.tensor().get_values().unwrap();
+    let actual_gradient = input.gradient().get_values().unwrap();
+    let h = 1e-3;
+    for (i, x) in values.iter().enumerate() {
+        let x = *x as f64;
+        assert_lt!((f(x) - actual_output[i] as f64).abs(), 1e-4);
+        let expected = (f(x + h) - f(x - h)) / (2.0 * h);
+        assert_lt!((expected - actual_gradient[i] as f64).abs(), tolerance);
+    }
+}
+
+#[test]
+fn custom_unary_cube_with_analytic_backward() {
+    let device = Device::default();
+    let operator = CustomUnary::new(&device, |x| x * x * x, Some(Box::new(|x| 3.0 * x * x)));
+    gradient_check(&operator, |x| x * x * x, 1e-3);
+}
+
+#[test]
+fn custom_unary_cube_with_derivative() {
+    let device = Device::default();
+    let operator = CustomUnary::new_with_derivative(&device, |x| x * x * x, |x| 3.0 * x * x);
+    gradient_check(&operator, |x| x * x * x, 1e-3);
+}
+
+#[test]
+fn custom_unary_cube_with_numerical_backward() {
+    let device = Device::default();
+    let operator = CustomUnary::new_with_numerical_derivative(&device, |x| x * x * x);
+    gradient_check(&operator, |x| x * x * x, 1e-2);
+}
+
+#[test]
+fn custom_unary_without_backward_falls_back_to_numerical_derivative() {
+    let device = Device::default();
+    let operator = CustomUnary::new(&device, |x| x * x * x, None);
+    gradient_check(&operator, |x| x * x * x, 1e-2);
+    // The warning was printed by this constructor or by another one before.
+    assert!(!warn_numerical_derivative_once());
+}
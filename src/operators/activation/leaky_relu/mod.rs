@@ -0,0 +1,127 @@
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::stream::DeviceStream;
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad, Category, ExecutableOperator,
+    OperatorAttributes,
+};
+use crate::{tensor::Tensor, UnaryOperator};
+use crate::{
+    tensor::{Error, ErrorEnum},
+    DeviceTrait, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// https://onnx.ai/onnx/operators/onnx__LeakyRelu.html
+pub struct LeakyRelu {
+    device: Device,
+    negative_slope: f32,
+}
+
+impl LeakyRelu {
+    pub fn new(device: &Device) -> Self {
+        Self::new_with_negative_slope(device, 0.01)
+    }
+
+    pub fn new_with_negative_slope(device: &Device, negative_slope: f32) -> Self {
+        Self {
+            device: device.clone(),
+            negative_slope,
+        }
+    }
+}
+
+fn get_negative_slope(attributes: &OperatorAttributes) -> Result<f32, Error> {
+    match attributes {
+        OperatorAttributes::F32(negative_slope) => Ok(*negative_slope),
+        _ => Err(error!(ErrorEnum::UnsupportedOperation)),
+    }
+}
+
+impl ExecutableOperator for LeakyRelu {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let negative_slope = get_negative_slope(attributes)?;
+        let input = inputs[0];
+        let output = outputs[0];
+        device.leaky_relu(negative_slope, input, output, device_stream)
+    }
+}
+
+impl UnaryOperator for LeakyRelu {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::LeakyRelu,
+            OperatorAttributes::F32(self.negative_slope),
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::LeakyReluBackward,
+                OperatorAttributes::F32(self.negative_slope),
+                &[&input.tensor(), &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+pub struct LeakyReluBackward {}
+
+impl ExecutableOperator for LeakyReluBackward {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let negative_slope = get_negative_slope(attributes)?;
+        let input = inputs[0];
+        let output_gradient = inputs[1];
+        let input_gradient = outputs[0];
+        device.leaky_relu_backward(
+            negative_slope,
+            input,
+            output_gradient,
+            input_gradient,
+            device_stream,
+        )
+    }
+}
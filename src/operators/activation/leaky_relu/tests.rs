@@ -0,0 +1,63 @@
+use crate::{
+    leaky_relu::LeakyRelu, new_tensor_with_grad, stream::StreamTrait, Device, TensorWithGrad,
+    UnaryOperator,
+};
+
+#[test]
+fn leaky_relu_forward_and_backward() {
+    let device = Device::default();
+    let negative_slope = 0.5;
+    let input = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![
+            -2.0, -0.5, 0.0, //
+            0.5, 1.0, 3.0, //
+        ],
+        &[],
+        true,
+        false,
+    )
+    .unwrap();
+    let output: TensorWithGrad = LeakyRelu::new_with_negative_slope(&device, negative_slope)
+        .forward(&input)
+        .unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    output
+        .gradient()
+        .set_values(vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+        ])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    assert_eq!(
+        vec![
+            -1.0, -0.25, 0.0, //
+            0.5, 1.0, 3.0, //
+        ],
+        output.tensor().get_values().unwrap(),
+    );
+    assert_eq!(
+        vec![
+            0.5, 1.0, 1.5, //
+            4.0, 5.0, 6.0, //
+        ],
+        input.gradient().get_values().unwrap(),
+    );
+}
+
+#[test]
+fn leaky_relu_default_negative_slope() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(device, 1, 2, vec![-1.0, 2.0], &[], false, false).unwrap();
+    let output: TensorWithGrad = LeakyRelu::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![-0.01, 2.0], output.tensor().get_values().unwrap());
+}
@@ -0,0 +1,105 @@
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::stream::DeviceStream;
+use crate::{
+    instruction, new_tensor, new_tensor_with_grad, Category, ExecutableOperator, OperatorAttributes,
+};
+use crate::{tensor::Error, DeviceTrait, TensorWithGrad};
+use crate::{tensor::Tensor, UnaryOperator};
+
+#[cfg(test)]
+mod tests;
+
+/// https://onnx.ai/onnx/operators/onnx__LogSoftmax.html
+/// LogSoftmax(x) = x - max - log(sum(exp(x - max)))
+pub struct LogSoftmax {
+    device: Device,
+}
+
+impl LogSoftmax {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for LogSoftmax {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        device.log_softmax(input, output, device_stream)
+    }
+}
+
+impl UnaryOperator for LogSoftmax {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::LogSoftmax,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::LogSoftmaxBackward,
+                OperatorAttributes::None,
+                &[&output.tensor(), &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Backward of LogSoftmax.
+/// input_gradient = output_gradient - softmax(x) * sum(output_gradient)
+pub struct LogSoftmaxBackward {}
+
+impl ExecutableOperator for LogSoftmaxBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let output = inputs[0];
+        let output_gradient = inputs[1];
+        let input_gradient = outputs[0];
+        device.log_softmax_backward(output, output_gradient, input_gradient, device_stream)
+    }
+}
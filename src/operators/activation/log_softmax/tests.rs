@@ -0,0 +1,101 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    log_softmax::LogSoftmax, new_tensor_with_grad, stream::StreamTrait, Device, Softmax,
+    TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn log_softmax_matches_log_of_softmax() {
+    let device = Device::default();
+    let values = vec![
+        0.1, 0.2, -0.3, 1.5, //
+        -2.0, 0.0, 3.0, -1.0, //
+    ];
+    let input = new_tensor_with_grad!(device, 2, 4, values, &[], false, false).unwrap();
+    let log_softmax: TensorWithGrad = LogSoftmax::new(&device).forward(&input).unwrap();
+    let softmax: TensorWithGrad = Softmax::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    log_softmax.forward(&device, &device_stream).unwrap();
+    softmax.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let expected = softmax
+        .tensor()
+        .get_values()
+        .unwrap()
+        .into_iter()
+        .map(|x| x.ln())
+        .collect::<Vec<_>>();
+    let actual = log_softmax.tensor().get_values().unwrap();
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_lt!((expected - actual).abs(), 1e-5);
+    }
+}
+
+#[test]
+fn log_softmax_is_finite_for_large_logits() {
+    let device = Device::default();
+    let values = vec![
+        60.0, -60.0, 0.0, //
+        -60.0, 60.0, 59.0, //
+    ];
+    let input = new_tensor_with_grad!(device, 2, 3, values, &[], false, false).unwrap();
+    let log_softmax: TensorWithGrad = LogSoftmax::new(&device).forward(&input).unwrap();
+    let softmax: TensorWithGrad = Softmax::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    log_softmax.forward(&device, &device_stream).unwrap();
+    softmax.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let composed = softmax
+        .tensor()
+        .get_values()
+        .unwrap()
+        .into_iter()
+        .map(|x| x.ln())
+        .collect::<Vec<_>>();
+    assert!(composed.iter().any(|x| *x == f32::NEG_INFINITY));
+
+    let actual = log_softmax.tensor().get_values().unwrap();
+    assert!(actual.iter().all(|x| x.is_finite()));
+    assert_lt!((actual[1] - -120.0).abs(), 1e-3);
+}
+
+#[test]
+fn log_softmax_backward() {
+    let device = Device::default();
+    let values = vec![
+        1.0, 2.0, 3.0, //
+        -1.0, 0.0, 1.0, //
+    ];
+    let input = new_tensor_with_grad!(device, 2, 3, values, &[], true, false).unwrap();
+    let log_softmax: TensorWithGrad = LogSoftmax::new(&device).forward(&input).unwrap();
+    let softmax: TensorWithGrad = Softmax::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    log_softmax.forward(&device, &device_stream).unwrap();
+    softmax.forward(&device, &device_stream).unwrap();
+    let output_gradient = vec![
+        1.0, 0.0, 0.0, //
+        0.5, 0.5, 1.0, //
+    ];
+    log_softmax
+        .gradient()
+        .set_values(output_gradient.clone())
+        .unwrap();
+    log_softmax
+        .compute_gradient(&device, &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+
+    let softmax = softmax.tensor().get_values().unwrap();
+    let actual = input.gradient().get_values().unwrap();
+    for row in 0..2 {
+        let sum: f32 = output_gradient[row * 3..(row + 1) * 3].iter().sum();
+        for col in 0..3 {
+            let index = row * 3 + col;
+            let expected = output_gradient[index] - softmax[index] * sum;
+            assert_lt!((expected - actual[index]).abs(), 1e-5);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad, BinaryOperator, Category,
+    OperatorAttributes,
+};
+use crate::{
+    tensor::{Error, ErrorEnum, Tensor},
+    TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Row-wise softmax over the positions where mask is 1.
+/// Positions where mask is 0 get a probability of exactly 0 and no gradient.
+///
+/// Masked values are replaced by the lowest f32 before the softmax.
+/// exp underflows to 0 for them, like it would for -inf, but 0 * mask stays finite.
+/// A row where every position is masked is all zeros.
+///
+/// The backward is the full softmax Jacobian:
+/// dx = y * (dy - sum(dy * y))
+pub struct MaskedSoftmax {
+    device: Device,
+}
+
+impl MaskedSoftmax {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl BinaryOperator for MaskedSoftmax {
+    fn forward(
+        &self,
+        input: &TensorWithGrad,
+        mask: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let mask_t: &Tensor = &mask.tensor();
+        if *input_t.size() != *mask_t.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let device = &self.device;
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input, mask],
+            true,
+            false,
+        )?;
+        let output_t = output.tensor().clone();
+
+        let ones = new_tensor!(device, rows, cols, vec![1.0; len])?;
+        let lowest = new_tensor!(device, 1, 1, vec![f32::MIN])?;
+        let masked_input = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        let bias = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        let probabilities = new_tensor!(device, rows, cols, vec![0.0; len])?;
+
+        output.push_instruction(instruction!(
+            OpCode::Mul,
+            OperatorAttributes::None,
+            &[input_t, mask_t],
+            &[&masked_input],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Sub,
+            OperatorAttributes::None,
+            &[&ones, mask_t],
+            &[&bias],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&lowest, &bias],
+            &[&bias],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&masked_input, &bias],
+            &[&masked_input],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Softmax,
+            OperatorAttributes::None,
+            &[&masked_input],
+            &[&probabilities],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Mul,
+            OperatorAttributes::None,
+            &[&probabilities, mask_t],
+            &[&output_t],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let output_gradient: &Tensor = &output.gradient();
+            let input_gradient: &Tensor = &input.gradient();
+            let zero = new_tensor!(device, 1, 1, vec![0.0])?;
+            let ones_col = new_tensor!(device, cols, 1, vec![1.0; cols])?;
+            let ones_row = new_tensor!(device, 1, cols, vec![1.0; cols])?;
+            let product = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            let row_sums = new_tensor!(device, rows, 1, vec![0.0; rows])?;
+            let broadcasted_sums = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+
+            // sum(dy * y) for each row
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[output_gradient, &output_t],
+                &[&product],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&zero, &row_sums],
+                &[&row_sums],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Gemm,
+                OperatorAttributes::ThreeBools(false, false, false),
+                &[&product, &ones_col, &row_sums],
+                &[&row_sums],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&zero, &broadcasted_sums],
+                &[&broadcasted_sums],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Gemm,
+                OperatorAttributes::ThreeBools(false, false, false),
+                &[&row_sums, &ones_row, &broadcasted_sums],
+                &[&broadcasted_sums],
+                Category::Gradient,
+            ));
+
+            // y * (dy - sum(dy * y)), zeroed where the mask is 0.
+            output.push_instruction(instruction!(
+                OpCode::Sub,
+                OperatorAttributes::None,
+                &[output_gradient, &broadcasted_sums],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[&output_t, &tmp],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[&tmp, mask_t],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[input_gradient, &tmp],
+                &[input_gradient],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,101 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    masked_softmax::MaskedSoftmax, new_tensor_with_grad, stream::StreamTrait, BinaryOperator,
+    Device, TensorWithGrad,
+};
+
+#[test]
+fn masked_positions_have_zero_probability() {
+    let device = Device::default();
+    let values = vec![
+        0.1, 2.0, -0.3, 1.5, //
+        -2.0, 0.0, 3.0, -1.0, //
+        1.0, 1.0, 1.0, 1.0, //
+    ];
+    let mask = vec![
+        1.0, 0.0, 1.0, 1.0, //
+        0.0, 1.0, 0.0, 1.0, //
+        0.0, 0.0, 0.0, 0.0, //
+    ];
+    let input = new_tensor_with_grad!(device, 3, 4, values, &[], false, false).unwrap();
+    let mask = new_tensor_with_grad!(device, 3, 4, mask.clone(), &[], false, false).unwrap();
+    let output: TensorWithGrad = MaskedSoftmax::new(&device).forward(&input, &mask).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let mask = mask.tensor().get_values().unwrap();
+    let actual = output.tensor().get_values().unwrap();
+    for (probability, mask) in actual.iter().zip(mask.iter()) {
+        if *mask == 0.0 {
+            assert_eq!(0.0, *probability);
+        }
+    }
+    for row in 0..2 {
+        let sum = actual[row * 4..(row + 1) * 4].iter().sum::<f32>();
+        assert_lt!((sum - 1.0).abs(), 1e-6);
+    }
+    // Every position is masked in the last row.
+    assert_eq!(vec![0.0; 4], actual[8..12].to_vec());
+}
+
+#[test]
+fn masked_softmax_backward_matches_finite_differences() {
+    let device = Device::default();
+    let values = vec![
+        0.1, 2.0, -0.3, 1.5, //
+        -2.0, 0.0, 3.0, -1.0, //
+    ];
+    let mask_values = vec![
+        1.0, 0.0, 1.0, 1.0, //
+        0.0, 1.0, 1.0, 1.0, //
+    ];
+    // Weights of the loss sum(w * y), so that dy = w.
+    let weights = vec![
+        1.0, -2.0, 0.5, 3.0, //
+        2.0, 1.0, -1.0, 0.25, //
+    ];
+    let input = new_tensor_with_grad!(device, 2, 4, values.clone(), &[], true, false).unwrap();
+    let mask = new_tensor_with_grad!(device, 2, 4, mask_values.clone(), &[], false, false).unwrap();
+    let output: TensorWithGrad = MaskedSoftmax::new(&device).forward(&input, &mask).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    output.gradient().set_values(weights.clone()).unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let actual = input.gradient().get_values().unwrap();
+
+    let loss = |values: &[f32]| -> f64 {
+        let mut loss = 0.0;
+        for row in 0..2 {
+            let range = row * 4..(row + 1) * 4;
+            let max = range
+                .clone()
+                .filter(|i| mask_values[*i] == 1.0)
+                .map(|i| values[i] as f64)
+                .fold(f64::MIN, f64::max);
+            let sum = range
+                .clone()
+                .filter(|i| mask_values[*i] == 1.0)
+                .map(|i| (values[i] as f64 - max).exp())
+                .sum::<f64>();
+            for i in range.filter(|i| mask_values[*i] == 1.0) {
+                loss += weights[i] as f64 * (values[i] as f64 - max).exp() / sum;
+            }
+        }
+        loss
+    };
+    let h = 1e-3;
+    for index in 0..values.len() {
+        let mut plus = values.clone();
+        plus[index] += h;
+        let mut minus = values.clone();
+        minus[index] -= h;
+        let expected = (loss(&plus) - loss(&minus)) / (2.0 * h as f64);
+        if mask_values[index] == 0.0 {
+            assert_eq!(0.0, actual[index]);
+        }
+        assert_lt!((expected - actual[index] as f64).abs(), 1e-3);
+    }
+}
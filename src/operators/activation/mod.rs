@@ -2,4 +2,10 @@ mod sigmoid;
 pub use sigmoid::*;
 mod softmax;
 pub use softmax::*;
+pub mod custom_unary;
 pub mod gelu;
+pub mod leaky_relu;
+pub mod log_softmax;
+pub mod masked_softmax;
+pub mod silu;
+pub mod tanh;
@@ -0,0 +1,107 @@
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::stream::DeviceStream;
+use crate::{
+    instruction, new_tensor, new_tensor_with_grad, Category, ExecutableOperator, OperatorAttributes,
+};
+use crate::{tensor::Error, DeviceTrait, TensorWithGrad};
+use crate::{tensor::Tensor, UnaryOperator};
+
+#[cfg(test)]
+mod tests;
+
+/// SiLU(x) = x * sigmoid(x)
+/// See
+/// Sigmoid-Weighted Linear Units for Neural Network Function Approximation in Reinforcement Learning
+/// https://arxiv.org/abs/1702.03118
+pub struct Silu {
+    device: Device,
+}
+
+impl Silu {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for Silu {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        device.silu(input, output, device_stream)
+    }
+}
+
+impl UnaryOperator for Silu {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::Silu,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::SiluBackward,
+                OperatorAttributes::None,
+                &[&input.tensor(), &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Fused backward of SiLU.
+/// input_gradient = output_gradient * sigmoid(x) * (1 + x * (1 - sigmoid(x)))
+pub struct SiluBackward {}
+
+impl ExecutableOperator for SiluBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output_gradient = inputs[1];
+        let input_gradient = outputs[0];
+        device.silu_backward(input, output_gradient, input_gradient, device_stream)
+    }
+}
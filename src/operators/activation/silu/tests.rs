@@ -0,0 +1,40 @@
+use more_asserts::assert_lt;
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+use crate::{
+    new_tensor_with_grad, silu::Silu, stream::StreamTrait, Device, TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn silu_backward_matches_finite_differences() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 8;
+    let mut rng = thread_rng();
+    let uniform = Uniform::new(-4.0, 4.0);
+    let values = (0..rows * cols)
+        .map(|_| rng.sample(uniform))
+        .collect::<Vec<f32>>();
+    assert!(values.iter().any(|x| *x < 0.0));
+
+    let input =
+        new_tensor_with_grad!(device, rows, cols, values.clone(), &[], true, false).unwrap();
+    let output: TensorWithGrad = Silu::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    let output_gradient = output.gradient();
+    output_gradient
+        .set_values(vec![1.0; output_gradient.len()])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let silu = |x: f64| x / (1.0 + (-x).exp());
+    let h = 1e-3;
+    let actual = input.gradient().get_values().unwrap();
+    for (x, actual) in values.iter().zip(actual.iter()) {
+        let x = *x as f64;
+        let expected = (silu(x + h) - silu(x - h)) / (2.0 * h);
+        assert_lt!((expected - *actual as f64).abs(), 1e-3);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::devices::Device;
+use crate::opcode::OpCode;
+use crate::{instruction, new_tensor, new_tensor_with_grad, Category, OperatorAttributes};
+use crate::{tensor::Error, TensorWithGrad};
+use crate::{tensor::Tensor, UnaryOperator};
+
+#[cfg(test)]
+mod tests;
+
+/// tanh(x) = 2 * sigmoid(2 * x) - 1
+/// The instructions are the ones of Sigmoid, ScalarMul and ScalarAdd,
+/// so that tanh runs on the device without a kernel of its own.
+pub struct Tanh {
+    device: Device,
+}
+
+impl Tanh {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl UnaryOperator for Tanh {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let device = &self.device;
+        let output =
+            new_tensor_with_grad!(device, rows, cols, vec![0.0; len], &[input], true, false)?;
+
+        let two = new_tensor!(device, 1, 1, vec![2.0])?;
+        let minus_one = new_tensor!(device, 1, 1, vec![-1.0])?;
+        let two_x = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&two, &input.tensor()],
+            &[&two_x],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Sigmoid,
+            OperatorAttributes::None,
+            &[&two_x],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&two, &output.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarAdd,
+            OperatorAttributes::None,
+            &[&minus_one, &output.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        // tanh'(x) = 1 - tanh(x)^2
+        if input.gradient().requires_grad() {
+            let ones = new_tensor!(device, rows, cols, vec![1.0; len])?;
+            let square = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            let derivative = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[&output.tensor(), &output.tensor()],
+                &[&square],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Sub,
+                OperatorAttributes::None,
+                &[&ones, &square],
+                &[&derivative],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[&derivative, &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,37 @@
+use more_asserts::assert_lt;
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tanh::Tanh, Device, TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn tanh_and_its_gradient_match_the_host_tanh() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 8;
+    let mut rng = thread_rng();
+    let uniform = Uniform::new(-4.0, 4.0);
+    let values = (0..rows * cols)
+        .map(|_| rng.sample(uniform))
+        .collect::<Vec<f32>>();
+
+    let input =
+        new_tensor_with_grad!(device, rows, cols, values.clone(), &[], true, false).unwrap();
+    let output: TensorWithGrad = Tanh::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    let output_gradient = output.gradient();
+    output_gradient
+        .set_values(vec![1.0; output_gradient.len()])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    let actual = output.tensor().get_values().unwrap();
+    let actual_gradient = input.gradient().get_values().unwrap();
+    for ((x, actual), actual_gradient) in values.iter().zip(actual).zip(actual_gradient) {
+        assert_lt!((x.tanh() - actual).abs(), 1e-5);
+        assert_lt!((1.0 - x.tanh().powi(2) - actual_gradient).abs(), 1e-5);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    tensor::Error, Device, Linear, ScaledDotProductAttention, TensorWithGrad, TernaryOperator,
-    UnaryOperator, WeightsInitialization,
+    derive_seeds, tensor::Error, AttentionSimilarity, Device, Linear, RelativePositionBias,
+    ScaledDotProductAttention, Similarity, TensorWithGrad, TernaryOperator, UnaryOperator,
+    WeightsInitialization,
 };
 
 /// See:
@@ -13,6 +14,24 @@ pub struct AttentionHead {
     attention: ScaledDotProductAttention,
 }
 
+/// The configuration of an AttentionHead.
+#[derive(Clone, Copy, Debug)]
+pub struct AttentionHeadConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub head_cols: usize,
+    pub causal_mask: bool,
+    pub dropout_probability: f32,
+    pub learnable_scale: bool,
+    /// With a maximum distance, a relative position bias instead of absolute positions,
+    /// see RelativePositionBias.
+    pub max_distance: Option<usize>,
+    /// The scores of the queries and the keys, see AttentionSimilarity.
+    pub similarity: AttentionSimilarity,
+    /// The seeds of the Q, K and V projections and of the similarity are derived from seed.
+    pub seed: Option<u64>,
+}
+
 impl AttentionHead {
     pub fn try_new(
         device: &Device,
@@ -21,40 +40,89 @@ impl AttentionHead {
         head_cols: usize,
         causal_mask: bool,
         dropout_probability: f32,
+        learnable_scale: bool,
+    ) -> Result<Self, Error> {
+        Self::try_new_with_config(
+            device,
+            &AttentionHeadConfig {
+                rows,
+                cols,
+                head_cols,
+                causal_mask,
+                dropout_probability,
+                learnable_scale,
+                max_distance: None,
+                similarity: AttentionSimilarity::ScaledDot,
+                seed: None,
+            },
+        )
+    }
+
+    pub fn try_new_with_config(
+        device: &Device,
+        config: &AttentionHeadConfig,
     ) -> Result<Self, Error> {
-        let q = Linear::new(
+        let AttentionHeadConfig {
+            rows,
+            cols,
+            head_cols,
+            causal_mask,
+            dropout_probability,
+            learnable_scale,
+            max_distance,
+            similarity,
+            seed,
+        } = *config;
+        let seeds = derive_seeds(seed, 4);
+        let q = Linear::new_with_seed(
             device,
             head_cols,
             cols,
             WeightsInitialization::Kaiming,
             rows,
+            seeds[0],
         )?;
-        let k = Linear::new(
+        let k = Linear::new_with_seed(
             device,
             head_cols,
             cols,
             WeightsInitialization::Kaiming,
             rows,
+            seeds[1],
         )?;
-        let v = Linear::new(
+        let v = Linear::new_with_seed(
             device,
             head_cols,
             cols,
             WeightsInitialization::Kaiming,
             rows,
+            seeds[2],
         )?;
-        let attention = ScaledDotProductAttention::try_new(
+        // The dot products are scaled with the columns of the model,
+        // and the additive scores project the queries and the keys of the head.
+        let similarity_cols = match similarity {
+            AttentionSimilarity::Additive { .. } => head_cols,
+            _ => cols,
+        };
+        let similarity = Similarity::try_new(device, rows, similarity_cols, similarity, seeds[3])?;
+        let attention = ScaledDotProductAttention::try_new_with_similarity(
             device,
             rows,
-            cols,
             causal_mask,
             dropout_probability,
+            learnable_scale,
+            max_distance,
+            similarity,
         )
         .unwrap();
 
         let head = Self { q, k, v, attention };
         Ok(head)
     }
+
+    pub fn relative_position_bias(&self) -> Option<&RelativePositionBias> {
+        self.attention.relative_position_bias()
+    }
 }
 
 impl TernaryOperator for AttentionHead {
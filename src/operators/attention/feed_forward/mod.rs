@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    derive_seeds, gelu::Gelu, leaky_relu::LeakyRelu, silu::Silu, tensor::Error, Device, Linear,
+    TensorWithGrad, UnaryOperator, WeightsInitialization,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The activation between the two linear layers of a FeedForward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FeedForwardActivation {
+    Relu,
+    #[default]
+    Gelu,
+    Silu,
+}
+
+/// The position-wise feed-forward network of a Transformer:
+/// a linear layer, an activation and another linear layer.
+///
+/// See:
+/// Attention Is All You Need
+/// https://arxiv.org/abs/1706.03762
+pub struct FeedForward {
+    linear_1: Linear,
+    activation: Box<dyn UnaryOperator>,
+    linear_2: Linear,
+}
+
+impl FeedForward {
+    pub fn try_new(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        activation: FeedForwardActivation,
+    ) -> Result<Self, Error> {
+        Self::try_new_with_seed(device, rows, cols, activation, None, false)
+    }
+
+    /// The seeds of the linear layers are derived from seed.
+    /// When position_independent is true, the biases have one row that is shared by every row
+    /// of the input, otherwise there is one row of biases per row.
+    pub fn try_new_with_seed(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        activation: FeedForwardActivation,
+        seed: Option<u64>,
+        position_independent: bool,
+    ) -> Result<Self, Error> {
+        let seeds = derive_seeds(seed, 2);
+        let bias_rows = match position_independent {
+            true => 1,
+            false => rows,
+        };
+        let linear_1 = Linear::new_with_seed(
+            device,
+            cols,
+            cols,
+            WeightsInitialization::Kaiming,
+            bias_rows,
+            seeds[0],
+        )?;
+        let activation: Box<dyn UnaryOperator> = match activation {
+            FeedForwardActivation::Relu => {
+                Box::new(LeakyRelu::new_with_negative_slope(device, 0.0))
+            }
+            FeedForwardActivation::Gelu => Box::new(Gelu::new(device)),
+            FeedForwardActivation::Silu => Box::new(Silu::new(device)),
+        };
+        let linear_2 = Linear::new_with_seed(
+            device,
+            cols,
+            cols,
+            WeightsInitialization::Kaiming,
+            bias_rows,
+            seeds[1],
+        )?;
+        let feed_forward = Self {
+            linear_1,
+            activation,
+            linear_2,
+        };
+        Ok(feed_forward)
+    }
+}
+
+impl UnaryOperator for FeedForward {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let lin_1 = self.linear_1.forward(input)?;
+        let activated = self.activation.forward(&lin_1)?;
+        self.linear_2.forward(&activated)
+    }
+}
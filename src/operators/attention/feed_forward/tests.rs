@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, Device, FeedForward, FeedForwardActivation,
+    TensorWithGrad, UnaryOperator,
+};
+
+/// Run the forward instructions of the tape.
+fn forward(device: &Device, output: &TensorWithGrad) -> Vec<f32> {
+    let device_stream = device.new_stream().unwrap();
+    let mut processed = HashSet::new();
+    for tensor in output.get_tape().iter() {
+        if processed.insert(tensor.tensor().name()) {
+            tensor.forward(device, &device_stream).unwrap();
+        }
+    }
+    device_stream.wait_for().unwrap();
+    output.tensor().get_values().unwrap()
+}
+
+#[test]
+fn relu_and_gelu_give_different_outputs_with_the_same_weights() {
+    let device = Device::default();
+    let rows = 3;
+    let cols = 4;
+    let values = (0..(rows * cols))
+        .map(|x| ((x * 5) % 7) as f32 / 7.0 - 0.5)
+        .collect::<Vec<_>>();
+    let input = new_tensor_with_grad!(device, rows, cols, values, &[], false, false).unwrap();
+
+    let mut outputs = vec![];
+    for activation in [FeedForwardActivation::Relu, FeedForwardActivation::Gelu] {
+        let feed_forward =
+            FeedForward::try_new_with_seed(&device, rows, cols, activation, Some(42), false)
+                .unwrap();
+        let output = feed_forward.forward(&input).unwrap();
+        assert_eq!(*input.tensor().size(), *output.tensor().size());
+        outputs.push(forward(&device, &output));
+    }
+    assert_ne!(outputs[0], outputs[1]);
+}
@@ -1,7 +1,15 @@
 mod scaled_dot_product_attention;
 pub use scaled_dot_product_attention::*;
+mod relative_position_bias;
+pub use relative_position_bias::*;
+mod pairwise_add;
+pub use pairwise_add::*;
+mod similarity;
+pub use similarity::*;
 mod attention_head;
 pub use attention_head::*;
 mod multi_head_attention;
 pub use multi_head_attention::*;
+mod feed_forward;
+pub use feed_forward::*;
 pub mod transformer;
@@ -0,0 +1,166 @@
+use crate::{
+    derive_seeds, error,
+    tensor::{Error, ErrorEnum},
+    Concat, Device, Linear, NaryOperator, ScaledDotProductAttention, TensorWithGrad,
+    TernaryOperator, UnaryOperator, WeightsInitialization,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// See:
+/// Attention Is All You Need
+/// https://arxiv.org/abs/1706.03762
+///
+/// With fewer key-value heads than query heads, each key-value head is shared by a group of
+/// query heads.
+///
+/// See:
+/// GQA: Training Generalized Multi-Query Transformer Models from Multi-Head Checkpoints
+/// https://arxiv.org/abs/2305.13245
+pub struct MultiHeadAttention {
+    key_value_heads: Vec<KeyValueHead>,
+    concat: Concat,
+    linear: Linear,
+}
+
+/// The K and V projections shared by the query heads of a group.
+struct KeyValueHead {
+    k: Linear,
+    v: Linear,
+    query_heads: Vec<QueryHead>,
+}
+
+struct QueryHead {
+    q: Linear,
+    attention: ScaledDotProductAttention,
+}
+
+/// The configuration of a MultiHeadAttention.
+#[derive(Clone, Copy, Debug)]
+pub struct MultiHeadAttentionConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub causal_mask: bool,
+    pub num_heads: usize,
+    /// The number of key-value heads, which divides num_heads.
+    pub num_kv_heads: usize,
+    pub dropout_probability: f32,
+    pub learnable_scale: bool,
+    /// The seeds of the projections are derived from seed.
+    pub seed: Option<u64>,
+    /// The biases of the projections have one row that is shared by every position,
+    /// instead of one row per position, and the causal mask gives a probability of exactly 0
+    /// to the future positions, see ScaledDotProductAttention::try_new_with_masked_softmax.
+    /// The rows of a prefix then do not depend on the number of rows.
+    pub position_independent: bool,
+}
+
+impl MultiHeadAttention {
+    pub fn try_new(device: &Device, config: &MultiHeadAttentionConfig) -> Result<Self, Error> {
+        let MultiHeadAttentionConfig {
+            rows,
+            cols,
+            causal_mask,
+            num_heads,
+            num_kv_heads,
+            dropout_probability,
+            learnable_scale,
+            seed,
+            position_independent,
+        } = *config;
+        if num_heads == 0
+            || !cols.is_multiple_of(num_heads)
+            || num_kv_heads == 0
+            || !num_heads.is_multiple_of(num_kv_heads)
+        {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let seeds = derive_seeds(seed, 2 * num_kv_heads + num_heads + 1);
+        let head_cols = cols / num_heads;
+        let group_size = num_heads / num_kv_heads;
+        let bias_rows = match position_independent {
+            true => 1,
+            false => rows,
+        };
+        let new_projection = |seed: Option<u64>| {
+            Linear::new_with_seed(
+                device,
+                head_cols,
+                cols,
+                WeightsInitialization::Kaiming,
+                bias_rows,
+                seed,
+            )
+        };
+        let mut key_value_heads = vec![];
+        for kv_head in 0..num_kv_heads {
+            let k = new_projection(seeds[2 * kv_head])?;
+            let v = new_projection(seeds[2 * kv_head + 1])?;
+            let mut query_heads = vec![];
+            for head in (kv_head * group_size)..((kv_head + 1) * group_size) {
+                let q = new_projection(seeds[2 * num_kv_heads + head])?;
+                let attention = match causal_mask && position_independent {
+                    true => ScaledDotProductAttention::try_new_with_masked_softmax(
+                        device,
+                        rows,
+                        cols,
+                        dropout_probability,
+                        learnable_scale,
+                    )?,
+                    false => ScaledDotProductAttention::try_new(
+                        device,
+                        rows,
+                        cols,
+                        causal_mask,
+                        dropout_probability,
+                        learnable_scale,
+                    )?,
+                };
+                query_heads.push(QueryHead { q, attention });
+            }
+            key_value_heads.push(KeyValueHead { k, v, query_heads });
+        }
+
+        let concat = Concat::new(device);
+        let linear = Linear::new_with_seed(
+            device,
+            cols,
+            cols,
+            WeightsInitialization::Kaiming,
+            bias_rows,
+            seeds[2 * num_kv_heads + num_heads],
+        )?;
+        let multi_head_attention = Self {
+            key_value_heads,
+            concat,
+            linear,
+        };
+        Ok(multi_head_attention)
+    }
+}
+
+impl TernaryOperator for MultiHeadAttention {
+    fn forward(
+        &self,
+        q: &TensorWithGrad,
+        k: &TensorWithGrad,
+        v: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let mut attention_head_attentions = vec![];
+        for key_value_head in self.key_value_heads.iter() {
+            let k = key_value_head.k.forward(k)?;
+            let v = key_value_head.v.forward(v)?;
+            for query_head in key_value_head.query_heads.iter() {
+                let q = query_head.q.forward(q)?;
+                let attentions = query_head.attention.forward(&q, &k, &v)?;
+                attention_head_attentions.push(attentions);
+            }
+        }
+
+        let attention_head_attentions: Vec<_> = attention_head_attentions.iter().collect();
+        let concat = self.concat.forward(&attention_head_attentions)?;
+        let linear = self.linear.forward(&concat)?;
+        Ok(linear)
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tensor::ErrorEnum, Device, MultiHeadAttention,
+    MultiHeadAttentionConfig, TensorWithGrad, TernaryOperator,
+};
+
+fn random_values(len: usize) -> Vec<f32> {
+    let mut rng = thread_rng();
+    let uniform = Uniform::new(-1.0, 1.0);
+    (0..len).map(|_| rng.sample(uniform)).collect()
+}
+
+/// Run the forward and the backward of every node of the tape.
+fn forward_and_backward(device: &Device, output: &TensorWithGrad) {
+    let device_stream = device.new_stream().unwrap();
+    let mut tape = vec![];
+    let mut names = HashSet::new();
+    for tensor in output.get_tape().into_iter() {
+        if names.insert(tensor.tensor().name()) {
+            tape.push(tensor);
+        }
+    }
+    for tensor in tape.iter() {
+        tensor.forward(device, &device_stream).unwrap();
+    }
+    let output_gradient = output.gradient().clone();
+    output_gradient
+        .set_values(random_values(output_gradient.len()))
+        .unwrap();
+    for tensor in tape.iter().rev() {
+        tensor.compute_gradient(device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+}
+
+#[test]
+fn learnable_scale_receives_a_gradient() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 8;
+    let num_heads = 2;
+    let causal_mask = true;
+    let dropout_probability = 0.0;
+    let learnable_scale = true;
+    let attention = MultiHeadAttention::try_new(
+        &device,
+        &MultiHeadAttentionConfig {
+            rows,
+            cols,
+            causal_mask,
+            num_heads,
+            num_kv_heads: num_heads,
+            dropout_probability,
+            learnable_scale,
+            seed: None,
+            position_independent: false,
+        },
+    )
+    .unwrap();
+    let input = new_tensor_with_grad!(
+        device,
+        rows,
+        cols,
+        random_values(rows * cols),
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let output = attention.forward(&input, &input, &input).unwrap();
+    forward_and_backward(&device, &output);
+
+    // One scale per head.
+    let scales = device
+        .parameter_tensors()
+        .iter()
+        .filter(|parameter| *parameter.tensor().size() == [1, 1])
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(num_heads, scales.len());
+    for scale in scales.iter() {
+        let gradient = scale.gradient().get_values().unwrap();
+        assert_ne!(0.0, gradient[0]);
+    }
+}
+
+#[test]
+fn grouped_query_attention_shares_key_value_projections() {
+    let rows = 4;
+    let cols = 8;
+    let num_heads = 4;
+    let head_cols = cols / num_heads;
+    // The biases have one row per input row.
+    let projection_parameters = (cols + rows) * head_cols;
+    let output_parameters = (cols + rows) * cols;
+    // Returns the number of parameters of the K and V projections and the output.
+    let key_value_parameters = |num_kv_heads: usize| {
+        let device = Device::default();
+        let attention = MultiHeadAttention::try_new(
+            &device,
+            &MultiHeadAttentionConfig {
+                rows,
+                cols,
+                causal_mask: true,
+                num_heads,
+                num_kv_heads,
+                dropout_probability: 0.0,
+                learnable_scale: false,
+                seed: None,
+                position_independent: false,
+            },
+        )
+        .unwrap();
+        let input = new_tensor_with_grad!(
+            device,
+            rows,
+            cols,
+            random_values(rows * cols),
+            &[],
+            false,
+            false
+        )
+        .unwrap();
+        let output = attention.forward(&input, &input, &input).unwrap();
+        forward_and_backward(&device, &output);
+        assert_eq!(vec![rows, cols], *output.tensor().size());
+
+        let parameters = device
+            .parameter_tensors()
+            .iter()
+            .map(|parameter| parameter.tensor().len())
+            .sum::<usize>();
+        parameters - num_heads * projection_parameters - output_parameters
+    };
+    assert_eq!(key_value_parameters(num_heads) / 2, key_value_parameters(2));
+}
+
+#[test]
+fn num_kv_heads_must_divide_num_heads() {
+    let device = Device::default();
+    let result = MultiHeadAttention::try_new(
+        &device,
+        &MultiHeadAttentionConfig {
+            rows: 4,
+            cols: 8,
+            causal_mask: true,
+            num_heads: 4,
+            num_kv_heads: 3,
+            dropout_probability: 0.0,
+            learnable_scale: false,
+            seed: None,
+            position_independent: false,
+        },
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        result.map(|_| ()).map_err(|e| e.error().clone())
+    );
+}
@@ -0,0 +1,155 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Not ONNX-compliant
+/// output[i * right_rows + j] = left[i] + right[j] for every pair of rows (i, j).
+/// This is the broadcast of left and right over the pairs, without expanding them first.
+pub struct PairwiseAdd {
+    device: Device,
+}
+
+impl PairwiseAdd {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.to_owned(),
+        }
+    }
+}
+
+impl ExecutableOperator for PairwiseAdd {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let left = inputs[0];
+        let right = inputs[1];
+        let output = outputs[0];
+        let cols = left.cols();
+        if right.cols() != cols || output.len() != left.rows() * right.rows() * cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let left = left.get_values()?;
+        let right = right.get_values()?;
+        let mut values = Vec::with_capacity(output.len());
+        for left_row in left.chunks(cols) {
+            for right_row in right.chunks(cols) {
+                values.extend(left_row.iter().zip(right_row.iter()).map(|(x, y)| x + y));
+            }
+        }
+        output.set_values(values)
+    }
+}
+
+impl BinaryOperator for PairwiseAdd {
+    fn forward(
+        &self,
+        left: &TensorWithGrad,
+        right: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let left_rows = left.tensor().rows();
+        let right_rows = right.tensor().rows();
+        let cols = left.tensor().cols();
+        if right.tensor().cols() != cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let rows = left_rows * right_rows;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; rows * cols],
+            &[left, right],
+            true,
+            false,
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::PairwiseAdd,
+            OperatorAttributes::None,
+            &[&left.tensor(), &right.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        {
+            let left_gradient: &Tensor = &left.gradient();
+            let right_gradient: &Tensor = &right.gradient();
+            if left_gradient.requires_grad() || right_gradient.requires_grad() {
+                let left_tmp =
+                    new_tensor!(self.device, left_rows, cols, vec![0.0; left_rows * cols])?;
+                let right_tmp =
+                    new_tensor!(self.device, right_rows, cols, vec![0.0; right_rows * cols])?;
+                output.push_instruction(instruction!(
+                    OpCode::PairwiseAddBackward,
+                    OperatorAttributes::None,
+                    &[&output.gradient()],
+                    &[&left_tmp, &right_tmp],
+                    Category::Gradient,
+                ));
+                for (tmp, gradient) in [(&left_tmp, left_gradient), (&right_tmp, right_gradient)] {
+                    if gradient.requires_grad() {
+                        output.push_instruction(instruction!(
+                            OpCode::Add,
+                            OperatorAttributes::None,
+                            &[gradient, tmp],
+                            &[gradient],
+                            Category::Gradient,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// left_gradient[i] is the sum of the output gradients of the pairs (i, j) over j,
+/// and right_gradient[j] is their sum over i.
+pub struct PairwiseAddBackward {}
+
+impl ExecutableOperator for PairwiseAddBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let output_gradient = inputs[0];
+        let left_gradient = outputs[0];
+        let right_gradient = outputs[1];
+        let cols = left_gradient.cols();
+        let right_rows = right_gradient.rows();
+        if right_gradient.cols() != cols
+            || output_gradient.len() != left_gradient.rows() * right_rows * cols
+        {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let output_gradient = output_gradient.get_values()?;
+        let mut left_values = vec![0.0; left_gradient.len()];
+        let mut right_values = vec![0.0; right_gradient.len()];
+        for (pair, row) in output_gradient.chunks(cols).enumerate() {
+            let (i, j) = (pair / right_rows, pair % right_rows);
+            for (col, value) in row.iter().enumerate() {
+                left_values[i * cols + col] += value;
+                right_values[j * cols + col] += value;
+            }
+        }
+        left_gradient.set_values(left_values)?;
+        right_gradient.set_values(right_values)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tensor::ErrorEnum, BinaryOperator, Device,
+    PairwiseAdd,
+};
+
+#[test]
+fn pairwise_add_adds_every_pair_of_rows_and_sums_their_gradients() {
+    let device = Device::default();
+    let left = new_tensor_with_grad!(
+        device,
+        2,
+        2,
+        vec![
+            1.0, 2.0, //
+            3.0, 4.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let right = new_tensor_with_grad!(
+        device,
+        3,
+        2,
+        vec![
+            10.0, 20.0, //
+            30.0, 40.0, //
+            50.0, 60.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let output = PairwiseAdd::new(&device).forward(&left, &right).unwrap();
+    assert_eq!(vec![6, 2], *output.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            11.0, 22.0, //
+            31.0, 42.0, //
+            51.0, 62.0, //
+            13.0, 24.0, //
+            33.0, 44.0, //
+            53.0, 64.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output
+        .gradient()
+        .set_values(vec![
+            1.0, 2.0, //
+            3.0, 4.0, //
+            5.0, 6.0, //
+            7.0, 8.0, //
+            9.0, 10.0, //
+            11.0, 12.0, //
+        ])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            9.0, 12.0, //
+            27.0, 30.0, //
+        ],
+        left.gradient().get_values().unwrap()
+    );
+    assert_eq!(
+        vec![
+            8.0, 10.0, //
+            12.0, 14.0, //
+            16.0, 18.0, //
+        ],
+        right.gradient().get_values().unwrap()
+    );
+}
+
+#[test]
+fn pairwise_add_rejects_rows_with_other_columns() {
+    let device = Device::default();
+    let left = new_tensor_with_grad!(device, 2, 2, vec![0.0; 4], &[], true, false).unwrap();
+    let right = new_tensor_with_grad!(device, 2, 3, vec![0.0; 6], &[], true, false).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        PairwiseAdd::new(&device)
+            .forward(&left, &right)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
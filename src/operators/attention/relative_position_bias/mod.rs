@@ -0,0 +1,187 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Adds a learned bias to the attention scores, which depends only on the distance
+/// between the key position and the query position.
+/// Distances are clamped to [-max_distance, max_distance], so the bias table has
+/// 2 * max_distance + 1 values.
+///
+/// See:
+/// Exploring the Limits of Transfer Learning with a Unified Text-to-Text Transformer
+/// https://arxiv.org/abs/1910.10683
+pub struct RelativePositionBias {
+    device: Device,
+    rows: usize,
+    max_distance: usize,
+    table: TensorWithGrad,
+}
+
+impl RelativePositionBias {
+    /// The scores are rows x rows. The biases start at 0.
+    pub fn try_new(device: &Device, rows: usize, max_distance: usize) -> Result<Self, Error> {
+        let buckets = 2 * max_distance + 1;
+        let table = new_tensor_with_grad!(device, 1, buckets, vec![0.0; buckets], &[], true, true)?;
+        let op = Self {
+            device: device.clone(),
+            rows,
+            max_distance,
+            table,
+        };
+        Ok(op)
+    }
+
+    /// The bias of each clamped distance, from -max_distance to max_distance.
+    pub fn table(&self) -> &TensorWithGrad {
+        &self.table
+    }
+
+    /// The index in the table of the bias of each score, in row-major order.
+    pub fn buckets(&self) -> Vec<usize> {
+        let max_distance = self.max_distance as i64;
+        let mut buckets = Vec::with_capacity(self.rows * self.rows);
+        for query in 0..self.rows as i64 {
+            for key in 0..self.rows as i64 {
+                let distance = (key - query).clamp(-max_distance, max_distance);
+                buckets.push((distance + max_distance) as usize);
+            }
+        }
+        buckets
+    }
+}
+
+impl UnaryOperator for RelativePositionBias {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        if input_t.rows() != self.rows || input_t.cols() != self.rows {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let device = &self.device;
+        let rows = self.rows;
+        let table: &Tensor = &self.table.tensor();
+        let output = new_tensor_with_grad!(
+            device,
+            rows,
+            rows,
+            vec![0.0; rows * rows],
+            &[input, &self.table],
+            true,
+            false
+        )?;
+        let buckets = OperatorAttributes::Vec(self.buckets());
+
+        // output := biases + scores
+        output.push_instruction(instruction!(
+            OpCode::RelativePositionBias,
+            buckets.clone(),
+            &[table],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&output.tensor(), input_t],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        {
+            let output_gradient: &Tensor = &output.gradient();
+            let table_gradient: &Tensor = &self.table.gradient();
+            if table_gradient.requires_grad() {
+                let tmp = new_tensor!(device, 1, table.cols(), vec![0.0; table.cols()])?;
+                output.push_instruction(instruction!(
+                    OpCode::RelativePositionBiasBackward,
+                    buckets,
+                    &[output_gradient],
+                    &[&tmp],
+                    Category::Gradient,
+                ));
+                output.push_instruction(instruction!(
+                    OpCode::Add,
+                    OperatorAttributes::None,
+                    &[table_gradient, &tmp],
+                    &[table_gradient],
+                    Category::Gradient,
+                ));
+            }
+
+            let input_gradient: &Tensor = &input.gradient();
+            if input_gradient.requires_grad() {
+                output.push_instruction(instruction!(
+                    OpCode::Add,
+                    OperatorAttributes::None,
+                    &[input_gradient, output_gradient],
+                    &[input_gradient],
+                    Category::Gradient,
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+fn buckets_attribute(attributes: &OperatorAttributes) -> Result<&[usize], Error> {
+    match attributes {
+        OperatorAttributes::Vec(buckets) => Ok(buckets),
+        _ => Err(error!(ErrorEnum::UnsupportedOperation)),
+    }
+}
+
+impl ExecutableOperator for RelativePositionBias {
+    /// biases[i] = table[buckets[i]]
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let buckets = buckets_attribute(attributes)?;
+        let table = inputs[0];
+        let biases = outputs[0];
+        if buckets.len() != biases.len() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let table = table.get_values()?;
+        let values = buckets
+            .iter()
+            .map(|bucket| table[*bucket])
+            .collect::<Vec<_>>();
+        biases.set_values(values)
+    }
+}
+
+/// table_gradient[bucket] is the sum of the bias gradients of the scores in the bucket.
+pub struct RelativePositionBiasBackward {}
+
+impl ExecutableOperator for RelativePositionBiasBackward {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let buckets = buckets_attribute(attributes)?;
+        let biases_gradient = inputs[0];
+        let table_gradient = outputs[0];
+        device_stream.wait_for()?;
+        let biases_gradient = biases_gradient.get_values()?;
+        let mut values = vec![0.0; table_gradient.len()];
+        for (bucket, gradient) in buckets.iter().zip(biases_gradient.iter()) {
+            values[*bucket] += gradient;
+        }
+        table_gradient.set_values(values)
+    }
+}
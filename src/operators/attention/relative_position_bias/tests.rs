@@ -0,0 +1,111 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, AttentionHead, AttentionHeadConfig,
+    AttentionSimilarity, Device, RelativePositionBias, TernaryOperator, UnaryOperator,
+};
+
+#[test]
+fn scores_at_the_same_distance_share_the_same_bias() {
+    let device = Device::default();
+    let rows = 3;
+    let max_distance = 1;
+    let relative_position_bias =
+        RelativePositionBias::try_new(&device, rows, max_distance).unwrap();
+    relative_position_bias
+        .table()
+        .tensor()
+        .set_values(vec![10.0, 20.0, 30.0])
+        .unwrap();
+    let scores =
+        new_tensor_with_grad!(device, rows, rows, vec![0.5; rows * rows], &[], true, false)
+            .unwrap();
+    let output = relative_position_bias.forward(&scores).unwrap();
+
+    let device_stream = device.new_stream().unwrap();
+    let tape = output.get_tape();
+    for tensor in tape.iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    // The key at distance -2 is clamped to distance -1.
+    assert_eq!(
+        vec![
+            20.5, 30.5, 30.5, //
+            10.5, 20.5, 30.5, //
+            10.5, 10.5, 20.5, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output
+        .gradient()
+        .set_values(vec![1.0; rows * rows])
+        .unwrap();
+    for tensor in tape.iter().rev() {
+        tensor.compute_gradient(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    // Each bias receives the gradients of the scores at its distance.
+    assert_eq!(
+        vec![3.0, 3.0, 3.0],
+        relative_position_bias
+            .table()
+            .gradient()
+            .get_values()
+            .unwrap()
+    );
+    assert_eq!(
+        vec![1.0; rows * rows],
+        scores.gradient().get_values().unwrap()
+    );
+}
+
+#[test]
+fn attention_head_relative_position_bias_receives_gradients() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 6;
+    let head_cols = 3;
+    let max_distance = 2;
+    let attention_head = AttentionHead::try_new_with_config(
+        &device,
+        &AttentionHeadConfig {
+            rows,
+            cols,
+            head_cols,
+            causal_mask: true,
+            dropout_probability: 0.0,
+            learnable_scale: false,
+            max_distance: Some(max_distance),
+            similarity: AttentionSimilarity::ScaledDot,
+            seed: None,
+        },
+    )
+    .unwrap();
+    let table = attention_head.relative_position_bias().unwrap().table();
+    assert_eq!(vec![1, 2 * max_distance + 1], *table.tensor().size());
+
+    let values = (0..(rows * cols))
+        .map(|x| ((x * 7) % 11) as f32 / 11.0)
+        .collect::<Vec<_>>();
+    let input = new_tensor_with_grad!(device, rows, cols, values, &[], true, false).unwrap();
+    let output = attention_head.forward(&input, &input, &input).unwrap();
+
+    let device_stream = device.new_stream().unwrap();
+    let tape = output.get_tape();
+    for tensor in tape.iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    let output_gradient = (0..(rows * head_cols))
+        .map(|x| x as f32 / 10.0)
+        .collect::<Vec<_>>();
+    output.gradient().set_values(output_gradient).unwrap();
+    for tensor in tape.iter().rev() {
+        tensor.compute_gradient(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    let gradient = table.gradient().get_values().unwrap();
+    assert!(gradient.iter().any(|x| *x != 0.0));
+    // With the causal mask, the keys after the query do not contribute.
+    assert_eq!(0.0, gradient[max_distance + 1]);
+    assert_eq!(0.0, gradient[max_distance + 2]);
+}
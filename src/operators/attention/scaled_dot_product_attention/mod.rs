@@ -1,5 +1,6 @@
 use crate::{
-    tensor::Error, BinaryOperator, Device, Dropout, Mask, MatMul, ScalarMul, Softmax,
+    masked_softmax::MaskedSoftmax, tensor::Error, AttentionSimilarity, BinaryOperator, Device,
+    Dropout, LearnableScale, Mask, MatMul, RelativePositionBias, Similarity, Softmax,
     TensorWithGrad, TernaryOperator, UnaryOperator,
 };
 
@@ -9,9 +10,12 @@ mod tests;
 /// Attention Is All You Need
 /// https://arxiv.org/abs/1706.03762
 pub struct ScaledDotProductAttention {
-    qk_matmul: MatMul,
-    scale: ScalarMul,
+    similarity: Similarity,
+    learnable_scale: Option<LearnableScale>,
+    relative_position_bias: Option<RelativePositionBias>,
     mask: Option<Mask>,
+    /// With a mask, the future positions get a probability of exactly 0 instead of a score of 0.
+    masked_softmax: Option<MaskedSoftmax>,
     softmax: Softmax,
     dropout: Option<Dropout>,
     matmul: MatMul,
@@ -24,10 +28,87 @@ impl ScaledDotProductAttention {
         cols: usize,
         mask: bool,
         dropout_probability: f32,
+        learnable_scale: bool,
     ) -> Result<Self, Error> {
-        let qk_matmul = MatMul::new(device, true);
-        let alpha = 1.0 / f32::sqrt(cols as f32);
-        let scale = ScalarMul::new(device, alpha);
+        Self::try_new_with_relative_position_bias(
+            device,
+            rows,
+            cols,
+            mask,
+            dropout_probability,
+            learnable_scale,
+            None,
+        )
+    }
+
+    /// A causal attention where the future positions get a probability of exactly 0,
+    /// see MaskedSoftmax, so that the attention of a position does not depend
+    /// on the positions after it.
+    /// With try_new, the future scores are multiplied by 0 before the softmax,
+    /// so the future positions still get some probability.
+    pub fn try_new_with_masked_softmax(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        dropout_probability: f32,
+        learnable_scale: bool,
+    ) -> Result<Self, Error> {
+        let attention = Self::try_new(
+            device,
+            rows,
+            cols,
+            true,
+            dropout_probability,
+            learnable_scale,
+        )?;
+        Ok(Self {
+            masked_softmax: Some(MaskedSoftmax::new(device)),
+            ..attention
+        })
+    }
+
+    /// With max_distance, a learned bias that depends on the distance between the key and the
+    /// query is added to the scaled scores, see RelativePositionBias.
+    pub fn try_new_with_relative_position_bias(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        mask: bool,
+        dropout_probability: f32,
+        learnable_scale: bool,
+        max_distance: Option<usize>,
+    ) -> Result<Self, Error> {
+        let similarity =
+            Similarity::try_new(device, rows, cols, AttentionSimilarity::ScaledDot, None)?;
+        Self::try_new_with_similarity(
+            device,
+            rows,
+            mask,
+            dropout_probability,
+            learnable_scale,
+            max_distance,
+            similarity,
+        )
+    }
+
+    /// With the given scores of the queries and the keys, see AttentionSimilarity.
+    pub fn try_new_with_similarity(
+        device: &Device,
+        rows: usize,
+        mask: bool,
+        dropout_probability: f32,
+        learnable_scale: bool,
+        max_distance: Option<usize>,
+        similarity: Similarity,
+    ) -> Result<Self, Error> {
+        let learnable_scale = match learnable_scale {
+            false => None,
+            true => Some(LearnableScale::try_new(device)?),
+        };
+        let relative_position_bias = match max_distance {
+            Some(max_distance) => Some(RelativePositionBias::try_new(device, rows, max_distance)?),
+            None => None,
+        };
         let mask = match mask {
             false => None,
             true => {
@@ -39,20 +120,31 @@ impl ScaledDotProductAttention {
         let dropout = if dropout_probability == 0.0 {
             None
         } else {
-            Some(Dropout::try_new(device, rows, rows, dropout_probability)?)
+            Some(Dropout::try_new_with_identity_eval(
+                device,
+                rows,
+                rows,
+                dropout_probability,
+            )?)
         };
         let matmul = MatMul::new(device, false);
 
         let attention = Self {
-            qk_matmul,
-            scale,
+            similarity,
+            learnable_scale,
+            relative_position_bias,
             mask,
+            masked_softmax: None,
             softmax,
             dropout,
             matmul,
         };
         Ok(attention)
     }
+
+    pub fn relative_position_bias(&self) -> Option<&RelativePositionBias> {
+        self.relative_position_bias.as_ref()
+    }
 }
 
 impl TernaryOperator for ScaledDotProductAttention {
@@ -62,13 +154,22 @@ impl TernaryOperator for ScaledDotProductAttention {
         k: &TensorWithGrad,
         v: &TensorWithGrad,
     ) -> Result<TensorWithGrad, Error> {
-        let weights = self.qk_matmul.forward(q, k)?;
-        let scaled_weights = self.scale.forward(&weights)?;
-        let masked_weights = match &self.mask {
-            Some(mask) => mask.forward(&scaled_weights)?,
+        let scaled_weights = self.similarity.forward(q, k)?;
+        let scaled_weights = match &self.learnable_scale {
+            Some(learnable_scale) => learnable_scale.forward(&scaled_weights)?,
             _ => scaled_weights,
         };
-        let softmaxed_weights = self.softmax.forward(&masked_weights)?;
+        let scaled_weights = match &self.relative_position_bias {
+            Some(relative_position_bias) => relative_position_bias.forward(&scaled_weights)?,
+            _ => scaled_weights,
+        };
+        let softmaxed_weights = match (&self.mask, &self.masked_softmax) {
+            (Some(mask), Some(masked_softmax)) => {
+                masked_softmax.forward(&scaled_weights, mask.mask())?
+            }
+            (Some(mask), None) => self.softmax.forward(&mask.forward(&scaled_weights)?)?,
+            (None, _) => self.softmax.forward(&scaled_weights)?,
+        };
         let with_dropout = match &self.dropout {
             Some(dropout) => dropout.forward(&softmaxed_weights)?,
             _ => softmaxed_weights,
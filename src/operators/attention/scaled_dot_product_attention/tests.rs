@@ -1,5 +1,10 @@
+use std::collections::HashSet;
+
+use more_asserts::{assert_gt, assert_lt};
+
 use crate::{
-    new_tensor_with_grad, tensor::Tensor, Device, ScaledDotProductAttention, TernaryOperator,
+    new_tensor_with_grad, stream::StreamTrait, tensor::Tensor, Category, Device,
+    ScaledDotProductAttention, TensorWithGrad, TernaryOperator,
 };
 
 #[test]
@@ -20,7 +25,8 @@ fn forward() {
     .unwrap();
     let dropout_probability = 0.1;
     let attention =
-        ScaledDotProductAttention::try_new(&device, rows, cols, mask, dropout_probability).unwrap();
+        ScaledDotProductAttention::try_new(&device, rows, cols, mask, dropout_probability, false)
+            .unwrap();
 
     let output = attention.forward(&input, &input, &input).unwrap();
     let device_stream = device.new_stream().unwrap();
@@ -33,3 +39,139 @@ fn forward() {
         assert!(actual_value.is_finite());
     }
 }
+
+/// Execute the instructions of the tape in the given dropout mode.
+fn forward_with_dropout_mode(device: &Device, output: &TensorWithGrad, mode: Category) {
+    let device_stream = device.new_stream().unwrap();
+    let mut processed = HashSet::new();
+    for tensor in output.get_tape().iter() {
+        if !processed.insert(tensor.tensor().name()) {
+            continue;
+        }
+        for instruction in tensor.forward_instructions().iter() {
+            let category = instruction.category();
+            if category == mode || category == Category::Inference {
+                instruction.execute(device, &device_stream).unwrap();
+            }
+        }
+    }
+    device_stream.wait_for().unwrap();
+}
+
+#[test]
+fn attention_dropout_in_training_and_eval() {
+    let device = Device::default();
+    let rows = 64;
+    let mask = false;
+    let dropout_probability = 0.5;
+    // Equal scores give uniform attention weights.
+    let qk = new_tensor_with_grad!(
+        device,
+        rows,
+        rows,
+        vec![1.0; rows * rows],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    // With an identity V, the attentions are the attention weights.
+    let mut identity = vec![0.0; rows * rows];
+    for i in 0..rows {
+        identity[i * rows + i] = 1.0;
+    }
+    let v = new_tensor_with_grad!(device, rows, rows, identity, &[], false, false).unwrap();
+    let attention =
+        ScaledDotProductAttention::try_new(&device, rows, rows, mask, dropout_probability, false)
+            .unwrap();
+    let output = attention.forward(&qk, &qk, &v).unwrap();
+    let weight = 1.0 / rows as f32;
+
+    forward_with_dropout_mode(&device, &output, Category::EnableDropout);
+    let values = output.tensor().get_values().unwrap();
+    let zeros = values.iter().filter(|x| **x == 0.0).count();
+    let zero_fraction = zeros as f32 / values.len() as f32;
+    assert_gt!(zero_fraction, dropout_probability - 0.1);
+    assert_lt!(zero_fraction, dropout_probability + 0.1);
+    for value in values.iter().filter(|x| **x != 0.0) {
+        assert_lt!((value - weight / (1.0 - dropout_probability)).abs(), 1e-5);
+    }
+
+    forward_with_dropout_mode(&device, &output, Category::DisableDropout);
+    let values = output.tensor().get_values().unwrap();
+    for value in values.iter() {
+        assert_lt!((value - weight).abs(), 1e-5);
+    }
+}
+
+#[test]
+fn learnable_scale_starts_at_the_fixed_scale() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 8;
+    let mask = true;
+    let dropout_probability = 0.0;
+    let values = (0..rows * cols)
+        .map(|x| (x as f32 * 0.37).sin())
+        .collect::<Vec<_>>();
+    let input = new_tensor_with_grad!(device, rows, cols, values, &[], false, false).unwrap();
+
+    let fixed =
+        ScaledDotProductAttention::try_new(&device, rows, cols, mask, dropout_probability, false)
+            .unwrap();
+    assert_eq!(0, device.parameter_tensors().len());
+    let learnable =
+        ScaledDotProductAttention::try_new(&device, rows, cols, mask, dropout_probability, true)
+            .unwrap();
+    assert_eq!(1, device.parameter_tensors().len());
+
+    let fixed_output = fixed.forward(&input, &input, &input).unwrap();
+    let learnable_output = learnable.forward(&input, &input, &input).unwrap();
+    forward_with_dropout_mode(&device, &fixed_output, Category::DisableDropout);
+    forward_with_dropout_mode(&device, &learnable_output, Category::DisableDropout);
+
+    let expected = fixed_output.tensor().get_values().unwrap();
+    let actual = learnable_output.tensor().get_values().unwrap();
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_lt!((expected - actual).abs(), 1e-6);
+    }
+}
+
+#[test]
+fn masked_softmax_gives_no_weight_to_the_future_positions() {
+    let device = Device::default();
+    let rows = 4;
+    let qk = new_tensor_with_grad!(
+        device,
+        rows,
+        rows,
+        vec![1.0; rows * rows],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    // With an identity V, the attentions are the attention weights.
+    let mut identity = vec![0.0; rows * rows];
+    for i in 0..rows {
+        identity[i * rows + i] = 1.0;
+    }
+    let v = new_tensor_with_grad!(device, rows, rows, identity, &[], false, false).unwrap();
+    let attention =
+        ScaledDotProductAttention::try_new_with_masked_softmax(&device, rows, rows, 0.0, false)
+            .unwrap();
+    let output = attention.forward(&qk, &qk, &v).unwrap();
+    forward_with_dropout_mode(&device, &output, Category::DisableDropout);
+
+    // Equal scores give uniform weights over the position and the positions before it.
+    let weights = output.tensor().get_values().unwrap();
+    for row in 0..rows {
+        for col in 0..rows {
+            let expected = match col <= row {
+                true => 1.0 / (row + 1) as f32,
+                false => 0.0,
+            };
+            assert_lt!((weights[row * rows + col] - expected).abs(), 1e-6);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    derive_seeds, error, new_tensor_with_grad,
+    tanh::Tanh,
+    tensor::{Error, ErrorEnum},
+    weights_initialization_rng, BinaryOperator, Device, Linear, MatMul, PairwiseAdd, Reshape,
+    ScalarMul, TensorWithGrad, UnaryOperator, WeightsInitialization,
+};
+use rand::Rng;
+use rand_distr::Normal;
+
+#[cfg(test)]
+mod tests;
+
+/// How the attention scores of the queries and the keys are computed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttentionSimilarity {
+    /// q_i . k_j
+    Dot,
+    /// q_i . k_j / sqrt(cols)
+    ScaledDot,
+    /// v . tanh(W_q q_i + W_k k_j), with a hidden layer of the given size,
+    /// which is a feed-forward layer over the concatenation of q_i and k_j.
+    ///
+    /// See:
+    /// Neural Machine Translation by Jointly Learning to Align and Translate
+    /// https://arxiv.org/abs/1409.0473
+    Additive { hidden: usize },
+}
+
+/// The attention scores, a rows x rows matrix, of queries and keys with cols columns.
+/// Q K^T uses the transb flag of Gemm, so no transposed copy of K is allocated.
+pub struct Similarity {
+    qk_matmul: MatMul,
+    scale: Option<ScalarMul>,
+    additive: Option<AdditiveSimilarity>,
+}
+
+impl Similarity {
+    /// The seed initializes the weights of the additive similarity.
+    pub fn try_new(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        similarity: AttentionSimilarity,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let scale = match similarity {
+            AttentionSimilarity::ScaledDot => {
+                let alpha = 1.0 / f32::sqrt(cols as f32);
+                Some(ScalarMul::new(device, alpha))
+            }
+            _ => None,
+        };
+        let additive = match similarity {
+            AttentionSimilarity::Additive { hidden } => Some(AdditiveSimilarity::try_new(
+                device, rows, cols, hidden, seed,
+            )?),
+            _ => None,
+        };
+        let op = Self {
+            qk_matmul: MatMul::new(device, true),
+            scale,
+            additive,
+        };
+        Ok(op)
+    }
+}
+
+impl BinaryOperator for Similarity {
+    fn forward(&self, q: &TensorWithGrad, k: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        if let Some(additive) = &self.additive {
+            return additive.forward(q, k);
+        }
+        let weights = self.qk_matmul.forward(q, k)?;
+        match &self.scale {
+            Some(scale) => scale.forward(&weights),
+            None => Ok(weights),
+        }
+    }
+}
+
+/// The rows x rows pairs (i, j) are the rows of rows * rows x hidden matrices.
+/// The projections of the queries and of the keys are added for every pair with PairwiseAdd.
+struct AdditiveSimilarity {
+    w_q: Linear,
+    w_k: Linear,
+    v: TensorWithGrad,
+    pairwise_add: PairwiseAdd,
+    tanh: Tanh,
+    v_matmul: MatMul,
+    reshape: Reshape,
+}
+
+impl AdditiveSimilarity {
+    fn try_new(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        hidden: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let seeds = derive_seeds(seed, 3);
+        let w_q = Linear::new_no_bias_with_seed(
+            device,
+            hidden,
+            cols,
+            WeightsInitialization::Kaiming,
+            seeds[0],
+        )?;
+        let w_k = Linear::new_no_bias_with_seed(
+            device,
+            hidden,
+            cols,
+            WeightsInitialization::Kaiming,
+            seeds[1],
+        )?;
+        let mut rng = weights_initialization_rng(seeds[2]);
+        let stddev = (1.0 / hidden as f32).sqrt();
+        let distribution =
+            Normal::new(0.0, stddev).map_err(|_| error!(ErrorEnum::UnsupportedOperation))?;
+        let v = (0..hidden)
+            .map(|_| rng.sample(distribution))
+            .collect::<Vec<_>>();
+        let v = new_tensor_with_grad!(device, 1, hidden, v, &[], true, true)?;
+
+        let pairs = rows * rows;
+        let tanh = Tanh::new(device);
+        let op = Self {
+            w_q,
+            w_k,
+            v,
+            pairwise_add: PairwiseAdd::new(device),
+            tanh,
+            v_matmul: MatMul::new(device, true),
+            reshape: Reshape::new(device, vec![pairs, 1], vec![rows, rows]),
+        };
+        Ok(op)
+    }
+}
+
+impl BinaryOperator for AdditiveSimilarity {
+    fn forward(&self, q: &TensorWithGrad, k: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let q = self.w_q.forward(q)?;
+        let k = self.w_k.forward(k)?;
+        let q_plus_k = self.pairwise_add.forward(&q, &k)?;
+        let hidden = self.tanh.forward(&q_plus_k)?;
+        let scores = self.v_matmul.forward(&hidden, &self.v)?;
+        self.reshape.forward(&scores)
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, AttentionHead, AttentionHeadConfig,
+    AttentionSimilarity, BinaryOperator, Device, DeviceTrait, MatMul, Similarity, Softmax,
+    TensorWithGrad, TernaryOperator, UnaryOperator,
+};
+
+/// Run the forward instructions of the tape.
+fn forward(device: &Device, output: &TensorWithGrad) -> Vec<f32> {
+    let device_stream = device.new_stream().unwrap();
+    let mut processed = HashSet::new();
+    for tensor in output.get_tape().iter() {
+        if processed.insert(tensor.tensor().name()) {
+            tensor.forward(device, &device_stream).unwrap();
+        }
+    }
+    device_stream.wait_for().unwrap();
+    output.tensor().get_values().unwrap()
+}
+
+#[test]
+fn dot_and_additive_similarities_give_different_valid_attention_weights() {
+    let device = Device::default();
+    let rows = 3;
+    let cols = 4;
+    let q_values = (0..(rows * cols))
+        .map(|x| ((x * 5) % 7) as f32 / 7.0 - 0.5)
+        .collect::<Vec<_>>();
+    let k_values = (0..(rows * cols))
+        .map(|x| ((x * 3) % 11) as f32 / 11.0 - 0.5)
+        .collect::<Vec<_>>();
+    let q = new_tensor_with_grad!(device, rows, cols, q_values, &[], false, false).unwrap();
+    let k = new_tensor_with_grad!(device, rows, cols, k_values, &[], false, false).unwrap();
+    let softmax = Softmax::new(&device);
+
+    let mut all_scores = vec![];
+    for similarity in [
+        AttentionSimilarity::Dot,
+        AttentionSimilarity::Additive { hidden: 5 },
+    ] {
+        let similarity = Similarity::try_new(&device, rows, cols, similarity, None).unwrap();
+        let scores = similarity.forward(&q, &k).unwrap();
+        assert_eq!(vec![rows, rows], *scores.tensor().size());
+        let weights = softmax.forward(&scores).unwrap();
+        forward(&device, &weights);
+        for row in weights.tensor().rows_iter().unwrap().iter() {
+            assert_lt!((1.0 - row.iter().sum::<f32>()).abs(), 1e-6);
+        }
+        all_scores.push(scores.tensor().get_values().unwrap());
+    }
+    assert_ne!(all_scores[0], all_scores[1]);
+}
+
+#[test]
+fn attention_head_with_additive_similarity() {
+    let device = Device::default();
+    let rows = 4;
+    let cols = 6;
+    let head_cols = 3;
+    let attention_head = AttentionHead::try_new_with_config(
+        &device,
+        &AttentionHeadConfig {
+            rows,
+            cols,
+            head_cols,
+            causal_mask: true,
+            dropout_probability: 0.0,
+            learnable_scale: false,
+            max_distance: None,
+            similarity: AttentionSimilarity::Additive { hidden: 8 },
+            seed: None,
+        },
+    )
+    .unwrap();
+    let values = (0..(rows * cols))
+        .map(|x| ((x * 7) % 11) as f32 / 11.0)
+        .collect::<Vec<_>>();
+    let input = new_tensor_with_grad!(device, rows, cols, values, &[], true, false).unwrap();
+    let output = attention_head.forward(&input, &input, &input).unwrap();
+    assert_eq!(vec![rows, head_cols], *output.tensor().size());
+
+    let values = forward(&device, &output);
+    assert!(values.iter().all(|x| x.is_finite()));
+
+    // The gradient reaches the input through the additive scores.
+    let device_stream = device.new_stream().unwrap();
+    output
+        .gradient()
+        .set_values(vec![1.0; rows * head_cols])
+        .unwrap();
+    let mut processed = HashSet::new();
+    for tensor in output.get_tape().iter().rev() {
+        if processed.insert(tensor.tensor().name()) {
+            tensor.compute_gradient(&device, &device_stream).unwrap();
+        }
+    }
+    device_stream.wait_for().unwrap();
+    let gradient = input.gradient().get_values().unwrap();
+    assert!(gradient.iter().any(|x| *x != 0.0));
+}
+
+#[test]
+fn dot_similarity_does_not_transpose_the_keys() {
+    let device = Device::default();
+    let rows = 3;
+    let cols = 4;
+    let q_values = (0..(rows * cols))
+        .map(|x| ((x * 5) % 7) as f32 / 7.0 - 0.5)
+        .collect::<Vec<_>>();
+    let k_values = (0..(rows * cols))
+        .map(|x| ((x * 3) % 11) as f32 / 11.0 - 0.5)
+        .collect::<Vec<_>>();
+    let q = new_tensor_with_grad!(device, rows, cols, q_values, &[], false, false).unwrap();
+    let k = new_tensor_with_grad!(device, rows, cols, k_values, &[], false, false).unwrap();
+
+    // Q K^T with the transb flag of Gemm.
+    let similarity =
+        Similarity::try_new(&device, rows, cols, AttentionSimilarity::Dot, None).unwrap();
+    let tensor_count = device.tensor_count();
+    let scores = similarity.forward(&q, &k).unwrap();
+    let similarity_tensors = device.tensor_count() - tensor_count;
+    let scores = forward(&device, &scores);
+
+    // Q K^T with a transposed copy of K.
+    let k_transpose = new_tensor_with_grad!(
+        device,
+        cols,
+        rows,
+        vec![0.0; rows * cols],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let device_stream = device.new_stream().unwrap();
+    device
+        .transpose(&k.tensor(), &k_transpose.tensor(), &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+    let tensor_count = device.tensor_count();
+    let expected_scores = MatMul::new(&device, false)
+        .forward(&q, &k_transpose)
+        .unwrap();
+    let matmul_tensors = device.tensor_count() - tensor_count;
+    let expected_scores = forward(&device, &expected_scores);
+
+    assert_eq!(expected_scores, scores);
+    // Only the scores are allocated, there is no buffer for the transposed keys.
+    assert_eq!(matmul_tensors, similarity_tensors);
+}
+
+#[test]
+fn additive_similarities_with_the_same_seed_give_the_same_scores() {
+    let device = Device::default();
+    let rows = 3;
+    let cols = 4;
+    let q_values = (0..(rows * cols))
+        .map(|x| ((x * 5) % 7) as f32 / 7.0 - 0.5)
+        .collect::<Vec<_>>();
+    let q = new_tensor_with_grad!(device, rows, cols, q_values, &[], false, false).unwrap();
+    let scores = |seed: u64| {
+        let similarity = Similarity::try_new(
+            &device,
+            rows,
+            cols,
+            AttentionSimilarity::Additive { hidden: 5 },
+            Some(seed),
+        )
+        .unwrap();
+        let scores = similarity.forward(&q, &q).unwrap();
+        forward(&device, &scores)
+    };
+    assert_eq!(scores(42), scores(42));
+    assert_ne!(scores(42), scores(43));
+}
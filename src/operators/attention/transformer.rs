@@ -1,7 +1,7 @@
 use crate::{
-    gelu::Gelu, statistics::layer_norm::LayerNormalization, tensor::Error, Add, BinaryOperator,
-    Device, Dropout, Linear, MultiHeadAttention, TensorWithGrad, TernaryOperator, UnaryOperator,
-    WeightsInitialization,
+    statistics::layer_norm::LayerNormalization, tensor::Error, Add, BinaryOperator, Device,
+    Dropout, FeedForward, FeedForwardActivation, MultiHeadAttention, MultiHeadAttentionConfig,
+    TensorWithGrad, TernaryOperator, UnaryOperator,
 };
 
 /// See:
@@ -17,12 +17,28 @@ pub struct Transformer {
     dropout_1: Dropout,
     layer_norm_2: LayerNormalization,
     add: Add,
-    linear_1: Linear,
-    gelu: Gelu,
-    linear_2: Linear,
+    feed_forward: FeedForward,
     dropout_2: Dropout,
 }
 
+/// The configuration of a Transformer.
+#[derive(Clone, Copy, Debug)]
+pub struct TransformerConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub causal_mask: bool,
+    pub num_heads: usize,
+    /// The attention dropout is applied to the softmax weights, before multiplying by V.
+    pub attention_dropout_probability: f32,
+    /// The residual dropout is applied to the outputs of the sub-layers, before each residual add.
+    pub residual_dropout_probability: f32,
+    /// The biases and the layer normalization parameters have one row that is shared
+    /// by every position, so that the parameters do not depend on the number of rows.
+    pub position_independent: bool,
+    /// The activation of the feed-forward network.
+    pub activation: FeedForwardActivation,
+}
+
 impl Transformer {
     pub fn try_new(
         device: &Device,
@@ -32,23 +48,64 @@ impl Transformer {
         num_heads: usize,
         dropout_probability: f32,
     ) -> Result<Self, Error> {
-        let layer_norm_1 = LayerNormalization::try_new(device, rows, cols)?;
-        let multi_head_attention = MultiHeadAttention::try_new(
+        Self::try_new_with_config(
             device,
+            &TransformerConfig {
+                rows,
+                cols,
+                causal_mask,
+                num_heads,
+                attention_dropout_probability: dropout_probability,
+                residual_dropout_probability: dropout_probability,
+                position_independent: false,
+                activation: FeedForwardActivation::Gelu,
+            },
+        )
+    }
+
+    pub fn try_new_with_config(device: &Device, config: &TransformerConfig) -> Result<Self, Error> {
+        let TransformerConfig {
             rows,
             cols,
             causal_mask,
             num_heads,
-            dropout_probability,
+            attention_dropout_probability,
+            residual_dropout_probability,
+            position_independent,
+            activation,
+        } = *config;
+        let new_layer_norm = || match position_independent {
+            true => LayerNormalization::try_new_position_independent(device, rows, cols),
+            false => LayerNormalization::try_new(device, rows, cols),
+        };
+        let layer_norm_1 = new_layer_norm()?;
+        let multi_head_attention = MultiHeadAttention::try_new(
+            device,
+            &MultiHeadAttentionConfig {
+                rows,
+                cols,
+                causal_mask,
+                num_heads,
+                num_kv_heads: num_heads,
+                dropout_probability: attention_dropout_probability,
+                learnable_scale: false,
+                seed: None,
+                position_independent,
+            },
         )?;
-        let dropout_1 = Dropout::try_new(device, rows, cols, dropout_probability)?;
+        let dropout_1 = Dropout::try_new(device, rows, cols, residual_dropout_probability)?;
         let add = Add::new(device);
-        let layer_norm_2 = LayerNormalization::try_new(device, rows, cols)?;
+        let layer_norm_2 = new_layer_norm()?;
 
-        let linear_1 = Linear::new(device, cols, cols, WeightsInitialization::Kaiming, rows)?;
-        let gelu = Gelu::new(device);
-        let linear_2 = Linear::new(device, cols, cols, WeightsInitialization::Kaiming, rows)?;
-        let dropout_2 = Dropout::try_new(device, rows, cols, dropout_probability)?;
+        let feed_forward = FeedForward::try_new_with_seed(
+            device,
+            rows,
+            cols,
+            activation,
+            None,
+            position_independent,
+        )?;
+        let dropout_2 = Dropout::try_new(device, rows, cols, residual_dropout_probability)?;
 
         let transformer = Self {
             layer_norm_1,
@@ -56,9 +113,7 @@ impl Transformer {
             dropout_1,
             layer_norm_2,
             add,
-            linear_1,
-            gelu,
-            linear_2,
+            feed_forward,
             dropout_2,
         };
         Ok(transformer)
@@ -76,10 +131,8 @@ impl UnaryOperator for Transformer {
         let with_dropout_1 = self.dropout_1.forward(&attended)?;
         let residual_1 = self.add.forward(&with_dropout_1, &input)?;
         let normalized_output = self.layer_norm_2.forward(&residual_1)?;
-        let lin_1 = self.linear_1.forward(&normalized_output)?;
-        let activated = self.gelu.forward(&lin_1)?;
-        let lin_2 = self.linear_2.forward(&activated)?;
-        let with_dropout_2 = self.dropout_2.forward(&lin_2)?;
+        let feed_forward = self.feed_forward.forward(&normalized_output)?;
+        let with_dropout_2 = self.dropout_2.forward(&feed_forward)?;
         let residual_2 = self.add.forward(&with_dropout_2, &normalized_output)?;
         Ok(residual_2)
     }
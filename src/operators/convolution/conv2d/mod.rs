@@ -0,0 +1,413 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    weights_initialization_rng, BinaryOperator, Category, Device, ExecutableOperator, MatMul,
+    OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+use rand::Rng;
+use rand_distr::Normal;
+
+#[cfg(test)]
+mod tests;
+
+/// 2D convolution of an image batch in NCHW layout, without bias.
+/// The input is [batch, in_channels, height, width] and the output is
+/// [batch, out_channels, output_height, output_width].
+/// The forward is im2col followed by a matrix multiplication with the weights,
+/// so it runs on the device gemm.
+pub struct Conv2D {
+    weights: TensorWithGrad,
+    im2col: Im2Col,
+    matmul: MatMul,
+    device: Device,
+}
+
+impl Conv2D {
+    /// The weights are [out_channels, in_channels * kernel_size * kernel_size].
+    pub fn new(
+        device: &Device,
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        stride: usize,
+        padding: usize,
+    ) -> Result<Self, Error> {
+        if in_channels == 0 || out_channels == 0 || kernel_size == 0 || stride == 0 {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let weights_cols = in_channels * kernel_size * kernel_size;
+        let fan_in = weights_cols as f32;
+        let distribution = Normal::new(0.0, (2.0 / fan_in).sqrt())
+            .map_err(|_| error!(ErrorEnum::UnsupportedOperation))?;
+        let mut rng = weights_initialization_rng(None);
+        let weights = (0..(out_channels * weights_cols))
+            .map(|_| rng.sample(distribution))
+            .collect::<Vec<_>>();
+        let weights =
+            new_tensor_with_grad!(device, out_channels, weights_cols, weights, &[], true, true)?;
+        let transb = true;
+        let op = Self {
+            weights,
+            im2col: Im2Col::new(device, kernel_size, stride, padding),
+            matmul: MatMul::new(device, transb),
+            device: device.clone(),
+        };
+        Ok(op)
+    }
+
+    pub fn weights(&self) -> &TensorWithGrad {
+        &self.weights
+    }
+}
+
+impl UnaryOperator for Conv2D {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let (batch, in_channels, height, width) = input.tensor().nchw()?;
+        let weights_cols = self.weights.tensor().cols();
+        let kernel_size = self.im2col.kernel_size;
+        if in_channels * kernel_size * kernel_size != weights_cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let (output_height, output_width) = self.im2col.output_size(height, width)?;
+        let columns = self.im2col.forward(input)?;
+        let product = self.matmul.forward(&columns, &self.weights)?;
+        NhwcToNchw::new(&self.device, batch, output_height, output_width).forward(&product)
+    }
+}
+
+/// Unfold the kernel windows of an NCHW image batch into rows.
+/// The output has one row per (batch, output_y, output_x) and one column per
+/// (channel, kernel_y, kernel_x), so a convolution is a matrix multiplication.
+pub struct Im2Col {
+    device: Device,
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+}
+
+impl Im2Col {
+    pub fn new(device: &Device, kernel_size: usize, stride: usize, padding: usize) -> Self {
+        Self {
+            device: device.clone(),
+            kernel_size,
+            stride,
+            padding,
+        }
+    }
+
+    pub fn output_size(&self, height: usize, width: usize) -> Result<(usize, usize), Error> {
+        convolution_output_size(height, width, self.kernel_size, self.stride, self.padding)
+    }
+}
+
+pub(crate) fn convolution_output_size(
+    height: usize,
+    width: usize,
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+) -> Result<(usize, usize), Error> {
+    if stride == 0 || height + 2 * padding < kernel_size || width + 2 * padding < kernel_size {
+        return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+    }
+    let output_height = (height + 2 * padding - kernel_size) / stride + 1;
+    let output_width = (width + 2 * padding - kernel_size) / stride + 1;
+    Ok((output_height, output_width))
+}
+
+/// The attributes are [kernel_size, stride, padding].
+fn convolution_attributes(attributes: &OperatorAttributes) -> Result<(usize, usize, usize), Error> {
+    match attributes {
+        OperatorAttributes::Vec(values) if values.len() == 3 => {
+            Ok((values[0], values[1], values[2]))
+        }
+        _ => Err(error!(ErrorEnum::UnsupportedOperation)),
+    }
+}
+
+/// Call f(column_index, image_index) for every element of the unfolded windows
+/// that is not in the padding.
+fn for_each_window_element<F>(
+    size: &[usize],
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut(usize, usize),
+{
+    let (batch, channels, height, width) = match size {
+        &[n, c, h, w] => (n, c, h, w),
+        _ => return Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+    };
+    let (output_height, output_width) =
+        convolution_output_size(height, width, kernel_size, stride, padding)?;
+    let image_strides = Tensor::get_strides(size);
+    let columns = channels * kernel_size * kernel_size;
+    for b in 0..batch {
+        for output_y in 0..output_height {
+            for output_x in 0..output_width {
+                let row = (b * output_height + output_y) * output_width + output_x;
+                for channel in 0..channels {
+                    for kernel_y in 0..kernel_size {
+                        let y = output_y * stride + kernel_y;
+                        if y < padding || y - padding >= height {
+                            continue;
+                        }
+                        for kernel_x in 0..kernel_size {
+                            let x = output_x * stride + kernel_x;
+                            if x < padding || x - padding >= width {
+                                continue;
+                            }
+                            let col = (channel * kernel_size + kernel_y) * kernel_size + kernel_x;
+                            let image_index = b * image_strides[0]
+                                + channel * image_strides[1]
+                                + (y - padding) * image_strides[2]
+                                + (x - padding);
+                            f(row * columns + col, image_index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl ExecutableOperator for Im2Col {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let (kernel_size, stride, padding) = convolution_attributes(attributes)?;
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        let image = input.get_values()?;
+        let mut columns = vec![0.0; output.len()];
+        let size = input.size().clone();
+        for_each_window_element(&size, kernel_size, stride, padding, |index, image_index| {
+            columns[index] = image[image_index];
+        })?;
+        output.set_values(columns)
+    }
+}
+
+impl UnaryOperator for Im2Col {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let (batch, channels, height, width) = input.tensor().nchw()?;
+        let (output_height, output_width) = self.output_size(height, width)?;
+        let rows = batch * output_height * output_width;
+        let cols = channels * self.kernel_size * self.kernel_size;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; rows * cols],
+            &[input],
+            true,
+            false
+        )?;
+        let attributes = OperatorAttributes::Vec(vec![self.kernel_size, self.stride, self.padding]);
+
+        output.push_instruction(instruction!(
+            OpCode::Im2Col,
+            attributes.clone(),
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let len = input.tensor().len();
+            let tmp = new_tensor!(device, batch * channels, height * width, vec![0.0; len])?;
+            tmp.resize(&[batch, channels, height, width])?;
+            output.push_instruction(instruction!(
+                OpCode::Col2Im,
+                attributes,
+                &[&output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Fold unfolded windows back into an NCHW image batch, summing overlapping windows.
+/// This is the backward of Im2Col.
+pub struct Col2Im {}
+
+impl ExecutableOperator for Col2Im {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let (kernel_size, stride, padding) = convolution_attributes(attributes)?;
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        let columns = input.get_values()?;
+        let mut image = vec![0.0; output.len()];
+        let size = output.size().clone();
+        for_each_window_element(&size, kernel_size, stride, padding, |index, image_index| {
+            image[image_index] += columns[index];
+        })?;
+        output.set_values(image)
+    }
+}
+
+/// Reorder rows [batch * height * width, channels] into [batch, channels, height, width].
+pub struct NhwcToNchw {
+    device: Device,
+    batch: usize,
+    height: usize,
+    width: usize,
+}
+
+impl NhwcToNchw {
+    pub fn new(device: &Device, batch: usize, height: usize, width: usize) -> Self {
+        Self {
+            device: device.clone(),
+            batch,
+            height,
+            width,
+        }
+    }
+}
+
+/// Copy between [batch, height, width, channels] and [batch, channels, height, width].
+/// size is the NCHW size.
+fn permute_nhwc_nchw(size: &[usize], values: &[f32], to_nchw: bool) -> Result<Vec<f32>, Error> {
+    let (batch, channels, height, width) = match size {
+        &[n, c, h, w] => (n, c, h, w),
+        _ => return Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+    };
+    let pixels = height * width;
+    let mut output = vec![0.0; values.len()];
+    for b in 0..batch {
+        for pixel in 0..pixels {
+            for channel in 0..channels {
+                let nhwc = (b * pixels + pixel) * channels + channel;
+                let nchw = (b * channels + channel) * pixels + pixel;
+                if to_nchw {
+                    output[nchw] = values[nhwc];
+                } else {
+                    output[nhwc] = values[nchw];
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+impl ExecutableOperator for NhwcToNchw {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        let values = input.get_values()?;
+        let size = output.size().clone();
+        output.set_values(permute_nhwc_nchw(&size, &values, true)?)
+    }
+}
+
+impl UnaryOperator for NhwcToNchw {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let channels = input_t.cols();
+        let pixels = self.height * self.width;
+        if rows != self.batch * pixels {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let size = [self.batch, channels, self.height, self.width];
+        let output = new_tensor_with_grad!(
+            self.device,
+            self.batch * channels,
+            pixels,
+            vec![0.0; input_t.len()],
+            &[input],
+            true,
+            false
+        )?;
+        output.tensor().resize(&size)?;
+        if output.gradient().requires_grad() {
+            output.gradient().resize(&size)?;
+        }
+
+        output.push_instruction(instruction!(
+            OpCode::NhwcToNchw,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, channels, vec![0.0; input_t.len()])?;
+            output.push_instruction(instruction!(
+                OpCode::NchwToNhwc,
+                OperatorAttributes::None,
+                &[&output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Reorder [batch, channels, height, width] into rows [batch * height * width, channels].
+/// This is the backward of NhwcToNchw.
+pub struct NchwToNhwc {}
+
+impl ExecutableOperator for NchwToNhwc {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        let values = input.get_values()?;
+        let size = input.size().clone();
+        output.set_values(permute_nhwc_nchw(&size, &values, false)?)
+    }
+}
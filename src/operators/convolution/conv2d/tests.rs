@@ -0,0 +1,116 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tensor::ErrorEnum, Conv2D, Device, TensorWithGrad,
+    UnaryOperator,
+};
+
+/// A 5x5 image with pixel (y, x) = 5 * y + x, in a batch of 1 with 1 channel.
+fn image_5x5(device: &Device) -> TensorWithGrad {
+    let values = (0..25).map(|x| x as f32).collect::<Vec<_>>();
+    let input = new_tensor_with_grad!(device, 5, 5, values, &[], true, false).unwrap();
+    input.tensor().resize(&[1, 1, 5, 5]).unwrap();
+    input.gradient().resize(&[1, 1, 5, 5]).unwrap();
+    input
+}
+
+/// A plus-shaped 3x3 kernel.
+fn plus_conv2d(device: &Device, stride: usize, padding: usize) -> Conv2D {
+    let conv2d = Conv2D::new(device, 1, 1, 3, stride, padding).unwrap();
+    conv2d
+        .weights()
+        .tensor()
+        .set_values(vec![
+            0.0, 1.0, 0.0, //
+            1.0, 1.0, 1.0, //
+            0.0, 1.0, 0.0, //
+        ])
+        .unwrap();
+    conv2d
+}
+
+#[test]
+fn conv2d_3x3_kernel_on_5x5_input() {
+    let device = Device::default();
+    let input = image_5x5(&device);
+    let conv2d = plus_conv2d(&device, 1, 0);
+    let output = conv2d.forward(&input).unwrap();
+    assert_eq!(vec![1, 1, 3, 3], *output.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    let tape = output.get_tape();
+    for tensor in tape.iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    // Each output is the sum of the plus around the center, which is 5 times the center.
+    assert_eq!(
+        vec![
+            30.0, 35.0, 40.0, //
+            55.0, 60.0, 65.0, //
+            80.0, 85.0, 90.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output.gradient().set_values(vec![1.0; 9]).unwrap();
+    for tensor in tape.iter().rev() {
+        tensor.compute_gradient(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    // Each pixel receives the kernel weights of the windows that cover it.
+    assert_eq!(
+        vec![
+            0.0, 1.0, 1.0, 1.0, 0.0, //
+            1.0, 3.0, 4.0, 3.0, 1.0, //
+            1.0, 4.0, 5.0, 4.0, 1.0, //
+            1.0, 3.0, 4.0, 3.0, 1.0, //
+            0.0, 1.0, 1.0, 1.0, 0.0, //
+        ],
+        input.gradient().get_values().unwrap()
+    );
+    // Each weight receives the sum of the pixels it sees, which is 9 times the center of them.
+    assert_eq!(
+        vec![
+            54.0, 63.0, 72.0, //
+            99.0, 108.0, 117.0, //
+            144.0, 153.0, 162.0, //
+        ],
+        conv2d.weights().gradient().get_values().unwrap()
+    );
+}
+
+#[test]
+fn conv2d_with_stride_and_padding() {
+    let device = Device::default();
+    let input = image_5x5(&device);
+    let conv2d = plus_conv2d(&device, 2, 1);
+    let output = conv2d.forward(&input).unwrap();
+    assert_eq!(vec![1, 1, 3, 3], *output.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    for tensor in output.get_tape().iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            6.0, 13.0, 16.0, //
+            41.0, 60.0, 55.0, //
+            56.0, 83.0, 66.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+}
+
+#[test]
+fn conv2d_rejects_a_matrix_input() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(device, 5, 5, vec![0.0; 25], &[], true, false).unwrap();
+    let conv2d = plus_conv2d(&device, 1, 0);
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        conv2d
+            .forward(&input)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
@@ -0,0 +1,118 @@
+use crate::{
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Mean of each channel of an image batch in NCHW layout.
+/// [batch, channels, height, width] is reduced to [batch, channels],
+/// as used before the classification head of a CNN.
+pub struct GlobalAvgPool2D {
+    device: Device,
+}
+
+impl GlobalAvgPool2D {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for GlobalAvgPool2D {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        let (_, _, height, width) = input.nchw()?;
+        let pixels = height * width;
+        device_stream.wait_for()?;
+        let values = input.get_values()?;
+        let means = values
+            .chunks(pixels.max(1))
+            .map(|plane| plane.iter().sum::<f32>() / pixels as f32)
+            .collect::<Vec<_>>();
+        output.set_values(means)
+    }
+}
+
+impl UnaryOperator for GlobalAvgPool2D {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let (batch, channels, height, width) = input.tensor().nchw()?;
+        let output = new_tensor_with_grad!(
+            self.device,
+            batch,
+            channels,
+            vec![0.0; batch * channels],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::GlobalAvgPool2D,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let len = input.tensor().len();
+            let tmp = new_tensor!(device, batch * channels, height * width, vec![0.0; len])?;
+            tmp.resize(&[batch, channels, height, width])?;
+            output.push_instruction(instruction!(
+                OpCode::GlobalAvgPool2DBackward,
+                OperatorAttributes::None,
+                &[&output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Each pixel of a channel receives 1 / (height * width) of the gradient of the channel mean.
+pub struct GlobalAvgPool2DBackward {}
+
+impl ExecutableOperator for GlobalAvgPool2DBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let output_gradient = inputs[0];
+        let input_gradient = outputs[0];
+        let (_, _, height, width) = input_gradient.nchw()?;
+        let pixels = height * width;
+        device_stream.wait_for()?;
+        let values = output_gradient
+            .get_values()?
+            .into_iter()
+            .flat_map(|gradient| vec![gradient / pixels as f32; pixels])
+            .collect::<Vec<_>>();
+        input_gradient.set_values(values)
+    }
+}
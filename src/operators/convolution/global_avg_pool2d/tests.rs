@@ -0,0 +1,53 @@
+use crate::{new_tensor_with_grad, stream::StreamTrait, Device, GlobalAvgPool2D, UnaryOperator};
+
+#[test]
+fn global_avg_pool2d_mean_and_backward() {
+    let device = Device::default();
+    // 2 images with 2 channels of 2x2 pixels.
+    let input = new_tensor_with_grad!(
+        device,
+        4,
+        4,
+        vec![
+            1.0, 2.0, 3.0, 4.0, //
+            -1.0, -1.0, 1.0, 1.0, //
+            0.0, 0.0, 0.0, 8.0, //
+            10.0, 20.0, 30.0, 40.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    input.tensor().resize(&[2, 2, 2, 2]).unwrap();
+    input.gradient().resize(&[2, 2, 2, 2]).unwrap();
+    let output = GlobalAvgPool2D::new(&device).forward(&input).unwrap();
+    assert_eq!(vec![2, 2], *output.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            2.5, 0.0, //
+            2.0, 25.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output
+        .gradient()
+        .set_values(vec![4.0, 8.0, -4.0, 1.0])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            1.0, 1.0, 1.0, 1.0, //
+            2.0, 2.0, 2.0, 2.0, //
+            -1.0, -1.0, -1.0, -1.0, //
+            0.25, 0.25, 0.25, 0.25, //
+        ],
+        input.gradient().get_values().unwrap()
+    );
+}
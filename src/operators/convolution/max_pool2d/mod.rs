@@ -0,0 +1,196 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+use super::conv2d::convolution_output_size;
+
+#[cfg(test)]
+mod tests;
+
+/// 2D max pooling of an image batch in NCHW layout.
+/// Each channel is pooled independently, so the output is
+/// [batch, channels, output_height, output_width].
+/// The forward records the position of each maximum in its window so that the backward
+/// routes the gradient to it.
+/// The positions are stored in an f32 tensor, so they are kept relative to the window,
+/// which makes them small integers that are exact whatever the size of the input.
+pub struct MaxPool2D {
+    device: Device,
+    window_size: usize,
+    stride: usize,
+}
+
+impl MaxPool2D {
+    pub fn new(device: &Device, window_size: usize, stride: usize) -> Result<Self, Error> {
+        if window_size == 0 || stride == 0 {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        // Integers above 2^24 are not exact in f32.
+        if window_size * window_size > 1 << f32::MANTISSA_DIGITS {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let op = Self {
+            device: device.clone(),
+            window_size,
+            stride,
+        };
+        Ok(op)
+    }
+}
+
+/// The attributes are [window_size, stride].
+fn pooling_attributes(attributes: &OperatorAttributes) -> Result<(usize, usize), Error> {
+    match attributes {
+        OperatorAttributes::Vec(values) if values.len() == 2 => Ok((values[0], values[1])),
+        _ => Err(error!(ErrorEnum::UnsupportedOperation)),
+    }
+}
+
+impl ExecutableOperator for MaxPool2D {
+    /// The outputs are the maxima and the index of each maximum in its window.
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let (window_size, stride) = pooling_attributes(attributes)?;
+        let input = inputs[0];
+        let output = outputs[0];
+        let argmax = outputs[1];
+        let (batch, channels, height, width) = input.nchw()?;
+        let (output_height, output_width) =
+            convolution_output_size(height, width, window_size, stride, 0)?;
+        device_stream.wait_for()?;
+        let image = input.get_values()?;
+        let mut maxima = vec![0.0; output.len()];
+        let mut positions = vec![0.0; argmax.len()];
+        for plane in 0..(batch * channels) {
+            for output_y in 0..output_height {
+                for output_x in 0..output_width {
+                    let mut position = 0;
+                    let mut maximum = f32::NEG_INFINITY;
+                    for window_y in 0..window_size {
+                        for window_x in 0..window_size {
+                            let y = output_y * stride + window_y;
+                            let x = output_x * stride + window_x;
+                            let index = (plane * height + y) * width + x;
+                            // The first maximum wins ties.
+                            if image[index] > maximum {
+                                maximum = image[index];
+                                position = window_y * window_size + window_x;
+                            }
+                        }
+                    }
+                    let index = (plane * output_height + output_y) * output_width + output_x;
+                    maxima[index] = maximum;
+                    positions[index] = position as f32;
+                }
+            }
+        }
+        output.set_values(maxima)?;
+        argmax.set_values(positions)
+    }
+}
+
+impl UnaryOperator for MaxPool2D {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let (batch, channels, height, width) = input.tensor().nchw()?;
+        let (output_height, output_width) =
+            convolution_output_size(height, width, self.window_size, self.stride, 0)?;
+        let size = [batch, channels, output_height, output_width];
+        let rows = batch * channels;
+        let cols = output_height * output_width;
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+        output.tensor().resize(&size)?;
+        if output.gradient().requires_grad() {
+            output.gradient().resize(&size)?;
+        }
+        let device = &self.device;
+        let argmax = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        argmax.resize(&size)?;
+        let attributes = OperatorAttributes::Vec(vec![self.window_size, self.stride]);
+
+        output.push_instruction(instruction!(
+            OpCode::MaxPool2D,
+            attributes,
+            &[&input.tensor()],
+            &[&output.tensor(), &argmax],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let input_len = input.tensor().len();
+            let tmp = new_tensor!(device, rows, height * width, vec![0.0; input_len])?;
+            tmp.resize(&[batch, channels, height, width])?;
+            output.push_instruction(instruction!(
+                OpCode::MaxPool2DBackward,
+                OperatorAttributes::Vec(vec![self.window_size, self.stride]),
+                &[&output.gradient(), &argmax],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Route each output gradient to the position of its maximum.
+/// Overlapping windows that share a maximum sum their gradients.
+pub struct MaxPool2DBackward {}
+
+impl ExecutableOperator for MaxPool2DBackward {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let (window_size, stride) = pooling_attributes(attributes)?;
+        let output_gradient = inputs[0];
+        let argmax = inputs[1];
+        let input_gradient = outputs[0];
+        let (batch, channels, height, width) = input_gradient.nchw()?;
+        let (output_height, output_width) =
+            convolution_output_size(height, width, window_size, stride, 0)?;
+        device_stream.wait_for()?;
+        let output_gradient = output_gradient.get_values()?;
+        let positions = argmax.get_values()?;
+        let mut values = vec![0.0; input_gradient.len()];
+        for plane in 0..(batch * channels) {
+            for output_y in 0..output_height {
+                for output_x in 0..output_width {
+                    let index = (plane * output_height + output_y) * output_width + output_x;
+                    let position = positions[index] as usize;
+                    let y = output_y * stride + position / window_size;
+                    let x = output_x * stride + position % window_size;
+                    values[(plane * height + y) * width + x] += output_gradient[index];
+                }
+            }
+        }
+        input_gradient.set_values(values)
+    }
+}
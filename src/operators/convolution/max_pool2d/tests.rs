@@ -0,0 +1,99 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tensor::ErrorEnum, Device, MaxPool2D, UnaryOperator,
+};
+
+#[test]
+fn max_pool2d_2x2_window_on_4x4_input() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(
+        device,
+        4,
+        4,
+        vec![
+            1.0, 5.0, 2.0, 0.0, //
+            3.0, 4.0, 8.0, 6.0, //
+            -1.0, -2.0, 7.0, 9.0, //
+            -4.0, -3.0, 9.5, 2.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    input.tensor().resize(&[1, 1, 4, 4]).unwrap();
+    input.gradient().resize(&[1, 1, 4, 4]).unwrap();
+    let output = MaxPool2D::new(&device, 2, 2)
+        .unwrap()
+        .forward(&input)
+        .unwrap();
+    assert_eq!(vec![1, 1, 2, 2], *output.tensor().size());
+
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            5.0, 8.0, //
+            -1.0, 9.5, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output
+        .gradient()
+        .set_values(vec![1.0, 2.0, 3.0, 4.0])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 2.0, 0.0, //
+            3.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 4.0, 0.0, //
+        ],
+        input.gradient().get_values().unwrap()
+    );
+}
+
+#[test]
+fn max_pool2d_rejects_an_empty_window() {
+    let device = Device::default();
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        MaxPool2D::new(&device, 0, 2)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn max_pool2d_routes_the_gradient_beyond_2_pow_24() {
+    let device = Device::default();
+    // The last element is at index 2^24 + 3, which is not exact in f32.
+    let (height, width) = (2, (1 << 23) + 2);
+    let len = height * width;
+    let mut values = vec![0.0; len];
+    values[len - 1] = 1.0;
+    let input = new_tensor_with_grad!(device, height, width, values, &[], true, false).unwrap();
+    input.tensor().resize(&[1, 1, height, width]).unwrap();
+    input.gradient().resize(&[1, 1, height, width]).unwrap();
+    let output = MaxPool2D::new(&device, 2, 2)
+        .unwrap()
+        .forward(&input)
+        .unwrap();
+
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    output
+        .gradient()
+        .set_values(vec![1.0; output.gradient().len()])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let gradient = input.gradient().get_values().unwrap();
+    assert_eq!(1.0, gradient[len - 1]);
+    assert_eq!(0.0, gradient[len - 2]);
+    assert_eq!(0.0, gradient[len - 3]);
+}
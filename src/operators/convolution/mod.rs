@@ -0,0 +1,6 @@
+mod conv2d;
+pub use conv2d::*;
+mod max_pool2d;
+pub use max_pool2d::*;
+mod global_avg_pool2d;
+pub use global_avg_pool2d::*;
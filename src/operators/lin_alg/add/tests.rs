@@ -1,6 +1,11 @@
 use rand::Rng;
 
-use crate::{new_tensor, Add, Device, ExecutableOperator};
+use crate::{
+    new_tensor,
+    opcode::OpCode,
+    tensor::{DType, ErrorEnum},
+    Add, Device, ExecutableOperator,
+};
 
 #[test]
 fn matrix_addition_result() {
@@ -86,3 +91,29 @@ fn big_matrix_addition() {
     )
     .unwrap();
 }
+
+#[test]
+fn add_rejects_mixed_dtypes() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let lhs = new_tensor!(device, 1, 2, vec![1.0, 2.0]).unwrap();
+    let rhs = new_tensor!(device, 1, 2, vec![3.0, 4.0])
+        .unwrap()
+        .with_dtype(DType::F16);
+    let output = new_tensor!(device, 1, 2, vec![0.0, 0.0]).unwrap();
+    let op_result = OpCode::Add.execute(
+        &Default::default(),
+        &[&lhs, &rhs],
+        &[&output],
+        &device,
+        &device_stream,
+    );
+    assert_eq!(
+        op_result.map_err(|e| e.error().clone()),
+        Err(ErrorEnum::DTypeMismatch {
+            expected: DType::F32,
+            found: DType::F16,
+        })
+    );
+    assert_eq!(vec![0.0, 0.0], output.get_values().unwrap());
+}
@@ -0,0 +1,120 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::DeviceStream,
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, Device, DeviceTrait, ExecutableOperator, OperatorAttributes,
+    TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Not ONNX-compliant
+/// output = input + biases, where the biases have 1 row that is broadcast to every row
+/// of the input, or as many rows as the input.
+/// The gradient of 1-row biases is the sum of the rows of the output gradient.
+pub struct BiasAdd {
+    device: Device,
+}
+
+impl BiasAdd {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.to_owned(),
+        }
+    }
+}
+
+impl ExecutableOperator for BiasAdd {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let biases = inputs[1];
+        let output = outputs[0];
+        if biases.cols() != input.cols()
+            || (biases.rows() != 1 && biases.rows() != input.rows())
+            || output.len() != input.len()
+        {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device.bias_add(input, biases, output, device_stream)
+    }
+}
+
+impl BinaryOperator for BiasAdd {
+    fn forward(
+        &self,
+        input: &TensorWithGrad,
+        biases: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let rows = input.tensor().rows();
+        let cols = input.tensor().cols();
+        let bias_rows = biases.tensor().rows();
+        if biases.tensor().cols() != cols || (bias_rows != 1 && bias_rows != rows) {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input, biases],
+            true,
+            false,
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::BiasAdd,
+            OperatorAttributes::None,
+            &[&input.tensor(), &biases.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        {
+            let output_gradient: &Tensor = &output.gradient();
+            if biases.gradient().requires_grad() {
+                let biases_gradient: &Tensor = &biases.gradient();
+                if bias_rows == rows {
+                    output.push_instruction(instruction!(
+                        OpCode::Add,
+                        OperatorAttributes::None,
+                        &[biases_gradient, output_gradient],
+                        &[biases_gradient],
+                        Category::Gradient,
+                    ));
+                } else {
+                    // Gemm accumulates in its output: biases_gradient += ones x output_gradient.
+                    let ones = new_tensor!(self.device, 1, rows, vec![1.0; rows])?;
+                    output.push_instruction(instruction!(
+                        OpCode::Gemm,
+                        OperatorAttributes::ThreeBools(false, false, false),
+                        &[&ones, output_gradient, biases_gradient],
+                        &[biases_gradient],
+                        Category::Gradient,
+                    ));
+                }
+            }
+
+            if input.gradient().requires_grad() {
+                let input_gradient: &Tensor = &input.gradient();
+                output.push_instruction(instruction!(
+                    OpCode::Add,
+                    OperatorAttributes::None,
+                    &[input_gradient, output_gradient],
+                    &[input_gradient],
+                    Category::Gradient,
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+}
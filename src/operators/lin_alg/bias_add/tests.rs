@@ -0,0 +1,70 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, tensor::ErrorEnum, BiasAdd, BinaryOperator, Device,
+};
+
+#[test]
+fn bias_add_broadcasts_the_biases_and_sums_their_gradient() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(
+        device,
+        3,
+        4,
+        vec![
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 6.0, 7.0, 8.0, //
+            9.0, 10.0, 11.0, 12.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let biases =
+        new_tensor_with_grad!(device, 1, 4, vec![10.0, 20.0, 30.0, 40.0], &[], true, true).unwrap();
+    let output = BiasAdd::new(&device).forward(&input, &biases).unwrap();
+
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            11.0, 22.0, 33.0, 44.0, //
+            15.0, 26.0, 37.0, 48.0, //
+            19.0, 30.0, 41.0, 52.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    output
+        .gradient()
+        .set_values(vec![
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 6.0, 7.0, 8.0, //
+            9.0, 10.0, 11.0, 12.0, //
+        ])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![15.0, 18.0, 21.0, 24.0],
+        biases.gradient().get_values().unwrap()
+    );
+    assert_eq!(
+        output.gradient().get_values().unwrap(),
+        input.gradient().get_values().unwrap()
+    );
+}
+
+#[test]
+fn bias_add_rejects_biases_with_other_columns() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(device, 3, 2, vec![0.0; 6], &[], true, false).unwrap();
+    let biases = new_tensor_with_grad!(device, 1, 3, vec![0.0; 3], &[], true, true).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        BiasAdd::new(&device)
+            .forward(&input, &biases)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
@@ -0,0 +1,80 @@
+use crate::{
+    error, instruction, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::DeviceStream,
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, Device, DeviceTrait, ExecutableOperator, OperatorAttributes,
+    TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Cosine similarity between the rows of two tensors.
+/// The output has one row per input row and one column.
+pub struct CosineSimilarity {
+    device: Device,
+}
+
+impl CosineSimilarity {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for CosineSimilarity {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input_0 = inputs[0];
+        let input_1 = inputs[1];
+        let output = outputs[0];
+        if *input_0.size() != *input_1.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        if output.rows() != input_0.rows() || output.cols() != 1 {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device.cosine_similarity(input_0, input_1, output, device_stream)
+    }
+}
+
+impl BinaryOperator for CosineSimilarity {
+    fn forward(
+        &self,
+        input_0: &TensorWithGrad,
+        input_1: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let input_0_t: &Tensor = &input_0.tensor();
+        let input_1_t: &Tensor = &input_1.tensor();
+        if *input_0_t.size() != *input_1_t.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let rows = input_0_t.rows();
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            1,
+            vec![0.0; rows],
+            &[input_0, input_1],
+            false,
+            false,
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::CosineSimilarity,
+            OperatorAttributes::None,
+            &[input_0_t, input_1_t],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{new_tensor, CosineSimilarity, Device, ExecutableOperator, OperatorAttributes};
+
+#[test]
+fn test_cosine_similarity() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let left = new_tensor!(
+        device,
+        3,
+        2,
+        vec![
+            1.0, 0.0, //
+            3.0, 4.0, //
+            1.0, 2.0, //
+        ],
+    )
+    .unwrap();
+    let right = new_tensor!(
+        device,
+        3,
+        2,
+        vec![
+            0.0, 1.0, //
+            3.0, 4.0, //
+            -1.0, -2.0, //
+        ],
+    )
+    .unwrap();
+    let output = new_tensor!(device, 3, 1, vec![Default::default(); 3],).unwrap();
+
+    CosineSimilarity::execute(
+        &OperatorAttributes::None,
+        &[&left, &right],
+        &[&output],
+        &device,
+        &device_stream,
+    )
+    .unwrap();
+
+    let expected = [0.0, 1.0, -1.0];
+    let actual = output.get_values().unwrap();
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert!((expected - actual).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_cosine_similarity_with_zero_row() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let left = new_tensor!(device, 1, 2, vec![0.0, 0.0],).unwrap();
+    let right = new_tensor!(device, 1, 2, vec![1.0, 2.0],).unwrap();
+    let output = new_tensor!(device, 1, 1, vec![Default::default(); 1],).unwrap();
+
+    CosineSimilarity::execute(
+        &OperatorAttributes::None,
+        &[&left, &right],
+        &[&output],
+        &device,
+        &device_stream,
+    )
+    .unwrap();
+
+    assert_eq!(vec![0.0], output.get_values().unwrap());
+}
@@ -0,0 +1,325 @@
+use crate::{
+    copy_slice,
+    devices::Device,
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    transpose::Transpose,
+    BinaryOperator, Category, DeviceTrait, ExecutableOperator, MatMul, OperatorAttributes,
+    TensorWithGrad, UnaryOperator,
+};
+use rand::{distributions::Uniform, Rng};
+
+use super::weights_initialization_rng;
+
+#[cfg(test)]
+mod tests;
+
+pub struct Embedding {
+    device: Device,
+    embedding_table: TensorWithGrad,
+    matmul: MatMul,
+}
+
+impl Embedding {
+    pub fn new(
+        device: &Device,
+        num_embeddings: usize,
+        embedding_dim: usize,
+    ) -> Result<Self, Error> {
+        Self::new_with_seed(device, num_embeddings, embedding_dim, None)
+    }
+
+    /// Two embeddings with the same seed have identical tables.
+    pub fn new_with_seed(
+        device: &Device,
+        num_embeddings: usize,
+        embedding_dim: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let embedding_table = get_embedding_table(device, num_embeddings, embedding_dim, seed)?;
+        let len = embedding_table.len();
+        let transposed = new_tensor!(device, embedding_dim, num_embeddings, vec![0.0; len])?;
+        let device_stream = device.new_stream()?;
+        Transpose::execute(
+            &Default::default(),
+            &[&embedding_table],
+            &[&transposed],
+            device,
+            &device_stream,
+        )?;
+        device_stream.wait_for()?;
+        let embedding_table = new_tensor_with_grad!(
+            device,
+            transposed.rows(),
+            transposed.cols(),
+            transposed.get_values().unwrap(),
+            &[],
+            true,
+            true,
+        )?;
+
+        let transb = true;
+        let matmul = MatMul::new(device, transb);
+
+        //let id_entry = Identity::new("Embedding entry".into());
+        //let id_exit = Identity::new("Embedding exit".into());
+        let op = Self {
+            //id_entry,
+            //id_exit,
+            device: device.clone(),
+            embedding_table,
+            matmul,
+        };
+        Ok(op)
+    }
+}
+
+impl UnaryOperator for Embedding {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        //let input = self.id_entry.forward(input)?;
+        let output = self.matmul.forward(input, &self.embedding_table)?;
+        //let output = self.id_exit.forward(&output)?;
+        Ok(output)
+    }
+}
+
+impl Embedding {
+    /// Look up the embeddings of token ids directly, without building one-hot rows.
+    /// The output is the same as forward with the one-hot encoding of token_ids.
+    pub fn forward_ids(&self, token_ids: &[usize]) -> Result<TensorWithGrad, Error> {
+        let device = &self.device;
+        let table: &Tensor = &self.embedding_table.tensor();
+        // The table is stored transposed (embedding_dim x num_embeddings) for the matmul.
+        let embedding_dim = table.rows();
+        let num_embeddings = table.cols();
+        if token_ids.iter().any(|token| *token >= num_embeddings) {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let rows = token_ids.len();
+        let output = new_tensor_with_grad!(
+            device,
+            rows,
+            embedding_dim,
+            vec![0.0; rows * embedding_dim],
+            &[&self.embedding_table],
+            true,
+            false,
+        )?;
+        let token_ids = OperatorAttributes::Vec(token_ids.to_vec());
+
+        // One embedding per row, so that a lookup is a contiguous copy.
+        let table_rows = new_tensor!(
+            device,
+            num_embeddings,
+            embedding_dim,
+            vec![0.0; table.len()]
+        )?;
+        output.push_instruction(instruction!(
+            OpCode::Transpose,
+            OperatorAttributes::None,
+            &[table],
+            &[&table_rows],
+            Category::Inference,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::EmbeddingGather,
+            token_ids.clone(),
+            &[&table_rows],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if self.embedding_table.gradient().requires_grad() {
+            let zero = new_tensor!(device, 1, 1, vec![0.0])?;
+            let gradient_rows = new_tensor!(
+                device,
+                num_embeddings,
+                embedding_dim,
+                vec![0.0; table.len()]
+            )?;
+            let output_row = new_tensor!(device, 1, embedding_dim, vec![0.0; embedding_dim])?;
+            let gradient_row = new_tensor!(device, 1, embedding_dim, vec![0.0; embedding_dim])?;
+            let gradient = new_tensor!(
+                device,
+                embedding_dim,
+                num_embeddings,
+                vec![0.0; table.len()]
+            )?;
+            let table_gradient: &Tensor = &self.embedding_table.gradient();
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&zero, &gradient_rows],
+                &[&gradient_rows],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::EmbeddingGatherBackward,
+                token_ids,
+                &[&output.gradient()],
+                &[&gradient_rows, &output_row, &gradient_row],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Transpose,
+                OperatorAttributes::None,
+                &[&gradient_rows],
+                &[&gradient],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[table_gradient, &gradient],
+                &[table_gradient],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Not ONNX-compliant
+/// Copy row token_ids[i] of the table into row i of the output.
+pub struct EmbeddingGather {}
+
+impl ExecutableOperator for EmbeddingGather {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let token_ids = match attributes {
+            OperatorAttributes::Vec(token_ids) => token_ids,
+            _ => return Err(error!(ErrorEnum::UnsupportedOperation)),
+        };
+        let table = inputs[0];
+        let output = outputs[0];
+        if token_ids.len() != output.rows() || table.cols() != output.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        for (row, token) in token_ids.iter().enumerate() {
+            if *token >= table.rows() {
+                return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+            }
+            copy_slice(
+                table.cols(),
+                table,
+                *token,
+                0,
+                output,
+                row,
+                0,
+                device,
+                device_stream,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Not ONNX-compliant
+/// Add row i of the output gradient into row token_ids[i] of the table gradient.
+/// outputs[1] and outputs[2] are one-row scratch tensors.
+pub struct EmbeddingGatherBackward {}
+
+impl ExecutableOperator for EmbeddingGatherBackward {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let token_ids = match attributes {
+            OperatorAttributes::Vec(token_ids) => token_ids,
+            _ => return Err(error!(ErrorEnum::UnsupportedOperation)),
+        };
+        let output_gradient = inputs[0];
+        let table_gradient = outputs[0];
+        let output_row = outputs[1];
+        let gradient_row = outputs[2];
+        let cols = table_gradient.cols();
+        if token_ids.len() != output_gradient.rows() || output_gradient.cols() != cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        for (row, token) in token_ids.iter().enumerate() {
+            if *token >= table_gradient.rows() {
+                return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+            }
+            copy_slice(
+                cols,
+                output_gradient,
+                row,
+                0,
+                output_row,
+                0,
+                0,
+                device,
+                device_stream,
+            )?;
+            copy_slice(
+                cols,
+                table_gradient,
+                *token,
+                0,
+                gradient_row,
+                0,
+                0,
+                device,
+                device_stream,
+            )?;
+            device.axpy(
+                cols as i32,
+                &device_stream.one,
+                output_row,
+                1,
+                gradient_row,
+                1,
+                device_stream,
+            )?;
+            copy_slice(
+                cols,
+                gradient_row,
+                0,
+                0,
+                table_gradient,
+                *token,
+                0,
+                device,
+                device_stream,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn get_embedding_table(
+    device: &Device,
+    num_embeddings: usize,
+    embedding_dim: usize,
+    seed: Option<u64>,
+) -> Result<Tensor, Error> {
+    let mut rng = weights_initialization_rng(seed);
+    let mut embeddings_table: Vec<f32> = Vec::new();
+    let left = 0.0;
+    let right = 1.0;
+    let uniform = Uniform::new(left, right);
+
+    let mut token = 0;
+    while token < num_embeddings {
+        let mut token_embeddings: Vec<f32> = Vec::new();
+        for _ in 0..embedding_dim {
+            let value = rng.sample(uniform);
+            token_embeddings.push(value);
+        }
+        embeddings_table.append(&mut token_embeddings);
+        token += 1;
+    }
+    new_tensor!(device, num_embeddings, embedding_dim, embeddings_table)
+}
@@ -0,0 +1,72 @@
+use more_asserts::assert_lt;
+
+use crate::{new_tensor_with_grad, stream::StreamTrait, Device, Embedding, UnaryOperator};
+
+#[test]
+fn forward_ids_matches_one_hot_forward() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let num_embeddings = 6;
+    let embedding_dim = 4;
+    let embedding = Embedding::new(&device, num_embeddings, embedding_dim).unwrap();
+    let token_ids = [2, 5, 2];
+
+    let mut one_hot = vec![0.0; token_ids.len() * num_embeddings];
+    for (row, token) in token_ids.iter().enumerate() {
+        one_hot[row * num_embeddings + token] = 1.0;
+    }
+    let input = new_tensor_with_grad!(
+        device,
+        token_ids.len(),
+        num_embeddings,
+        one_hot,
+        &[],
+        false,
+        false,
+    )
+    .unwrap();
+    let expected = embedding.forward(&input).unwrap();
+    let actual = embedding.forward_ids(&token_ids).unwrap();
+    expected.forward(&device, &device_stream).unwrap();
+    actual.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    assert_eq!(*expected.tensor().size(), *actual.tensor().size());
+    assert_eq!(
+        expected.tensor().get_values().unwrap(),
+        actual.tensor().get_values().unwrap()
+    );
+
+    // The gradient of the table is the same too, including for the repeated token.
+    let output_gradient = (0..token_ids.len() * embedding_dim)
+        .map(|x| x as f32 * 0.5 - 2.0)
+        .collect::<Vec<_>>();
+    let table = &device.parameter_tensors()[0];
+    expected
+        .gradient()
+        .set_values(output_gradient.clone())
+        .unwrap();
+    expected.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let expected_gradient = table.gradient().get_values().unwrap();
+
+    table
+        .gradient()
+        .set_values(vec![0.0; expected_gradient.len()])
+        .unwrap();
+    actual.gradient().set_values(output_gradient).unwrap();
+    actual.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let actual_gradient = table.gradient().get_values().unwrap();
+
+    for (expected, actual) in expected_gradient.iter().zip(actual_gradient.iter()) {
+        assert_lt!((expected - actual).abs(), 1e-6);
+    }
+}
+
+#[test]
+fn forward_ids_rejects_out_of_range_tokens() {
+    let device = Device::default();
+    let embedding = Embedding::new(&device, 6, 4).unwrap();
+    assert!(embedding.forward_ids(&[1, 6]).is_err());
+}
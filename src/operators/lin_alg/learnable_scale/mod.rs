@@ -0,0 +1,100 @@
+use crate::{
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    tensor::{Error, Tensor},
+    Category, Device, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Multiplies the input by a scalar parameter.
+/// The scalar starts at 1 and is optimized like the other parameters.
+/// dL/dx = scale * dL/dy
+/// dL/dscale = sum(x * dL/dy)
+pub struct LearnableScale {
+    device: Device,
+    scale: TensorWithGrad,
+}
+
+impl LearnableScale {
+    pub fn try_new(device: &Device) -> Result<Self, Error> {
+        let scale = new_tensor_with_grad!(device, 1, 1, vec![1.0], &[], true, true)?;
+        let op = Self {
+            device: device.clone(),
+            scale,
+        };
+        Ok(op)
+    }
+
+    pub fn scale(&self) -> &TensorWithGrad {
+        &self.scale
+    }
+}
+
+impl UnaryOperator for LearnableScale {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let device = &self.device;
+        let input_t: &Tensor = &input.tensor();
+        let scale_t: &Tensor = &self.scale.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input, &self.scale],
+            true,
+            false
+        )?;
+        let output_gradient = output.gradient().clone();
+
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[scale_t, input_t],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        let input_gradient: &Tensor = &input.gradient();
+        if input_gradient.requires_grad() {
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[scale_t, &output_gradient],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[input_gradient, &tmp],
+                &[input_gradient],
+                Category::Gradient,
+            ));
+        }
+
+        let scale_gradient: &Tensor = &self.scale.gradient();
+        let tmp = new_tensor!(device, 1, 1, vec![0.0])?;
+        output.push_instruction(instruction!(
+            OpCode::Dot,
+            OperatorAttributes::None,
+            &[input_t, &output_gradient],
+            &[&tmp],
+            Category::Gradient,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[scale_gradient, &tmp],
+            &[scale_gradient],
+            Category::Gradient,
+        ));
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{new_tensor_with_grad, stream::StreamTrait, Device, LearnableScale, UnaryOperator};
+
+#[test]
+fn learnable_scale_forward_and_backward() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let op = LearnableScale::try_new(&device).unwrap();
+    op.scale().tensor().set_values(vec![2.0]).unwrap();
+    let input =
+        new_tensor_with_grad!(device, 1, 3, vec![1.0, -2.0, 3.0], &[], true, false).unwrap();
+
+    let output = op.forward(&input).unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![2.0, -4.0, 6.0], output.tensor().get_values().unwrap());
+
+    output.gradient().set_values(vec![1.0, 0.5, 2.0]).unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![2.0, 1.0, 4.0], input.gradient().get_values().unwrap());
+    // 1 * 1 + (-2) * 0.5 + 3 * 2
+    assert_eq!(vec![6.0], op.scale().gradient().get_values().unwrap());
+}
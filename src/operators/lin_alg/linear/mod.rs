@@ -0,0 +1,271 @@
+use crate::{
+    error, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::DeviceStream,
+    tensor::{Error, ErrorEnum, Tensor},
+    BiasAdd, BinaryOperator, Device, DeviceTrait, ExecutableOperator, Gemm, MatMul,
+    OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::Normal;
+
+#[cfg(test)]
+mod tests;
+
+pub enum WeightsInitialization {
+    None,
+    Kaiming,
+    Xavier,
+}
+
+pub struct Linear {
+    weights: TensorWithGrad,
+    biases: Option<TensorWithGrad>,
+    matmul: MatMul,
+    bias_add: BiasAdd,
+}
+
+/// The RNG of the weights initialization of a layer.
+/// With a seed, the initialization is reproducible, otherwise it comes from entropy.
+pub fn weights_initialization_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Derive the seeds of the sub-layers of a layer from the seed of the layer.
+pub fn derive_seeds(seed: Option<u64>, count: usize) -> Vec<Option<u64>> {
+    match seed {
+        Some(seed) => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..count).map(|_| Some(rng.gen())).collect()
+        }
+        None => vec![None; count],
+    }
+}
+
+pub(crate) fn kaiming_initialization(
+    weights_rows: usize,
+    _weights_cols: usize,
+    weights: &mut Vec<f32>,
+    rng: &mut StdRng,
+) -> Result<(), Error> {
+    let mean = 0.0;
+    let fan_in = weights_rows as f32;
+    let stddev = (2.0 / fan_in).sqrt();
+    let distribution =
+        Normal::new(mean, stddev).map_err(|_| error!(ErrorEnum::UnsupportedOperation))?;
+
+    for index in 0..weights.len() {
+        weights[index] = rng.sample(distribution);
+    }
+    Ok(())
+}
+
+impl Linear {
+    pub fn new(
+        device: &Device,
+        weights_rows: usize,
+        weights_cols: usize,
+        weights_initialization: WeightsInitialization,
+        bias_rows: usize,
+    ) -> Result<Self, Error> {
+        Self::try_new(
+            device,
+            weights_rows,
+            weights_cols,
+            weights_initialization,
+            Some(bias_rows),
+            None,
+        )
+    }
+
+    /// Two linear layers with the same seed have identical weights.
+    pub fn new_with_seed(
+        device: &Device,
+        weights_rows: usize,
+        weights_cols: usize,
+        weights_initialization: WeightsInitialization,
+        bias_rows: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        Self::try_new(
+            device,
+            weights_rows,
+            weights_cols,
+            weights_initialization,
+            Some(bias_rows),
+            seed,
+        )
+    }
+
+    /// A linear layer without biases, as used by attention projections.
+    /// Only the weights are registered as a parameter.
+    pub fn new_no_bias(
+        device: &Device,
+        weights_rows: usize,
+        weights_cols: usize,
+        weights_initialization: WeightsInitialization,
+    ) -> Result<Self, Error> {
+        Self::new_no_bias_with_seed(
+            device,
+            weights_rows,
+            weights_cols,
+            weights_initialization,
+            None,
+        )
+    }
+
+    pub fn new_no_bias_with_seed(
+        device: &Device,
+        weights_rows: usize,
+        weights_cols: usize,
+        weights_initialization: WeightsInitialization,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        Self::try_new(
+            device,
+            weights_rows,
+            weights_cols,
+            weights_initialization,
+            None,
+            seed,
+        )
+    }
+
+    fn try_new(
+        device: &Device,
+        weights_rows: usize,
+        weights_cols: usize,
+        weights_initialization: WeightsInitialization,
+        bias_rows: Option<usize>,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let mut weights = Vec::new();
+        weights.resize(weights_rows * weights_cols, 0.0);
+        match weights_initialization {
+            WeightsInitialization::None => {}
+            WeightsInitialization::Kaiming => {
+                let mut rng = weights_initialization_rng(seed);
+                kaiming_initialization(weights_rows, weights_cols, &mut weights, &mut rng)?;
+            }
+            WeightsInitialization::Xavier => todo!(),
+        }
+
+        let weights =
+            new_tensor_with_grad!(device, weights_rows, weights_cols, weights, &[], true, true)?;
+
+        let biases = match bias_rows {
+            Some(bias_rows) => {
+                let biases_len = bias_rows * weights_rows;
+                let biases = new_tensor_with_grad!(
+                    device,
+                    bias_rows,
+                    weights_rows,
+                    vec![0.0; biases_len],
+                    &[],
+                    true,
+                    true,
+                )?;
+                biases.set_no_decay(true);
+                Some(biases)
+            }
+            None => None,
+        };
+
+        let transb = true;
+        let op = Self {
+            weights,
+            biases,
+            matmul: MatMul::new(device, transb),
+            bias_add: BiasAdd::new(device),
+        };
+        Ok(op)
+    }
+
+    pub fn weights(&self) -> &TensorWithGrad {
+        &self.weights
+    }
+
+    pub fn biases(&self) -> Option<&TensorWithGrad> {
+        self.biases.as_ref()
+    }
+
+    /// The weights, then the biases if any.
+    pub fn parameters(&self) -> Vec<&TensorWithGrad> {
+        std::iter::once(&self.weights)
+            .chain(self.biases.iter())
+            .collect()
+    }
+}
+
+impl UnaryOperator for Linear {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let product = self.matmul.forward(input, &self.weights)?;
+        match &self.biases {
+            Some(biases) => self.bias_add.forward(&product, biases),
+            None => Ok(product),
+        }
+    }
+}
+
+/// A linear layer followed by an element-wise activation, in one instruction.
+/// The inputs are [input, weights] or [input, weights, biases] and the output is the activation.
+/// The product and the biases are computed in the output, so the pre-activation
+/// tensors are not touched. The operator fusion pass of the neural machine emits it.
+pub struct LinearActivation {}
+
+impl LinearActivation {
+    /// The activations that are applied element-wise and can run in place.
+    pub fn is_fusable_activation(opcode: &OpCode) -> bool {
+        matches!(
+            opcode,
+            OpCode::Sigmoid | OpCode::Gelu | OpCode::Silu | OpCode::LeakyRelu
+        )
+    }
+}
+
+impl ExecutableOperator for LinearActivation {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let (transb, activation, activation_attributes) = match attributes {
+            OperatorAttributes::LinearActivation(transb, activation, activation_attributes)
+                if Self::is_fusable_activation(activation) =>
+            {
+                (*transb, activation, activation_attributes)
+            }
+            _ => return Err(error!(ErrorEnum::UnsupportedOperation)),
+        };
+        let input = inputs[0];
+        let weights = inputs[1];
+        let output = outputs[0];
+        Gemm::gemm(
+            false,
+            transb,
+            &device_stream.one,
+            input,
+            weights,
+            &device_stream.zero,
+            output,
+            false,
+            device,
+            device_stream,
+        )?;
+        if let Some(biases) = inputs.get(2) {
+            device.bias_add(output, biases, output, device_stream)?;
+        }
+        activation.execute(
+            activation_attributes,
+            &[output],
+            &[output],
+            device,
+            device_stream,
+        )
+    }
+}
@@ -0,0 +1,133 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    adam_w::AdamW, new_tensor_with_grad, stream::StreamTrait, Device, Linear, MultiHeadAttention,
+    MultiHeadAttentionConfig, OptimizerTrait, UnaryOperator, WeightsInitialization,
+};
+
+#[test]
+fn linear_without_bias() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let linear = Linear::new_no_bias(&device, 2, 3, WeightsInitialization::Kaiming).unwrap();
+    let parameters = device.parameter_tensors().clone();
+    assert_eq!(1, parameters.len());
+    assert_eq!(vec![2, 3], *parameters[0].tensor().size());
+    assert_eq!(6, device.parameter_count());
+
+    let input = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            -1.0, 0.5, 4.0, //
+        ],
+        &[],
+        false,
+        false,
+    )
+    .unwrap();
+    let output = linear.forward(&input).unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    // x @ W^T
+    let x = input.tensor().get_values().unwrap();
+    let w = parameters[0].tensor().get_values().unwrap();
+    let mut expected = vec![0.0; 4];
+    for row in 0..2 {
+        for col in 0..2 {
+            for k in 0..3 {
+                expected[row * 2 + col] += x[row * 3 + k] * w[col * 3 + k];
+            }
+        }
+    }
+    assert_eq!(vec![2, 2], *output.tensor().size());
+    let actual = output.tensor().get_values().unwrap();
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        assert_lt!((actual - expected).abs(), 1e-6);
+    }
+}
+
+#[test]
+fn weight_decay_skips_biases() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let _linear = Linear::new(&device, 2, 3, WeightsInitialization::None, 1).unwrap();
+    let parameters = device.parameter_tensors().clone();
+    assert_eq!(2, parameters.len());
+    let weights = &parameters[0];
+    let biases = &parameters[1];
+    assert!(!weights.no_decay());
+    assert!(biases.no_decay());
+
+    weights.tensor().set_values(vec![1.0; 6]).unwrap();
+    biases.tensor().set_values(vec![1.0; 2]).unwrap();
+
+    // With zero gradients, only the decay term changes the parameters.
+    let learning_rate = 0.1;
+    let weight_decay = 0.5;
+    let optimizer = AdamW::try_new(learning_rate, 0.9, 0.999, 1e-8, weight_decay).unwrap();
+    let instructions = optimizer.optimize(&device, &parameters).unwrap();
+    for instruction in instructions.iter() {
+        instruction.execute(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+
+    let decayed = 1.0 - learning_rate * weight_decay;
+    for value in weights.tensor().get_values().unwrap() {
+        assert_lt!((value - decayed).abs(), 1e-6);
+    }
+    assert_eq!(vec![1.0; 2], biases.tensor().get_values().unwrap());
+}
+
+#[test]
+fn linear_weights_are_reproducible_with_a_seed() {
+    let weights = |seed: u64| {
+        let device = Device::default();
+        let _linear =
+            Linear::new_with_seed(&device, 4, 3, WeightsInitialization::Kaiming, 1, Some(seed))
+                .unwrap();
+        let parameters = device.parameter_tensors().clone();
+        let weights = parameters[0].tensor().get_values().unwrap();
+        weights
+    };
+    let weights_1 = weights(42);
+    let weights_2 = weights(42);
+    let bits = |values: &[f32]| values.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(&weights_1), bits(&weights_2));
+    assert_ne!(weights_1, weights(43));
+}
+
+#[test]
+fn multi_head_attention_weights_are_reproducible_with_a_seed() {
+    let parameters = |seed: u64| {
+        let device = Device::default();
+        let _attention = MultiHeadAttention::try_new(
+            &device,
+            &MultiHeadAttentionConfig {
+                rows: 2,
+                cols: 4,
+                causal_mask: false,
+                num_heads: 2,
+                num_kv_heads: 2,
+                dropout_probability: 0.0,
+                learnable_scale: false,
+                seed: Some(seed),
+                position_independent: false,
+            },
+        )
+        .unwrap();
+        let parameters = device.parameter_tensors().clone();
+        parameters
+            .iter()
+            .map(|x| x.tensor().get_values().unwrap())
+            .collect::<Vec<_>>()
+    };
+    let parameters_1 = parameters(7);
+    assert_eq!(parameters_1, parameters(7));
+    assert_ne!(parameters_1, parameters(8));
+    // Each projection has its own derived seed.
+    assert_ne!(parameters_1[0], parameters_1[2]);
+}
@@ -0,0 +1,113 @@
+use crate::{
+    error, kaiming_initialization, new_tensor_with_grad,
+    tensor::{Error, ErrorEnum, Tensor},
+    weights_initialization_rng, Add, BinaryOperator, Device, Linear, MatMul, Mul, TensorWithGrad,
+    UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A frozen linear layer with a trainable low-rank adapter:
+///   output = linear(x) + (x @ A) @ B * alpha / rank
+/// A is rank columns wide and initialized like the weights of a layer, B is initialized to 0,
+/// so the adapted layer starts as the base layer.
+/// The parameters of the base layer are frozen, only A and B are optimized.
+///
+/// See:
+/// LoRA: Low-Rank Adaptation of Large Language Models
+/// https://arxiv.org/abs/2106.09685
+pub struct LoraLinear {
+    device: Device,
+    linear: Linear,
+    a: TensorWithGrad,
+    b: TensorWithGrad,
+    scaling: f32,
+    matmul: MatMul,
+    mul: Mul,
+    add: Add,
+}
+
+impl LoraLinear {
+    pub fn try_new(
+        device: &Device,
+        linear: Linear,
+        rank: usize,
+        alpha: f32,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        let (out_features, in_features) = {
+            let weights: &Tensor = &linear.weights().tensor();
+            (weights.rows(), weights.cols())
+        };
+        if rank == 0 || rank > in_features.min(out_features) {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        for parameter in linear.parameters() {
+            device.freeze_parameter(parameter);
+        }
+
+        let mut a = vec![0.0; in_features * rank];
+        let mut rng = weights_initialization_rng(seed);
+        kaiming_initialization(in_features, rank, &mut a, &mut rng)?;
+        let a = new_tensor_with_grad!(device, in_features, rank, a, &[], true, true)?;
+        let b = new_tensor_with_grad!(
+            device,
+            rank,
+            out_features,
+            vec![0.0; rank * out_features],
+            &[],
+            true,
+            true
+        )?;
+
+        let transb = false;
+        let op = Self {
+            device: device.clone(),
+            linear,
+            a,
+            b,
+            scaling: alpha / rank as f32,
+            matmul: MatMul::new(device, transb),
+            mul: Mul::new(device),
+            add: Add::new(device),
+        };
+        Ok(op)
+    }
+
+    /// The frozen base layer.
+    pub fn linear(&self) -> &Linear {
+        &self.linear
+    }
+
+    /// The down projection, in_features x rank.
+    pub fn a(&self) -> &TensorWithGrad {
+        &self.a
+    }
+
+    /// The up projection, rank x out_features.
+    pub fn b(&self) -> &TensorWithGrad {
+        &self.b
+    }
+}
+
+impl UnaryOperator for LoraLinear {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let base = self.linear.forward(input)?;
+        let down = self.matmul.forward(input, &self.a)?;
+        let up = self.matmul.forward(&down, &self.b)?;
+        let (rows, cols) = (up.tensor().rows(), up.tensor().cols());
+        // Mul is used instead of ScalarMul for the gradient to be scaled too.
+        let scaling = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![self.scaling; rows * cols],
+            &[],
+            false,
+            false
+        )?;
+        let adapter = self.mul.forward(&scaling, &up)?;
+        self.add.forward(&base, &adapter)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    neural_program::NeuralProgram, new_tensor_with_grad, schedulers::DefaultStreamScheduler,
+    stream::StreamTrait, sum_of_squared_errors::SumOfSquaredErrors, tensor::Error, Adam, Device,
+    Linear, LoraLinear, Model, NeuralMachine, TensorWithGrad, UnaryModel, UnaryOperator,
+    WeightsInitialization,
+};
+
+const ROWS: usize = 2;
+const IN_FEATURES: usize = 4;
+const OUT_FEATURES: usize = 3;
+
+struct LoraModel {
+    lora_linear: LoraLinear,
+}
+
+impl UnaryModel for LoraModel {}
+
+impl UnaryOperator for LoraModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        self.lora_linear.forward(input)
+    }
+}
+
+impl Model for LoraModel {
+    fn input_size(&self) -> Vec<usize> {
+        vec![ROWS, IN_FEATURES]
+    }
+    fn output_size(&self) -> Vec<usize> {
+        vec![ROWS, OUT_FEATURES]
+    }
+}
+
+fn new_lora_linear(device: &Device) -> LoraLinear {
+    let linear = Linear::new_with_seed(
+        device,
+        OUT_FEATURES,
+        IN_FEATURES,
+        WeightsInitialization::Kaiming,
+        ROWS,
+        Some(1),
+    )
+    .unwrap();
+    LoraLinear::try_new(device, linear, 2, 4.0, Some(2)).unwrap()
+}
+
+fn input(device: &Device) -> TensorWithGrad {
+    let values = (0..(ROWS * IN_FEATURES))
+        .map(|x| x as f32 / 10.0 - 0.3)
+        .collect::<Vec<_>>();
+    new_tensor_with_grad!(device, ROWS, IN_FEATURES, values, &[], false, false).unwrap()
+}
+
+fn forward(device: &Device, output: &TensorWithGrad) -> Vec<f32> {
+    let device_stream = device.new_stream().unwrap();
+    for tensor in output.get_tape().iter() {
+        tensor.forward(device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    output.tensor().get_values().unwrap()
+}
+
+#[test]
+fn lora_linear_with_zero_adapters_is_the_base_layer() {
+    let device = Device::default();
+    let lora_linear = new_lora_linear(&device);
+    lora_linear
+        .a()
+        .tensor()
+        .set_values(vec![0.0; IN_FEATURES * 2])
+        .unwrap();
+    let input = input(&device);
+    let output = forward(&device, &lora_linear.forward(&input).unwrap());
+    let expected = forward(&device, &lora_linear.linear().forward(&input).unwrap());
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn lora_linear_trains_only_the_adapters() {
+    let device = Device::default();
+    let model = LoraModel {
+        lora_linear: new_lora_linear(&device),
+    };
+    // Only A and B are optimized.
+    assert_eq!(2, device.parameter_tensors().len());
+
+    let lora_linear = &model.lora_linear;
+    let values = |tensor: &TensorWithGrad| tensor.tensor().get_values().unwrap();
+    let weights = values(lora_linear.linear().weights());
+    let biases = values(lora_linear.linear().biases().unwrap());
+    let a = values(lora_linear.a());
+    let b = values(lora_linear.b());
+
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = Adam::try_new(0.01, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+    let input = input(&device);
+    let expected_output = new_tensor_with_grad!(
+        device,
+        ROWS,
+        OUT_FEATURES,
+        vec![1.0; ROWS * OUT_FEATURES],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    for _ in 0..3 {
+        neural_machine.infer(&input).unwrap();
+        neural_machine.loss(&expected_output).unwrap();
+        neural_machine.compute_gradient().unwrap();
+        neural_machine.optimize().unwrap();
+    }
+
+    assert_eq!(weights, values(lora_linear.linear().weights()));
+    assert_eq!(biases, values(lora_linear.linear().biases().unwrap()));
+    assert_ne!(a, values(lora_linear.a()));
+    assert_ne!(b, values(lora_linear.b()));
+}
@@ -2,6 +2,8 @@ mod gemm;
 pub use gemm::*;
 mod linear;
 pub use linear::*;
+mod lora_linear;
+pub use lora_linear::*;
 mod embedding;
 pub use embedding::*;
 mod matmul;
@@ -14,6 +16,8 @@ mod scalar_add;
 pub use scalar_add::*;
 mod add;
 pub use add::*;
+mod bias_add;
+pub use bias_add::*;
 mod sub;
 pub use sub::*;
 mod clip_norm;
@@ -22,6 +26,14 @@ mod div;
 pub use div::*;
 mod sqrt;
 pub use sqrt::*;
+mod cosine_similarity;
+pub use cosine_similarity::*;
+mod row_norm;
+pub use row_norm::*;
+mod outer;
+pub use outer::*;
+mod learnable_scale;
+pub use learnable_scale::*;
 pub mod clip;
 pub mod dot_product;
 pub mod identity;
@@ -0,0 +1,61 @@
+use crate::{
+    devices::Device,
+    error,
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, MatMul, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Outer product of a column vector (N x 1) and a row vector (1 x M).
+/// The output is N x M.
+/// The backward pass is the one of MatMul:
+/// the gradient of a is G b^T and the gradient of b is a^T G.
+pub struct Outer {
+    matmul: MatMul,
+}
+
+impl Outer {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            matmul: MatMul::new(device, false),
+        }
+    }
+}
+
+impl BinaryOperator for Outer {
+    fn forward(
+        &self,
+        input_0: &TensorWithGrad,
+        input_1: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        Tensor::check_outer_sizes(&input_0.tensor(), &input_1.tensor())?;
+        self.matmul.forward(input_0, input_1)
+    }
+}
+
+impl Tensor {
+    /// Compute the outer product a b into out, on the host.
+    /// a is N x 1, b is 1 x M and out is N x M.
+    pub fn outer(a: &Tensor, b: &Tensor, out: &Tensor) -> Result<(), Error> {
+        Self::check_outer_sizes(a, b)?;
+        if out.rows() != a.rows() || out.cols() != b.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let a = a.get_values()?;
+        let b = b.get_values()?;
+        let values = a
+            .iter()
+            .flat_map(|x| b.iter().map(move |y| x * y))
+            .collect::<Vec<_>>();
+        out.set_values(values)
+    }
+
+    fn check_outer_sizes(a: &Tensor, b: &Tensor) -> Result<(), Error> {
+        if a.cols() != 1 || b.rows() != 1 {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    new_tensor, new_tensor_with_grad, stream::StreamTrait, tensor::Tensor, BinaryOperator, Device,
+    Outer,
+};
+
+#[test]
+fn outer_product_of_small_vectors() {
+    let device = Device::default();
+    let a = new_tensor!(device, 3, 1, vec![1.0, 2.0, 3.0],).unwrap();
+    let b = new_tensor!(device, 1, 2, vec![4.0, -1.0],).unwrap();
+    let out = new_tensor!(device, 3, 2, vec![0.0; 6],).unwrap();
+
+    Tensor::outer(&a, &b, &out).unwrap();
+
+    assert_eq!(
+        vec![
+            4.0, -1.0, //
+            8.0, -2.0, //
+            12.0, -3.0, //
+        ],
+        out.get_values().unwrap()
+    );
+}
+
+#[test]
+fn outer_product_rejects_non_vectors() {
+    let device = Device::default();
+    let a = new_tensor!(device, 2, 2, vec![1.0; 4],).unwrap();
+    let b = new_tensor!(device, 1, 2, vec![1.0; 2],).unwrap();
+    let out = new_tensor!(device, 2, 2, vec![0.0; 4],).unwrap();
+    assert!(Tensor::outer(&a, &b, &out).is_err());
+}
+
+#[test]
+fn outer_operator_forward_and_backward() {
+    let device = Device::default();
+    let a = new_tensor_with_grad!(device, 3, 1, vec![1.0, 2.0, 3.0], &[], true, false).unwrap();
+    let b = new_tensor_with_grad!(device, 1, 2, vec![4.0, -1.0], &[], true, false).unwrap();
+    let output = Outer::new(&device).forward(&a, &b).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    assert_eq!(
+        vec![
+            4.0, -1.0, //
+            8.0, -2.0, //
+            12.0, -3.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    // G is the gradient of the loss with respect to the output.
+    output
+        .gradient()
+        .set_values(vec![
+            1.0, 0.0, //
+            0.0, 2.0, //
+            1.0, 1.0, //
+        ])
+        .unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+
+    // a' = G b^T
+    assert_eq!(vec![4.0, -2.0, 3.0], a.gradient().get_values().unwrap());
+    // b' = a^T G
+    assert_eq!(vec![4.0, 7.0], b.gradient().get_values().unwrap());
+}
@@ -0,0 +1,108 @@
+use crate::{
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+    EPSILON,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// L2 norm of each row of a tensor.
+/// The output has one row per input row and one column.
+pub struct RowNorm {
+    device: Device,
+}
+
+impl RowNorm {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for RowNorm {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        let squares = outputs[1];
+        input.row_norms(device, squares, output, device_stream)
+    }
+}
+
+impl UnaryOperator for RowNorm {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        let output =
+            new_tensor_with_grad!(self.device, rows, 1, vec![0.0; rows], &[input], true, false)?;
+        let device = &self.device;
+        let squares = new_tensor!(device, rows, cols, vec![0.0; rows * cols])?;
+
+        output.push_instruction(instruction!(
+            OpCode::RowNorm,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor(), &squares],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; rows * cols])?;
+            output.push_instruction(instruction!(
+                OpCode::RowNormBackward,
+                OperatorAttributes::None,
+                &[&input.tensor(), &output.tensor(), &output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// input_gradient = output_gradient * x / ||x||
+/// Zero rows are guarded with EPSILON, so their gradient is zero.
+pub struct RowNormBackward {}
+
+impl ExecutableOperator for RowNormBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let norms = inputs[1];
+        let output_gradient = inputs[2];
+        let input_gradient = outputs[0];
+        device_stream.wait_for()?;
+        let norms = norms.get_values()?;
+        let output_gradient = output_gradient.get_values()?;
+        let mut values = input.get_values()?;
+        for (row, values) in values.chunks_mut(input.cols().max(1)).enumerate() {
+            let scale = output_gradient[row] / norms[row].max(EPSILON);
+            values.iter_mut().for_each(|x| *x *= scale);
+        }
+        input_gradient.set_values(values)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, Device, RowNorm, TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn row_norm_of_known_rows_and_backward_direction() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(
+        device,
+        3,
+        2,
+        vec![
+            3.0, 4.0, //
+            0.0, 0.0, //
+            -6.0, 8.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let output: TensorWithGrad = RowNorm::new(&device).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![5.0, 0.0, 10.0], output.tensor().get_values().unwrap());
+
+    let output_gradient = output.gradient();
+    output_gradient.set_values(vec![1.0, 1.0, 2.0]).unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    // The gradient is x / ||x||, scaled by the output gradient, and zero rows have no direction.
+    assert_eq!(
+        vec![
+            0.6, 0.8, //
+            0.0, 0.0, //
+            -1.2, 1.6, //
+        ],
+        input.gradient().get_values().unwrap()
+    );
+}
@@ -1,4 +1,4 @@
-use crate::{new_tensor, Device, ExecutableOperator};
+use crate::{new_tensor, stream::StreamTrait, tensor::ErrorEnum, Device, ExecutableOperator};
 
 use super::Transpose;
 
@@ -27,3 +27,46 @@ fn transpose() {
         }
     }
 }
+
+#[test]
+fn transpose_into_matches_the_allocating_transpose() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    for (rows, cols) in [(3, 5), (5, 3), (1, 4), (4, 4)] {
+        let values = (0..rows * cols).map(|x| x as f32).collect::<Vec<_>>();
+        let input = new_tensor!(device, rows, cols, values.clone()).unwrap();
+        let output = new_tensor!(device, cols, rows, vec![-1.0; rows * cols]).unwrap();
+        input
+            .transpose_into(&device, &output, &device_stream)
+            .unwrap();
+        device_stream.wait_for().unwrap();
+
+        let mut expected = vec![0.0; rows * cols];
+        for row in 0..rows {
+            for col in 0..cols {
+                expected[col * rows + row] = values[row * cols + col];
+            }
+        }
+        assert_eq!(expected, output.get_values().unwrap());
+    }
+}
+
+#[test]
+fn transpose_into_in_place_and_shape_checks() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let matrix = new_tensor!(device, 2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    matrix
+        .transpose_into(&device, &matrix, &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![1.0, 3.0, 2.0, 4.0], matrix.get_values().unwrap());
+
+    let input = new_tensor!(device, 2, 3, vec![0.0; 6]).unwrap();
+    let output = new_tensor!(device, 2, 3, vec![0.0; 6]).unwrap();
+    let result = input.transpose_into(&device, &output, &device_stream);
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        result.map_err(|e| e.error().clone())
+    );
+}
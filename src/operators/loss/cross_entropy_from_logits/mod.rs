@@ -0,0 +1,86 @@
+use crate::{
+    devices::Device,
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    tensor::{Error, Tensor},
+    BinaryOperator, Category, OperatorAttributes, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Cross-entropy loss computed directly from the logits.
+/// The loss is -sum(expected * LogSoftmax(logits)), which is stable for large logits.
+/// The gradient in respect to the logits is softmax(logits) - expected.
+#[derive(Clone)]
+pub struct CrossEntropyFromLogits {
+    device: Device,
+}
+
+impl CrossEntropyFromLogits {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+        }
+    }
+}
+
+impl BinaryOperator for CrossEntropyFromLogits {
+    fn forward(
+        &self,
+        expected: &TensorWithGrad,
+        actual: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let device = &self.device;
+        let logits: &Tensor = &actual.tensor();
+        let rows = logits.rows();
+        let cols = logits.cols();
+        let len = rows * cols;
+        let output =
+            new_tensor_with_grad!(device, 1, 1, vec![0.0], &[expected, actual], true, false)?;
+
+        let log_probabilities = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        output.push_instruction(instruction!(
+            OpCode::LogSoftmax,
+            OperatorAttributes::None,
+            &[logits],
+            &[&log_probabilities],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Dot,
+            OperatorAttributes::None,
+            &[&expected.tensor(), &log_probabilities],
+            &[&output.tensor()],
+            Category::Loss,
+        ));
+        let minus_one = new_tensor!(device, 1, 1, vec![-1.0])?;
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&minus_one, &output.tensor()],
+            &[&output.tensor()],
+            Category::Loss,
+        ));
+
+        if actual.gradient().requires_grad() {
+            let probabilities = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::Softmax,
+                OperatorAttributes::None,
+                &[logits],
+                &[&probabilities],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Sub,
+                OperatorAttributes::None,
+                &[&probabilities, &expected.tensor()],
+                &[&actual.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
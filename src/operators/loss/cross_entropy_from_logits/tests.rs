@@ -0,0 +1,62 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, BinaryOperator, CrossEntropyFromLogits, Device,
+    Softmax, SoftmaxCrossEntropyLoss, TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn cross_entropy_from_logits_matches_softmax_and_cross_entropy_loss() {
+    let device = Device::default();
+    let logits = vec![
+        0.5, -1.0, 2.0, 0.0, //
+        1.0, 3.0, -2.0, 0.5, //
+    ];
+    let expected = vec![
+        0.0, 0.0, 1.0, 0.0, //
+        1.0, 0.0, 0.0, 0.0, //
+    ];
+    let device_stream = device.new_stream().unwrap();
+
+    // Fused
+    let fused_expected =
+        new_tensor_with_grad!(device, 2, 4, expected.clone(), &[], false, false).unwrap();
+    let fused_logits =
+        new_tensor_with_grad!(device, 2, 4, logits.clone(), &[], true, false).unwrap();
+    let fused_loss: TensorWithGrad = CrossEntropyFromLogits::new(&device)
+        .forward(&fused_expected, &fused_logits)
+        .unwrap();
+    fused_loss.forward(&device, &device_stream).unwrap();
+    fused_loss
+        .compute_gradient(&device, &device_stream)
+        .unwrap();
+
+    // Composed
+    let composed_expected =
+        new_tensor_with_grad!(device, 2, 4, expected.clone(), &[], false, false).unwrap();
+    let composed_logits =
+        new_tensor_with_grad!(device, 2, 4, logits.clone(), &[], true, false).unwrap();
+    let softmax = Softmax::new_with_next_is_cross_entropy_loss(&device)
+        .forward(&composed_logits)
+        .unwrap();
+    let composed_loss: TensorWithGrad = SoftmaxCrossEntropyLoss::new(&device)
+        .forward(&composed_expected, &softmax)
+        .unwrap();
+    softmax.forward(&device, &device_stream).unwrap();
+    composed_loss.forward(&device, &device_stream).unwrap();
+    composed_loss
+        .compute_gradient(&device, &device_stream)
+        .unwrap();
+    device_stream.wait_for().unwrap();
+
+    let fused_loss_value = fused_loss.tensor().get_values().unwrap()[0];
+    let composed_loss_value = composed_loss.tensor().get_values().unwrap()[0];
+    assert_lt!((fused_loss_value - composed_loss_value).abs(), 1e-4);
+
+    // The gradient in respect to the logits.
+    let fused_gradient = fused_logits.gradient().get_values().unwrap();
+    let composed_gradient = softmax.gradient().get_values().unwrap();
+    for (fused, composed) in fused_gradient.iter().zip(composed_gradient.iter()) {
+        assert_lt!((fused - composed).abs(), 1e-5);
+    }
+}
@@ -1,3 +1,20 @@
+mod cross_entropy_from_logits;
 mod softmax_cross_entropy_loss;
 pub mod sum_of_squared_errors;
+mod weighted_sum;
+pub use cross_entropy_from_logits::*;
 pub use softmax_cross_entropy_loss::*;
+pub use weighted_sum::*;
+
+/// How the losses of the elements are reduced.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reduction {
+    /// One loss, the sum of the losses of the elements.
+    Sum,
+    /// One loss, the sum divided by the number of elements.
+    /// The gradient is divided by the number of elements too.
+    Mean,
+    /// One loss per row, in a tensor with one column.
+    /// The gradient of each row is the gradient of the loss of that row.
+    None,
+}
@@ -1,21 +1,71 @@
 use crate::{
     devices::Device,
-    instruction, new_tensor_with_grad,
+    error, instruction, new_tensor, new_tensor_with_grad,
     opcode::OpCode,
-    stream::DeviceStream,
-    tensor::{Error, Tensor},
-    BinaryOperator, Category, DeviceTrait, ExecutableOperator, OperatorAttributes, TensorWithGrad,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, DeviceTrait, ExecutableOperator, OperatorAttributes, Reduction,
+    TensorWithGrad, EPSILON,
 };
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Clone)]
 pub struct SoftmaxCrossEntropyLoss {
     device: Device,
+    ignore_index: Option<usize>,
+    label_smoothing: f32,
+    reduction: Reduction,
 }
 
 impl SoftmaxCrossEntropyLoss {
     pub fn new(device: &Device) -> Self {
         Self {
             device: device.clone(),
+            ignore_index: None,
+            label_smoothing: 0.0,
+            reduction: Reduction::Sum,
+        }
+    }
+
+    /// Rows of the expected tensor whose class is `ignore_index` contribute
+    /// zero loss and zero gradient.
+    /// Reduction::Mean divides the loss by the number of non-ignored rows.
+    pub fn new_with_ignore_index(
+        device: &Device,
+        ignore_index: usize,
+        reduction: Reduction,
+    ) -> Self {
+        Self {
+            device: device.clone(),
+            ignore_index: Some(ignore_index),
+            label_smoothing: 0.0,
+            reduction,
+        }
+    }
+
+    /// The one-hot expected tensor is mixed with a uniform distribution
+    /// before computing the loss and the gradient:
+    /// (1 - label_smoothing) * expected + label_smoothing / num_classes
+    /// See Rethinking the Inception Architecture for Computer Vision
+    /// https://arxiv.org/abs/1512.00567
+    pub fn new_with_label_smoothing(device: &Device, label_smoothing: f32) -> Self {
+        Self {
+            device: device.clone(),
+            ignore_index: None,
+            label_smoothing,
+            reduction: Reduction::Sum,
+        }
+    }
+
+    /// See Reduction.
+    pub fn new_with_reduction(device: &Device, reduction: Reduction) -> Self {
+        Self {
+            device: device.clone(),
+            ignore_index: None,
+            label_smoothing: 0.0,
+            reduction,
         }
     }
 }
@@ -31,7 +81,29 @@ impl ExecutableOperator for SoftmaxCrossEntropyLoss {
         let expected = inputs[0];
         let actual = inputs[1];
         let loss = outputs[0];
-        device.cross_entropy_loss(expected, actual, loss, device_stream)
+        if loss.len() == 1 {
+            return device.cross_entropy_loss(expected, actual, loss, device_stream);
+        }
+        // One loss per row.
+        if *expected.size() != *actual.size() || loss.len() != expected.rows() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let expected = expected.rows_iter()?;
+        let actual = actual.rows_iter()?;
+        let losses = expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(p, q)| {
+                -p.iter()
+                    .zip(q.iter())
+                    .map(|(p_i, q_i)| p_i * f32::ln(q_i + EPSILON))
+                    .sum::<f32>()
+            })
+            .collect();
+        loss.set_values(losses)?;
+        device_stream.wait_for_default()?;
+        Ok(())
     }
 }
 
@@ -41,23 +113,57 @@ impl BinaryOperator for SoftmaxCrossEntropyLoss {
         expected: &TensorWithGrad,
         actual: &TensorWithGrad,
     ) -> Result<TensorWithGrad, Error> {
+        let output_rows = match self.reduction {
+            Reduction::None => expected.tensor().rows(),
+            Reduction::Sum | Reduction::Mean => 1,
+        };
         let output = new_tensor_with_grad!(
             self.device,
+            output_rows,
             1,
-            1,
-            vec![0.0],
+            vec![0.0; output_rows],
             &[expected, actual],
             true,
             false
         )?;
 
+        if !(0.0..=1.0).contains(&self.label_smoothing) {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let smoothed_expected = self.smooth_labels(expected, &output)?;
+
+        if let Some(ignore_index) = self.ignore_index {
+            return self.forward_with_ignore_index(
+                expected,
+                &smoothed_expected,
+                actual,
+                output,
+                ignore_index,
+            );
+        }
+
         output.push_instruction(instruction!(
             OpCode::SoftmaxCrossEntropyLoss,
             OperatorAttributes::None,
-            &[&expected.tensor(), &actual.tensor(),],
+            &[&smoothed_expected, &actual.tensor(),],
             &[&output.tensor()],
             Category::Loss,
         ));
+        let mean_scale = match self.reduction {
+            Reduction::Mean => {
+                let len = actual.tensor().len();
+                let mean_scale = new_tensor!(self.device, 1, 1, vec![1.0 / len as f32])?;
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[&mean_scale, &output.tensor()],
+                    &[&output.tensor()],
+                    Category::Loss,
+                ));
+                Some(mean_scale)
+            }
+            Reduction::Sum | Reduction::None => None,
+        };
 
         // When Cross-Entropy Loss is used with a Softmax activation function,
         // then we don't need to derive the softmax activations.
@@ -67,10 +173,206 @@ impl BinaryOperator for SoftmaxCrossEntropyLoss {
             output.push_instruction(instruction!(
                 OpCode::Sub,
                 OperatorAttributes::None,
-                &[&actual.tensor(), &expected.tensor()],
+                &[&actual.tensor(), &smoothed_expected],
+                &[&actual.gradient()],
+                Category::Gradient,
+            ));
+            if let Some(mean_scale) = &mean_scale {
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[mean_scale, &actual.gradient()],
+                    &[&actual.gradient()],
+                    Category::Gradient,
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+impl SoftmaxCrossEntropyLoss {
+    /// Without label smoothing, this is the expected tensor.
+    fn smooth_labels(
+        &self,
+        expected: &TensorWithGrad,
+        output: &TensorWithGrad,
+    ) -> Result<Tensor, Error> {
+        let expected: &Tensor = &expected.tensor();
+        if self.label_smoothing == 0.0 {
+            return Ok(expected.clone());
+        }
+        let device = &self.device;
+        let rows = expected.rows();
+        let cols = expected.cols();
+        let smoothed_expected = new_tensor!(device, rows, cols, vec![0.0; rows * cols])?;
+        let keep = new_tensor!(device, 1, 1, vec![1.0 - self.label_smoothing])?;
+        let uniform = new_tensor!(device, 1, 1, vec![self.label_smoothing / cols as f32])?;
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&keep, expected],
+            &[&smoothed_expected],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarAdd,
+            OperatorAttributes::None,
+            &[&uniform, &smoothed_expected],
+            &[&smoothed_expected],
+            Category::Loss,
+        ));
+        Ok(smoothed_expected)
+    }
+
+    /// The ignored rows are found with the one-hot expected tensor,
+    /// and the loss uses the smoothed expected tensor.
+    /// The mask is built on the device: expected x selection, where the row ignore_index
+    /// of the selection is ones, is 1 for the ignored rows, and the mask is 1 minus that.
+    fn forward_with_ignore_index(
+        &self,
+        expected: &TensorWithGrad,
+        smoothed_expected: &Tensor,
+        actual: &TensorWithGrad,
+        output: TensorWithGrad,
+        ignore_index: usize,
+    ) -> Result<TensorWithGrad, Error> {
+        let device = &self.device;
+        let rows = expected.tensor().rows();
+        let cols = expected.tensor().cols();
+        let len = rows * cols;
+        if ignore_index >= cols {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let mut selection = vec![0.0; cols * cols];
+        for col in 0..cols {
+            selection[ignore_index * cols + col] = 1.0;
+        }
+        let selection = new_tensor!(device, cols, cols, selection)?;
+        let zero = new_tensor!(device, 1, 1, vec![0.0])?;
+        let one = new_tensor!(device, 1, 1, vec![1.0])?;
+        let minus_one = new_tensor!(device, 1, 1, vec![-1.0])?;
+        let mask = new_tensor!(device, rows, cols, vec![1.0; len])?;
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&zero, &mask],
+            &[&mask],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Gemm,
+            OperatorAttributes::ThreeBools(false, false, false),
+            &[&expected.tensor(), &selection, &mask],
+            &[&mask],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&minus_one, &mask],
+            &[&mask],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::ScalarAdd,
+            OperatorAttributes::None,
+            &[&one, &mask],
+            &[&mask],
+            Category::Loss,
+        ));
+
+        let masked_expected = new_tensor!(device, rows, cols, vec![0.0; len])?;
+        output.push_instruction(instruction!(
+            OpCode::Mul,
+            OperatorAttributes::None,
+            &[&mask, smoothed_expected],
+            &[&masked_expected],
+            Category::Loss,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::SoftmaxCrossEntropyLoss,
+            OperatorAttributes::None,
+            &[&masked_expected, &actual.tensor()],
+            &[&output.tensor()],
+            Category::Loss,
+        ));
+
+        // The mean is over the non-ignored rows, which are counted with the mask,
+        // whose rows are all ones or all zeros.
+        // The count is at least 1 so that ignoring every row gives a zero loss.
+        let mean_scale = match self.reduction {
+            Reduction::Mean => {
+                let count = new_tensor!(device, 1, 1, vec![0.0])?;
+                let cols_reciprocal = new_tensor!(device, 1, 1, vec![1.0 / cols as f32])?;
+                let max_count = new_tensor!(device, 1, 1, vec![rows as f32])?;
+                let mean_scale = new_tensor!(device, 1, 1, vec![0.0])?;
+                output.push_instruction(instruction!(
+                    OpCode::ReduceSum,
+                    OperatorAttributes::None,
+                    &[&mask],
+                    &[&count],
+                    Category::Loss,
+                ));
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[&cols_reciprocal, &count],
+                    &[&count],
+                    Category::Loss,
+                ));
+                output.push_instruction(instruction!(
+                    OpCode::Clip,
+                    OperatorAttributes::None,
+                    &[&one, &max_count, &count],
+                    &[&count],
+                    Category::Loss,
+                ));
+                output.push_instruction(instruction!(
+                    OpCode::Div,
+                    OperatorAttributes::None,
+                    &[&one, &count],
+                    &[&mean_scale],
+                    Category::Loss,
+                ));
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[&mean_scale, &output.tensor()],
+                    &[&output.tensor()],
+                    Category::Loss,
+                ));
+                Some(mean_scale)
+            }
+            Reduction::Sum | Reduction::None => None,
+        };
+
+        if actual.gradient().requires_grad() {
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::Sub,
+                OperatorAttributes::None,
+                &[&actual.tensor(), smoothed_expected],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Mul,
+                OperatorAttributes::None,
+                &[&mask, &tmp],
                 &[&actual.gradient()],
                 Category::Gradient,
             ));
+            if let Some(mean_scale) = &mean_scale {
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[mean_scale, &actual.gradient()],
+                    &[&actual.gradient()],
+                    Category::Gradient,
+                ));
+            }
         }
 
         Ok(output)
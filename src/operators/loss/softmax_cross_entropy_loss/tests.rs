@@ -0,0 +1,191 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, BinaryOperator, Device, Reduction, Softmax,
+    SoftmaxCrossEntropyLoss, TensorWithGrad, UnaryOperator,
+};
+
+#[test]
+fn ignored_position_contributes_no_loss_and_no_gradient() {
+    let device = Device::default();
+    let ignore_index = 0;
+    let logits = vec![
+        0.5, -1.0, 2.0, //
+        1.0, 3.0, -2.0, //
+        -0.5, 1.5, 0.0, //
+    ];
+    // The second position is padding.
+    let expected = vec![
+        0.0, 0.0, 1.0, //
+        1.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, //
+    ];
+    let compute_loss = |reduction: Reduction| {
+        let device_stream = device.new_stream().unwrap();
+        let expected =
+            new_tensor_with_grad!(device, 3, 3, expected.clone(), &[], false, false).unwrap();
+        let logits = new_tensor_with_grad!(device, 3, 3, logits.clone(), &[], true, false).unwrap();
+        let softmax = Softmax::new_with_next_is_cross_entropy_loss(&device)
+            .forward(&logits)
+            .unwrap();
+        let loss: TensorWithGrad =
+            SoftmaxCrossEntropyLoss::new_with_ignore_index(&device, ignore_index, reduction)
+                .forward(&expected, &softmax)
+                .unwrap();
+        softmax.forward(&device, &device_stream).unwrap();
+        loss.forward(&device, &device_stream).unwrap();
+        loss.compute_gradient(&device, &device_stream).unwrap();
+        device_stream.wait_for().unwrap();
+        let probabilities = softmax.tensor().get_values().unwrap();
+        let loss_values = loss.tensor().get_values().unwrap();
+        let gradient = softmax.gradient().get_values().unwrap();
+        (probabilities, loss_values, gradient)
+    };
+
+    let (probabilities, sum, sum_gradient) = compute_loss(Reduction::Sum);
+    let expected_sum = -(probabilities[2].ln() + probabilities[7].ln());
+    assert_eq!(1, sum.len());
+    assert_lt!((sum[0] - expected_sum).abs(), 1e-4);
+    let expected_gradient = vec![
+        probabilities[0],
+        probabilities[1],
+        probabilities[2] - 1.0,
+        0.0,
+        0.0,
+        0.0,
+        probabilities[6],
+        probabilities[7] - 1.0,
+        probabilities[8],
+    ];
+    for (actual, expected) in sum_gradient.iter().zip(expected_gradient.iter()) {
+        assert_lt!((actual - expected).abs(), 1e-5);
+    }
+
+    // The mean is over the 2 non-ignored rows.
+    let n = 2.0;
+    let (_, mean, mean_gradient) = compute_loss(Reduction::Mean);
+    assert_eq!(1, mean.len());
+    assert_lt!((mean[0] - expected_sum / n).abs(), 1e-5);
+    for (actual, expected) in mean_gradient.iter().zip(expected_gradient.iter()) {
+        assert_lt!((actual - expected / n).abs(), 1e-5);
+    }
+
+    let (_, per_row, _) = compute_loss(Reduction::None);
+    assert_eq!(3, per_row.len());
+    assert_eq!(0.0, per_row[1]);
+    assert_lt!((per_row.iter().sum::<f32>() - expected_sum).abs(), 1e-4);
+}
+
+#[test]
+fn ignoring_every_position_gives_a_zero_mean() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let expected =
+        new_tensor_with_grad!(device, 2, 2, vec![1.0, 0.0, 1.0, 0.0], &[], false, false).unwrap();
+    let actual = new_tensor_with_grad!(device, 2, 2, vec![0.5; 4], &[], true, false).unwrap();
+    let loss = SoftmaxCrossEntropyLoss::new_with_ignore_index(&device, 0, Reduction::Mean)
+        .forward(&expected, &actual)
+        .unwrap();
+    loss.forward(&device, &device_stream).unwrap();
+    loss.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![0.0], loss.tensor().get_values().unwrap());
+    assert!(actual
+        .gradient()
+        .get_values()
+        .unwrap()
+        .iter()
+        .all(|x| *x == 0.0));
+}
+
+#[test]
+fn label_smoothing_penalizes_a_perfect_prediction() {
+    let device = Device::default();
+    // The first class is predicted with a probability that is almost 1.
+    let logits = vec![
+        30.0, 0.0, 0.0, 0.0, //
+        0.0, 30.0, 0.0, 0.0, //
+    ];
+    let expected = vec![
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+    ];
+    let compute_loss = |loss_operator: SoftmaxCrossEntropyLoss| {
+        let device_stream = device.new_stream().unwrap();
+        let expected =
+            new_tensor_with_grad!(device, 2, 4, expected.clone(), &[], false, false).unwrap();
+        let logits = new_tensor_with_grad!(device, 2, 4, logits.clone(), &[], true, false).unwrap();
+        let softmax = Softmax::new_with_next_is_cross_entropy_loss(&device)
+            .forward(&logits)
+            .unwrap();
+        let loss: TensorWithGrad = loss_operator.forward(&expected, &softmax).unwrap();
+        softmax.forward(&device, &device_stream).unwrap();
+        loss.forward(&device, &device_stream).unwrap();
+        loss.compute_gradient(&device, &device_stream).unwrap();
+        device_stream.wait_for().unwrap();
+        let loss_value = loss.tensor().get_values().unwrap()[0];
+        let gradient = softmax.gradient().get_values().unwrap();
+        (loss_value, gradient)
+    };
+
+    let (loss, gradient) = compute_loss(SoftmaxCrossEntropyLoss::new(&device));
+    assert_lt!(loss.abs(), 1e-4);
+    assert!(gradient.iter().all(|x| x.abs() < 1e-4));
+
+    let label_smoothing = 0.1;
+    let (loss, gradient) = compute_loss(SoftmaxCrossEntropyLoss::new_with_label_smoothing(
+        &device,
+        label_smoothing,
+    ));
+    assert_lt!(0.1, loss);
+    // The gradient pulls the prediction towards the smoothed target.
+    let smoothed_hot = 1.0 - label_smoothing + label_smoothing / 4.0;
+    let smoothed_cold = label_smoothing / 4.0;
+    assert_lt!((gradient[0] - (1.0 - smoothed_hot)).abs(), 1e-4);
+    assert_lt!((gradient[2] + smoothed_cold).abs(), 1e-4);
+}
+
+#[test]
+fn mean_reduction_divides_the_loss_and_the_gradient_by_the_elements() {
+    let device = Device::default();
+    let logits = vec![
+        0.5, -1.0, 2.0, //
+        1.0, 3.0, -2.0, //
+    ];
+    let expected = vec![
+        0.0, 0.0, 1.0, //
+        1.0, 0.0, 0.0, //
+    ];
+    let compute_loss = |reduction: Reduction| {
+        let device_stream = device.new_stream().unwrap();
+        let expected =
+            new_tensor_with_grad!(device, 2, 3, expected.clone(), &[], false, false).unwrap();
+        let logits = new_tensor_with_grad!(device, 2, 3, logits.clone(), &[], true, false).unwrap();
+        let softmax = Softmax::new_with_next_is_cross_entropy_loss(&device)
+            .forward(&logits)
+            .unwrap();
+        let loss = SoftmaxCrossEntropyLoss::new_with_reduction(&device, reduction)
+            .forward(&expected, &softmax)
+            .unwrap();
+        softmax.forward(&device, &device_stream).unwrap();
+        loss.forward(&device, &device_stream).unwrap();
+        loss.compute_gradient(&device, &device_stream).unwrap();
+        device_stream.wait_for().unwrap();
+        let loss_values = loss.tensor().get_values().unwrap();
+        let gradient = softmax.gradient().get_values().unwrap();
+        (loss_values, gradient)
+    };
+
+    let n = 6.0;
+    let (sum, sum_gradient) = compute_loss(Reduction::Sum);
+    let (mean, mean_gradient) = compute_loss(Reduction::Mean);
+    assert_eq!(1, mean.len());
+    assert_lt!((mean[0] - sum[0] / n).abs(), 1e-6);
+    for (sum, mean) in sum_gradient.iter().zip(mean_gradient.iter()) {
+        assert_lt!((mean - sum / n).abs(), 1e-6);
+    }
+
+    let (per_row, _) = compute_loss(Reduction::None);
+    assert_eq!(2, per_row.len());
+    assert_lt!((per_row.iter().sum::<f32>() - sum[0]).abs(), 1e-5);
+}
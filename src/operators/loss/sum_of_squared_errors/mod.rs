@@ -1,10 +1,11 @@
 use crate::{
     devices::Device,
-    instruction, new_tensor, new_tensor_with_grad,
+    error, instruction, new_tensor, new_tensor_with_grad,
     opcode::OpCode,
-    stream::DeviceStream,
-    tensor::{Error, Tensor},
-    BinaryOperator, Category, DeviceTrait, ExecutableOperator, OperatorAttributes, TensorWithGrad,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    BinaryOperator, Category, DeviceTrait, ExecutableOperator, OperatorAttributes, Reduction,
+    TensorWithGrad,
 };
 
 #[cfg(test)]
@@ -12,12 +13,19 @@ mod tests;
 
 pub struct SumOfSquaredErrors {
     device: Device,
+    reduction: Reduction,
 }
 
 impl SumOfSquaredErrors {
     pub fn new(device: &Device) -> Self {
+        Self::new_with_reduction(device, Reduction::Sum)
+    }
+
+    /// See Reduction.
+    pub fn new_with_reduction(device: &Device, reduction: Reduction) -> Self {
         Self {
             device: device.clone(),
+            reduction,
         }
     }
 }
@@ -33,7 +41,30 @@ impl ExecutableOperator for SumOfSquaredErrors {
         let expected = inputs[0];
         let actual = inputs[1];
         let loss = outputs[0];
-        device.reduce_sum_square(expected, actual, loss, device_stream)
+        if loss.len() == 1 {
+            return device.reduce_sum_square(expected, actual, loss, device_stream);
+        }
+        // One loss per row.
+        if *expected.size() != *actual.size() || loss.len() != expected.rows() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let expected = expected.rows_iter()?;
+        let actual = actual.rows_iter()?;
+        let losses = expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(expected, actual)| {
+                expected
+                    .iter()
+                    .zip(actual.iter())
+                    .map(|(expected, actual)| (expected - actual) * (expected - actual))
+                    .sum::<f32>()
+            })
+            .collect();
+        loss.set_values(losses)?;
+        device_stream.wait_for_default()?;
+        Ok(())
     }
 }
 
@@ -43,15 +74,25 @@ impl BinaryOperator for SumOfSquaredErrors {
         input_1: &TensorWithGrad,
         input_2: &TensorWithGrad,
     ) -> Result<TensorWithGrad, Error> {
+        let rows = input_1.tensor().rows();
+        let len = input_1.tensor().len();
+        let output_rows = match self.reduction {
+            Reduction::None => rows,
+            Reduction::Sum | Reduction::Mean => 1,
+        };
         let output = new_tensor_with_grad!(
             self.device,
+            output_rows,
             1,
-            1,
-            vec![0.0],
+            vec![0.0; output_rows],
             &[input_1, input_2],
             true,
             false
         )?;
+        let mean_scale = match self.reduction {
+            Reduction::Mean => Some(new_tensor!(self.device, 1, 1, vec![1.0 / len as f32])?),
+            Reduction::Sum | Reduction::None => None,
+        };
         let inputs = [input_1, input_2];
         let outputs = [&output];
 
@@ -62,6 +103,15 @@ impl BinaryOperator for SumOfSquaredErrors {
             &[&outputs[0].tensor()],
             Category::Loss,
         ));
+        if let Some(mean_scale) = &mean_scale {
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[mean_scale, &output.tensor()],
+                &[&output.tensor()],
+                Category::Loss,
+            ));
+        }
         let inputs = [input_1, input_2];
         let outputs = [input_2];
         let inputs: &[&Tensor] = &[&inputs[0].tensor(), &inputs[1].tensor()];
@@ -88,6 +138,15 @@ impl BinaryOperator for SumOfSquaredErrors {
                 &[output_gradient],
                 Category::Gradient,
             ));
+            if let Some(mean_scale) = &mean_scale {
+                output.push_instruction(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[mean_scale, output_gradient],
+                    &[output_gradient],
+                    Category::Gradient,
+                ));
+            }
         }
 
         Ok(output)
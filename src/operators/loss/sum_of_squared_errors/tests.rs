@@ -1,6 +1,8 @@
+use more_asserts::assert_lt;
+
 use crate::{
     new_tensor, new_tensor_with_grad, stream::StreamTrait, tensor::Tensor, BinaryOperator, Device,
-    ExecutableOperator,
+    ExecutableOperator, Reduction,
 };
 
 use super::SumOfSquaredErrors;
@@ -67,3 +69,38 @@ fn evaluate() {
         (4.0 - 1.0_f32).powf(2.0) * 8.0,
     );
 }
+
+#[test]
+fn mean_reduction_divides_the_loss_and_the_gradient_by_the_elements() {
+    let device = Device::default();
+    let compute_loss = |reduction: Reduction| {
+        let device_stream = device.new_stream().unwrap();
+        let expected =
+            new_tensor_with_grad!(device, 2, 2, vec![4.0, 2.0, 0.0, -1.0], &[], false, false)
+                .unwrap();
+        let actual =
+            new_tensor_with_grad!(device, 2, 2, vec![1.0, 1.0, 1.0, 1.0], &[], true, false)
+                .unwrap();
+        let loss = SumOfSquaredErrors::new_with_reduction(&device, reduction)
+            .forward(&expected, &actual)
+            .unwrap();
+        loss.forward(&device, &device_stream).unwrap();
+        loss.compute_gradient(&device, &device_stream).unwrap();
+        device_stream.wait_for().unwrap();
+        let loss_values = loss.tensor().get_values().unwrap();
+        let gradient = actual.gradient().get_values().unwrap();
+        (loss_values, gradient)
+    };
+
+    let (sum, sum_gradient) = compute_loss(Reduction::Sum);
+    assert_eq!(vec![15.0], sum);
+    let (mean, mean_gradient) = compute_loss(Reduction::Mean);
+    assert_eq!(vec![15.0 / 4.0], mean);
+    for (sum, mean) in sum_gradient.iter().zip(mean_gradient.iter()) {
+        assert_lt!((mean - sum / 4.0).abs(), 1e-6);
+    }
+
+    let (per_row, per_row_gradient) = compute_loss(Reduction::None);
+    assert_eq!(vec![10.0, 5.0], per_row);
+    assert_eq!(sum_gradient, per_row_gradient);
+}
@@ -0,0 +1,134 @@
+use crate::{
+    devices::Device,
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    tensor::{Error, Tensor},
+    BinaryOperator, Category, OperatorAttributes, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Weighted sum of losses, for models with multiple loss terms.
+/// loss = Σ weight_i * loss_i(expected, actual)
+/// The gradient of each loss in respect to actual is scaled by its weight.
+///
+/// Each loss sees its own view of actual, which shares the values of actual but has its own gradient,
+/// so that the losses do not overwrite each other's gradient.
+/// All the instructions belong to the output, so the gradients of the losses are summed
+/// into the gradient of actual before actual propagates its gradient.
+pub struct WeightedSum {
+    device: Device,
+    losses: Vec<(Box<dyn BinaryOperator>, f32)>,
+}
+
+impl WeightedSum {
+    pub fn new(device: &Device, losses: Vec<(Box<dyn BinaryOperator>, f32)>) -> Self {
+        Self {
+            device: device.clone(),
+            losses,
+        }
+    }
+}
+
+impl BinaryOperator for WeightedSum {
+    fn forward(
+        &self,
+        expected: &TensorWithGrad,
+        actual: &TensorWithGrad,
+    ) -> Result<TensorWithGrad, Error> {
+        let device = &self.device;
+        let output =
+            new_tensor_with_grad!(device, 1, 1, vec![0.0], &[expected, actual], true, false)?;
+        let zero = new_tensor!(device, 1, 1, vec![0.0])?;
+        output.push_instruction(instruction!(
+            OpCode::ScalarMul,
+            OperatorAttributes::None,
+            &[&zero, &output.tensor()],
+            &[&output.tensor()],
+            Category::Loss,
+        ));
+
+        let actual_t: &Tensor = &actual.tensor();
+        let rows = actual_t.rows();
+        let cols = actual_t.cols();
+        let len = rows * cols;
+        let requires_grad = actual.gradient().requires_grad();
+        let mut gradient_instructions = vec![];
+        for (loss_operator, weight) in self.losses.iter() {
+            let weight = new_tensor!(device, 1, 1, vec![*weight])?;
+            let actual_gradient = match requires_grad {
+                true => new_tensor!(device, rows, cols, vec![0.0; len])?,
+                false => new_tensor!(device, 0, 0, vec![])?,
+            };
+            let loss_actual = TensorWithGrad::new(actual_t.clone(), actual_gradient, &[]);
+            let loss = loss_operator.forward(expected, &loss_actual)?;
+
+            let tape = loss.get_tape();
+            for tensor in tape.iter() {
+                for instruction in tensor.forward_instructions() {
+                    output.push_instruction(instruction);
+                }
+            }
+            let weighted_loss = new_tensor!(device, 1, 1, vec![0.0])?;
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&weight, &loss.tensor()],
+                &[&weighted_loss],
+                Category::Loss,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&output.tensor(), &weighted_loss],
+                &[&output.tensor()],
+                Category::Loss,
+            ));
+
+            if requires_grad {
+                gradient_instructions.push(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[&zero, &loss_actual.gradient()],
+                    &[&loss_actual.gradient()],
+                    Category::Gradient,
+                ));
+                for tensor in tape.iter().rev() {
+                    gradient_instructions.extend(tensor.gradient_instructions());
+                }
+                let weighted_gradient = new_tensor!(device, rows, cols, vec![0.0; len])?;
+                gradient_instructions.push(instruction!(
+                    OpCode::ScalarMul,
+                    OperatorAttributes::None,
+                    &[&weight, &loss_actual.gradient()],
+                    &[&weighted_gradient],
+                    Category::Gradient,
+                ));
+                gradient_instructions.push(instruction!(
+                    OpCode::Add,
+                    OperatorAttributes::None,
+                    &[&actual.gradient(), &weighted_gradient],
+                    &[&actual.gradient()],
+                    Category::Gradient,
+                ));
+            }
+        }
+
+        if requires_grad {
+            // The losses usually overwrite the gradient of actual, so it is accumulated from zero.
+            output.push_instruction(instruction!(
+                OpCode::ScalarMul,
+                OperatorAttributes::None,
+                &[&zero, &actual.gradient()],
+                &[&actual.gradient()],
+                Category::Gradient,
+            ));
+            for instruction in gradient_instructions {
+                output.push_instruction(instruction);
+            }
+        }
+
+        Ok(output)
+    }
+}
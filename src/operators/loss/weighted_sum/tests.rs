@@ -0,0 +1,68 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, sum_of_squared_errors::SumOfSquaredErrors,
+    BinaryOperator, Device, SoftmaxCrossEntropyLoss, TensorWithGrad, WeightedSum,
+};
+
+/// Returns the loss and the gradient in respect to actual.
+fn loss_and_gradient(device: &Device, loss_operator: &impl BinaryOperator) -> (f32, Vec<f32>) {
+    let expected = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![
+            0.0, 1.0, 0.0, //
+            1.0, 0.0, 0.0, //
+        ],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let actual = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![
+            0.2, 0.5, 0.3, //
+            0.6, 0.1, 0.3, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let loss: TensorWithGrad = loss_operator.forward(&expected, &actual).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    loss.forward(device, &device_stream).unwrap();
+    loss.compute_gradient(device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let loss = loss.tensor().get_values().unwrap()[0];
+    let gradient = actual.gradient().get_values().unwrap();
+    (loss, gradient)
+}
+
+#[test]
+fn weighted_sum_scales_each_loss_gradient() {
+    let device = Device::default();
+    let (loss_1, gradient_1) = loss_and_gradient(&device, &SumOfSquaredErrors::new(&device));
+    let (loss_2, gradient_2) = loss_and_gradient(&device, &SoftmaxCrossEntropyLoss::new(&device));
+
+    let weighted_sum = WeightedSum::new(
+        &device,
+        vec![
+            (Box::new(SumOfSquaredErrors::new(&device)), 0.3),
+            (Box::new(SoftmaxCrossEntropyLoss::new(&device)), 0.7),
+        ],
+    );
+    let (loss, gradient) = loss_and_gradient(&device, &weighted_sum);
+
+    assert_lt!((loss - (0.3 * loss_1 + 0.7 * loss_2)).abs(), 1e-5);
+    for i in 0..gradient.len() {
+        let expected = 0.3 * gradient_1[i] + 0.7 * gradient_2[i];
+        assert_lt!((gradient[i] - expected).abs(), 1e-5);
+    }
+    // The two losses contribute different gradients.
+    assert_ne!(gradient_1, gradient_2);
+}
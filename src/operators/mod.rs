@@ -8,6 +8,8 @@ mod lin_alg;
 pub use lin_alg::*;
 mod attention;
 pub use attention::*;
+mod convolution;
+pub use convolution::*;
 mod reduce;
 pub use reduce::*;
 pub mod analysis;
@@ -19,6 +21,7 @@ use crate::{
     tensor::{Error, Tensor},
     Device, TensorWithGrad,
 };
+use std::sync::{atomic::AtomicU64, Arc};
 
 pub trait UnaryOperator {
     fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error>;
@@ -65,4 +68,11 @@ pub enum OperatorAttributes {
     String(String),
     Vec(Vec<usize>),
     F32(f32),
+    ElementwiseFn(custom_unary::ElementwiseFn),
+    /// transb of the Gemm, then the op code and the attributes of the activation,
+    /// see LinearActivation.
+    LinearActivation(bool, opcode::OpCode, Box<OperatorAttributes>),
+    /// The probability, the seed and the counter of the draws of a Bernoulli, see Bernoulli.
+    /// The counter is shared by the clones of the attributes.
+    SeededBernoulli(f32, u64, Arc<AtomicU64>),
 }
@@ -1,18 +1,31 @@
 use crate::{
     analysis::min::Min,
+    checkpoint::{Allocate, Deallocate},
+    custom_unary::{CustomUnary, CustomUnaryBackward},
     dot_product::Dot,
     gelu::{Gelu, GeluDerivative},
     identity::Identity,
+    leaky_relu::{LeakyRelu, LeakyReluBackward},
+    log_softmax::{LogSoftmax, LogSoftmaxBackward},
     pow::Pow,
     reduce_l2::ReduceL2,
     reduce_sum::ReduceSum,
-    statistics::{bernoulli::Bernoulli, standardization::Standardization},
+    silu::{Silu, SiluBackward},
+    statistics::{
+        batch_norm::{BatchNorm, BatchNormBackward},
+        bernoulli::Bernoulli,
+        standardization::Standardization,
+    },
     stream::DeviceStream,
     sum_of_squared_errors::SumOfSquaredErrors,
     tensor::{Error, Tensor},
     transpose::Transpose,
-    Add, ClipNorm, Concat, Device, Div, ExecutableOperator, Gemm, Mul, OperatorAttributes, Reshape,
-    ScalarAdd, ScalarMul, Sigmoid, Softmax, SoftmaxCrossEntropyLoss, Sqrt, Sub, Unconcat,
+    Add, BiasAdd, ClipNorm, Col2Im, Concat, CosineSimilarity, Device, Div, EmbeddingGather,
+    EmbeddingGatherBackward, ExecutableOperator, Gemm, GlobalAvgPool2D, GlobalAvgPool2DBackward,
+    Im2Col, LinearActivation, MaxPool2D, MaxPool2DBackward, Mul, NchwToNhwc, NhwcToNchw,
+    OperatorAttributes, Pad, PadBackward, PairwiseAdd, PairwiseAddBackward, RelativePositionBias,
+    RelativePositionBiasBackward, Reshape, RowNorm, RowNormBackward, ScalarAdd, ScalarMul, Sigmoid,
+    Softmax, SoftmaxCrossEntropyLoss, Sqrt, Sub, ToDevice, Unconcat,
 };
 
 use super::clip::Clip;
@@ -31,6 +44,10 @@ pub enum OpCode {
     /// https://onnx.ai/onnx/operators/onnx__Add.html
     Add,
 
+    /// Not ONNX-compliant
+    /// Add with biases that are broadcast to every row.
+    BiasAdd,
+
     /// Not ONNX-compliant
     /// TODO remove this op code and use Add with broadcast
     ScalarAdd,
@@ -102,6 +119,18 @@ pub enum OpCode {
     Gelu,
     GeluDerivative,
 
+    /// Not ONNX-compliant
+    Silu,
+    SiluBackward,
+
+    /// https://onnx.ai/onnx/operators/onnx__LeakyRelu.html
+    LeakyRelu,
+    LeakyReluBackward,
+
+    /// https://onnx.ai/onnx/operators/onnx__LogSoftmax.html
+    LogSoftmax,
+    LogSoftmaxBackward,
+
     /// TODO
     /// https://onnx.ai/onnx/operators/onnx__Conv.html
     /// Conv,
@@ -114,6 +143,77 @@ pub enum OpCode {
 
     /// Not ONNX-compliant
     Dot,
+
+    /// Not ONNX-compliant
+    CosineSimilarity,
+
+    /// Not ONNX-compliant
+    EmbeddingGather,
+    EmbeddingGatherBackward,
+
+    /// Not ONNX-compliant
+    /// Copy a tensor to another device through the host.
+    ToDevice,
+
+    /// Not ONNX-compliant
+    /// L2 norm of each row.
+    RowNorm,
+    RowNormBackward,
+
+    /// Not ONNX-compliant
+    /// Element-wise user-supplied closure.
+    CustomUnary,
+    CustomUnaryBackward,
+
+    /// Not ONNX-compliant
+    /// Unfold the kernel windows of an NCHW image batch into rows, see Conv2D.
+    Im2Col,
+    Col2Im,
+
+    /// Not ONNX-compliant
+    /// Reorder between NHWC rows and an NCHW image batch.
+    NhwcToNchw,
+    NchwToNhwc,
+
+    /// Not ONNX-compliant
+    /// Maximum of each window of an NCHW image batch, with the position of the maximum.
+    MaxPool2D,
+    MaxPool2DBackward,
+
+    /// Not ONNX-compliant
+    /// Mean over the spatial dimensions of an NCHW image batch.
+    GlobalAvgPool2D,
+    GlobalAvgPool2DBackward,
+
+    /// Not ONNX-compliant
+    /// Gemm, bias and element-wise activation fused by the neural machine.
+    LinearActivation,
+
+    /// Not ONNX-compliant
+    /// Pad a tensor at the bottom and at the right with a value.
+    Pad,
+    PadBackward,
+
+    /// Not ONNX-compliant
+    /// Gather the relative position biases of the attention scores.
+    RelativePositionBias,
+    RelativePositionBiasBackward,
+
+    /// Not ONNX-compliant
+    /// Add every row of a tensor to every row of another tensor, see PairwiseAdd.
+    PairwiseAdd,
+    PairwiseAddBackward,
+
+    /// Not ONNX-compliant
+    /// Like BatchNormalization without scale and bias, with the training mode in an input.
+    /// https://onnx.ai/onnx/operators/onnx__BatchNormalization.html
+    BatchNorm,
+    BatchNormBackward,
+
+    /// Not ONNX-compliant
+    /// Allocate and free the buffers of the activations, see Checkpoint.
+    Allocate,
+    Deallocate,
 }
 
 impl From<&OpCode> for String {
@@ -123,6 +223,7 @@ impl From<&OpCode> for String {
             OpCode::Identity => "Identity".into(),
             OpCode::ReduceSum => "ReduceSum".into(),
             OpCode::Add => "Add".into(),
+            OpCode::BiasAdd => "BiasAdd".into(),
             OpCode::Sub => "Sub".into(),
             OpCode::Mul => "Mul".into(),
             OpCode::Div => "Div".into(),
@@ -137,6 +238,12 @@ impl From<&OpCode> for String {
             OpCode::Sigmoid => "Sigmoid".into(),
             OpCode::Gelu => "Gelu".into(),
             OpCode::GeluDerivative => "GeluDerivative".into(),
+            OpCode::Silu => "Silu".into(),
+            OpCode::SiluBackward => "SiluBackward".into(),
+            OpCode::LeakyRelu => "LeakyRelu".into(),
+            OpCode::LeakyReluBackward => "LeakyReluBackward".into(),
+            OpCode::LogSoftmax => "LogSoftmax".into(),
+            OpCode::LogSoftmaxBackward => "LogSoftmaxBackward".into(),
             OpCode::Reshape => "Reshape".into(),
             OpCode::Concat => "Concat".into(),
             OpCode::Unconcat => "Unconcat".into(),
@@ -147,11 +254,67 @@ impl From<&OpCode> for String {
             OpCode::Transpose => "Transpose".into(),
             OpCode::Pow => "Pow".into(),
             OpCode::Dot => "Dot".into(),
+            OpCode::CosineSimilarity => "CosineSimilarity".into(),
+            OpCode::EmbeddingGather => "EmbeddingGather".into(),
+            OpCode::EmbeddingGatherBackward => "EmbeddingGatherBackward".into(),
+            OpCode::ToDevice => "ToDevice".into(),
+            OpCode::RowNorm => "RowNorm".into(),
+            OpCode::RowNormBackward => "RowNormBackward".into(),
+            OpCode::CustomUnary => "CustomUnary".into(),
+            OpCode::CustomUnaryBackward => "CustomUnaryBackward".into(),
+            OpCode::Im2Col => "Im2Col".into(),
+            OpCode::Col2Im => "Col2Im".into(),
+            OpCode::NhwcToNchw => "NhwcToNchw".into(),
+            OpCode::NchwToNhwc => "NchwToNhwc".into(),
+            OpCode::MaxPool2D => "MaxPool2D".into(),
+            OpCode::MaxPool2DBackward => "MaxPool2DBackward".into(),
+            OpCode::GlobalAvgPool2D => "GlobalAvgPool2D".into(),
+            OpCode::GlobalAvgPool2DBackward => "GlobalAvgPool2DBackward".into(),
+            OpCode::LinearActivation => "LinearActivation".into(),
+            OpCode::Pad => "Pad".into(),
+            OpCode::PadBackward => "PadBackward".into(),
+            OpCode::RelativePositionBias => "RelativePositionBias".into(),
+            OpCode::RelativePositionBiasBackward => "RelativePositionBiasBackward".into(),
+            OpCode::PairwiseAdd => "PairwiseAdd".into(),
+            OpCode::PairwiseAddBackward => "PairwiseAddBackward".into(),
+            OpCode::BatchNorm => "BatchNorm".into(),
+            OpCode::BatchNormBackward => "BatchNormBackward".into(),
+            OpCode::Allocate => "Allocate".into(),
+            OpCode::Deallocate => "Deallocate".into(),
         }
     }
 }
 
 impl OpCode {
+    /// Whether the operator is computed on the host with a copy of its values,
+    /// whatever the device of its tensors.
+    pub fn is_computed_on_host(&self) -> bool {
+        matches!(
+            self,
+            OpCode::CustomUnary
+                | OpCode::CustomUnaryBackward
+                | OpCode::RelativePositionBias
+                | OpCode::RelativePositionBiasBackward
+                | OpCode::PairwiseAdd
+                | OpCode::PairwiseAddBackward
+                | OpCode::Im2Col
+                | OpCode::Col2Im
+                | OpCode::NchwToNhwc
+                | OpCode::NhwcToNchw
+                | OpCode::GlobalAvgPool2D
+                | OpCode::GlobalAvgPool2DBackward
+                | OpCode::MaxPool2D
+                | OpCode::MaxPool2DBackward
+                | OpCode::RowNormBackward
+                | OpCode::SumOfSquaredErrors
+                | OpCode::BatchNorm
+                | OpCode::BatchNormBackward
+                | OpCode::Bernoulli
+                | OpCode::Pad
+                | OpCode::PadBackward
+        )
+    }
+
     pub fn execute(
         &self,
         attributes: &OperatorAttributes,
@@ -160,12 +323,14 @@ impl OpCode {
         device: &Device,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        Tensor::check_dtypes(inputs.iter().chain(outputs.iter()).copied())?;
         match self {
             OpCode::Gemm => Gemm::execute(attributes, inputs, outputs, device, device_stream),
             OpCode::Identity => {
                 Identity::execute(attributes, inputs, outputs, device, device_stream)
             }
             OpCode::Add => Add::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::BiasAdd => BiasAdd::execute(attributes, inputs, outputs, device, device_stream),
             OpCode::Mul => Mul::execute(attributes, inputs, outputs, device, device_stream),
             OpCode::Sub => Sub::execute(attributes, inputs, outputs, device, device_stream),
             OpCode::Div => Div::execute(attributes, inputs, outputs, device, device_stream),
@@ -180,6 +345,22 @@ impl OpCode {
             OpCode::GeluDerivative => {
                 GeluDerivative::execute(attributes, inputs, outputs, device, device_stream)
             }
+            OpCode::Silu => Silu::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::SiluBackward => {
+                SiluBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::LeakyRelu => {
+                LeakyRelu::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::LeakyReluBackward => {
+                LeakyReluBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::LogSoftmax => {
+                LogSoftmax::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::LogSoftmaxBackward => {
+                LogSoftmaxBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
             OpCode::Standardization => {
                 Standardization::execute(attributes, inputs, outputs, device, device_stream)
             }
@@ -215,6 +396,83 @@ impl OpCode {
             }
             OpCode::Pow => Pow::execute(attributes, inputs, outputs, device, device_stream),
             OpCode::Dot => Dot::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::CosineSimilarity => {
+                CosineSimilarity::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::EmbeddingGather => {
+                EmbeddingGather::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::EmbeddingGatherBackward => {
+                EmbeddingGatherBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::ToDevice => {
+                ToDevice::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::RowNorm => RowNorm::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::RowNormBackward => {
+                RowNormBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::CustomUnary => {
+                CustomUnary::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::CustomUnaryBackward => {
+                CustomUnaryBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::Im2Col => Im2Col::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::Col2Im => Col2Im::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::NhwcToNchw => {
+                NhwcToNchw::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::NchwToNhwc => {
+                NchwToNhwc::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::MaxPool2D => {
+                MaxPool2D::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::MaxPool2DBackward => {
+                MaxPool2DBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::GlobalAvgPool2D => {
+                GlobalAvgPool2D::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::GlobalAvgPool2DBackward => {
+                GlobalAvgPool2DBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::LinearActivation => {
+                LinearActivation::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::Pad => Pad::execute(attributes, inputs, outputs, device, device_stream),
+            OpCode::PadBackward => {
+                PadBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::RelativePositionBias => {
+                RelativePositionBias::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::RelativePositionBiasBackward => RelativePositionBiasBackward::execute(
+                attributes,
+                inputs,
+                outputs,
+                device,
+                device_stream,
+            ),
+            OpCode::PairwiseAdd => {
+                PairwiseAdd::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::PairwiseAddBackward => {
+                PairwiseAddBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::BatchNorm => {
+                BatchNorm::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::BatchNormBackward => {
+                BatchNormBackward::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::Allocate => {
+                Allocate::execute(attributes, inputs, outputs, device, device_stream)
+            }
+            OpCode::Deallocate => {
+                Deallocate::execute(attributes, inputs, outputs, device, device_stream)
+            }
         }
     }
 }
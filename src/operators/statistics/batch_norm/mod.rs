@@ -0,0 +1,277 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+const EPSILON: f32 = 1e-5;
+
+/// Normalizes each column (feature) across the rows (batch).
+/// In training, the batch statistics are used and the running statistics are updated:
+///   running = (1 - momentum) * running + momentum * batch
+/// The running variance uses the unbiased batch variance.
+/// In eval, the running statistics are used.
+/// The training mode follows the dropout mode of the machine.
+///
+/// Like Standardization, there is no gain nor bias.
+///
+/// See:
+/// Batch Normalization: Accelerating Deep Network Training by Reducing Internal Covariate Shift
+/// https://arxiv.org/abs/1502.03167
+/// https://onnx.ai/onnx/operators/onnx__BatchNormalization.html
+pub struct BatchNorm {
+    device: Device,
+    momentum: f32,
+    running_mean: Tensor,
+    running_variance: Tensor,
+    mean: Tensor,
+    inverse_std: Tensor,
+    training: Tensor,
+    training_flag: Tensor,
+    eval_flag: Tensor,
+}
+
+impl BatchNorm {
+    pub fn try_new(device: &Device, features: usize, momentum: f32) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&momentum) {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let running_mean = new_tensor!(device, 1, features, vec![0.0; features])?;
+        let running_variance = new_tensor!(device, 1, features, vec![1.0; features])?;
+        let mean = new_tensor!(device, 1, features, vec![0.0; features])?;
+        let inverse_std = new_tensor!(device, 1, features, vec![1.0; features])?;
+        let training = new_tensor!(device, 1, 1, vec![0.0])?;
+        let training_flag = new_tensor!(device, 1, 1, vec![1.0])?;
+        let eval_flag = new_tensor!(device, 1, 1, vec![0.0])?;
+        let op = Self {
+            device: device.clone(),
+            momentum,
+            running_mean,
+            running_variance,
+            mean,
+            inverse_std,
+            training,
+            training_flag,
+            eval_flag,
+        };
+        Ok(op)
+    }
+
+    pub fn running_mean(&self) -> &Tensor {
+        &self.running_mean
+    }
+
+    pub fn running_variance(&self) -> &Tensor {
+        &self.running_variance
+    }
+}
+
+impl UnaryOperator for BatchNorm {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        if cols != self.running_mean.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; rows * cols],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::Identity,
+            OperatorAttributes::None,
+            &[&self.training_flag],
+            &[&self.training],
+            Category::EnableDropout,
+        ));
+        output.push_instruction(instruction!(
+            OpCode::Identity,
+            OperatorAttributes::None,
+            &[&self.eval_flag],
+            &[&self.training],
+            Category::DisableDropout,
+        ));
+
+        output.push_instruction(instruction!(
+            OpCode::BatchNorm,
+            OperatorAttributes::F32(self.momentum),
+            &[
+                input_t,
+                &self.training,
+                &self.running_mean,
+                &self.running_variance,
+            ],
+            &[
+                &output.tensor(),
+                &self.running_mean,
+                &self.running_variance,
+                &self.mean,
+                &self.inverse_std,
+            ],
+            Category::Inference,
+        ));
+
+        let input_gradient: &Tensor = &input.gradient();
+        if input_gradient.requires_grad() {
+            let tmp = new_tensor!(self.device, rows, cols, vec![0.0; rows * cols])?;
+            output.push_instruction(instruction!(
+                OpCode::BatchNormBackward,
+                OperatorAttributes::None,
+                &[
+                    &output.gradient(),
+                    &output.tensor(),
+                    &self.training,
+                    &self.inverse_std,
+                ],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[input_gradient, &tmp],
+                &[input_gradient],
+                Category::Gradient,
+            ));
+        }
+        Ok(output)
+    }
+}
+
+impl ExecutableOperator for BatchNorm {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let momentum = match attributes {
+            OperatorAttributes::F32(momentum) => *momentum,
+            _ => return Err(error!(ErrorEnum::IncorrectOperatorConfiguration)),
+        };
+        let input = inputs[0];
+        let output = outputs[0];
+        let rows = input.rows();
+        let cols = input.cols();
+        device_stream.wait_for()?;
+        let training = inputs[1].get_values()?[0] != 0.0;
+        let values = input.get_values()?;
+        let mut running_mean = inputs[2].get_values()?;
+        let mut running_variance = inputs[3].get_values()?;
+
+        let (mean, variance) = if training {
+            let mut mean = vec![0.0; cols];
+            let mut variance = vec![0.0; cols];
+            for row in values.chunks(cols) {
+                for (col, value) in row.iter().enumerate() {
+                    mean[col] += value / rows as f32;
+                }
+            }
+            for row in values.chunks(cols) {
+                for (col, value) in row.iter().enumerate() {
+                    variance[col] += (value - mean[col]).powi(2) / rows as f32;
+                }
+            }
+            let bessel_correction = if rows > 1 {
+                rows as f32 / (rows - 1) as f32
+            } else {
+                1.0
+            };
+            for col in 0..cols {
+                running_mean[col] = (1.0 - momentum) * running_mean[col] + momentum * mean[col];
+                running_variance[col] = (1.0 - momentum) * running_variance[col]
+                    + momentum * variance[col] * bessel_correction;
+            }
+            outputs[1].set_values(running_mean)?;
+            outputs[2].set_values(running_variance)?;
+            (mean, variance)
+        } else {
+            (running_mean, running_variance)
+        };
+
+        let inverse_std = variance
+            .iter()
+            .map(|variance| 1.0 / (variance + EPSILON).sqrt())
+            .collect::<Vec<_>>();
+        let normalized = values
+            .chunks(cols)
+            .flat_map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(col, value)| (value - mean[col]) * inverse_std[col])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        output.set_values(normalized)?;
+        outputs[3].set_values(mean)?;
+        outputs[4].set_values(inverse_std)
+    }
+}
+
+/// In training, the batch statistics depend on the input:
+///   dx = inverse_std / n * (n * dy - sum(dy) - y * sum(dy * y))
+/// In eval, the running statistics are constants:
+///   dx = inverse_std * dy
+pub struct BatchNormBackward {}
+
+impl ExecutableOperator for BatchNormBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let output_gradient = inputs[0];
+        let output = inputs[1];
+        let input_gradient = outputs[0];
+        let rows = output.rows();
+        let cols = output.cols();
+        device_stream.wait_for()?;
+        let training = inputs[2].get_values()?[0] != 0.0;
+        let inverse_std = inputs[3].get_values()?;
+        let output_gradient = output_gradient.get_values()?;
+        let output = output.get_values()?;
+
+        let mut sum = vec![0.0; cols];
+        let mut dot = vec![0.0; cols];
+        if training {
+            for (gradients, outputs) in output_gradient.chunks(cols).zip(output.chunks(cols)) {
+                for col in 0..cols {
+                    sum[col] += gradients[col];
+                    dot[col] += gradients[col] * outputs[col];
+                }
+            }
+        }
+        let n = rows as f32;
+        let values = output_gradient
+            .chunks(cols)
+            .zip(output.chunks(cols))
+            .flat_map(|(gradients, outputs)| {
+                (0..cols)
+                    .map(|col| match training {
+                        true => {
+                            inverse_std[col] / n
+                                * (n * gradients[col] - sum[col] - outputs[col] * dot[col])
+                        }
+                        false => inverse_std[col] * gradients[col],
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        input_gradient.set_values(values)
+    }
+}
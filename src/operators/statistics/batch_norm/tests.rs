@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor_with_grad, statistics::batch_norm::BatchNorm, stream::StreamTrait, Category, Device,
+    TensorWithGrad, UnaryOperator,
+};
+
+/// Run the forward instructions of the tape in training (EnableDropout) or in eval
+/// (DisableDropout).
+fn forward_with_mode(device: &Device, output: &TensorWithGrad, mode: Category) {
+    let device_stream = device.new_stream().unwrap();
+    let mut processed = HashSet::new();
+    for tensor in output.get_tape().iter() {
+        if !processed.insert(tensor.tensor().name()) {
+            continue;
+        }
+        for instruction in tensor.forward_instructions().iter() {
+            let category = instruction.category();
+            if category == mode || category == Category::Inference {
+                instruction.execute(device, &device_stream).unwrap();
+            }
+        }
+    }
+    device_stream.wait_for().unwrap();
+}
+
+fn column_statistics(values: &[f32], cols: usize) -> Vec<(f32, f32)> {
+    let rows = values.len() / cols;
+    (0..cols)
+        .map(|col| {
+            let column = values.iter().skip(col).step_by(cols).collect::<Vec<_>>();
+            let mean = column.iter().copied().sum::<f32>() / rows as f32;
+            let variance = column.iter().map(|x| (*x - mean).powi(2)).sum::<f32>() / rows as f32;
+            (mean, variance)
+        })
+        .collect()
+}
+
+const VALUES: [f32; 12] = [
+    1.0, 10.0, -3.0, //
+    2.0, 14.0, -1.0, //
+    4.0, 12.0, -2.0, //
+    5.0, 8.0, 2.0, //
+];
+
+#[test]
+fn batch_norm_normalizes_the_batch_in_training() {
+    let device = Device::default();
+    let batch_norm = BatchNorm::try_new(&device, 3, 0.1).unwrap();
+    let input = new_tensor_with_grad!(device, 4, 3, VALUES.to_vec(), &[], true, false).unwrap();
+    let output = batch_norm.forward(&input).unwrap();
+    forward_with_mode(&device, &output, Category::EnableDropout);
+
+    let output_values = output.tensor().get_values().unwrap();
+    for (mean, variance) in column_statistics(&output_values, 3) {
+        assert_lt!(mean.abs(), 1e-5);
+        assert_lt!((variance - 1.0).abs(), 1e-3);
+    }
+}
+
+#[test]
+fn batch_norm_uses_the_running_statistics_in_eval() {
+    let device = Device::default();
+    let batch_norm = BatchNorm::try_new(&device, 3, 0.1).unwrap();
+    let input = new_tensor_with_grad!(device, 4, 3, VALUES.to_vec(), &[], true, false).unwrap();
+    let output = batch_norm.forward(&input).unwrap();
+    for _ in 0..100 {
+        forward_with_mode(&device, &output, Category::EnableDropout);
+    }
+
+    let running_mean = batch_norm.running_mean().get_values().unwrap();
+    let running_variance = batch_norm.running_variance().get_values().unwrap();
+    for (col, (mean, variance)) in column_statistics(&VALUES, 3).into_iter().enumerate() {
+        // The running variance is unbiased.
+        let variance = variance * 4.0 / 3.0;
+        assert_lt!((running_mean[col] - mean).abs(), 1e-3);
+        assert_lt!((running_variance[col] - variance).abs(), 1e-3);
+    }
+
+    // A shifted batch in eval is normalized with the running statistics,
+    // which do not change.
+    let shifted = VALUES.iter().map(|x| x + 1.0).collect::<Vec<_>>();
+    input.tensor().set_values(shifted.clone()).unwrap();
+    forward_with_mode(&device, &output, Category::DisableDropout);
+    let output_values = output.tensor().get_values().unwrap();
+    for (i, value) in shifted.iter().enumerate() {
+        let col = i % 3;
+        let expected = (value - running_mean[col]) / (running_variance[col] + 1e-5).sqrt();
+        assert_lt!((output_values[i] - expected).abs(), 1e-5);
+    }
+    assert_eq!(
+        running_mean,
+        batch_norm.running_mean().get_values().unwrap()
+    );
+    assert_eq!(
+        running_variance,
+        batch_norm.running_variance().get_values().unwrap()
+    );
+}
+
+#[test]
+fn batch_norm_gradient_matches_finite_differences() {
+    let device = Device::default();
+    let batch_norm = BatchNorm::try_new(&device, 3, 0.0).unwrap();
+    let input = new_tensor_with_grad!(device, 4, 3, VALUES.to_vec(), &[], true, false).unwrap();
+    let output = batch_norm.forward(&input).unwrap();
+    let weights = (0..12).map(|x| (x as f32 * 0.7).sin()).collect::<Vec<_>>();
+    // loss = sum(weights * output)
+    let loss = |values: &[f32]| {
+        input.tensor().set_values(values.to_vec()).unwrap();
+        forward_with_mode(&device, &output, Category::EnableDropout);
+        let output_values = output.tensor().get_values().unwrap();
+        output_values
+            .iter()
+            .zip(weights.iter())
+            .map(|(x, w)| x * w)
+            .sum::<f32>()
+    };
+
+    loss(&VALUES);
+    output.gradient().set_values(weights.clone()).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let gradient = input.gradient().get_values().unwrap();
+
+    let h = 1e-2;
+    for i in 0..VALUES.len() {
+        let mut plus = VALUES.to_vec();
+        plus[i] += h;
+        let mut minus = VALUES.to_vec();
+        minus[i] -= h;
+        let expected = (loss(&plus) - loss(&minus)) / (2.0 * h);
+        assert_lt!((gradient[i] - expected).abs(), 1e-2);
+    }
+}
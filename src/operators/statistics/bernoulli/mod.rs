@@ -0,0 +1,67 @@
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use rand_distr::Uniform;
+use std::sync::atomic::Ordering;
+
+use crate::{
+    error,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Device, ExecutableOperator, OperatorAttributes,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// With OperatorAttributes::F32(probability), the trials come from the thread RNG.
+/// With OperatorAttributes::SeededBernoulli(probability, seed, step), step is a host counter
+/// of the draws.
+/// The trials only depend on the seed and on the step, so they are reproducible.
+pub struct Bernoulli {}
+
+impl ExecutableOperator for Bernoulli {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let input = inputs[0];
+        let output = outputs[0];
+        let n = input.len();
+        let trials = match attributes {
+            OperatorAttributes::F32(probability) => bernoulli(&mut thread_rng(), n, *probability),
+            OperatorAttributes::SeededBernoulli(probability, seed, step) => {
+                let step = step.fetch_add(1, Ordering::Relaxed);
+                let mut rng = step_rng(*seed, step);
+                bernoulli(&mut rng, n, *probability)
+            }
+            // No probability was provided.
+            _ => return Err(error!(ErrorEnum::IncorrectOperatorConfiguration)),
+        };
+        output.set_values(trials)?;
+        device_stream.wait_for_default()?;
+        Ok(())
+    }
+}
+
+/// The RNG of a step, derived from the seed with the multiplier of Fibonacci hashing
+/// so that consecutive steps have unrelated streams.
+fn step_rng(seed: u64, step: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ step.wrapping_mul(0x9E3779B97F4A7C15))
+}
+
+fn bernoulli(rng: &mut impl Rng, n: usize, probability: f32) -> Vec<f32> {
+    let uniform = Uniform::new(0.0, 1.0);
+
+    (0..n)
+        .map(|_| {
+            let random_number = if rng.sample(uniform) <= probability {
+                1.0
+            } else {
+                0.0
+            };
+            random_number
+        })
+        .collect::<Vec<_>>()
+}
@@ -0,0 +1,63 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::{new_tensor, opcode::OpCode, tensor::ErrorEnum, Device, OperatorAttributes};
+
+#[test]
+fn bernoulli_without_probability_returns_an_error() {
+    let device = Device::default();
+    let input = new_tensor!(device, 1, 4, vec![0.0; 4]).unwrap();
+    let output = new_tensor!(device, 1, 4, vec![0.0; 4]).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    let result = OpCode::Bernoulli.execute(
+        &OperatorAttributes::None,
+        &[&input],
+        &[&output],
+        &device,
+        &device_stream,
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        result.map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn bernoulli_with_probability_one_samples_ones() {
+    let device = Device::default();
+    let input = new_tensor!(device, 1, 4, vec![0.0; 4]).unwrap();
+    let output = new_tensor!(device, 1, 4, vec![0.0; 4]).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    OpCode::Bernoulli
+        .execute(
+            &OperatorAttributes::F32(1.0),
+            &[&input],
+            &[&output],
+            &device,
+            &device_stream,
+        )
+        .unwrap();
+    assert_eq!(vec![1.0; 4], output.get_values().unwrap());
+}
+
+#[test]
+fn seeded_bernoulli_draws_new_trials_beyond_2_pow_24_steps() {
+    let device = Device::default();
+    let input = new_tensor!(device, 1, 64, vec![0.0; 64]).unwrap();
+    let output = new_tensor!(device, 1, 64, vec![0.0; 64]).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    // 2^24 + 1 is not exact in f32, so a f32 counter would stall here.
+    let step = Arc::new(AtomicU64::new(1 << 24));
+    let attributes = OperatorAttributes::SeededBernoulli(0.5, 42, step.clone());
+    let mut trials = vec![];
+    for _ in 0..2 {
+        OpCode::Bernoulli
+            .execute(&attributes, &[&input], &[&output], &device, &device_stream)
+            .unwrap();
+        trials.push(output.get_values().unwrap());
+    }
+    assert_eq!((1 << 24) + 2, step.load(Ordering::Relaxed));
+    assert_ne!(trials[0], trials[1]);
+}
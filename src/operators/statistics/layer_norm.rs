@@ -1,6 +1,6 @@
 use crate::{
-    new_tensor_with_grad, tensor::Error, Add, BinaryOperator, Device, Mul, TensorWithGrad,
-    UnaryOperator,
+    new_tensor_with_grad, tensor::Error, Add, BiasAdd, BinaryOperator, Device, MatMul, Mul,
+    TensorWithGrad, UnaryOperator,
 };
 
 use super::standardization::Standardization;
@@ -15,6 +15,8 @@ pub struct LayerNormalization {
     add: Add,
     gain: TensorWithGrad,
     bias: TensorWithGrad,
+    /// With one row of parameters, the column of ones that broadcasts the gain to every row.
+    broadcast: Option<(TensorWithGrad, MatMul, BiasAdd)>,
 }
 
 impl LayerNormalization {
@@ -23,6 +25,8 @@ impl LayerNormalization {
             new_tensor_with_grad!(device, rows, cols, vec![1.0; rows * cols], &[], true, true)?;
         let bias =
             new_tensor_with_grad!(device, rows, cols, vec![0.0; rows * cols], &[], true, true)?;
+        gain.set_no_decay(true);
+        bias.set_no_decay(true);
         let standardization = Standardization::new(device);
         let mul = Mul::new(device);
         let add = Add::new(device);
@@ -32,6 +36,30 @@ impl LayerNormalization {
             add,
             gain,
             bias,
+            broadcast: None,
+        };
+        Ok(op)
+    }
+
+    /// The gain and the bias have one row, which is shared by every position,
+    /// so that the parameters do not depend on the number of rows.
+    pub fn try_new_position_independent(
+        device: &Device,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, Error> {
+        let gain = new_tensor_with_grad!(device, 1, cols, vec![1.0; cols], &[], true, true)?;
+        let bias = new_tensor_with_grad!(device, 1, cols, vec![0.0; cols], &[], true, true)?;
+        gain.set_no_decay(true);
+        bias.set_no_decay(true);
+        let ones = new_tensor_with_grad!(device, rows, 1, vec![1.0; rows], &[], false, false)?;
+        let op = Self {
+            standardization: Standardization::new(device),
+            mul: Mul::new(device),
+            add: Add::new(device),
+            gain,
+            bias,
+            broadcast: Some((ones, MatMul::new(device, false), BiasAdd::new(device))),
         };
         Ok(op)
     }
@@ -40,8 +68,17 @@ impl LayerNormalization {
 impl UnaryOperator for LayerNormalization {
     fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, crate::tensor::Error> {
         let standardized = self.standardization.forward(input)?;
-        let with_gain = self.mul.forward(&self.gain, &standardized)?;
-        let with_bias = self.add.forward(&with_gain, &self.bias)?;
-        Ok(with_bias)
+        match &self.broadcast {
+            Some((ones, matmul, bias_add)) => {
+                let gain = matmul.forward(ones, &self.gain)?;
+                let with_gain = self.mul.forward(&gain, &standardized)?;
+                bias_add.forward(&with_gain, &self.bias)
+            }
+            None => {
+                let with_gain = self.mul.forward(&self.gain, &standardized)?;
+                let with_bias = self.add.forward(&with_gain, &self.bias)?;
+                Ok(with_bias)
+            }
+        }
     }
 }
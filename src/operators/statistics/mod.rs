@@ -1,3 +1,4 @@
+pub mod batch_norm;
 pub mod bernoulli;
 pub mod layer_norm;
 pub mod standardization;
@@ -103,27 +103,14 @@ impl ExecutableOperator for Unconcat {
         device: &Device,
         device_stream: &DeviceStream,
     ) -> Result<(), Error> {
+        // Each output is a column block of the input, for example the gradient of an attention head.
         let src = inputs[0];
         for output_index in 0..outputs.len() {
             let dst = outputs[output_index];
-            let dst_col = 0;
-            let input_rows = dst.rows();
-            let input_cols = dst.cols();
-            for dst_row in 0..input_rows {
-                let src_row = dst_row;
-                let src_col = output_index * input_cols;
-                copy_slice(
-                    dst.cols(),
-                    src,
-                    src_row,
-                    src_col,
-                    dst,
-                    dst_row,
-                    dst_col,
-                    device,
-                    device_stream,
-                )?;
-            }
+            let start = output_index * dst.cols();
+            let end = start + dst.cols();
+            src.slice_cols(start, end)?
+                .copy_to(device, dst, device_stream)?;
         }
         Ok(())
     }
@@ -4,6 +4,7 @@ use crate::{
     tensor::{Error, Tensor},
     Category, Device, OperatorAttributes, TensorWithGrad, UnaryOperator,
 };
+use std::sync::{atomic::AtomicU64, Arc};
 
 #[cfg(test)]
 mod tests;
@@ -14,8 +15,13 @@ mod tests;
 pub struct Dropout {
     device: Device,
     probability: f32,
+    seed: Option<u64>,
+    /// The number of masks drawn with the seed.
+    step: Arc<AtomicU64>,
     mask: Tensor,
     alpha: Tensor,
+    /// The values of alpha in training and in evaluation, when evaluation is the identity.
+    mode_alphas: Option<(Tensor, Tensor)>,
 }
 
 impl Dropout {
@@ -24,18 +30,84 @@ impl Dropout {
         mask_rows: usize,
         mask_cols: usize,
         dropout_probability: f32,
+    ) -> Result<Self, Error> {
+        Self::try_new_with_options(
+            device,
+            mask_rows,
+            mask_cols,
+            dropout_probability,
+            None,
+            false,
+        )
+    }
+
+    /// Inverted dropout, as used on the attention weights:
+    /// the kept activations are only scaled during training so that evaluation is the identity.
+    pub fn try_new_with_identity_eval(
+        device: &Device,
+        mask_rows: usize,
+        mask_cols: usize,
+        dropout_probability: f32,
+    ) -> Result<Self, Error> {
+        Self::try_new_with_options(
+            device,
+            mask_rows,
+            mask_cols,
+            dropout_probability,
+            None,
+            true,
+        )
+    }
+
+    /// With a seed, the masks only depend on the seed and on the number of masks
+    /// drawn so far, so two runs draw the same masks.
+    pub fn try_new_with_seed(
+        device: &Device,
+        mask_rows: usize,
+        mask_cols: usize,
+        dropout_probability: f32,
+        seed: Option<u64>,
+    ) -> Result<Self, Error> {
+        Self::try_new_with_options(
+            device,
+            mask_rows,
+            mask_cols,
+            dropout_probability,
+            seed,
+            false,
+        )
+    }
+
+    fn try_new_with_options(
+        device: &Device,
+        mask_rows: usize,
+        mask_cols: usize,
+        dropout_probability: f32,
+        seed: Option<u64>,
+        identity_eval: bool,
     ) -> Result<Self, Error> {
         let len = mask_rows * mask_cols;
         let mask = vec![1.0; len];
         let mask = new_tensor!(device, mask_rows, mask_cols, mask)?;
         let probability = 1.0 - dropout_probability;
-        let alpha = 1.0 / (1.0 - dropout_probability);
-        let alpha = new_tensor!(device, mask_rows, mask_cols, vec![alpha])?;
+        let training_alpha = 1.0 / (1.0 - dropout_probability);
+        let alpha = new_tensor!(device, 1, 1, vec![training_alpha])?;
+        let mode_alphas = match identity_eval {
+            true => Some((
+                new_tensor!(device, 1, 1, vec![training_alpha])?,
+                new_tensor!(device, 1, 1, vec![1.0])?,
+            )),
+            false => None,
+        };
+        let step = Arc::new(AtomicU64::new(0));
         let mask = Self {
             device: device.clone(),
             probability,
+            seed,
+            step,
             mask,
             alpha,
+            mode_alphas,
         };
         Ok(mask)
     }
@@ -56,13 +128,23 @@ impl UnaryOperator for Dropout {
             false,
         )?;
 
-        output.push_instruction(instruction!(
-            OpCode::Bernoulli,
-            OperatorAttributes::F32(self.probability),
-            &[&self.mask],
-            &[&self.mask],
-            Category::EnableDropout,
-        ));
+        let training_mask = match self.seed {
+            Some(seed) => instruction!(
+                OpCode::Bernoulli,
+                OperatorAttributes::SeededBernoulli(self.probability, seed, self.step.clone()),
+                &[&self.mask],
+                &[&self.mask],
+                Category::EnableDropout,
+            ),
+            None => instruction!(
+                OpCode::Bernoulli,
+                OperatorAttributes::F32(self.probability),
+                &[&self.mask],
+                &[&self.mask],
+                Category::EnableDropout,
+            ),
+        };
+        output.push_instruction(training_mask);
 
         output.push_instruction(instruction!(
             OpCode::Bernoulli,
@@ -72,6 +154,23 @@ impl UnaryOperator for Dropout {
             Category::DisableDropout,
         ));
 
+        if let Some((training_alpha, eval_alpha)) = &self.mode_alphas {
+            output.push_instruction(instruction!(
+                OpCode::Identity,
+                OperatorAttributes::None,
+                &[training_alpha],
+                &[&self.alpha],
+                Category::EnableDropout,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Identity,
+                OperatorAttributes::None,
+                &[eval_alpha],
+                &[&self.alpha],
+                Category::DisableDropout,
+            ));
+        }
+
         output.push_instruction(instruction!(
             OpCode::Mul,
             OperatorAttributes::None,
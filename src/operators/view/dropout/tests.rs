@@ -1 +1,82 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, Category, Device, Dropout, TensorWithGrad,
+    UnaryOperator,
+};
 
+/// Draw a training mask and apply it to ones.
+fn training_forward(device: &Device, output: &TensorWithGrad) -> Vec<f32> {
+    let device_stream = device.new_stream().unwrap();
+    for instruction in output.forward_instructions().iter() {
+        let category = instruction.category();
+        if category == Category::EnableDropout || category == Category::Inference {
+            instruction.execute(device, &device_stream).unwrap();
+        }
+    }
+    device_stream.wait_for().unwrap();
+    output.tensor().get_values().unwrap()
+}
+
+/// The outputs of a dropout with the seed for two steps.
+fn seeded_masks(seed: u64) -> Vec<Vec<f32>> {
+    let device = Device::default();
+    let (rows, cols) = (16, 16);
+    let dropout = Dropout::try_new_with_seed(&device, rows, cols, 0.5, Some(seed)).unwrap();
+    let input = new_tensor_with_grad!(
+        device,
+        rows,
+        cols,
+        vec![1.0; rows * cols],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let output = dropout.forward(&input).unwrap();
+    (0..2).map(|_| training_forward(&device, &output)).collect()
+}
+
+#[test]
+fn dropout_masks_are_reproducible_with_a_seed() {
+    let masks = seeded_masks(42);
+    assert_eq!(masks, seeded_masks(42));
+    // Each step draws a new mask.
+    assert_ne!(masks[0], masks[1]);
+    assert_ne!(masks, seeded_masks(43));
+    // The kept activations are scaled by 1 / (1 - p).
+    assert!(masks[0].iter().all(|x| *x == 0.0 || *x == 2.0));
+}
+
+/// Apply the evaluation mask to ones.
+fn eval_forward(device: &Device, dropout: &Dropout, (rows, cols): (usize, usize)) -> Vec<f32> {
+    let input = new_tensor_with_grad!(
+        device,
+        rows,
+        cols,
+        vec![1.0; rows * cols],
+        &[],
+        false,
+        false
+    )
+    .unwrap();
+    let output = dropout.forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    for instruction in output.forward_instructions().iter() {
+        let category = instruction.category();
+        if category == Category::DisableDropout || category == Category::Inference {
+            instruction.execute(device, &device_stream).unwrap();
+        }
+    }
+    device_stream.wait_for().unwrap();
+    let values = output.tensor().get_values().unwrap();
+    values
+}
+
+#[test]
+fn only_the_identity_eval_dropout_is_the_identity_in_evaluation() {
+    let device = Device::default();
+    let shape = (4, 4);
+    let dropout = Dropout::try_new(&device, shape.0, shape.1, 0.5).unwrap();
+    assert_eq!(vec![2.0; 16], eval_forward(&device, &dropout, shape));
+    let dropout = Dropout::try_new_with_identity_eval(&device, shape.0, shape.1, 0.5).unwrap();
+    assert_eq!(vec![1.0; 16], eval_forward(&device, &dropout, shape));
+}
@@ -34,6 +34,11 @@ impl Mask {
         let mask = Self { mask, mul };
         Ok(mask)
     }
+
+    /// 1 where a position can attend to another position and 0 for the future positions.
+    pub fn mask(&self) -> &TensorWithGrad {
+        &self.mask
+    }
 }
 
 impl UnaryOperator for Mask {
@@ -6,3 +6,7 @@ mod concat;
 pub use concat::*;
 mod dropout;
 pub use dropout::*;
+mod to_device;
+pub use to_device::*;
+mod pad;
+pub use pad::*;
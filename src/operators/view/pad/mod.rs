@@ -0,0 +1,127 @@
+use crate::{
+    error, instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Category, Device, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Pad a tensor to rows x cols, with the input in the top-left corner and value elsewhere,
+/// for example to batch sequences of different lengths.
+/// The backward keeps the gradient of the top-left corner.
+pub struct Pad {
+    device: Device,
+    rows: usize,
+    cols: usize,
+    value: f32,
+}
+
+impl Pad {
+    pub fn new(device: &Device, rows: usize, cols: usize, value: f32) -> Self {
+        Self {
+            device: device.clone(),
+            rows,
+            cols,
+            value,
+        }
+    }
+}
+
+impl ExecutableOperator for Pad {
+    fn execute(
+        attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let value = match attributes {
+            OperatorAttributes::F32(value) => *value,
+            _ => return Err(error!(ErrorEnum::UnsupportedOperation)),
+        };
+        let input = inputs[0];
+        let output = outputs[0];
+        device_stream.wait_for()?;
+        input.pad_into(output, value)
+    }
+}
+
+impl UnaryOperator for Pad {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_t: &Tensor = &input.tensor();
+        let rows = input_t.rows();
+        let cols = input_t.cols();
+        if rows > self.rows || cols > self.cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let output = new_tensor_with_grad!(
+            self.device,
+            self.rows,
+            self.cols,
+            vec![self.value; self.rows * self.cols],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::Pad,
+            OperatorAttributes::F32(self.value),
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        if input.gradient().requires_grad() {
+            let device = &self.device;
+            let tmp = new_tensor!(device, rows, cols, vec![0.0; rows * cols])?;
+            output.push_instruction(instruction!(
+                OpCode::PadBackward,
+                OperatorAttributes::None,
+                &[&output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &tmp],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Copy the top-left corner of the output gradient, which has the size of the input gradient.
+pub struct PadBackward {}
+
+impl ExecutableOperator for PadBackward {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        let output_gradient = inputs[0];
+        let input_gradient = outputs[0];
+        let cols = input_gradient.cols();
+        if input_gradient.rows() > output_gradient.rows() || cols > output_gradient.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device_stream.wait_for()?;
+        let values = output_gradient
+            .rows_iter()?
+            .iter()
+            .take(input_gradient.rows())
+            .flat_map(|row| row[..cols].to_vec())
+            .collect::<Vec<_>>();
+        input_gradient.set_values(values)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{new_tensor_with_grad, stream::StreamTrait, Device, Pad, UnaryOperator};
+
+#[test]
+fn pad_2x3_to_4x4_and_backward() {
+    let device = Device::default();
+    let input = new_tensor_with_grad!(
+        device,
+        2,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+        ],
+        &[],
+        true,
+        false
+    )
+    .unwrap();
+    let output = Pad::new(&device, 4, 4, 0.0).forward(&input).unwrap();
+    let device_stream = device.new_stream().unwrap();
+    output.forward(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            1.0, 2.0, 3.0, 0.0, //
+            4.0, 5.0, 6.0, 0.0, //
+            0.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 0.0, //
+        ],
+        output.tensor().get_values().unwrap()
+    );
+
+    let output_gradient = (0..16).map(|x| x as f32).collect::<Vec<_>>();
+    output.gradient().set_values(output_gradient).unwrap();
+    output.compute_gradient(&device, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![
+            0.0, 1.0, 2.0, //
+            4.0, 5.0, 6.0, //
+        ],
+        input.gradient().get_values().unwrap()
+    );
+}
@@ -0,0 +1,98 @@
+use crate::{
+    devices::Device,
+    instruction, new_tensor, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, Tensor},
+    Category, ExecutableOperator, OperatorAttributes, TensorWithGrad, UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Move a tensor to the target device.
+/// This is the boundary between operators placed on different devices,
+/// for example an embedding table kept on the host feeding layers on a GPU.
+/// The gradient goes back to the device of the input, where it is added to the input gradient.
+pub struct ToDevice {
+    source: Device,
+    device: Device,
+}
+
+impl ToDevice {
+    /// Move tensors from the host of device to device.
+    pub fn new(device: &Device) -> Self {
+        Self::new_from(&device.host(), device)
+    }
+
+    /// Move tensors from source to device.
+    pub fn new_from(source: &Device, device: &Device) -> Self {
+        Self {
+            source: source.clone(),
+            device: device.clone(),
+        }
+    }
+}
+
+impl ExecutableOperator for ToDevice {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        _device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        // Previous kernels on the stream may still be writing the input.
+        device_stream.wait_for()?;
+        let input = inputs[0];
+        let output = outputs[0];
+        output.copy_values_from(input)
+    }
+}
+
+impl UnaryOperator for ToDevice {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        let input_tensor: &Tensor = &input.tensor();
+        let rows = input_tensor.rows();
+        let cols = input_tensor.cols();
+        let len = rows * cols;
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; len],
+            &[input],
+            true,
+            false
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::ToDevice,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        let input_gradient: &Tensor = &input.gradient();
+        if input_gradient.requires_grad() {
+            let tmp = new_tensor!(self.source, rows, cols, vec![0.0; len])?;
+            output.push_instruction(instruction!(
+                OpCode::ToDevice,
+                OperatorAttributes::None,
+                &[&output.gradient()],
+                &[&tmp],
+                Category::Gradient,
+            ));
+            output.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[input_gradient, &tmp],
+                &[input_gradient],
+                Category::Gradient,
+            ));
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    devices::Device, neural_program::NeuralProgram, new_tensor_with_grad,
+    schedulers::DefaultStreamScheduler, stochastic_gradient_descent::StochasticGradientDescent,
+    stream::StreamTrait, sum_of_squared_errors::SumOfSquaredErrors, tensor::Error, Category,
+    Linear, Model, NeuralMachine, TensorWithGrad, ToDevice, UnaryModel, UnaryOperator,
+    WeightsInitialization,
+};
+
+fn execute(device: &Device, output: &TensorWithGrad, category: Category) -> Result<(), Error> {
+    let device_stream = device.new_stream()?;
+    for tensor in output.get_tape().iter() {
+        for instruction in tensor.forward_instructions().iter() {
+            if instruction.category() == category {
+                instruction.execute(device, &device_stream)?;
+            }
+        }
+    }
+    device_stream.wait_for()
+}
+
+#[test]
+fn to_device_copies_values_and_gradient() {
+    let device = Device::cpu();
+    let host = device.host();
+    let input = new_tensor_with_grad!(host, 1, 3, vec![1.0, 2.0, 3.0], &[], true, false).unwrap();
+    let output = ToDevice::new(&device).forward(&input).unwrap();
+    // The host shares tensor names with the device.
+    assert_ne!(input.tensor().name(), output.tensor().name());
+
+    execute(&device, &output, Category::Inference).unwrap();
+    assert_eq!(vec![1.0, 2.0, 3.0], output.tensor().get_values().unwrap());
+
+    output.gradient().set_values(vec![4.0, 5.0, 6.0]).unwrap();
+    for instruction in output.gradient_instructions().iter() {
+        instruction
+            .execute(&device, &device.new_stream().unwrap())
+            .unwrap();
+    }
+    assert_eq!(vec![4.0, 5.0, 6.0], input.gradient().get_values().unwrap());
+}
+
+#[test]
+fn to_device_adds_to_the_input_gradient() {
+    let device = Device::cpu();
+    let host = device.host();
+    let input = new_tensor_with_grad!(host, 1, 2, vec![1.0, 2.0], &[], true, false).unwrap();
+    let output = ToDevice::new(&device).forward(&input).unwrap();
+    // Another consumer of the input already wrote its gradient.
+    input.gradient().set_values(vec![10.0, 20.0]).unwrap();
+    output.gradient().set_values(vec![1.0, 2.0]).unwrap();
+    for instruction in output.gradient_instructions().iter() {
+        instruction
+            .execute(&device, &device.new_stream().unwrap())
+            .unwrap();
+    }
+    assert_eq!(vec![11.0, 22.0], input.gradient().get_values().unwrap());
+}
+
+struct HostLinearModel {
+    linear: Linear,
+    to_device: ToDevice,
+}
+
+impl UnaryModel for HostLinearModel {}
+
+impl UnaryOperator for HostLinearModel {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        self.to_device.forward(&self.linear.forward(input)?)
+    }
+}
+
+impl Model for HostLinearModel {
+    fn input_size(&self) -> Vec<usize> {
+        vec![1, 2]
+    }
+    fn output_size(&self) -> Vec<usize> {
+        vec![1, 1]
+    }
+}
+
+#[test]
+fn host_parameters_are_trained() {
+    let device = Device::cpu();
+    let host = device.host();
+    let model = HostLinearModel {
+        linear: Linear::new(&host, 1, 2, WeightsInitialization::Kaiming, 1).unwrap(),
+        to_device: ToDevice::new(&device),
+    };
+    assert_eq!(0, device.parameter_tensors().len());
+    assert_eq!(2, device.host_parameter_tensors().len());
+
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = StochasticGradientDescent::new(0.1);
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+    let parameters = || {
+        device
+            .host_parameter_tensors()
+            .iter()
+            .map(|x| x.tensor().get_values().unwrap())
+            .collect::<Vec<_>>()
+    };
+    let before = parameters();
+
+    let input = new_tensor_with_grad!(device, 1, 2, vec![0.5, -0.5], &[], false, false).unwrap();
+    let expected_output =
+        new_tensor_with_grad!(device, 1, 1, vec![3.0], &[], false, false).unwrap();
+    neural_machine.infer(&input).unwrap();
+    neural_machine.loss(&expected_output).unwrap();
+    neural_machine.compute_gradient().unwrap();
+    neural_machine.optimize().unwrap();
+
+    assert_ne!(before, parameters());
+    let gradients_are_reset = device
+        .host_parameter_tensors()
+        .iter()
+        .all(|x| x.gradient().get_values().unwrap().iter().all(|x| *x == 0.0));
+    assert!(gradients_are_reset);
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn embedding_on_host_feeds_attention_on_cuda() {
+    use crate::{
+        opcode::OpCode, Embedding, MultiHeadAttention, MultiHeadAttentionConfig, TernaryOperator,
+    };
+    use more_asserts::assert_lt;
+
+    let cuda = Device::cuda().unwrap();
+    let host = cuda.host();
+    let sequence_length = 4;
+    let vocab_size = 6;
+    let n_embd = 8;
+    let embedding = Embedding::new(&host, vocab_size, n_embd).unwrap();
+    let attention = MultiHeadAttention::try_new(
+        &cuda,
+        &MultiHeadAttentionConfig {
+            rows: sequence_length,
+            cols: n_embd,
+            causal_mask: true,
+            num_heads: 2,
+            num_kv_heads: 2,
+            dropout_probability: 0.0,
+            learnable_scale: false,
+            seed: None,
+            position_independent: false,
+        },
+    )
+    .unwrap();
+
+    let mut values = vec![0.0; sequence_length * vocab_size];
+    for (row, token) in [3, 1, 5, 0].into_iter().enumerate() {
+        values[row * vocab_size + token] = 1.0;
+    }
+    let input = new_tensor_with_grad!(host, sequence_length, vocab_size, values, &[], false, false)
+        .unwrap();
+
+    // Embedding on the host, attention on the GPU.
+    let embedded = embedding.forward(&input).unwrap();
+    let transferred = ToDevice::new(&cuda).forward(&embedded).unwrap();
+    let output = attention
+        .forward(&transferred, &transferred, &transferred)
+        .unwrap();
+    let has_transfer = output.get_tape().iter().any(|tensor| {
+        tensor
+            .forward_instructions()
+            .iter()
+            .any(|i| matches!(i.opcode(), OpCode::ToDevice))
+    });
+    assert!(has_transfer);
+    execute(&cuda, &output, Category::DisableDropout).unwrap();
+    execute(&cuda, &output, Category::Inference).unwrap();
+
+    // Same attention on an embedding that is computed on the host and copied by hand.
+    execute(&host, &embedded, Category::Inference).unwrap();
+    let values = embedded.tensor().get_values().unwrap();
+    let expected_input =
+        new_tensor_with_grad!(cuda, sequence_length, n_embd, values, &[], false, false).unwrap();
+    let expected = attention
+        .forward(&expected_input, &expected_input, &expected_input)
+        .unwrap();
+    execute(&cuda, &expected, Category::DisableDropout).unwrap();
+    execute(&cuda, &expected, Category::Inference).unwrap();
+
+    let expected = expected.tensor().get_values().unwrap();
+    let actual = output.tensor().get_values().unwrap();
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_lt!((expected - actual).abs(), 1e-5);
+    }
+}
@@ -1,5 +1,10 @@
+use std::sync::{Arc, RwLock};
+
 use crate::{
-    common_adam::optimize, tensor::Error, Device, Instruction, OptimizerTrait, TensorWithGrad,
+    common_adam::{optimize, reset_state},
+    lr_scheduler::LearningRateTensors,
+    tensor::{Error, Tensor},
+    Device, Instruction, OptimizerTrait, TensorWithGrad,
 };
 
 /// See:
@@ -11,6 +16,8 @@ pub struct Adam {
     beta2: f32,
     epsilon: f32,
     weight_decay: f32,
+    state: Arc<RwLock<Vec<Tensor>>>,
+    learning_rate_tensors: LearningRateTensors,
 }
 
 impl Adam {
@@ -27,6 +34,8 @@ impl Adam {
             beta2,
             epsilon,
             weight_decay,
+            state: Default::default(),
+            learning_rate_tensors: Default::default(),
         };
         Ok(adam)
     }
@@ -48,6 +57,16 @@ impl OptimizerTrait for Adam {
             self.weight_decay,
             is_adam_w,
             tensors,
+            &mut self.state.write().unwrap(),
+            &self.learning_rate_tensors,
         )
     }
+
+    fn reset_state(&self) -> Result<(), Error> {
+        reset_state(&self.state.read().unwrap())
+    }
+
+    fn learning_rate_tensors(&self) -> LearningRateTensors {
+        self.learning_rate_tensors.clone()
+    }
 }
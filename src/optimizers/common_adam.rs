@@ -1,6 +1,10 @@
 use crate::{
-    instruction, new_tensor, opcode::OpCode, tensor::Error, Category, Device, Instruction,
-    OperatorAttributes, TensorWithGrad,
+    instruction,
+    lr_scheduler::LearningRateTensors,
+    new_tensor,
+    opcode::OpCode,
+    tensor::{Error, Tensor},
+    Category, Device, Instruction, OperatorAttributes, TensorWithGrad,
 };
 
 /// See:
@@ -22,6 +26,9 @@ use crate::{
 /// See:
 /// Decoupled Weight Decay Regularization
 /// https://arxiv.org/abs/1711.05101
+///
+/// The step count and the moments are pushed to state, see reset_state.
+/// The tensors derived from the learning rate are pushed to learning_rate_tensors.
 pub fn optimize(
     device: &Device,
     learning_rate: f32,
@@ -31,10 +38,13 @@ pub fn optimize(
     weight_decay: f32,
     is_adam_w: bool,
     tensors: &[TensorWithGrad],
+    state: &mut Vec<Tensor>,
+    learning_rate_tensors: &LearningRateTensors,
 ) -> Result<Vec<Instruction>, Error> {
     let mut instructions = vec![];
     let one = new_tensor!(device, 1, 1, vec![1.0])?;
     let t = new_tensor!(device, 1, 1, vec![0.0])?;
+    state.push(t.clone());
 
     instructions.push(instruction!(
         OpCode::Add,
@@ -44,11 +54,10 @@ pub fn optimize(
         Category::Optimization,
     ));
 
-    let adam_w_remaining_weight_after_decay = 1.0 - learning_rate * weight_decay;
+    let learning_rate_value = learning_rate;
+    let learning_rate = learning_rate_tensors.tensor(device, learning_rate_value, 1.0, 0.0)?;
     let adam_w_remaining_weight_after_decay =
-        new_tensor!(device, 1, 1, vec![adam_w_remaining_weight_after_decay])?;
-
-    let learning_rate = new_tensor!(device, 1, 1, vec![learning_rate])?;
+        learning_rate_tensors.tensor(device, learning_rate_value, -weight_decay, 1.0)?;
     let one_minus_beta1 = new_tensor!(device, 1, 1, vec![1.0 - beta1])?;
     let beta1 = new_tensor!(device, 1, 1, vec![beta1])?;
     let one_minus_beta2 = new_tensor!(device, 1, 1, vec![1.0 - beta2])?;
@@ -59,7 +68,26 @@ pub fn optimize(
     for optimizable_tensor in tensors {
         let theta = &optimizable_tensor.tensor();
 
-        if is_adam_w && weight_decay != 0.0 {
+        // The parameters with a learning rate multiplier have their own learning rate.
+        let multiplier = optimizable_tensor.learning_rate_multiplier();
+        let (learning_rate, adam_w_remaining_weight_after_decay) = if multiplier == 1.0 {
+            (
+                learning_rate.clone(),
+                adam_w_remaining_weight_after_decay.clone(),
+            )
+        } else {
+            (
+                learning_rate_tensors.tensor(device, learning_rate_value, multiplier, 0.0)?,
+                learning_rate_tensors.tensor(
+                    device,
+                    learning_rate_value,
+                    -multiplier * weight_decay,
+                    1.0,
+                )?,
+            )
+        };
+
+        if is_adam_w && weight_decay != 0.0 && !optimizable_tensor.no_decay() {
             instructions.push(instruction!(
                 OpCode::ScalarMul,
                 OperatorAttributes::None,
@@ -76,6 +104,8 @@ pub fn optimize(
         let m = new_tensor!(device, theta.rows(), theta.cols(), vec![0.0; theta.len()])?;
         // v_0
         let v = new_tensor!(device, theta.rows(), theta.cols(), vec![0.0; theta.len()])?;
+        state.push(m.clone());
+        state.push(v.clone());
 
         let tmp1 = new_tensor!(device, theta.rows(), theta.cols(), vec![0.0; theta.len()])?;
         let tmp2 = new_tensor!(device, theta.rows(), theta.cols(), vec![0.0; theta.len()])?;
@@ -280,3 +310,11 @@ pub fn optimize(
     }
     Ok(instructions)
 }
+
+/// Set the step count and the moments back to 0.
+pub fn reset_state(state: &[Tensor]) -> Result<(), Error> {
+    for tensor in state.iter() {
+        tensor.fill(0.0)?;
+    }
+    Ok(())
+}
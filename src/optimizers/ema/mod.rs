@@ -0,0 +1,125 @@
+use crate::{
+    error, new_tensor,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, ErrorEnum, Tensor},
+    Device, DeviceTrait, TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Exponential moving average of the parameters of a model.
+/// Call update after each optimizer step:
+///   ema = decay * ema + (1 - decay) * parameter
+/// The averaged weights usually evaluate better than the raw weights.
+/// swap exchanges them with the parameters for evaluation, and a second swap restores
+/// the raw weights to continue training.
+pub struct EmaTracker {
+    device: Device,
+    device_stream: DeviceStream,
+    parameters: Vec<TensorWithGrad>,
+    shadows: Vec<Tensor>,
+    /// Scratch buffer of swap, as long as the largest parameter.
+    swap_buffer: Tensor,
+    decay: Tensor,
+    one_minus_decay: Tensor,
+    swapped: bool,
+}
+
+impl EmaTracker {
+    /// The shadow copies start at the current values of the parameters.
+    pub fn try_new(
+        device: &Device,
+        parameters: &[TensorWithGrad],
+        decay: f32,
+    ) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&decay) {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let device_stream = device.new_stream()?;
+        let mut shadows = vec![];
+        for parameter in parameters.iter() {
+            let tensor: &Tensor = &parameter.tensor();
+            let shadow = new_tensor!(device, 1, tensor.len(), vec![0.0; tensor.len()])?;
+            shadow.resize(&tensor.size())?;
+            device.copy_to(tensor, &shadow, &device_stream)?;
+            shadows.push(shadow);
+        }
+        device_stream.wait_for()?;
+        let swap_len = parameters
+            .iter()
+            .map(|x| x.tensor().len())
+            .max()
+            .unwrap_or_default();
+        let tracker = Self {
+            device: device.clone(),
+            device_stream,
+            parameters: parameters.to_owned(),
+            shadows,
+            swap_buffer: new_tensor!(device, 1, swap_len, vec![0.0; swap_len])?,
+            decay: new_tensor!(device, 1, 1, vec![decay])?,
+            one_minus_decay: new_tensor!(device, 1, 1, vec![1.0 - decay])?,
+            swapped: false,
+        };
+        Ok(tracker)
+    }
+
+    /// Track the EMA of all the parameters of the device, see Device::parameter_tensors.
+    pub fn for_device(device: &Device, decay: f32) -> Result<Self, Error> {
+        let parameters = device.parameter_tensors().clone();
+        Self::try_new(device, &parameters, decay)
+    }
+
+    pub fn decay(&self) -> Result<f32, Error> {
+        (&self.decay).try_into()
+    }
+
+    /// The averaged weights, in the order of the parameters.
+    /// While swapped, these are the raw weights.
+    pub fn shadows(&self) -> &[Tensor] {
+        &self.shadows
+    }
+
+    pub fn is_swapped(&self) -> bool {
+        self.swapped
+    }
+
+    /// Fold the current parameters into the averages.
+    /// The raw weights must be in the model, so this fails while swapped.
+    pub fn update(&mut self) -> Result<(), Error> {
+        if self.swapped {
+            return Err(error!(ErrorEnum::UnsupportedOperation));
+        }
+        for (parameter, shadow) in self.parameters.iter().zip(self.shadows.iter()) {
+            let tensor: &Tensor = &parameter.tensor();
+            let n = tensor.len() as i32;
+            self.device.scal(&self.decay, shadow, &self.device_stream)?;
+            self.device.axpy(
+                n,
+                &self.one_minus_decay,
+                tensor,
+                1,
+                shadow,
+                1,
+                &self.device_stream,
+            )?;
+        }
+        self.device_stream.wait_for()
+    }
+
+    /// Exchange the averaged weights and the raw weights of the model.
+    pub fn swap(&mut self) -> Result<(), Error> {
+        for (parameter, shadow) in self.parameters.iter().zip(self.shadows.iter()) {
+            let tensor: &Tensor = &parameter.tensor();
+            let n = tensor.len() as i32;
+            let tmp = &self.swap_buffer;
+            let stream = &self.device_stream;
+            self.device.copy(n, tensor, 0, 1, tmp, 0, 1, stream)?;
+            self.device.copy_to(shadow, tensor, stream)?;
+            self.device.copy(n, tmp, 0, 1, shadow, 0, 1, stream)?;
+        }
+        self.device_stream.wait_for()?;
+        self.swapped = !self.swapped;
+        Ok(())
+    }
+}
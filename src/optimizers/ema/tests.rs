@@ -0,0 +1,57 @@
+use more_asserts::assert_lt;
+
+use crate::{ema::EmaTracker, new_tensor_with_grad, tensor::ErrorEnum, Device};
+
+#[test]
+fn ema_lags_the_raw_weights_with_the_expected_decay() {
+    let device = Device::default();
+    let parameter = new_tensor_with_grad!(device, 1, 2, vec![0.0, 0.0], &[], true, true).unwrap();
+    let decay = 0.5;
+    let mut ema = EmaTracker::try_new(&device, &[parameter.clone()], decay).unwrap();
+
+    // Each optimizer step moves the raw weights by +1 and -1.
+    let mut expected = 0.0;
+    for step in 1..=3 {
+        let raw = step as f32;
+        parameter.tensor().set_values(vec![raw, -raw]).unwrap();
+        ema.update().unwrap();
+        expected = decay * expected + (1.0 - decay) * raw;
+        let values = ema.shadows()[0].get_values().unwrap();
+        assert_lt!((expected - values[0]).abs(), 1e-6);
+        assert_lt!((-expected - values[1]).abs(), 1e-6);
+        // The average lags behind the raw weights, in the direction of the updates.
+        assert_lt!(0.0, values[0]);
+        assert_lt!(values[0], raw);
+    }
+    assert_eq!(2.125, expected);
+
+    let tensor_count = device.tensor_count();
+    ema.swap().unwrap();
+    assert!(ema.is_swapped());
+    assert_eq!(
+        vec![2.125, -2.125],
+        parameter.tensor().get_values().unwrap()
+    );
+    assert_eq!(
+        Err(ErrorEnum::UnsupportedOperation),
+        ema.update().map_err(|e| e.error().clone())
+    );
+
+    ema.swap().unwrap();
+    assert!(!ema.is_swapped());
+    // The swaps reuse the same scratch buffer.
+    assert_eq!(tensor_count, device.tensor_count());
+    assert_eq!(vec![3.0, -3.0], parameter.tensor().get_values().unwrap());
+    assert_eq!(vec![2.125, -2.125], ema.shadows()[0].get_values().unwrap());
+}
+
+#[test]
+fn ema_rejects_a_decay_outside_of_the_unit_interval() {
+    let device = Device::default();
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        EmaTracker::try_new(&device, &[], 1.5)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
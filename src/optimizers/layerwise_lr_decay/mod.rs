@@ -0,0 +1,29 @@
+use crate::{
+    error,
+    tensor::{Error, ErrorEnum},
+    TensorWithGrad,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Layer-wise learning rate decay.
+/// The layers are ordered from the input to the output, and each layer is the group of
+/// its parameters. The learning rate multiplier of the parameters of layer i
+/// (starting at 0) is gamma^(layers - i), so the layers near the input learn slower.
+///
+/// See:
+/// ELECTRA: Pre-training Text Encoders as Discriminators Rather Than Generators
+/// https://arxiv.org/abs/2003.10555
+pub fn layerwise_lr_decay(layers: &[Vec<TensorWithGrad>], gamma: f32) -> Result<(), Error> {
+    if !(gamma > 0.0 && gamma <= 1.0) {
+        return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+    }
+    for (i, layer) in layers.iter().enumerate() {
+        let multiplier = gamma.powi((layers.len() - i) as i32);
+        for parameter in layer.iter() {
+            parameter.set_learning_rate_multiplier(multiplier);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    layerwise_lr_decay::layerwise_lr_decay, new_tensor_with_grad,
+    stochastic_gradient_descent::StochasticGradientDescent, stream::StreamTrait, tensor::ErrorEnum,
+    Device, Linear, OptimizerTrait, TensorWithGrad, WeightsInitialization,
+};
+
+#[test]
+fn layerwise_lr_decay_multipliers() {
+    let device = Device::default();
+    let layers = (0..3)
+        .map(|_| Linear::new(&device, 2, 2, WeightsInitialization::Kaiming, 1).unwrap())
+        .collect::<Vec<_>>();
+    let parameters = layers
+        .iter()
+        .map(|layer| layer.parameters().into_iter().cloned().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    layerwise_lr_decay(&parameters, 0.9).unwrap();
+
+    let expected = [0.9_f32.powi(3), 0.9_f32.powi(2), 0.9];
+    for (layer, expected) in parameters.iter().zip(expected) {
+        assert_eq!(2, layer.len());
+        for parameter in layer.iter() {
+            assert_eq!(expected, parameter.learning_rate_multiplier());
+        }
+    }
+
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        layerwise_lr_decay(&parameters, 0.0).map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn stochastic_gradient_descent_uses_the_learning_rate_multiplier() {
+    let device = Device::default();
+    let new_parameter = || -> TensorWithGrad {
+        let parameter = new_tensor_with_grad!(device, 1, 1, vec![0.0], &[], true, true).unwrap();
+        parameter.gradient().set_values(vec![1.0]).unwrap();
+        parameter
+    };
+    let parameters = vec![new_parameter(), new_parameter()];
+    layerwise_lr_decay(&[vec![parameters[0].clone()]], 0.5).unwrap();
+
+    let optimizer = StochasticGradientDescent::new(0.1);
+    let device_stream = device.new_stream().unwrap();
+    for instruction in optimizer.optimize(&device, &parameters).unwrap().iter() {
+        instruction.execute(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    let values = parameters
+        .iter()
+        .map(|x| x.tensor().get_values().unwrap()[0])
+        .collect::<Vec<_>>();
+    assert_lt!((values[0] + 0.05).abs(), 1e-7);
+    assert_lt!((values[1] + 0.1).abs(), 1e-7);
+}
@@ -0,0 +1,99 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    error, new_tensor,
+    tensor::{Error, ErrorEnum, Tensor},
+    Device,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A learning rate scheduler gives the learning rate to use at a given optimization step.
+pub trait LrScheduler {
+    fn learning_rate(&self, step: usize) -> f32;
+}
+
+/// The device tensors that hold the learning rate of the compiled optimizer instructions,
+/// so that the learning rate can change between optimizer steps.
+/// Each tensor holds scale * learning_rate + offset, for example the learning rate of a
+/// parameter with a learning rate multiplier, or the remaining weight after the decay of AdamW.
+#[derive(Clone, Default)]
+pub struct LearningRateTensors {
+    tensors: Arc<RwLock<Vec<(Tensor, f32, f32)>>>,
+}
+
+impl LearningRateTensors {
+    pub fn tensor(
+        &self,
+        device: &Device,
+        learning_rate: f32,
+        scale: f32,
+        offset: f32,
+    ) -> Result<Tensor, Error> {
+        let tensor = new_tensor!(device, 1, 1, vec![scale * learning_rate + offset])?;
+        self.tensors
+            .write()
+            .unwrap()
+            .push((tensor.clone(), scale, offset));
+        Ok(tensor)
+    }
+
+    pub fn set_learning_rate(&self, learning_rate: f32) -> Result<(), Error> {
+        for (tensor, scale, offset) in self.tensors.read().unwrap().iter() {
+            tensor.set_values(vec![scale * learning_rate + offset])?;
+        }
+        Ok(())
+    }
+}
+
+/// See:
+/// SGDR: Stochastic Gradient Descent with Warm Restarts
+/// https://arxiv.org/abs/1608.03983
+pub struct CosineAnnealing {
+    peak_lr: f32,
+    min_lr: f32,
+    cycle_length: usize,
+    warm_restarts: bool,
+}
+
+impl CosineAnnealing {
+    pub fn try_new(
+        peak_lr: f32,
+        min_lr: f32,
+        cycle_length: usize,
+        warm_restarts: bool,
+    ) -> Result<Self, Error> {
+        if cycle_length == 0 || min_lr > peak_lr {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let scheduler = Self {
+            peak_lr,
+            min_lr,
+            cycle_length,
+            warm_restarts,
+        };
+        Ok(scheduler)
+    }
+
+    pub fn cycle_length(&self) -> usize {
+        self.cycle_length
+    }
+
+    pub fn min_lr(&self) -> f32 {
+        self.min_lr
+    }
+}
+
+impl LrScheduler for CosineAnnealing {
+    fn learning_rate(&self, step: usize) -> f32 {
+        let step_in_cycle = if self.warm_restarts {
+            step % self.cycle_length
+        } else {
+            step.min(self.cycle_length)
+        };
+        let progress = step_in_cycle as f32 / self.cycle_length as f32;
+        self.min_lr
+            + 0.5 * (self.peak_lr - self.min_lr) * (1.0 + (std::f32::consts::PI * progress).cos())
+    }
+}
@@ -0,0 +1,158 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    adam_w::AdamW,
+    callback::NoTrainingCallback,
+    lr_scheduler::{CosineAnnealing, LrScheduler},
+    neural_program::NeuralProgram,
+    new_tensor_with_grad,
+    perceptron::PerceptronModel,
+    schedulers::DefaultStreamScheduler,
+    stochastic_gradient_descent::StochasticGradientDescent,
+    sum_of_squared_errors::SumOfSquaredErrors,
+    training_loop_with_callback, Device, NeuralMachine, TensorWithGrad, TrainingLoopConfig,
+};
+
+#[test]
+fn cosine_annealing_at_cycle_midpoint() {
+    let peak_lr = 0.1;
+    let min_lr = 0.01;
+    let cycle_length = 100;
+    let scheduler = CosineAnnealing::try_new(peak_lr, min_lr, cycle_length, true).unwrap();
+    assert_lt!((peak_lr - scheduler.learning_rate(0)).abs(), 1e-6);
+    let expected = (peak_lr + min_lr) / 2.0;
+    assert_lt!(
+        (expected - scheduler.learning_rate(cycle_length / 2)).abs(),
+        1e-6
+    );
+}
+
+#[test]
+fn cosine_annealing_with_warm_restart() {
+    let peak_lr = 0.1;
+    let min_lr = 0.01;
+    let cycle_length = 100;
+    let scheduler = CosineAnnealing::try_new(peak_lr, min_lr, cycle_length, true).unwrap();
+    assert_lt!(scheduler.learning_rate(cycle_length - 1), 0.011);
+    assert_lt!(
+        (peak_lr - scheduler.learning_rate(cycle_length)).abs(),
+        1e-6
+    );
+}
+
+#[test]
+fn cosine_annealing_without_warm_restart() {
+    let peak_lr = 0.1;
+    let min_lr = 0.01;
+    let cycle_length = 100;
+    let scheduler = CosineAnnealing::try_new(peak_lr, min_lr, cycle_length, false).unwrap();
+    assert_lt!(
+        (min_lr - scheduler.learning_rate(2 * cycle_length)).abs(),
+        1e-6
+    );
+}
+
+fn perceptron_examples(device: &Device) -> (Vec<TensorWithGrad>, Vec<TensorWithGrad>) {
+    let examples = [([2.0, 3.0], 5.0), ([1.0, -1.0], 0.0)];
+    let inputs = examples
+        .iter()
+        .map(|(input, _)| {
+            new_tensor_with_grad!(device, 1, 2, input.to_vec(), &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let outputs = examples
+        .iter()
+        .map(|(_, output)| {
+            new_tensor_with_grad!(device, 1, 1, vec![*output], &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    (inputs, outputs)
+}
+
+#[test]
+fn sgd_update_size_follows_the_schedule() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = StochasticGradientDescent::new(0.5);
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+    let (inputs, outputs) = perceptron_examples(&device);
+
+    let cycle_length = 4;
+    let scheduler = CosineAnnealing::try_new(0.1, 0.01, cycle_length, false).unwrap();
+    for step in 0..cycle_length {
+        neural_machine.infer(&inputs[0]).unwrap();
+        neural_machine.loss(&outputs[0]).unwrap();
+        neural_machine.compute_gradient().unwrap();
+        let parameters = device.parameter_tensors().clone();
+        let before = parameters
+            .iter()
+            .map(|x| {
+                (
+                    x.tensor().get_values().unwrap(),
+                    x.gradient().get_values().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let learning_rate = scheduler.learning_rate(step);
+        neural_machine.set_learning_rate(learning_rate).unwrap();
+        neural_machine.optimize().unwrap();
+
+        for (parameter, (values, gradient)) in parameters.iter().zip(before) {
+            let after = parameter.tensor().get_values().unwrap();
+            for ((after, value), gradient) in after.iter().zip(values).zip(gradient) {
+                assert_lt!((value - learning_rate * gradient - after).abs(), 1e-6);
+            }
+        }
+    }
+}
+
+struct ConstantLearningRate(f32);
+
+impl LrScheduler for ConstantLearningRate {
+    fn learning_rate(&self, _step: usize) -> f32 {
+        self.0
+    }
+}
+
+#[test]
+fn training_loop_sets_the_learning_rate_of_each_step() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    // The weight decay is derived from the learning rate too.
+    let optimizer = AdamW::try_new(0.1, 0.9, 0.999, 1e-8, 0.1).unwrap();
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+    let (inputs, outputs) = perceptron_examples(&device);
+    let parameters = || {
+        device
+            .parameter_tensors()
+            .iter()
+            .map(|x| x.tensor().get_values().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let initial_parameters = parameters();
+    training_loop_with_callback(
+        &TrainingLoopConfig {
+            shuffle_examples: false,
+            shuffle_seed: None,
+            batch_size: 1,
+            epochs: 2,
+        },
+        &mut neural_machine,
+        &inputs,
+        &outputs,
+        Some(&ConstantLearningRate(0.0)),
+        &mut NoTrainingCallback::default(),
+    )
+    .unwrap();
+    assert_eq!(initial_parameters, parameters());
+}
@@ -3,8 +3,15 @@ pub mod stochastic_gradient_descent;
 pub use adam::*;
 pub mod adam_w;
 pub mod common_adam;
+pub mod ema;
+pub mod layerwise_lr_decay;
+pub mod lr_scheduler;
+
+#[cfg(test)]
+mod tests;
 
 use crate::{tensor::Error, Device, Instruction, TensorWithGrad};
+use lr_scheduler::LearningRateTensors;
 
 pub trait OptimizerTrait {
     fn optimize(
@@ -12,4 +19,15 @@ pub trait OptimizerTrait {
         device: &Device,
         tensors: &[TensorWithGrad],
     ) -> Result<Vec<Instruction>, Error>;
+
+    /// Clear the state of the optimizer, like the moments and the step count of Adam,
+    /// so that the next step is like the first one.
+    /// The optimizers without state have nothing to clear.
+    fn reset_state(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The tensors of the learning rate in the instructions of optimize,
+    /// see NeuralMachine::set_learning_rate.
+    fn learning_rate_tensors(&self) -> LearningRateTensors;
 }
@@ -1,15 +1,19 @@
 use crate::{
-    instruction, new_tensor, opcode::OpCode, tensor::Error, Category, Device, Instruction,
-    OperatorAttributes, OptimizerTrait, TensorWithGrad,
+    instruction, lr_scheduler::LearningRateTensors, new_tensor, opcode::OpCode, tensor::Error,
+    Category, Device, Instruction, OperatorAttributes, OptimizerTrait, TensorWithGrad,
 };
 
 pub struct StochasticGradientDescent {
     learning_rate: f32,
+    learning_rate_tensors: LearningRateTensors,
 }
 
 impl StochasticGradientDescent {
     pub fn new(learning_rate: f32) -> Self {
-        Self { learning_rate }
+        Self {
+            learning_rate,
+            learning_rate_tensors: Default::default(),
+        }
     }
 }
 
@@ -32,7 +36,12 @@ impl OptimizerTrait for StochasticGradientDescent {
                 vec![0.0; tensor.len()]
             )?;
 
-            let alpha = new_tensor!(device, 1, 1, vec![self.learning_rate])?;
+            let alpha = self.learning_rate_tensors.tensor(
+                device,
+                self.learning_rate,
+                optimizable_tensor.learning_rate_multiplier(),
+                0.0,
+            )?;
             instructions.push(instruction!(
                 OpCode::ScalarMul,
                 OperatorAttributes::None,
@@ -52,4 +61,8 @@ impl OptimizerTrait for StochasticGradientDescent {
 
         Ok(instructions)
     }
+
+    fn learning_rate_tensors(&self) -> LearningRateTensors {
+        self.learning_rate_tensors.clone()
+    }
 }
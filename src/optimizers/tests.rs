@@ -0,0 +1,38 @@
+use crate::{
+    new_tensor_with_grad, stream::StreamTrait, Adam, Device, Instruction, OptimizerTrait,
+    TensorWithGrad,
+};
+
+/// Run an optimizer step with the gradient and return the change of the parameter.
+fn step(
+    device: &Device,
+    instructions: &[Instruction],
+    parameter: &TensorWithGrad,
+    gradient: f32,
+) -> f32 {
+    let device_stream = device.new_stream().unwrap();
+    parameter.gradient().set_values(vec![gradient]).unwrap();
+    let before = parameter.tensor().get_values().unwrap()[0];
+    for instruction in instructions.iter() {
+        instruction.execute(device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    parameter.tensor().get_values().unwrap()[0] - before
+}
+
+#[test]
+fn adam_step_after_reset_state_is_like_the_first_step() {
+    let device = Device::default();
+    let parameter = new_tensor_with_grad!(device, 1, 1, vec![0.0], &[], true, true).unwrap();
+    let optimizer = Adam::try_new(0.1, 0.9, 0.999, 1e-8, 0.0).unwrap();
+    let instructions = optimizer.optimize(&device, &[parameter.clone()]).unwrap();
+
+    let first_step = step(&device, &instructions, &parameter, 0.5);
+    step(&device, &instructions, &parameter, -3.0);
+    let continued_step = step(&device, &instructions, &parameter, 0.5);
+    assert_ne!(first_step, continued_step);
+
+    optimizer.reset_state().unwrap();
+    let step_after_reset = step(&device, &instructions, &parameter, 0.5);
+    assert_eq!(first_step, step_after_reset);
+}
@@ -1,4 +1,6 @@
 use std::fmt::Debug;
+#[cfg(feature = "ndarray")]
+mod ndarray_conversions;
 mod tensor;
 #[cfg(feature = "cuda")]
 use cudarc::nvrtc::CompileError;
@@ -24,6 +26,10 @@ impl Error {
             error,
         }
     }
+
+    pub fn error(&self) -> &ErrorEnum {
+        &self.error
+    }
 }
 
 #[macro_export]
@@ -39,6 +45,28 @@ pub enum ErrorEnum {
     UnsupportedOperation,
     IncorrectOperatorConfiguration,
     InputOutputError,
+    DTypeMismatch {
+        expected: DType,
+        found: DType,
+    },
+    /// The input or the output of a dataset example does not have the size declared by the model.
+    ExampleSizeMismatch {
+        example: usize,
+        tensor: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+    /// The compiled program of a neural machine has more instructions than the limit.
+    InstructionLimitExceeded {
+        limit: usize,
+        found: usize,
+    },
+    /// A transaction on an operand was executed before a prior transaction on the same operand.
+    /// Only the first violation is reported.
+    MemoryModelViolation {
+        transaction: String,
+        prior: String,
+    },
     #[cfg(feature = "cuda")]
     NvRtcCompilePtxError(CompileError),
     #[cfg(feature = "cuda")]
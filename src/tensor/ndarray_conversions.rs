@@ -0,0 +1,22 @@
+use ndarray::Array2;
+
+use crate::{
+    devices::Device,
+    error, new_tensor,
+    tensor::{Error, ErrorEnum, Tensor},
+};
+
+impl Tensor {
+    pub fn to_ndarray(&self) -> Result<Array2<f32>, Error> {
+        let values = self.get_values()?;
+        Array2::from_shape_vec((self.rows(), self.cols()), values)
+            .map_err(|_| error!(ErrorEnum::IncompatibleTensorShapes))
+    }
+
+    pub fn from_ndarray(device: &Device, array: &Array2<f32>) -> Result<Tensor, Error> {
+        let (rows, cols) = array.dim();
+        // iter() visits the elements in logical row-major order, whatever the memory layout.
+        let values = array.iter().copied().collect::<Vec<_>>();
+        new_tensor!(device, rows, cols, values)
+    }
+}
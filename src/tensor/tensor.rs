@@ -1,14 +1,30 @@
-use crate::devices::slice::DevSliceTrait;
+use crate::devices::slice::{DevSliceTrait, DeviceSlice};
 use crate::tensor::ErrorEnum;
-use crate::{devices::Device, error, slice::DevSlice, tensor::Error};
+use crate::{
+    devices::Device,
+    error, new_tensor,
+    slice::DevSlice,
+    stream::{DeviceStream, StreamTrait},
+    tensor::Error,
+    CpuDevice, DeviceTrait, Gemm,
+};
 
 use std::fmt;
 use std::sync::{Arc, RwLock};
 use std::{fmt::Display, ops::Deref, vec};
 
+/// Element type of a tensor.
+/// The device buffers hold f32 values, and F16 is only a tag until half-precision kernels land.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DType {
+    F32,
+    F16,
+}
+
 #[derive(Clone)]
 pub struct Tensor {
     name: usize,
+    dtype: DType,
     size: Arc<RwLock<Vec<usize>>>,
     device_slice: Arc<RwLock<DevSlice>>,
     #[cfg(debug_assertions)]
@@ -52,6 +68,7 @@ impl Tensor {
         buffer.set_values(values)?;
         let tensor = Self {
             name,
+            dtype: DType::F32,
             size: Arc::new(RwLock::new(vec![rows, cols])),
             device_slice: Arc::new(RwLock::new(buffer)),
             #[cfg(debug_assertions)]
@@ -64,10 +81,48 @@ impl Tensor {
         Ok(tensor)
     }
 
+    /// Build a tensor from the values of an iterator, in row-major order.
+    /// The iterator must yield exactly rows * cols values.
+    /// At most one extra value is consumed, so an infinite iterator is rejected.
+    pub fn from_iter<I>(device: &Device, rows: usize, cols: usize, values: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = f32>,
+    {
+        let len = rows * cols;
+        let mut values_vec = Vec::with_capacity(len);
+        values_vec.extend(values.into_iter().take(len + 1));
+        if values_vec.len() != len {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        new_tensor!(device, rows, cols, values_vec)
+    }
+
     pub fn name(&self) -> usize {
         self.name
     }
 
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    pub fn with_dtype(mut self, dtype: DType) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    /// All the operands of an operation must be F32, the only dtype of the device kernels.
+    /// F16 is rejected until half-precision kernels land.
+    pub fn check_dtypes<'a>(tensors: impl IntoIterator<Item = &'a Tensor>) -> Result<(), Error> {
+        let expected = DType::F32;
+        for tensor in tensors {
+            let found = tensor.dtype();
+            if found != expected {
+                return Err(error!(ErrorEnum::DTypeMismatch { expected, found }));
+            }
+        }
+        Ok(())
+    }
+
     pub fn requires_grad(&self) -> bool {
         self.len() > 0
     }
@@ -109,6 +164,52 @@ impl Tensor {
         }
     }
 
+    /// Number of dimensions. A matrix has rank 2, an image batch in NCHW layout has rank 4.
+    pub fn rank(&self) -> usize {
+        self.size.deref().read().unwrap().len()
+    }
+
+    /// Row-major strides: the last dimension is contiguous.
+    pub fn strides(&self) -> Vec<usize> {
+        Self::get_strides(&self.size.deref().read().unwrap())
+    }
+
+    pub fn get_strides(size: &[usize]) -> Vec<usize> {
+        let mut strides = vec![1; size.len()];
+        for i in (0..size.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * size[i + 1];
+        }
+        strides
+    }
+
+    /// Offset of the element at the given coordinates, one coordinate per dimension.
+    /// For a matrix, index_nd(&[row, col]) is index(row, col).
+    pub fn index_nd(&self, coordinates: &[usize]) -> Result<usize, Error> {
+        Self::get_index_nd(&self.size.deref().read().unwrap(), coordinates)
+    }
+
+    pub fn get_index_nd(size: &[usize], coordinates: &[usize]) -> Result<usize, Error> {
+        if coordinates.len() != size.len()
+            || coordinates.iter().zip(size.iter()).any(|(x, n)| x >= n)
+        {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let index = coordinates
+            .iter()
+            .zip(Self::get_strides(size).iter())
+            .map(|(x, stride)| x * stride)
+            .sum();
+        Ok(index)
+    }
+
+    /// Dimensions (batch, channels, height, width) of an image batch in NCHW layout.
+    pub fn nchw(&self) -> Result<(usize, usize, usize, usize), Error> {
+        match self.size.deref().read().unwrap().as_slice() {
+            &[n, c, h, w] => Ok((n, c, h, w)),
+            _ => Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+        }
+    }
+
     pub fn device_slice(&self) -> impl Deref<Target = DevSlice> + '_ {
         self.device_slice.read().unwrap()
     }
@@ -125,6 +226,14 @@ impl Tensor {
         self.device_slice.deref().read().unwrap().get_values()
     }
 
+    /// Whether the values live in host memory.
+    pub fn is_on_host(&self) -> bool {
+        matches!(
+            self.device_slice.deref().read().unwrap().buffer,
+            DeviceSlice::CpuDevSlice(_)
+        )
+    }
+
     /// Avoid using set_values unless necessary. It's bad for performance.
     pub fn set_values(&self, new_values: Vec<f32>) -> Result<(), Error> {
         debug_assert_eq!(new_values.len(), self.len());
@@ -138,6 +247,367 @@ impl Tensor {
             .set_values(new_values)
     }
 
+    /// Free the buffer, e.g. for an activation that is recomputed later.
+    /// The tensor keeps its shape, and it must be reallocated before it is used again.
+    pub fn deallocate(&self, device: &Device) {
+        let mut device_slice = self.device_slice.deref().write().unwrap();
+        device.release(device_slice.len());
+        *device_slice = device.buffer(0);
+    }
+
+    /// Allocate a zeroed buffer for a tensor that was deallocated.
+    pub fn reallocate(&self, device: &Device) {
+        let mut device_slice = self.device_slice.deref().write().unwrap();
+        if device_slice.len() != self.len() {
+            *device_slice = device.buffer(self.len());
+        }
+    }
+
+    /// Whether the tensor has a buffer for its values, see deallocate.
+    pub fn is_allocated(&self) -> bool {
+        self.device_slice.deref().read().unwrap().len() == self.len()
+    }
+
+    /// Copy the values of a tensor that may be on another device.
+    /// The copy goes directly between the two buffers.
+    pub fn copy_values_from(&self, source: &Tensor) -> Result<(), Error> {
+        if self.name() == source.name() {
+            return Ok(());
+        }
+        let source = source.device_slice.deref().read().unwrap();
+        self.device_slice
+            .deref()
+            .write()
+            .unwrap()
+            .copy_from(&source)
+    }
+
+    /// Allocate a tensor with the same shape and values on the target device.
+    /// The values go through the host, so this is meant for mixed CPU/GPU pipelines
+    /// and for validating device implementations, not for hot loops.
+    pub fn to_device(&self, target: &Device) -> Result<Tensor, Error> {
+        let values = self.get_values()?;
+        let tensor = new_tensor!(target, self.rows(), self.cols(), values)?;
+        Ok(tensor.with_dtype(self.dtype()))
+    }
+
+    /// c := a * b
+    /// The prior contents of c are overwritten.
+    pub fn matmul(
+        device: &Device,
+        a: &Tensor,
+        b: &Tensor,
+        c: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Self::matmul_with_beta(device, a, b, &device_stream.zero, c, device_stream)
+    }
+
+    /// c := a * b + c
+    /// The product is accumulated into the prior contents of c (beta = 1),
+    /// for example a bias broadcast into c beforehand.
+    pub fn matmul_acc(
+        device: &Device,
+        a: &Tensor,
+        b: &Tensor,
+        c: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        Self::matmul_with_beta(device, a, b, &device_stream.one, c, device_stream)
+    }
+
+    fn matmul_with_beta(
+        device: &Device,
+        a: &Tensor,
+        b: &Tensor,
+        beta: &Tensor,
+        c: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if a.cols() != b.rows() || c.rows() != a.rows() || c.cols() != b.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let alpha = &device_stream.one;
+        Gemm::gemm(
+            false,
+            false,
+            alpha,
+            a,
+            b,
+            beta,
+            c,
+            false,
+            device,
+            device_stream,
+        )
+    }
+
+    /// output := self^T
+    /// The values are written directly into the existing buffer of output, without allocating.
+    /// output can be self when self is square.
+    pub fn transpose_into(
+        &self,
+        device: &Device,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        device.transpose(self, output, device_stream)
+    }
+
+    /// Write the L2 norm of each row into output, which has one column.
+    /// squares has the shape of this tensor and receives the squared values,
+    /// which are summed along the columns.
+    pub fn row_norms(
+        &self,
+        device: &Device,
+        squares: &Tensor,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if output.rows() != self.rows() || output.cols() != 1 || *squares.size() != *self.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        device.mul(self, self, squares, device_stream)?;
+        device.reduce_sum_axis(1, squares, output, device_stream)?;
+        device.sqrt(output, output, device_stream)
+    }
+
+    /// A rows x cols tensor with this tensor in the top-left corner and value elsewhere,
+    /// computed on the host.
+    pub fn pad(
+        &self,
+        device: &Device,
+        rows: usize,
+        cols: usize,
+        value: f32,
+    ) -> Result<Tensor, Error> {
+        let output = new_tensor!(device, rows, cols, vec![value; rows * cols])?;
+        self.pad_into(&output, value)?;
+        Ok(output)
+    }
+
+    /// Write this tensor in the top-left corner of output, and value elsewhere, on the host.
+    pub fn pad_into(&self, output: &Tensor, value: f32) -> Result<(), Error> {
+        let (rows, cols) = (output.rows(), output.cols());
+        if self.rows() > rows || self.cols() > cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let mut values = vec![value; rows * cols];
+        for (row, input_row) in self.rows_iter()?.iter().enumerate() {
+            let start = row * cols;
+            values[start..start + input_row.len()].copy_from_slice(input_row);
+        }
+        output.set_values(values)
+    }
+
+    /// Clamp the values to [min, max] in place with the clip of the device,
+    /// without building a graph.
+    /// The bounds are written in the working memory of the stream, so the stream
+    /// waits for its previous clamp before they are overwritten.
+    pub fn clamp(
+        &self,
+        device: &Device,
+        min: f32,
+        max: f32,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if min > max {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        device_stream.wait_for()?;
+        device_stream.clip_min.set_values(vec![min])?;
+        device_stream.clip_max.set_values(vec![max])?;
+        device.clip(
+            &device_stream.clip_min,
+            &device_stream.clip_max,
+            self,
+            self,
+            device_stream,
+        )
+    }
+
+    /// Write the softmax of each row into output, computed on the host like the softmax
+    /// of the CPU device, without building a graph.
+    /// This is handy to turn logits into probabilities after inference.
+    pub fn softmax(&self, output: &Tensor) -> Result<(), Error> {
+        if *output.size() != *self.size() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let input = self.get_values()?;
+        let mut values = vec![0.0; input.len()];
+        CpuDevice::_softmax(
+            self.rows() as i32,
+            self.cols() as i32,
+            input.as_ptr(),
+            values.as_mut_ptr(),
+        )?;
+        output.set_values(values)
+    }
+
+    /// Write the diagonal of a square tensor into output, which has one column, on the host.
+    pub fn diagonal(&self, output: &Tensor) -> Result<(), Error> {
+        let n = self.rows();
+        if self.cols() != n || *output.size() != [n, 1] {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let values = self.get_values()?;
+        let diagonal = (0..n).map(|i| values[self.index(i, i)]).collect();
+        output.set_values(diagonal)
+    }
+
+    /// The sum of the diagonal of a square tensor, on the host.
+    pub fn trace(&self) -> Result<f32, Error> {
+        let n = self.rows();
+        if self.cols() != n {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let values = self.get_values()?;
+        Ok((0..n).map(|i| values[self.index(i, i)]).sum())
+    }
+
+    /// Copy the columns before col and the columns from col into two tensors, on the host.
+    pub fn split_at_col(&self, device: &Device, col: usize) -> Result<(Tensor, Tensor), Error> {
+        let (rows, cols) = (self.rows(), self.cols());
+        if col > cols {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let mut left = Vec::with_capacity(rows * col);
+        let mut right = Vec::with_capacity(rows * (cols - col));
+        for row in self.rows_iter()?.iter() {
+            left.extend_from_slice(&row[..col]);
+            right.extend_from_slice(&row[col..]);
+        }
+        let left = new_tensor!(device, rows, col, left)?;
+        let right = new_tensor!(device, rows, cols - col, right)?;
+        Ok((left, right))
+    }
+
+    /// Stack tensors vertically on the host. They must all have the same number of columns.
+    pub fn concat_rows(device: &Device, tensors: &[&Tensor]) -> Result<Tensor, Error> {
+        let cols = match tensors.first() {
+            Some(tensor) => tensor.cols(),
+            None => return Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+        };
+        let mut rows = 0;
+        let mut values = vec![];
+        for tensor in tensors.iter() {
+            if tensor.cols() != cols {
+                return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+            }
+            rows += tensor.rows();
+            values.extend(tensor.get_values()?);
+        }
+        new_tensor!(device, rows, cols, values)
+    }
+
+    /// Stack tensors horizontally on the host. They must all have the same number of rows.
+    pub fn concat_cols(device: &Device, tensors: &[&Tensor]) -> Result<Tensor, Error> {
+        let rows = match tensors.first() {
+            Some(tensor) => tensor.rows(),
+            None => return Err(error!(ErrorEnum::IncompatibleTensorShapes)),
+        };
+        if tensors.iter().any(|tensor| tensor.rows() != rows) {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let tensor_rows = tensors
+            .iter()
+            .map(|tensor| tensor.rows_iter())
+            .collect::<Result<Vec<_>, _>>()?;
+        let cols = tensors.iter().map(|tensor| tensor.cols()).sum::<usize>();
+        let mut values = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for tensor_rows in tensor_rows.iter() {
+                let start = row * tensor_rows.cols;
+                values.extend_from_slice(&tensor_rows.values[start..start + tensor_rows.cols]);
+            }
+        }
+        new_tensor!(device, rows, cols, values)
+    }
+
+    /// Select the k largest values of each row, in decreasing order, with their column indices.
+    /// Ties are broken by the lowest index.
+    pub fn topk_rows(&self, device: &Device, k: usize) -> Result<(Tensor, Vec<Vec<usize>>), Error> {
+        if k > self.cols() {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        let rows = self.rows_iter()?;
+        let mut values = Vec::with_capacity(self.rows() * k);
+        let mut indices = Vec::with_capacity(self.rows());
+        for row in rows.iter() {
+            let mut columns = (0..row.len()).collect::<Vec<_>>();
+            // The sort is stable, so equal values keep their column order.
+            columns.sort_by(|a, b| row[*b].total_cmp(&row[*a]));
+            columns.truncate(k);
+            values.extend(columns.iter().map(|col| row[*col]));
+            indices.push(columns);
+        }
+        let values = new_tensor!(device, self.rows(), k, values)?;
+        Ok((values, indices))
+    }
+
+    /// A view over the columns start..end of every row, without copying.
+    /// For example, the output of a fused QKV projection can be split into Q, K and V.
+    pub fn slice_cols(&self, start: usize, end: usize) -> Result<TensorView, Error> {
+        if start > end || end > self.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        Ok(TensorView {
+            tensor: self.clone(),
+            start,
+            end,
+        })
+    }
+
+    /// Overwrite every element with value.
+    pub fn fill(&self, value: f32) -> Result<(), Error> {
+        self.set_values(vec![value; self.len()])
+    }
+
+    /// Copy the values and iterate over them row by row.
+    /// Each row is a contiguous slice because tensors are stored in row-major order.
+    pub fn rows_iter(&self) -> Result<TensorRows, Error> {
+        let values = self.get_values()?;
+        Ok(TensorRows {
+            values,
+            cols: self.cols(),
+        })
+    }
+
+    /// The column of the minimum of each row, see get_row_argmaxes for the maximum.
+    /// The lowest column wins ties.
+    pub fn argmin_rows(&self) -> Result<Vec<usize>, Error> {
+        let rows = self.rows_iter()?;
+        let argmins = rows
+            .iter()
+            .map(|row| {
+                let mut argmin_col = 0;
+                for (col, value) in row.iter().enumerate() {
+                    if *value < row[argmin_col] {
+                        argmin_col = col;
+                    }
+                }
+                argmin_col
+            })
+            .collect();
+        Ok(argmins)
+    }
+
+    /// Copy the values as one Vec per row.
+    pub fn to_vec2(&self) -> Result<Vec<Vec<f32>>, Error> {
+        let rows = self.rows_iter()?;
+        Ok(rows.iter().map(|row| row.to_vec()).collect())
+    }
+
+    /// Apply `f` to each row and write the values back to the device.
+    pub fn rows_iter_mut<F>(&self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut [f32]),
+    {
+        let mut rows = self.rows_iter()?;
+        rows.iter_mut().for_each(&mut f);
+        self.set_values(rows.values)
+    }
+
     pub fn is_finite(&self) -> bool {
         let values = self.get_values().unwrap();
         for value in values {
@@ -148,6 +618,23 @@ impl Tensor {
         true
     }
 
+    /// The (row, col) of every NaN or infinite value, in row-major order.
+    pub fn non_finite_indices(&self) -> Result<Vec<(usize, usize)>, Error> {
+        let rows = self.rows_iter()?;
+        let indices = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| !value.is_finite())
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect();
+        Ok(indices)
+    }
+
     pub fn is_nan(&self) -> Result<bool, Error> {
         let values = self.get_values()?;
         for value in values {
@@ -168,6 +655,54 @@ impl Tensor {
         Ok(false)
     }
 
+    /// A hash of the size and of the bit patterns of the values.
+    /// It uses FNV-1a so that it does not change across Rust versions and platforms.
+    pub fn checksum(&self) -> Result<u64, Error> {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut write = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        let size = self.size().clone();
+        write(&(size.len() as u64).to_le_bytes());
+        for dimension in size.iter() {
+            write(&(*dimension as u64).to_le_bytes());
+        }
+        for value in self.get_values()? {
+            write(&value.to_bits().to_le_bytes());
+        }
+        Ok(hash)
+    }
+
+    /// The index in the flat values of each (row, col), one line per row.
+    /// Tensors are always stored in row-major order, while Gemm::_gemm calls a column-major
+    /// sgemm on them, so this helps to debug the transposes. The leading dimensions of
+    /// tensors with more than 2 dimensions are flattened into the rows.
+    pub fn debug_layout(&self) -> String {
+        let size = self.size().clone();
+        let strides = Self::get_strides(&size);
+        let cols = size.last().copied().unwrap_or(1);
+        let rows = self.len().checked_div(cols).unwrap_or(0);
+        let row_major = strides.last().map(|stride| *stride == 1).unwrap_or(true);
+        let mut layout = format!(
+            "size: {:?}, strides: {:?}, row-major: {}\n",
+            size, strides, row_major
+        );
+        for row in 0..rows {
+            let line = (0..cols)
+                .map(|col| format!("({}, {}) -> {}", row, col, row * cols + col))
+                .collect::<Vec<_>>()
+                .join("  ");
+            layout.push_str(&line);
+            layout.push('\n');
+        }
+        layout
+    }
+
     pub fn resize(&self, new_size: &[usize]) -> Result<(), Error> {
         let new_len = new_size.iter().product::<usize>();
         if new_len != self.len() {
@@ -180,6 +715,112 @@ impl Tensor {
     }
 }
 
+/// Host copy of the values of a Tensor, see Tensor::rows_iter.
+pub struct TensorRows {
+    values: Vec<f32>,
+    cols: usize,
+}
+
+impl TensorRows {
+    pub fn iter(&self) -> impl Iterator<Item = &[f32]> {
+        self.values.chunks(self.cols.max(1))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut [f32]> {
+        self.values.chunks_mut(self.cols.max(1))
+    }
+}
+
+impl<'a> IntoIterator for &'a TensorRows {
+    type Item = &'a [f32];
+    type IntoIter = std::slice::Chunks<'a, f32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.chunks(self.cols.max(1))
+    }
+}
+
+/// Columns start..end of a Tensor, see Tensor::slice_cols.
+/// The view shares the storage of the tensor, so writes are visible in the tensor.
+/// Element (row, col) of the view is at offset() + row * stride() + col in the storage.
+#[derive(Clone)]
+pub struct TensorView {
+    tensor: Tensor,
+    start: usize,
+    end: usize,
+}
+
+impl TensorView {
+    pub fn rows(&self) -> usize {
+        self.tensor.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn offset(&self) -> usize {
+        self.start
+    }
+
+    pub fn stride(&self) -> usize {
+        self.tensor.cols()
+    }
+
+    pub fn tensor(&self) -> &Tensor {
+        &self.tensor
+    }
+
+    /// Only the elements of the view are read, one row at a time.
+    pub fn get_values(&self) -> Result<Vec<f32>, Error> {
+        let device_slice = self.tensor.device_slice.deref().read().unwrap();
+        let mut values = Vec::with_capacity(self.rows() * self.cols());
+        for row in 0..self.rows() {
+            let offset = self.offset() + row * self.stride();
+            values.extend(device_slice.get_values_at(offset, self.cols())?);
+        }
+        Ok(values)
+    }
+
+    /// Only the elements of the view are written, one row at a time.
+    pub fn set_values(&self, new_values: Vec<f32>) -> Result<(), Error> {
+        if new_values.len() != self.rows() * self.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        let mut device_slice = self.tensor.device_slice.deref().write().unwrap();
+        for (row, new_row) in new_values.chunks(self.cols().max(1)).enumerate() {
+            let offset = self.offset() + row * self.stride();
+            device_slice.set_values_at(offset, new_row)?;
+        }
+        Ok(())
+    }
+
+    /// Copy the view into a contiguous tensor on the device, one row at a time.
+    pub fn copy_to(
+        &self,
+        device: &Device,
+        output: &Tensor,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        if output.rows() != self.rows() || output.cols() != self.cols() {
+            return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+        }
+        for row in 0..self.rows() {
+            device.copy(
+                self.cols() as i32,
+                &self.tensor,
+                (self.offset() + row * self.stride()) as i32,
+                1,
+                output,
+                (row * self.cols()) as i32,
+                1,
+                device_stream,
+            )?;
+        }
+        Ok(())
+    }
+}
+
 impl Display for Tensor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let self_values = self.get_values().map_err(|_| std::fmt::Error)?;
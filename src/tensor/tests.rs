@@ -1,6 +1,13 @@
 use std::vec;
 
-use crate::{new_tensor, tensor::ErrorEnum, Device};
+use more_asserts::assert_lt;
+
+use crate::{
+    new_tensor,
+    stream::StreamTrait,
+    tensor::{ErrorEnum, Tensor},
+    Device, DeviceTrait,
+};
 
 #[test]
 fn new() {
@@ -113,3 +120,632 @@ fn set_values() {
     let values = tensor.get_values().unwrap();
     assert_eq!(vec![4.0, 3.0, 2.0, 1.0], values);
 }
+
+#[test]
+fn rows_iter() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        3,
+        4,
+        vec![
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 6.0, 7.0, 8.0, //
+            -1.0, 0.5, 0.25, 2.0, //
+        ],
+    )
+    .unwrap();
+    let values = tensor.get_values().unwrap();
+
+    let mut expected = vec![];
+    for row in 0..tensor.rows() {
+        let mut sum = 0.0;
+        for col in 0..tensor.cols() {
+            sum += values[tensor.index(row, col)];
+        }
+        expected.push(sum);
+    }
+
+    let actual = tensor
+        .rows_iter()
+        .unwrap()
+        .iter()
+        .map(|row| row.iter().sum::<f32>())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn rows_iter_mut() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        2,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+        ],
+    )
+    .unwrap();
+    tensor
+        .rows_iter_mut(|row| {
+            let sum = row.iter().sum::<f32>();
+            row.iter_mut().for_each(|x| *x /= sum);
+        })
+        .unwrap();
+    let expected = vec![
+        1.0 / 6.0,
+        2.0 / 6.0,
+        3.0 / 6.0,
+        4.0 / 15.0,
+        5.0 / 15.0,
+        6.0 / 15.0,
+    ];
+    assert_eq!(expected, tensor.get_values().unwrap());
+}
+
+#[cfg(feature = "ndarray")]
+#[test]
+fn ndarray_round_trip() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        3,
+        4,
+        vec![
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 6.0, 7.0, 8.0, //
+            9.0, 10.0, 11.0, 12.0, //
+        ],
+    )
+    .unwrap();
+    let array = tensor.to_ndarray().unwrap();
+    assert_eq!((3, 4), array.dim());
+    assert_eq!(7.0, array[[1, 2]]);
+
+    let round_trip = crate::tensor::Tensor::from_ndarray(&device, &array).unwrap();
+    assert_eq!(tensor, round_trip);
+    assert_eq!(*tensor.size(), *round_trip.size());
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn to_device_round_trip() {
+    let cpu = Device::cpu();
+    let cuda = Device::cuda().unwrap();
+    let tensor = new_tensor!(
+        cpu,
+        2,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+        ],
+    )
+    .unwrap();
+
+    let on_cuda = tensor.to_device(&cuda).unwrap();
+    assert_eq!(*tensor.size(), *on_cuda.size());
+    let round_trip = on_cuda.to_device(&cpu).unwrap();
+    assert_eq!(tensor, round_trip);
+    assert_eq!(*tensor.size(), *round_trip.size());
+}
+
+#[test]
+fn to_device_same_kind() {
+    let device = Device::cpu();
+    let other = Device::cpu();
+    let tensor = new_tensor!(device, 1, 2, vec![7.0, 8.0],).unwrap();
+    let copy = tensor.to_device(&other).unwrap();
+    assert_eq!(tensor, copy);
+    assert_ne!(tensor.as_ptr(), copy.as_ptr());
+}
+
+#[test]
+fn fill_overwrites_values() {
+    let device = Device::default();
+    let tensor = new_tensor!(device, 2, 2, vec![1.0, 2.0, 3.0, 4.0],).unwrap();
+    tensor.fill(-3.0).unwrap();
+    assert_eq!(vec![-3.0; 4], tensor.get_values().unwrap());
+    assert_eq!(vec![2, 2], *tensor.size());
+}
+
+#[test]
+fn concat_rows_and_cols() {
+    let device = Device::default();
+    let a = new_tensor!(
+        device,
+        2,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+        ],
+    )
+    .unwrap();
+    let b = new_tensor!(
+        device,
+        2,
+        3,
+        vec![
+            7.0, 8.0, 9.0, //
+            10.0, 11.0, 12.0, //
+        ],
+    )
+    .unwrap();
+
+    let rows = crate::tensor::Tensor::concat_rows(&device, &[&a, &b]).unwrap();
+    assert_eq!(vec![4, 3], *rows.size());
+    assert_eq!(
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+            7.0, 8.0, 9.0, //
+            10.0, 11.0, 12.0, //
+        ],
+        rows.get_values().unwrap()
+    );
+
+    let cols = crate::tensor::Tensor::concat_cols(&device, &[&a, &b]).unwrap();
+    assert_eq!(vec![2, 6], *cols.size());
+    assert_eq!(
+        vec![
+            1.0, 2.0, 3.0, 7.0, 8.0, 9.0, //
+            4.0, 5.0, 6.0, 10.0, 11.0, 12.0, //
+        ],
+        cols.get_values().unwrap()
+    );
+}
+
+#[test]
+fn concat_with_mismatched_shapes() {
+    let device = Device::default();
+    let a = new_tensor!(device, 2, 3, vec![0.0; 6],).unwrap();
+    let b = new_tensor!(device, 3, 2, vec![0.0; 6],).unwrap();
+    let op_result = crate::tensor::Tensor::concat_rows(&device, &[&a, &b]);
+    assert_eq!(
+        op_result.map_err(|e| e.error),
+        Err(ErrorEnum::IncompatibleTensorShapes)
+    );
+    let op_result = crate::tensor::Tensor::concat_cols(&device, &[&a, &b]);
+    assert_eq!(
+        op_result.map_err(|e| e.error),
+        Err(ErrorEnum::IncompatibleTensorShapes)
+    );
+}
+
+#[test]
+fn topk_rows() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        2,
+        5,
+        vec![
+            0.1, 0.9, 0.3, 0.9, -1.0, //
+            5.0, -2.0, 4.0, 0.0, 4.5, //
+        ],
+    )
+    .unwrap();
+
+    let (values, indices) = tensor.topk_rows(&device, 2).unwrap();
+
+    assert_eq!(vec![2, 2], *values.size());
+    assert_eq!(
+        vec![
+            0.9, 0.9, //
+            5.0, 4.5, //
+        ],
+        values.get_values().unwrap()
+    );
+    // Ties are broken by the lowest index.
+    assert_eq!(vec![vec![1, 3], vec![0, 4]], indices);
+
+    let op_result = tensor.topk_rows(&device, 6);
+    assert_eq!(
+        op_result.map(|_| ()).map_err(|e| e.error),
+        Err(ErrorEnum::IncorrectOperatorConfiguration)
+    );
+}
+
+#[test]
+fn slice_cols_is_a_view_over_the_parent() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        3,
+        6,
+        vec![
+            0.0, 1.0, 2.0, 3.0, 4.0, 5.0, //
+            6.0, 7.0, 8.0, 9.0, 10.0, 11.0, //
+            12.0, 13.0, 14.0, 15.0, 16.0, 17.0, //
+        ],
+    )
+    .unwrap();
+    let view = tensor.slice_cols(2, 5).unwrap();
+    assert_eq!((3, 3), (view.rows(), view.cols()));
+    assert_eq!((2, 6), (view.offset(), view.stride()));
+    let storage = tensor.get_values().unwrap();
+    let values = view.get_values().unwrap();
+    for row in 0..3 {
+        for col in 0..3 {
+            assert_eq!(
+                storage[view.offset() + row * view.stride() + col],
+                values[row * 3 + col]
+            );
+        }
+    }
+
+    view.set_values(vec![-1.0; 9]).unwrap();
+    assert_eq!(
+        vec![
+            0.0, 1.0, -1.0, -1.0, -1.0, 5.0, //
+            6.0, 7.0, -1.0, -1.0, -1.0, 11.0, //
+            12.0, 13.0, -1.0, -1.0, -1.0, 17.0, //
+        ],
+        tensor.get_values().unwrap()
+    );
+
+    let device_stream = device.new_stream().unwrap();
+    let output = new_tensor!(device, 3, 3, vec![0.0; 9]).unwrap();
+    view.copy_to(&device, &output, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(vec![-1.0; 9], output.get_values().unwrap());
+
+    assert_eq!(
+        tensor.slice_cols(4, 7).map(|_| ()).map_err(|e| e.error),
+        Err(ErrorEnum::IncompatibleTensorShapes)
+    );
+}
+
+#[test]
+fn matmul_acc_accumulates_into_c() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let a = new_tensor!(&device, 2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    let b = new_tensor!(&device, 3, 2, vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0]).unwrap();
+    let product = vec![4.0, 5.0, 10.0, 11.0];
+    let bias = vec![0.5, -1.0, 0.5, -1.0];
+
+    let c = new_tensor!(&device, 2, 2, bias.clone()).unwrap();
+    Tensor::matmul_acc(&device, &a, &b, &c, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    let expected = bias
+        .iter()
+        .zip(product.iter())
+        .map(|(x, y)| x + y)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, c.get_values().unwrap());
+
+    // The overwrite variant ignores the prior contents of c.
+    let c = new_tensor!(&device, 2, 2, bias).unwrap();
+    Tensor::matmul(&device, &a, &b, &c, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(product, c.get_values().unwrap());
+
+    let c = new_tensor!(&device, 3, 2, vec![0.0; 6]).unwrap();
+    let result = Tensor::matmul_acc(&device, &a, &b, &c, &device_stream);
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        result.map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn nchw_strides_and_indexing() {
+    let device = Device::default();
+    let (n, c, h, w) = (2, 3, 4, 5);
+    let values = (0..(n * c * h * w)).map(|x| x as f32).collect::<Vec<_>>();
+    let tensor = new_tensor!(device, n * c, h * w, values.clone()).unwrap();
+    tensor.resize(&[n, c, h, w]).unwrap();
+
+    assert_eq!(4, tensor.rank());
+    assert_eq!((n, c, h, w), tensor.nchw().unwrap());
+    assert_eq!(vec![c * h * w, h * w, w, 1], tensor.strides());
+
+    let tensor_values = tensor.get_values().unwrap();
+    for b in 0..n {
+        for channel in 0..c {
+            for y in 0..h {
+                for x in 0..w {
+                    let expected = ((b * c + channel) * h + y) * w + x;
+                    let index = tensor.index_nd(&[b, channel, y, x]).unwrap();
+                    assert_eq!(expected, index);
+                    assert_eq!(values[expected], tensor_values[index]);
+                }
+            }
+        }
+    }
+
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        tensor.index_nd(&[0, 3, 0, 0]).map_err(|e| e.error)
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        tensor.index_nd(&[0, 0]).map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn nchw_reshape_to_and_from_matrix() {
+    let device = Device::default();
+    let (n, c, h, w) = (2, 1, 2, 3);
+    let values = (0..(n * c * h * w)).map(|x| x as f32).collect::<Vec<_>>();
+    let tensor = new_tensor!(device, n, c * h * w, values.clone()).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        tensor.nchw().map_err(|e| e.error)
+    );
+
+    tensor.resize(&[n, c, h, w]).unwrap();
+    assert_eq!(
+        tensor.index_nd(&[1, 0, 1, 2]).unwrap(),
+        Tensor::get_index(&[n, c * h * w], 1, h * w - 1)
+    );
+    assert_eq!(
+        Err(ErrorEnum::UnsupportedOperation),
+        tensor.resize(&[n, c * h]).map_err(|e| e.error)
+    );
+
+    tensor.resize(&[n, c * h * w]).unwrap();
+    assert_eq!(2, tensor.rank());
+    assert_eq!(tensor.index(1, 4), tensor.index_nd(&[1, 4]).unwrap());
+    assert_eq!(values, tensor.get_values().unwrap());
+}
+
+#[test]
+fn tensor_pad() {
+    let device = Device::default();
+    let tensor = new_tensor!(device, 1, 2, vec![1.0, 2.0]).unwrap();
+    let padded = tensor.pad(&device, 2, 3, -1.0).unwrap();
+    assert_eq!(
+        vec![
+            1.0, 2.0, -1.0, //
+            -1.0, -1.0, -1.0, //
+        ],
+        padded.get_values().unwrap()
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        tensor
+            .pad(&device, 1, 1, 0.0)
+            .map(|_| ())
+            .map_err(|e| e.error().clone())
+    );
+}
+
+#[test]
+fn tensor_checksum() {
+    let device = Device::default();
+    let values = vec![1.0, -2.0, 3.5, 0.25, 0.0, 6.0];
+    let tensor = new_tensor!(device, 2, 3, values.clone()).unwrap();
+    let other = new_tensor!(device, 2, 3, values.clone()).unwrap();
+    let checksum = tensor.checksum().unwrap();
+    assert_eq!(checksum, other.checksum().unwrap());
+
+    let mut changed_values = values.clone();
+    changed_values[4] = -0.0;
+    let changed = new_tensor!(device, 2, 3, changed_values).unwrap();
+    assert_ne!(checksum, changed.checksum().unwrap());
+
+    let transposed = new_tensor!(device, 3, 2, values).unwrap();
+    assert_ne!(checksum, transposed.checksum().unwrap());
+}
+
+#[test]
+fn tensor_softmax() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let values = vec![
+        1.0, 2.0, 3.0, //
+        -1.0, 0.0, 100.0, //
+    ];
+    let logits = new_tensor!(device, 2, 3, values).unwrap();
+    let probabilities = new_tensor!(device, 2, 3, vec![0.0; 6]).unwrap();
+    logits.softmax(&probabilities).unwrap();
+    for row in probabilities.rows_iter().unwrap().iter() {
+        assert_lt!((1.0 - row.iter().sum::<f32>()).abs(), 1e-6);
+    }
+
+    let expected = new_tensor!(device, 2, 3, vec![0.0; 6]).unwrap();
+    device.softmax(&logits, &expected, &device_stream).unwrap();
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        expected.get_values().unwrap(),
+        probabilities.get_values().unwrap()
+    );
+
+    let output = new_tensor!(device, 3, 2, vec![0.0; 6]).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        logits.softmax(&output).map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn tensor_clamp() {
+    let device = Device::default();
+    let device_stream = device.new_stream().unwrap();
+    let tensor = new_tensor!(device, 2, 3, vec![-3.0, -1.0, -0.5, 0.0, 0.75, 4.0]).unwrap();
+    let tensor_count = device.tensor_count();
+    tensor.clamp(&device, -1.0, 1.0, &device_stream).unwrap();
+    assert_eq!(tensor_count, device.tensor_count());
+    device_stream.wait_for().unwrap();
+    assert_eq!(
+        vec![-1.0, -1.0, -0.5, 0.0, 0.75, 1.0],
+        tensor.get_values().unwrap()
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncorrectOperatorConfiguration),
+        tensor
+            .clamp(&device, 1.0, -1.0, &device_stream)
+            .map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn tensor_split_at_col() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        2,
+        6,
+        vec![
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, //
+            7.0, 8.0, 9.0, 10.0, 11.0, 12.0, //
+        ]
+    )
+    .unwrap();
+    let (left, right) = tensor.split_at_col(&device, 4).unwrap();
+    assert_eq!(vec![2, 4], *left.size());
+    assert_eq!(
+        vec![
+            1.0, 2.0, 3.0, 4.0, //
+            7.0, 8.0, 9.0, 10.0, //
+        ],
+        left.get_values().unwrap()
+    );
+    assert_eq!(vec![2, 2], *right.size());
+    assert_eq!(
+        vec![
+            5.0, 6.0, //
+            11.0, 12.0, //
+        ],
+        right.get_values().unwrap()
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        tensor
+            .split_at_col(&device, 7)
+            .map(|_| ())
+            .map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn debug_layout() {
+    let device = Device::default();
+    let tensor = new_tensor!(device, 2, 3, (0..6).map(|x| x as f32).collect()).unwrap();
+    let layout = tensor.debug_layout();
+    let lines = layout.lines().collect::<Vec<_>>();
+    assert_eq!(3, lines.len());
+    assert_eq!("size: [2, 3], strides: [3, 1], row-major: true", lines[0]);
+    for row in 0..tensor.rows() {
+        let entries = lines[1 + row].split("  ").collect::<Vec<_>>();
+        assert_eq!(tensor.cols(), entries.len());
+        for col in 0..tensor.cols() {
+            let expected = format!("({}, {}) -> {}", row, col, tensor.index(row, col));
+            assert_eq!(expected, entries[col]);
+        }
+    }
+}
+
+#[test]
+fn to_vec2() {
+    let device = Device::default();
+    let tensor = new_tensor!(device, 2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    let values = tensor.get_values().unwrap();
+    let nested = tensor.to_vec2().unwrap();
+    assert_eq!(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]], nested);
+    for row in 0..tensor.rows() {
+        for col in 0..tensor.cols() {
+            assert_eq!(values[tensor.index(row, col)], nested[row][col]);
+        }
+    }
+}
+
+#[test]
+fn diagonal_and_trace() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        3,
+        3,
+        vec![
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 6.0, //
+            7.0, 8.0, 9.0, //
+        ]
+    )
+    .unwrap();
+    let diagonal = new_tensor!(device, 3, 1, vec![0.0; 3]).unwrap();
+    tensor.diagonal(&diagonal).unwrap();
+    assert_eq!(vec![1.0, 5.0, 9.0], diagonal.get_values().unwrap());
+    assert_eq!(15.0, tensor.trace().unwrap());
+
+    let not_square = new_tensor!(device, 2, 3, vec![0.0; 6]).unwrap();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        not_square.trace().map_err(|e| e.error)
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        not_square.diagonal(&diagonal).map_err(|e| e.error)
+    );
+}
+
+#[test]
+fn argmin_rows() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        2,
+        4,
+        vec![
+            3.0, -1.0, 2.0, 0.5, //
+            1.0, 4.0, 1.0, 7.0, //
+        ]
+    )
+    .unwrap();
+    // The second row has a tie, which the lowest column wins.
+    assert_eq!(vec![1, 0], tensor.argmin_rows().unwrap());
+}
+
+#[test]
+fn non_finite_indices() {
+    let device = Device::default();
+    let tensor = new_tensor!(
+        device,
+        3,
+        3,
+        vec![
+            1.0,
+            f32::NAN,
+            3.0, //
+            4.0,
+            5.0,
+            6.0, //
+            f32::NEG_INFINITY,
+            8.0,
+            9.0, //
+        ]
+    )
+    .unwrap();
+    assert_eq!(vec![(0, 1), (2, 0)], tensor.non_finite_indices().unwrap());
+}
+
+#[test]
+fn from_iter() {
+    let device = Device::default();
+    let tensor = Tensor::from_iter(&device, 2, 3, (0..6).map(|x| x as f32)).unwrap();
+    assert_eq!(vec![2, 3], *tensor.size());
+    assert_eq!(
+        vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0],
+        tensor.get_values().unwrap()
+    );
+}
+
+#[test]
+fn from_iter_with_the_wrong_count() {
+    let device = Device::default();
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        Tensor::from_iter(&device, 2, 3, (0..5).map(|x| x as f32)).map_err(|e| e.error().clone())
+    );
+    assert_eq!(
+        Err(ErrorEnum::IncompatibleTensorShapes),
+        Tensor::from_iter(&device, 2, 3, (0..).map(|x| x as f32)).map_err(|e| e.error().clone())
+    );
+}
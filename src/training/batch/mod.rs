@@ -0,0 +1,56 @@
+use rand::prelude::SliceRandom;
+use rand::{rngs::StdRng, SeedableRng};
+
+#[cfg(test)]
+mod tests;
+
+/// Splits the example indices into batches, once per epoch.
+/// When shuffling, the order comes from a StdRng.
+/// With a seed, the orderings of all epochs are the same from one run to the next.
+pub struct DataLoader {
+    indices: Vec<usize>,
+    batch_size: usize,
+    shuffle_examples: bool,
+    rng: StdRng,
+}
+
+impl DataLoader {
+    pub fn new(
+        num_examples: usize,
+        batch_size: usize,
+        shuffle_examples: bool,
+        shuffle_seed: Option<u64>,
+    ) -> Self {
+        let rng = match shuffle_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            indices: (0..num_examples).collect(),
+            batch_size,
+            shuffle_examples,
+            rng,
+        }
+    }
+
+    /// The batches of the next epoch.
+    pub fn next_epoch(&mut self) -> Vec<Vec<usize>> {
+        if self.shuffle_examples {
+            self.indices.shuffle(&mut self.rng);
+        }
+        make_batches(&self.indices, self.batch_size)
+    }
+}
+
+pub fn make_batches(indices: &[usize], batch_size: usize) -> Vec<Vec<usize>> {
+    let mut batches: Vec<Vec<_>> = vec![];
+    for index in indices.iter().copied() {
+        if batches.len() == 0 || batches[batches.len() - 1].len() == batch_size {
+            batches.push(vec![]);
+        }
+        let last = batches.len() - 1;
+        let batch = &mut batches[last];
+        batch.push(index);
+    }
+    batches
+}
@@ -0,0 +1,35 @@
+use super::DataLoader;
+
+#[test]
+fn same_seed_gives_same_orderings() {
+    let mut loader_1 = DataLoader::new(20, 4, true, Some(42));
+    let mut loader_2 = DataLoader::new(20, 4, true, Some(42));
+    let mut previous = None;
+    for _ in 0..3 {
+        let batches = loader_1.next_epoch();
+        assert_eq!(batches, loader_2.next_epoch());
+        assert_eq!(5, batches.len());
+        assert!(batches.iter().all(|batch| batch.len() == 4));
+        let mut indices = batches.concat();
+        // Each epoch is shuffled differently.
+        assert_ne!(previous, Some(indices.clone()));
+        previous = Some(indices.clone());
+        indices.sort();
+        assert_eq!((0..20).collect::<Vec<_>>(), indices);
+    }
+}
+
+#[test]
+fn different_seeds_give_different_orderings() {
+    let mut loader_1 = DataLoader::new(20, 4, true, Some(1));
+    let mut loader_2 = DataLoader::new(20, 4, true, Some(2));
+    assert_ne!(loader_1.next_epoch(), loader_2.next_epoch());
+}
+
+#[test]
+fn no_shuffling_keeps_the_order() {
+    let mut loader = DataLoader::new(5, 2, false, None);
+    for _ in 0..2 {
+        assert_eq!(vec![vec![0, 1], vec![2, 3], vec![4]], loader.next_epoch());
+    }
+}
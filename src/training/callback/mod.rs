@@ -0,0 +1,18 @@
+#[cfg(test)]
+mod tests;
+
+/// Hooks of the training loop, for custom logging or checkpointing.
+/// The losses are the ones recorded during the training forward passes.
+pub trait TrainingCallback {
+    /// Called after each optimizer step with the total loss of its batch.
+    fn on_step(&mut self, _step: usize, _loss: f32) {}
+
+    /// Called after each epoch with the total loss of its examples.
+    fn on_epoch_end(&mut self, _epoch: usize, _loss: f32) {}
+}
+
+/// A callback that does nothing.
+#[derive(Default)]
+pub struct NoTrainingCallback {}
+
+impl TrainingCallback for NoTrainingCallback {}
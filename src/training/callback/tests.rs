@@ -0,0 +1,90 @@
+use crate::{
+    callback::TrainingCallback, neural_program::NeuralProgram, new_tensor_with_grad,
+    perceptron::PerceptronModel, schedulers::DefaultStreamScheduler,
+    stochastic_gradient_descent::StochasticGradientDescent,
+    sum_of_squared_errors::SumOfSquaredErrors, training_loop_with_callback, Device, NeuralMachine,
+    TrainingLoopConfig,
+};
+
+#[derive(Default)]
+struct RecordingCallback {
+    steps: Vec<usize>,
+    epoch_losses: Vec<(usize, f32)>,
+}
+
+impl TrainingCallback for RecordingCallback {
+    fn on_step(&mut self, step: usize, _loss: f32) {
+        self.steps.push(step);
+    }
+
+    fn on_epoch_end(&mut self, epoch: usize, loss: f32) {
+        self.epoch_losses.push((epoch, loss));
+    }
+}
+
+#[test]
+fn callback_is_called_after_each_step_and_each_epoch() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = StochasticGradientDescent::new(0.01);
+    let batch_size = 2;
+    let program = NeuralProgram::try_new(
+        &device,
+        &model,
+        &loss_operator,
+        &optimizer,
+        false,
+        batch_size,
+    )
+    .unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let examples = [
+        ([2.0, 3.0], 5.0),
+        ([1.0, -1.0], 0.0),
+        ([0.5, 4.0], 4.5),
+        ([2.0, 2.0], 4.0),
+    ];
+    let inputs = examples
+        .iter()
+        .map(|(input, _)| {
+            new_tensor_with_grad!(device, 1, 2, input.to_vec(), &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let outputs = examples
+        .iter()
+        .map(|(_, output)| {
+            new_tensor_with_grad!(device, 1, 1, vec![*output], &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let epochs = 3;
+    let mut callback = RecordingCallback::default();
+    let metrics = training_loop_with_callback(
+        &TrainingLoopConfig {
+            shuffle_examples: false,
+            shuffle_seed: None,
+            batch_size,
+            epochs,
+        },
+        &mut neural_machine,
+        &inputs,
+        &outputs,
+        None,
+        &mut callback,
+    )
+    .unwrap();
+
+    assert_eq!((0..6).collect::<Vec<_>>(), callback.steps);
+    assert_eq!(
+        (0..epochs).collect::<Vec<_>>(),
+        callback
+            .epoch_losses
+            .iter()
+            .map(|(epoch, _)| *epoch)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(metrics.total_loss, callback.epoch_losses[epochs - 1].1);
+}
@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+
+use crate::{
+    instruction, new_tensor_with_grad,
+    opcode::OpCode,
+    stream::{DeviceStream, StreamTrait},
+    tensor::{Error, Tensor},
+    Category, Device, ExecutableOperator, Instruction, OperatorAttributes, TensorWithGrad,
+    UnaryOperator,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Gradient checkpointing.
+/// The activations of the wrapped subgraph are deallocated at the end of the forward pass
+/// and are reallocated and recomputed during the backward pass.
+/// The input is copied into the subgraph, and only the input and the output are kept.
+///
+/// See:
+/// Training Deep Nets with Sublinear Memory Cost
+/// https://arxiv.org/abs/1604.06174
+pub struct Checkpoint<Operator: UnaryOperator> {
+    device: Device,
+    operator: Operator,
+}
+
+pub fn checkpoint<Operator: UnaryOperator>(
+    device: &Device,
+    operator: Operator,
+) -> Checkpoint<Operator> {
+    Checkpoint {
+        device: device.clone(),
+        operator,
+    }
+}
+
+impl<Operator: UnaryOperator> UnaryOperator for Checkpoint<Operator> {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, Error> {
+        // The instructions of the subgraph input run before the subgraph,
+        // which allows to reallocate the activations before each forward pass.
+        let subgraph_input = new_tensor_with_grad!(
+            self.device,
+            input.tensor().rows(),
+            input.tensor().cols(),
+            vec![0.0; input.tensor().len()],
+            &[input],
+            true,
+            false,
+        )?;
+        subgraph_input.push_instruction(instruction!(
+            OpCode::Identity,
+            OperatorAttributes::None,
+            &[&input.tensor()],
+            &[&subgraph_input.tensor()],
+            Category::Inference,
+        ));
+        if input.gradient().requires_grad() {
+            subgraph_input.push_instruction(instruction!(
+                OpCode::Add,
+                OperatorAttributes::None,
+                &[&input.gradient(), &subgraph_input.gradient()],
+                &[&input.gradient()],
+                Category::Gradient,
+            ));
+        }
+
+        let subgraph_output = self.operator.forward(&subgraph_input)?;
+
+        let mut outside_tensors = subgraph_input
+            .get_tape()
+            .iter()
+            .map(|t| t.tensor().name())
+            .collect::<HashSet<_>>();
+        outside_tensors.insert(subgraph_input.tensor().name());
+
+        // Inference instructions of the subgraph, in execution order.
+        let mut recompute_instructions: Vec<Instruction> = vec![];
+        let mut processed_tensors = HashSet::<usize>::new();
+        for tensor in subgraph_output.get_tape().iter() {
+            let tensor_name = tensor.tensor().name();
+            if outside_tensors.contains(&tensor_name) || processed_tensors.contains(&tensor_name) {
+                continue;
+            }
+            for instruction in tensor.forward_instructions().into_iter() {
+                if instruction.category() == Category::Inference {
+                    recompute_instructions.push(instruction);
+                }
+            }
+            processed_tensors.insert(tensor_name);
+        }
+
+        let rows = subgraph_output.tensor().rows();
+        let cols = subgraph_output.tensor().cols();
+        let output = new_tensor_with_grad!(
+            self.device,
+            rows,
+            cols,
+            vec![0.0; rows * cols],
+            &[&subgraph_output],
+            true,
+            false,
+        )?;
+
+        output.push_instruction(instruction!(
+            OpCode::Identity,
+            OperatorAttributes::None,
+            &[&subgraph_output.tensor()],
+            &[&output.tensor()],
+            Category::Inference,
+        ));
+
+        let mut activations: Vec<Tensor> = vec![];
+        let mut activation_names = HashSet::<usize>::new();
+        for instruction in recompute_instructions.iter() {
+            for activation in instruction.outputs().iter() {
+                if activation_names.insert(activation.name()) {
+                    activations.push(activation.clone());
+                }
+            }
+        }
+        let activations = activations.iter().collect::<Vec<_>>();
+
+        subgraph_input.push_instruction(instruction!(
+            OpCode::Allocate,
+            OperatorAttributes::None,
+            &[],
+            &activations,
+            Category::Inference,
+        ));
+
+        output.push_instruction(instruction!(
+            OpCode::Deallocate,
+            OperatorAttributes::None,
+            &[],
+            &activations,
+            Category::Inference,
+        ));
+
+        // Recompute the activations of the subgraph before its gradient instructions.
+        output.push_instruction(instruction!(
+            OpCode::Allocate,
+            OperatorAttributes::None,
+            &[],
+            &activations,
+            Category::Gradient,
+        ));
+        for instruction in recompute_instructions.iter() {
+            output.push_instruction(instruction.with_category(Category::Gradient));
+        }
+
+        output.push_instruction(instruction!(
+            OpCode::Add,
+            OperatorAttributes::None,
+            &[&subgraph_output.gradient(), &output.gradient()],
+            &[&subgraph_output.gradient()],
+            Category::Gradient,
+        ));
+
+        Ok(output)
+    }
+}
+
+/// Allocate zeroed buffers for the outputs that were deallocated.
+pub struct Allocate {}
+
+impl ExecutableOperator for Allocate {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        _inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        _device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        for output in outputs.iter() {
+            output.reallocate(device);
+        }
+        Ok(())
+    }
+}
+
+/// Free the buffers of the outputs.
+pub struct Deallocate {}
+
+impl ExecutableOperator for Deallocate {
+    fn execute(
+        _attributes: &OperatorAttributes,
+        _inputs: &[&Tensor],
+        outputs: &[&Tensor],
+        device: &Device,
+        device_stream: &DeviceStream,
+    ) -> Result<(), Error> {
+        // Previous kernels on the stream may still be reading the buffers.
+        device_stream.wait_for()?;
+        for output in outputs.iter() {
+            output.deallocate(device);
+        }
+        Ok(())
+    }
+}
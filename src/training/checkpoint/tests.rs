@@ -0,0 +1,90 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    checkpoint::checkpoint, gelu::Gelu, new_tensor_with_grad, opcode::OpCode, stream::StreamTrait,
+    Device, Sigmoid, TensorWithGrad, UnaryOperator,
+};
+
+struct SigmoidGelu {
+    sigmoid: Sigmoid,
+    gelu: Gelu,
+}
+
+impl SigmoidGelu {
+    fn new(device: &Device) -> Self {
+        Self {
+            sigmoid: Sigmoid::new(device),
+            gelu: Gelu::new(device),
+        }
+    }
+}
+
+impl UnaryOperator for SigmoidGelu {
+    fn forward(&self, input: &TensorWithGrad) -> Result<TensorWithGrad, crate::tensor::Error> {
+        let state = self.sigmoid.forward(input)?;
+        self.gelu.forward(&state)
+    }
+}
+
+fn forward_and_backward(device: &Device, output: &TensorWithGrad) {
+    let device_stream = device.new_stream().unwrap();
+    let tape = output.get_tape();
+    for tensor in tape.iter() {
+        tensor.forward(device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    let gradient = output.gradient();
+    gradient.set_values(vec![1.0; gradient.len()]).unwrap();
+    for tensor in tape.iter().rev() {
+        tensor.compute_gradient(device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+}
+
+#[test]
+fn checkpoint_produces_identical_gradients() {
+    let device = Device::default();
+    let values = vec![
+        -1.0, 0.5, 2.0, //
+        3.0, -0.25, 0.0, //
+    ];
+
+    let input = new_tensor_with_grad!(device, 2, 3, values.clone(), &[], true, false).unwrap();
+    let output = SigmoidGelu::new(&device).forward(&input).unwrap();
+    forward_and_backward(&device, &output);
+
+    let checkpointed_input =
+        new_tensor_with_grad!(device, 2, 3, values.clone(), &[], true, false).unwrap();
+    let checkpointed_output = checkpoint(&device, SigmoidGelu::new(&device))
+        .forward(&checkpointed_input)
+        .unwrap();
+    let device_stream = device.new_stream().unwrap();
+    let tape = checkpointed_output.get_tape();
+    let used = device.get_memory_info().unwrap().used;
+    for tensor in tape.iter() {
+        tensor.forward(&device, &device_stream).unwrap();
+    }
+    device_stream.wait_for().unwrap();
+    assert_lt!(device.get_memory_info().unwrap().used, used);
+
+    assert_eq!(
+        output.tensor().get_values().unwrap(),
+        checkpointed_output.tensor().get_values().unwrap(),
+    );
+    // The sigmoid activation is deallocated after the forward pass.
+    let sigmoid_activation = tape
+        .iter()
+        .find(|x| {
+            x.forward_instructions()
+                .iter()
+                .any(|i| matches!(i.opcode(), OpCode::Sigmoid))
+        })
+        .unwrap();
+    assert!(!sigmoid_activation.tensor().is_allocated());
+
+    forward_and_backward(&device, &checkpointed_output);
+    assert_eq!(
+        input.gradient().get_values().unwrap(),
+        checkpointed_input.gradient().get_values().unwrap(),
+    );
+}
@@ -0,0 +1,73 @@
+use crate::{
+    error, get_row_argmaxes, new_tensor,
+    tensor::{Error, ErrorEnum, Tensor},
+    Device,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// https://en.wikipedia.org/wiki/Confusion_matrix
+/// Row i, column j counts the rows whose target argmax is i and whose prediction argmax is j.
+pub fn confusion_matrix(
+    device: &Device,
+    predictions: &Tensor,
+    targets: &Tensor,
+    num_classes: usize,
+) -> Result<Tensor, Error> {
+    if *predictions.size() != *targets.size() {
+        return Err(error!(ErrorEnum::IncompatibleTensorShapes));
+    }
+    let predicted_classes = get_row_argmaxes(predictions)?;
+    let target_classes = get_row_argmaxes(targets)?;
+    let matrix = new_tensor!(
+        device,
+        num_classes,
+        num_classes,
+        vec![0.0; num_classes * num_classes],
+    )?;
+    let mut counts = matrix.get_values()?;
+    for (target, predicted) in target_classes.iter().zip(predicted_classes.iter()) {
+        if *target >= num_classes || *predicted >= num_classes {
+            return Err(error!(ErrorEnum::IncorrectOperatorConfiguration));
+        }
+        counts[matrix.index(*target, *predicted)] += 1.0;
+    }
+    matrix.set_values(counts)?;
+    Ok(matrix)
+}
+
+/// Fraction of the rows predicted as `class` that are really `class`.
+pub fn precision(confusion_matrix: &Tensor, class: usize) -> Result<f32, Error> {
+    let values = confusion_matrix.get_values()?;
+    let true_positives = values[confusion_matrix.index(class, class)];
+    let predicted_positives = (0..confusion_matrix.rows())
+        .map(|row| values[confusion_matrix.index(row, class)])
+        .sum::<f32>();
+    Ok(ratio(true_positives, predicted_positives))
+}
+
+/// Fraction of the rows that are really `class` that are predicted as `class`.
+pub fn recall(confusion_matrix: &Tensor, class: usize) -> Result<f32, Error> {
+    let values = confusion_matrix.get_values()?;
+    let true_positives = values[confusion_matrix.index(class, class)];
+    let actual_positives = (0..confusion_matrix.cols())
+        .map(|col| values[confusion_matrix.index(class, col)])
+        .sum::<f32>();
+    Ok(ratio(true_positives, actual_positives))
+}
+
+/// Harmonic mean of precision and recall.
+pub fn f1(confusion_matrix: &Tensor, class: usize) -> Result<f32, Error> {
+    let precision = precision(confusion_matrix, class)?;
+    let recall = recall(confusion_matrix, class)?;
+    Ok(ratio(2.0 * precision * recall, precision + recall))
+}
+
+fn ratio(numerator: f32, denominator: f32) -> f32 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
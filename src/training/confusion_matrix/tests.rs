@@ -0,0 +1,59 @@
+use more_asserts::assert_lt;
+
+use crate::{
+    confusion_matrix::{confusion_matrix, f1, precision, recall},
+    new_tensor, Device,
+};
+
+#[test]
+fn confusion_matrix_with_three_classes() {
+    let device = Device::default();
+    let num_classes = 3;
+    // predicted classes: 0, 1, 1, 2, 0
+    let predictions = new_tensor!(
+        device,
+        5,
+        3,
+        vec![
+            0.7, 0.2, 0.1, //
+            0.1, 0.8, 0.1, //
+            0.3, 0.6, 0.1, //
+            0.2, 0.2, 0.6, //
+            0.5, 0.4, 0.1, //
+        ],
+    )
+    .unwrap();
+    // target classes: 0, 1, 0, 2, 2
+    let targets = new_tensor!(
+        device,
+        5,
+        3,
+        vec![
+            1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, //
+            0.0, 0.0, 1.0, //
+        ],
+    )
+    .unwrap();
+
+    let matrix = confusion_matrix(&device, &predictions, &targets, num_classes).unwrap();
+    assert_eq!(vec![3, 3], *matrix.size());
+    assert_eq!(
+        vec![
+            1.0, 1.0, 0.0, //
+            0.0, 1.0, 0.0, //
+            1.0, 0.0, 1.0, //
+        ],
+        matrix.get_values().unwrap()
+    );
+
+    assert_eq!(0.5, precision(&matrix, 0).unwrap());
+    assert_eq!(0.5, recall(&matrix, 0).unwrap());
+    assert_eq!(0.5, precision(&matrix, 1).unwrap());
+    assert_eq!(1.0, recall(&matrix, 1).unwrap());
+    assert_lt!((f1(&matrix, 1).unwrap() - 2.0 / 3.0).abs(), 1e-6);
+    assert_eq!(1.0, precision(&matrix, 2).unwrap());
+    assert_eq!(0.5, recall(&matrix, 2).unwrap());
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests;
+
+/// Records the loss of each example during the training forward passes,
+/// so that the total loss of an epoch does not need other forward passes.
+/// The loss of an example is recorded before the optimizer step of its batch,
+/// so the total of an epoch mixes the weights of all its steps: the examples of
+/// the first batch are evaluated with the weights of the start of the epoch,
+/// and those of the last batch with the weights before the last step.
+#[derive(Clone, Default)]
+pub struct LossAccumulator {
+    losses: Vec<f32>,
+}
+
+impl LossAccumulator {
+    pub fn record(&mut self, loss: f32) {
+        self.losses.push(loss);
+    }
+
+    pub fn losses(&self) -> &[f32] {
+        &self.losses
+    }
+
+    pub fn total(&self) -> f32 {
+        self.losses.iter().sum()
+    }
+
+    /// Start a new epoch.
+    pub fn clear(&mut self) {
+        self.losses.clear();
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    loss_accumulator::LossAccumulator, neural_program::NeuralProgram, new_tensor_with_grad,
+    perceptron::PerceptronModel, schedulers::DefaultStreamScheduler,
+    stochastic_gradient_descent::StochasticGradientDescent,
+    sum_of_squared_errors::SumOfSquaredErrors, tensor::Tensor, training_loop, Device,
+    NeuralMachine, TensorWithGrad,
+};
+
+#[test]
+fn record_and_clear() {
+    let mut loss_accumulator = LossAccumulator::default();
+    loss_accumulator.record(1.5);
+    loss_accumulator.record(2.0);
+    assert_eq!(&[1.5, 2.0], loss_accumulator.losses());
+    assert_eq!(3.5, loss_accumulator.total());
+    loss_accumulator.clear();
+    assert_eq!(0.0, loss_accumulator.total());
+}
+
+#[test]
+fn accumulated_total_loss_equals_the_total_loss_of_other_forward_passes() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    // The parameters do not change, so the losses of both passes are the same.
+    let optimizer = StochasticGradientDescent::new(0.0);
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let examples = [([2.0, 3.0], 5.0), ([1.0, -1.0], 0.0), ([0.5, 4.0], 4.5)];
+    let inputs = examples
+        .iter()
+        .map(|(input, _)| {
+            new_tensor_with_grad!(device, 1, 2, input.to_vec(), &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let outputs = examples
+        .iter()
+        .map(|(_, output)| {
+            new_tensor_with_grad!(device, 1, 1, vec![*output], &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let metrics = training_loop(false, None, 1, 1, &mut neural_machine, &inputs, &outputs).unwrap();
+
+    let mut total_loss = 0.0;
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        neural_machine.infer(input).unwrap();
+        let loss = neural_machine.loss(output).unwrap();
+        let loss: &Tensor = &loss.tensor();
+        let loss: f32 = loss.try_into().unwrap();
+        total_loss += loss;
+    }
+    assert_eq!(total_loss, metrics.total_loss);
+}
+
+#[test]
+fn accumulated_total_loss_is_recorded_before_each_optimizer_step() {
+    let device = Device::default();
+    let model = PerceptronModel::new(&device).unwrap();
+    let loss_operator = SumOfSquaredErrors::new(&device);
+    let optimizer = StochasticGradientDescent::new(0.01);
+    let program =
+        NeuralProgram::try_new(&device, &model, &loss_operator, &optimizer, false, 1).unwrap();
+    let mut neural_machine =
+        NeuralMachine::<f32, DefaultStreamScheduler>::try_new(&device, program, 1).unwrap();
+
+    let examples = [([2.0, 3.0], 5.0), ([1.0, -1.0], 0.0), ([0.5, 4.0], 4.5)];
+    let inputs = examples
+        .iter()
+        .map(|(input, _)| {
+            new_tensor_with_grad!(device, 1, 2, input.to_vec(), &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let outputs = examples
+        .iter()
+        .map(|(_, output)| {
+            new_tensor_with_grad!(device, 1, 1, vec![*output], &[], false, false).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let loss = |neural_machine: &mut NeuralMachine<f32, DefaultStreamScheduler>,
+                input: &TensorWithGrad,
+                output: &TensorWithGrad| {
+        neural_machine.infer(input).unwrap();
+        let loss = neural_machine.loss(output).unwrap();
+        let loss: &Tensor = &loss.tensor();
+        let loss: f32 = loss.try_into().unwrap();
+        loss
+    };
+
+    let initial_parameters = device
+        .parameter_tensors()
+        .iter()
+        .map(|x| x.tensor().get_values().unwrap())
+        .collect::<Vec<_>>();
+    let metrics = training_loop(false, None, 1, 1, &mut neural_machine, &inputs, &outputs).unwrap();
+
+    // The trained weights give another total loss.
+    let mut trained_total_loss = 0.0;
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        trained_total_loss += loss(&mut neural_machine, input, output);
+    }
+    assert_ne!(trained_total_loss, metrics.total_loss);
+
+    // Replay the epoch from the initial weights, recording each loss before its step.
+    for (parameter, values) in device.parameter_tensors().iter().zip(initial_parameters) {
+        parameter.tensor().set_values(values).unwrap();
+    }
+    let mut total_loss = 0.0;
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        total_loss += loss(&mut neural_machine, input, output);
+        neural_machine.compute_gradient().unwrap();
+        neural_machine.optimize().unwrap();
+    }
+    assert_eq!(total_loss, metrics.total_loss);
+}
@@ -5,6 +5,10 @@ pub use train::*;
 mod tensor_with_grad;
 pub use tensor_with_grad::*;
 pub mod batch;
+pub mod callback;
+pub mod checkpoint;
 pub mod clip_grad_norm;
+pub mod confusion_matrix;
 pub mod display;
+pub mod loss_accumulator;
 pub mod perplexity;
@@ -12,6 +12,8 @@ pub struct TensorWithGrad {
     instructions: Arc<RwLock<Vec<Instruction>>>,
     tensor: Arc<RwLock<Tensor>>,
     gradient: Arc<RwLock<Tensor>>,
+    no_decay: Arc<RwLock<bool>>,
+    learning_rate_multiplier: Arc<RwLock<f32>>,
 }
 
 impl TensorWithGrad {
@@ -22,9 +24,31 @@ impl TensorWithGrad {
             instructions: Default::default(),
             tensor: Arc::new(RwLock::new(tensor)),
             gradient: Arc::new(RwLock::new(gradient)),
+            no_decay: Default::default(),
+            learning_rate_multiplier: Arc::new(RwLock::new(1.0)),
         }
     }
 
+    /// Exclude this parameter from weight decay.
+    /// Biases and normalization gains are usually not decayed.
+    pub fn set_no_decay(&self, no_decay: bool) {
+        *self.no_decay.write().unwrap() = no_decay;
+    }
+
+    pub fn no_decay(&self) -> bool {
+        *self.no_decay.read().unwrap()
+    }
+
+    /// The optimizers multiply their learning rate by this multiplier for this parameter,
+    /// for example to give the layers of a model different learning rates.
+    pub fn set_learning_rate_multiplier(&self, multiplier: f32) {
+        *self.learning_rate_multiplier.write().unwrap() = multiplier;
+    }
+
+    pub fn learning_rate_multiplier(&self) -> f32 {
+        *self.learning_rate_multiplier.read().unwrap()
+    }
+
     pub fn push_instruction(&self, instruction: Instruction) {
         self.instructions.write().unwrap().push(instruction)
     }
@@ -2,11 +2,13 @@ use more_asserts::assert_ge;
 use more_asserts::assert_le;
 
 use crate::datasets::addition_perceptron::load_addition_perceptron;
+use crate::datasets::copy_task::load_copy_task;
 use crate::datasets::mega_man_attention_head::load_mega_man_attention_head;
 use crate::datasets::mega_man_linear::load_mega_man_linear;
 use crate::datasets::mega_man_multi_head_attention::load_mega_man_multi_head_attention;
 use crate::datasets::mega_man_transformers::load_mega_man_transformers;
 use crate::datasets::simple::load_simple;
+use crate::datasets::sine_regression::load_sine_regression;
 use crate::datasets::DatasetDetails;
 use crate::display::TensorPrinter;
 use crate::train_model;
@@ -91,3 +93,17 @@ fn mega_man_transformers() {
     let details = load_mega_man_transformers(&device).unwrap();
     test_model(details);
 }
+
+#[test]
+fn copy_task() {
+    let device = Device::default();
+    let details = load_copy_task(&device).unwrap();
+    test_model(details);
+}
+
+#[test]
+fn sine_regression() {
+    let device = Device::default();
+    let details = load_sine_regression(&device).unwrap();
+    test_model(details);
+}
@@ -1,9 +1,12 @@
 use std::time::SystemTime;
 
 use crate::{
-    batch::make_batches,
+    batch::DataLoader,
+    callback::{NoTrainingCallback, TrainingCallback},
     datasets::DatasetDetails,
     display::TensorPrinter,
+    loss_accumulator::LossAccumulator,
+    lr_scheduler::LrScheduler,
     neural_program::NeuralProgram,
     schedulers::DefaultStreamScheduler,
     tensor::{Error, Tensor},
@@ -26,6 +29,7 @@ pub struct Metrics {
 
 pub struct NeuralMachineTestOutput {
     pub initial_metrics: Metrics,
+    /// Evaluated with the trained weights.
     pub final_metrics: Metrics,
     pub expected_argmax_values: Vec<usize>,
     pub actual_argmax_values: Vec<usize>,
@@ -46,6 +50,7 @@ pub fn train_model<T>(
     let device = details.device;
     let clip_grad_norm = details.clip_gradient_norm;
     let shuffle_examples = details.shuffle_examples;
+    let shuffle_seed = details.shuffle_seed;
     let batch_size = details.batch_size;
     let optimizer = details.optimizer;
     let mut printer = details.printer;
@@ -63,6 +68,7 @@ pub fn train_model<T>(
         program,
         maximum_device_streams,
     )?;
+    neural_machine.validate_dataset(train_examples)?;
 
     let train_inputs: Vec<_> = train_examples.iter().map(|x| x.clone().0).collect();
     let train_outputs: Vec<_> = train_examples.iter().map(|x| x.clone().1).collect();
@@ -83,6 +89,7 @@ pub fn train_model<T>(
 
     training_loop(
         shuffle_examples,
+        shuffle_seed,
         batch_size,
         epochs,
         &mut neural_machine,
@@ -90,6 +97,8 @@ pub fn train_model<T>(
         &train_outputs,
     )?;
 
+    // The losses of the training loop are recorded before the optimizer steps,
+    // so the trained weights are evaluated again.
     let (final_metrics, expected_argmax_values, actual_argmax_values) = print_training_examples(
         epochs,
         &mut neural_machine,
@@ -204,14 +213,58 @@ pub fn get_row_argmax(tensor: &Tensor, row: usize) -> Result<usize, Error> {
     Ok(argmax_col)
 }
 
+/// The order and the size of the batches of a training loop.
+#[derive(Clone, Copy, Debug)]
+pub struct TrainingLoopConfig {
+    pub shuffle_examples: bool,
+    pub shuffle_seed: Option<u64>,
+    pub batch_size: usize,
+    pub epochs: usize,
+}
+
+/// Returns the total loss of the last epoch, recorded during its forward passes.
+/// Each loss is recorded before the optimizer step of its batch, so the total is not
+/// the total loss of the trained weights, see LossAccumulator.
 pub fn training_loop<T>(
     shuffle_examples: bool,
+    shuffle_seed: Option<u64>,
     batch_size: usize,
     epochs: usize,
     neural_machine: &mut NeuralMachine<T, DefaultStreamScheduler>,
     inputs: &Vec<TensorWithGrad>,
     outputs: &Vec<TensorWithGrad>,
-) -> Result<(), Error> {
+) -> Result<Metrics, Error> {
+    training_loop_with_callback(
+        &TrainingLoopConfig {
+            shuffle_examples,
+            shuffle_seed,
+            batch_size,
+            epochs,
+        },
+        neural_machine,
+        inputs,
+        outputs,
+        None,
+        &mut NoTrainingCallback::default(),
+    )
+}
+
+/// The callback is called after each optimizer step and after each epoch.
+/// The learning rate scheduler, if any, sets the learning rate of each optimizer step.
+pub fn training_loop_with_callback<T>(
+    config: &TrainingLoopConfig,
+    neural_machine: &mut NeuralMachine<T, DefaultStreamScheduler>,
+    inputs: &Vec<TensorWithGrad>,
+    outputs: &Vec<TensorWithGrad>,
+    lr_scheduler: Option<&dyn LrScheduler>,
+    callback: &mut dyn TrainingCallback,
+) -> Result<Metrics, Error> {
+    let TrainingLoopConfig {
+        shuffle_examples,
+        shuffle_seed,
+        batch_size,
+        epochs,
+    } = *config;
     if inputs.len() % batch_size != 0 {
         panic!(
             "Bad batch_size {} for examples count {}",
@@ -219,10 +272,12 @@ pub fn training_loop<T>(
             inputs.len()
         );
     }
-    let indices = (0..inputs.len()).collect::<Vec<_>>();
+    let mut data_loader = DataLoader::new(inputs.len(), batch_size, shuffle_examples, shuffle_seed);
     let mut global_step = 0;
+    let mut loss_accumulator = LossAccumulator::default();
     for epoch in 0..epochs {
-        let batches = make_batches(&indices, shuffle_examples, batch_size);
+        let batches = data_loader.next_epoch();
+        loss_accumulator.clear();
 
         for (batch_id, batch) in batches.iter().enumerate() {
             let mut batch_loss = 0.0;
@@ -235,6 +290,7 @@ pub fn training_loop<T>(
                 let loss: &Tensor = &loss.tensor();
                 let loss: f32 = loss.try_into()?;
                 batch_loss += loss;
+                loss_accumulator.record(loss);
                 neural_machine.compute_gradient()?;
             }
             println!(
@@ -246,13 +302,21 @@ pub fn training_loop<T>(
                 global_step + 1,
                 batch_loss
             );
+            if let Some(lr_scheduler) = lr_scheduler {
+                neural_machine.set_learning_rate(lr_scheduler.learning_rate(global_step))?;
+            }
             neural_machine.optimize()?;
+            callback.on_step(global_step, batch_loss);
             global_step += 1;
         }
+        callback.on_epoch_end(epoch, loss_accumulator.total());
     }
 
     neural_machine.disable_dropout()?;
-    Ok(())
+    let metrics = Metrics {
+        total_loss: loss_accumulator.total(),
+    };
+    Ok(metrics)
 }
 
 pub fn time_it<F: Fn() -> T, T>(text: &str, f: F) -> T {